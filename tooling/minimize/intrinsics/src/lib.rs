@@ -7,7 +7,7 @@ use std::fmt::Display;
 use std::alloc::{System, Layout, Allocator};
 use std::ptr::NonNull;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
 use std::thread::{JoinHandle, self, Thread};
 
 pub fn print(t: impl Display) {
@@ -154,3 +154,22 @@ pub unsafe fn atomic_fetch_sub(ptr: *mut u32, delta: u32) -> u32 {
     let atomic = AtomicU32::from_ptr(ptr);
     atomic.fetch_sub(delta, Ordering::SeqCst)
 }
+
+pub unsafe fn atomic_store_ptr<T>(ptr: *mut *mut T, value: *mut T) {
+    let atomic = AtomicPtr::from_ptr(ptr);
+    atomic.store(value, Ordering::SeqCst);
+}
+
+pub unsafe fn atomic_load_ptr<T>(ptr: *mut *mut T) -> *mut T {
+    let atomic = AtomicPtr::from_ptr(ptr);
+    atomic.load(Ordering::SeqCst)
+}
+
+pub unsafe fn compare_exchange_ptr<T>(ptr: *mut *mut T, current: *mut T, new: *mut T) -> *mut T {
+    let atomic = AtomicPtr::from_ptr(ptr);
+    let res = atomic.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst);
+    match res {
+        Ok(ret) => ret,
+        Err(ret) => ret,
+    }
+}
@@ -500,6 +500,19 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
                         next_block: target.as_ref().map(|t| self.bb_name_map[t]),
                     },
                 },
+            rs::sym::ptr_guaranteed_cmp =>
+                TerminatorResult {
+                    stmts: List::new(),
+                    terminator: Terminator::Intrinsic {
+                        intrinsic: IntrinsicOp::PtrGuaranteedCmp,
+                        arguments: args
+                            .iter()
+                            .map(|x| self.translate_operand(&x.node, x.span))
+                            .collect(),
+                        ret: self.translate_place(destination, span),
+                        next_block: target.as_ref().map(|t| self.bb_name_map[t]),
+                    },
+                },
             rs::sym::arith_offset => {
                 let lty = args[0].node.ty(&self.body, self.tcx);
                 let rty = args[1].node.ty(&self.body, self.tcx);
@@ -663,12 +676,11 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
             }
             rs::UnwindAction::Terminate(_) => {
                 // FIXME: do not ignore UnwindTerminateReason
-                let block_kind = if bb.is_cleanup { BbKind::Terminate } else { BbKind::Cleanup };
                 let unwind_bb_name = self.fresh_bb_name();
                 let unwind_bb = BasicBlock {
                     statements: list![],
-                    terminator: build::abort(),
-                    kind: block_kind,
+                    terminator: Terminator::Terminate,
+                    kind: BbKind::Terminate,
                 };
                 self.blocks.insert(unwind_bb_name, unwind_bb);
                 unwind_bb_name
@@ -739,9 +751,9 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
                 "create_lock" => IntrinsicOp::Lock(IntrinsicLockOp::Create),
                 "acquire" => IntrinsicOp::Lock(IntrinsicLockOp::Acquire),
                 "release" => IntrinsicOp::Lock(IntrinsicLockOp::Release),
-                "atomic_store" => IntrinsicOp::AtomicStore,
-                "atomic_load" => IntrinsicOp::AtomicLoad,
-                "compare_exchange" => IntrinsicOp::AtomicCompareExchange,
+                "atomic_store" | "atomic_store_ptr" => IntrinsicOp::AtomicStore,
+                "atomic_load" | "atomic_load_ptr" => IntrinsicOp::AtomicLoad,
+                "compare_exchange" | "compare_exchange_ptr" => IntrinsicOp::AtomicCompareExchange,
                 "atomic_fetch_add" => IntrinsicOp::AtomicFetchAndOp(IntBinOp::Add),
                 "atomic_fetch_sub" => IntrinsicOp::AtomicFetchAndOp(IntBinOp::Sub),
                 name => panic!("unsupported MiniRust intrinsic `{}`", name),
@@ -764,7 +776,7 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
                 .tcx
                 .fn_abi_of_instance(self.typing_env().as_query_input((instance, rs::List::empty())))
                 .unwrap();
-            let conv = translate_calling_convention(abi.conv);
+            let conv = translate_calling_convention(abi.conv, abi.can_unwind);
 
             let fn_sig = fn_ty.fn_sig(self.tcx);
             let mut args: List<ArgumentExpr> = if fn_sig.abi() == rustc_abi::ExternAbi::RustCall
@@ -100,11 +100,14 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
         }
 
         // convert mirs Local-types to minirust.
-        for (id, local_name) in &self.local_name_map {
-            let local_decl = &self.body.local_decls[*id];
+        // This goes in MIR order: translating a type can name new traits, and those names
+        // must not depend on the iteration order of `local_name_map`.
+        for id in self.body.local_decls.indices() {
+            let local_name = self.local_name_map[&id];
+            let local_decl = &self.body.local_decls[id];
             let span = local_decl.source_info.span;
             let ty = self.cx.translate_ty(local_decl.ty, span);
-            self.locals.insert(*local_name, ty);
+            self.locals.insert(local_name, ty);
         }
 
         let mut args = List::default(); // the locals that receive the function arguments
@@ -181,7 +184,10 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
         self.blocks.insert(init_bb, init_blk);
 
         // convert MIR BBs to minirust.
-        for (id, bb_name) in self.bb_name_map.clone() {
+        // Like the locals, they are translated in MIR order, since translating them
+        // names new blocks, locals, functions and globals.
+        for id in self.body.basic_blocks.indices() {
+            let bb_name = self.bb_name_map[&id];
             let bb_data = &self.body.basic_blocks[id].clone(); // TODO fix clone
             self.translate_bb(bb_name, bb_data);
         }
@@ -196,7 +202,7 @@ impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
             ret,
             blocks: self.blocks,
             start: init_bb,
-            calling_convention: translate_calling_convention(self.abi.conv),
+            calling_convention: translate_calling_convention(self.abi.conv, self.abi.can_unwind),
         };
 
         f
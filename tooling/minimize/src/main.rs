@@ -63,7 +63,7 @@ pub use miniutil::BasicMem;
 pub use miniutil::DefaultTarget;
 pub use miniutil::TreeBorrowMem;
 pub use miniutil::build::{self, TypeConv as _, unit_place};
-pub use miniutil::fmt::dump_program;
+pub use miniutil::fmt::{FmtOptions, fmt_program_with};
 pub use miniutil::run::*;
 
 // Get back some `std` items
@@ -169,11 +169,12 @@ fn main() {
         return be_rustc(all_args);
     }
     let (minimize_args, rustc_args) = split_args(all_args);
-    let dump = minimize_args.iter().any(|x| x == "--minimize-dump");
+    let color = minimize_args.iter().any(|x| x == "--minimize-dump-color");
+    let dump = color || minimize_args.iter().any(|x| x == "--minimize-dump");
 
     get_mini(rustc_args, |_tcx, prog| {
         if dump {
-            dump_program(prog);
+            println!("{}", fmt_program_with(prog, FmtOptions { color }));
         } else {
             match run_prog(prog, &minimize_args) {
                 // We can't use tcx.dcx().fatal due to <https://github.com/oli-obk/ui_test/issues/226>
@@ -187,6 +188,15 @@ fn main() {
                 TerminationInfo::Ub(err) => show_error!("UB: {}", err.get_internal()),
                 TerminationInfo::Deadlock => show_error!("program dead-locked"),
                 TerminationInfo::MemoryLeak => show_error!("program leaked memory"),
+                TerminationInfo::StackExhausted => show_error!("program exhausted the stack"),
+                TerminationInfo::ThreadsExhausted =>
+                    show_error!("program exceeded the thread limit"),
+                TerminationInfo::AllocationsExhausted =>
+                    show_error!("program exceeded the allocation limit"),
+                TerminationInfo::MemoryExhausted =>
+                    show_error!("program exceeded the memory limit"),
+                TerminationInfo::ReplayMismatch(err) =>
+                    show_error!("replay failed: {}", err.get_internal()),
             }
         }
     });
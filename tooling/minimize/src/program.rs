@@ -84,15 +84,19 @@ impl<'tcx> Ctxt<'tcx> {
         self.fn_name_map.insert(entry_instance, entry_name);
 
         // This is the main monomorphization loop.
-        // take any not-yet-implemented function:
-        while let Some(fn_name) =
-            self.fn_name_map.values().find(|k| !self.functions.contains_key(**k)).copied()
-        {
+        // Functions are translated in the order of their names, which is the order in which
+        // they were discovered. Translating a function names the functions, globals and vtables
+        // it refers to, so going through the `HashMap` in its own order would make those names
+        // differ between runs.
+        let mut next = 0;
+        while next < self.fn_name_map.len() {
+            let fn_name = FnName(Name::from_internal(next as _));
             let instance =
-                self.fn_name_map.iter().find(|(_, f)| **f == fn_name).map(|(r, _)| r).unwrap();
+                *self.fn_name_map.iter().find(|(_, f)| **f == fn_name).map(|(r, _)| r).unwrap();
 
-            let f = FnCtxt::new(*instance, &mut self).translate();
+            let f = FnCtxt::new(instance, &mut self).translate();
             self.functions.insert(fn_name, f);
+            next += 1;
         }
 
         let number_of_fns = self.fn_name_map.len();
@@ -348,8 +348,9 @@ pub fn translate_align(align: rs::Align) -> Align {
     Align::from_bytes(align.bytes()).unwrap()
 }
 
-pub fn translate_calling_convention(conv: rs::Conv) -> CallingConvention {
+pub fn translate_calling_convention(conv: rs::Conv, can_unwind: bool) -> CallingConvention {
     match conv {
+        rs::Conv::C if can_unwind => CallingConvention::CUnwind,
         rs::Conv::C => CallingConvention::C,
         rs::Conv::Rust => CallingConvention::Rust,
         _ => todo!(),
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The corpus directory; see its `manifest` for the format.
+const CORPUS: &str = "tests/corpus";
+
+/// Reads the manifest, mapping file names to expected outcomes.
+fn read_manifest(dir: &Path) -> BTreeMap<String, String> {
+    let manifest =
+        fs::read_to_string(dir.join("manifest")).expect("failed to read the corpus manifest");
+    let mut outcomes = BTreeMap::new();
+    for (idx, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((file, outcome)) = line.split_once(':') else {
+            panic!("manifest line {}: expected `file.rs: outcome`", idx + 1);
+        };
+        let old = outcomes.insert(file.trim().to_string(), outcome.trim().to_string());
+        assert!(old.is_none(), "manifest line {}: `{}` is listed twice", idx + 1, file.trim());
+    }
+    outcomes
+}
+
+/// Runs `file` with minimize and describes the outcome the way the manifest does.
+fn run(file: &Path) -> String {
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("corpus");
+    let output = Command::new(env!("CARGO_BIN_EXE_minimize"))
+        .arg(file)
+        .arg("--out-dir")
+        .arg(out_dir)
+        .output()
+        .expect("failed to run minimize");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.code() {
+        Some(0) => "stop".to_string(),
+        Some(101) =>
+            match stderr.trim().strip_prefix("fatal error: ") {
+                Some(msg) => msg.to_string(),
+                None => format!("unexpected error output:\n{stderr}"),
+            },
+        _ => format!("unexpected exit status {}:\n{stderr}", output.status),
+    }
+}
+
+fn main() {
+    // Ensure we have an up-to-date sysroot.
+    Command::new(env!("CARGO_BIN_EXE_minimize"))
+        .env("MINIMIZE_BUILD_SYSROOT", "only")
+        .status()
+        .expect("failed to build sysroot for testing");
+
+    let dir = Path::new(CORPUS);
+    let outcomes = read_manifest(dir);
+    let mut failures = Vec::new();
+
+    for entry in fs::read_dir(dir).expect("failed to read the corpus directory") {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "rs") {
+            let file = path.file_name().unwrap().to_string_lossy().into_owned();
+            if !outcomes.contains_key(&file) {
+                failures.push(format!("{file}: not listed in the manifest"));
+            }
+        }
+    }
+
+    for (file, expected) in outcomes.iter() {
+        let path = dir.join(file);
+        if !path.exists() {
+            failures.push(format!("{file}: listed in the manifest, but does not exist"));
+            continue;
+        }
+        let actual = run(&path);
+        if actual == *expected {
+            println!("{file} ... ok");
+        } else {
+            println!("{file} ... FAILED");
+            failures.push(format!("{file}: expected `{expected}`, got `{actual}`"));
+        }
+    }
+
+    if !failures.is_empty() {
+        eprintln!("\n{} corpus failures:", failures.len());
+        for failure in failures.iter() {
+            eprintln!("    {failure}");
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,5 @@
+fn main() {
+    assert!(black_box(false));
+}
+
+fn black_box<T>(t: T) -> T { t }
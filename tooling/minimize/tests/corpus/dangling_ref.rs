@@ -0,0 +1,9 @@
+fn main() {
+    let x = dangling();
+    let _y = unsafe { *x };
+}
+
+fn dangling() -> *const i32 {
+    let x = 2;
+    &x as *const i32
+}
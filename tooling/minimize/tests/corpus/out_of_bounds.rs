@@ -0,0 +1,5 @@
+fn main() {
+    let a = [0u8; 2];
+    let p = a.as_ptr().wrapping_add(5);
+    let _x = unsafe { *p };
+}
@@ -0,0 +1,7 @@
+fn main() {
+    let mut sum = 0;
+    for i in 0..10 {
+        sum += i;
+    }
+    assert!(sum == 45);
+}
@@ -0,0 +1,28 @@
+extern crate intrinsics;
+use intrinsics::*;
+
+fn main() {
+    let mut x: u32 = 1;
+    let mut y: u32 = 2;
+    let px = &mut x as *mut u32;
+    let py = &mut y as *mut u32;
+
+    let mut slot = px;
+    let slot_ptr = &mut slot as *mut *mut u32;
+
+    let p = unsafe { atomic_load_ptr(slot_ptr) };
+    print(unsafe { *p });
+
+    unsafe { atomic_store_ptr(slot_ptr, py) };
+    print(unsafe { *slot });
+
+    // Fails, the slot holds `py`.
+    let old = unsafe { compare_exchange_ptr(slot_ptr, px, px) };
+    print(unsafe { *old });
+
+    // Succeeds, and the stored pointer can be used to access `x`.
+    let old = unsafe { compare_exchange_ptr(slot_ptr, py, px) };
+    print(unsafe { *old });
+    unsafe { *atomic_load_ptr(slot_ptr) = 3 };
+    print(x);
+}
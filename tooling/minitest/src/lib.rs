@@ -2,9 +2,17 @@
 #![feature(freeze)]
 
 pub use miniutil::BasicMem;
+pub use miniutil::Retarget;
+pub use miniutil::TreeBorrowMem;
+pub use miniutil::analysis::*;
 pub use miniutil::build::*;
 pub use miniutil::fmt::*;
+pub use miniutil::promela::*;
+pub use miniutil::report::*;
 pub use miniutil::run::*;
+pub use miniutil::stdlib;
+pub use miniutil::symbolic::*;
+pub use miniutil::transform::*;
 
 pub use minirust_rs::libspecr::hidden::*;
 pub use minirust_rs::libspecr::prelude::*;
@@ -21,18 +29,108 @@ pub use std::string::String;
 
 mod tests;
 
+std::thread_local! {
+    /// The programs dumped by the test running on this thread, to be printed if it fails,
+    /// together with the name of that test.
+    static PENDING_DUMPS: std::cell::RefCell<(Option<String>, Vec<String>)> = Default::default();
+}
+
+/// Runs `f` on the pending dumps of the current test.
+/// The test harness names the thread running a test after the test, so when a thread runs another test,
+/// the dumps of the previous one (which passed, or they would have been printed) are dropped first.
+fn with_pending_dumps<O>(f: impl FnOnce(&mut Vec<String>) -> O) -> O {
+    let test = std::thread::current().name().map(str::to_owned);
+    PENDING_DUMPS.with(|pending| {
+        let (owner, dumps) = &mut *pending.borrow_mut();
+        if *owner != test {
+            *owner = test;
+            dumps.clear();
+        }
+        f(dumps)
+    })
+}
+
+/// Dump the program, but only if the current test fails: the dump is printed when the test panics.
+/// Setting the `MINIRUST_DUMP` environment variable prints every dump right away instead,
+/// with colors if it is set to `color`.
+///
+/// This shadows `miniutil::fmt::dump_program`, which always prints.
+pub fn dump_program(prog: Program) {
+    dump_with(|options| fmt_program_with(prog, options));
+}
+
+/// Like `dump_program`, but shows the names of fields, locals and blocks and the comments
+/// given while building the program with `p`.
+pub fn dump_program_with_debug_info(prog: Program, p: &ProgramBuilder) {
+    dump_with(|options| {
+        fmt_program_with_debug_info(prog, options, p.field_names(), p.debug_info())
+    });
+}
+
+fn dump_with(fmt: impl Fn(FmtOptions) -> String) {
+    if let Some(dump) = std::env::var_os("MINIRUST_DUMP") {
+        println!("{}", fmt(FmtOptions { color: dump == "color" }));
+        return;
+    }
+
+    static INSTALL_HOOK: std::sync::Once = std::sync::Once::new();
+    INSTALL_HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            for dump in take_pending_dumps() {
+                eprintln!("{dump}");
+            }
+            default_hook(info);
+        }));
+    });
+    with_pending_dumps(|dumps| dumps.push(fmt(FmtOptions::default())));
+}
+
+/// Remove and return the dumps that the current thread has not printed yet.
+pub fn take_pending_dumps() -> Vec<String> {
+    with_pending_dumps(std::mem::take)
+}
+
 #[track_caller]
 pub fn assert_stop<M: Memory>(prog: Program) {
     assert_eq!(run_program::<M>(prog), TerminationInfo::MachineStop);
 }
 
+/// Run the program once for each scheduler seed in `0..seeds`, and check that every run ends with `expected`.
+/// A failure names the seed, so it can be reproduced with `run_program_with_seed`.
 #[track_caller]
-pub fn assert_stop_always<M: Memory>(prog: Program, attempts: usize) {
-    for _ in 0..attempts {
-        assert_eq!(run_program::<M>(prog), TerminationInfo::MachineStop);
+pub fn assert_always<M: Memory>(prog: Program, seeds: usize, expected: TerminationInfo) {
+    for seed in 0..seeds {
+        let info = run_program_with_seed::<M>(prog, seed);
+        assert_eq!(info, expected, "unexpected outcome with scheduler seed {seed}");
     }
 }
 
+/// Run the program once for each scheduler seed in `0..seeds`, and check that some run ends with `expected`.
+/// All other runs have to stop normally.
+#[track_caller]
+pub fn assert_sometimes<M: Memory>(prog: Program, seeds: usize, expected: TerminationInfo) {
+    for seed in 0..seeds {
+        match run_program_with_seed::<M>(prog, seed) {
+            info if info == expected => return,
+            TerminationInfo::MachineStop => continue,
+            info => panic!("unexpected outcome with scheduler seed {seed}: {info:?}"),
+        }
+    }
+    panic!("did not get {expected:?} with any of {seeds} scheduler seeds");
+}
+
+#[track_caller]
+pub fn assert_stop_always<M: Memory>(prog: Program, seeds: usize) {
+    assert_always::<M>(prog, seeds, TerminationInfo::MachineStop);
+}
+
+#[track_caller]
+pub fn assert_ub_sometimes<M: Memory>(prog: Program, seeds: usize, msg: &str) {
+    let msg = minirust_rs::prelude::String::from_internal(msg.to_string());
+    assert_sometimes::<M>(prog, seeds, TerminationInfo::Ub(msg));
+}
+
 #[track_caller]
 pub fn assert_abort<M: Memory>(prog: Program) {
     assert_eq!(run_program::<M>(prog), TerminationInfo::Abort);
@@ -46,6 +144,15 @@ pub fn assert_ub<M: Memory>(prog: Program, msg: &str) {
     );
 }
 
+/// Like `assert_ub`, but without checking well-formedness first (see `unchecked_config`).
+#[track_caller]
+pub fn assert_ub_unchecked<M: Memory>(prog: Program, msg: &str) {
+    assert_eq!(
+        run_program_with_config::<M>(prog, unchecked_config()),
+        TerminationInfo::Ub(minirust_rs::prelude::String::from_internal(msg.to_string()))
+    );
+}
+
 #[track_caller]
 pub fn assert_ub_eventually<M: Memory>(prog: Program, attempts: usize, msg: &str) {
     let msg = minirust_rs::prelude::String::from_internal(msg.to_string());
@@ -64,6 +171,46 @@ pub fn assert_ub_eventually<M: Memory>(prog: Program, attempts: usize, msg: &str
     panic!("did not get expected output after {} attempts", attempts);
 }
 
+/// Run the program multiple times, guessing provenance in `with_exposed_provenance` demonically.
+/// Checks that the program stops no matter which provenance gets picked.
+#[track_caller]
+pub fn assert_stop_demonic_provenance<M: Memory>(prog: Program, attempts: usize) {
+    let mut config = default_config();
+    config.provenance_choice = ProvenanceChoice::Demonic;
+    for _ in 0..attempts {
+        assert_eq!(run_program_with_config::<M>(prog, config), TerminationInfo::MachineStop);
+    }
+}
+
+/// Run the program multiple times, guessing provenance in `with_exposed_provenance` demonically.
+/// Checks that some provenance choice leads to the given UB.
+#[track_caller]
+pub fn assert_ub_eventually_demonic_provenance<M: Memory>(
+    prog: Program,
+    attempts: usize,
+    msg: &str,
+) {
+    let mut config = default_config();
+    config.provenance_choice = ProvenanceChoice::Demonic;
+    let msg = minirust_rs::prelude::String::from_internal(msg.to_string());
+    for _ in 0..attempts {
+        match run_program_with_config::<M>(prog, config) {
+            TerminationInfo::MachineStop => continue,
+            TerminationInfo::Ub(res) if res == msg => {
+                // Got the expected result.
+                return;
+            }
+            termination_info => {
+                panic!(
+                    "unexpected outcome in `assert_ub_eventually_demonic_provenance`: {:?}",
+                    termination_info
+                );
+            }
+        }
+    }
+    panic!("did not get expected output after {} attempts", attempts);
+}
+
 /// Create program that assigns `expr` to local of type T and checks if it causes UB.
 #[track_caller]
 pub fn assert_ub_expr<T: TypeConv + Freeze, M: Memory>(expr: ValueExpr, msg: &str) {
@@ -81,6 +228,122 @@ pub fn assert_ub_expr<T: TypeConv + Freeze, M: Memory>(expr: ValueExpr, msg: &st
     assert_ub::<M>(p, msg);
 }
 
+/// Create program that loads a `T` through a misaligned pointer and checks that this is UB.
+#[track_caller]
+pub fn assert_misaligned_load_ub<T: TypeConv + Freeze, M: Memory>() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.misaligned_ptr::<T>(1);
+    let local = f.declare_local::<T>();
+    f.storage_live(local);
+    f.assign(local, load(deref(ptr, T::get_type())));
+    f.exit();
+
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<M>(p, "loading from a place based on a misaligned pointer");
+}
+
+/// Create program that stores `val` through a misaligned `*const T` and checks that this is UB.
+#[track_caller]
+pub fn assert_misaligned_store_ub<T: TypeConv, M: Memory>(val: ValueExpr) {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.misaligned_ptr::<T>(1);
+    f.assign(deref(ptr, T::get_type()), val);
+    f.exit();
+
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<M>(p, "storing to a place based on a misaligned pointer");
+}
+
+/// Create program that creates a `&T` from a misaligned pointer and checks that this is UB.
+#[track_caller]
+pub fn assert_misaligned_ref_ub<T: TypeConv + Freeze, M: Memory>() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.misaligned_ptr::<T>(1);
+    let local = f.declare_local::<&T>();
+    f.storage_live(local);
+    f.assign(local, addr_of(deref(ptr, T::get_type()), <&T>::get_type()));
+    f.exit();
+
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<M>(p, "Value::Ptr: unaligned safe pointer");
+}
+
+/// Run the program once and check that block `bb` of function `func` is not executed,
+/// no matter how the program terminates.
+#[track_caller]
+pub fn assert_block_never_reached<M: Memory>(prog: Program, func: FnName, bb: BbName) {
+    let marker = format!(
+        "coverage: reached block {} of function {}",
+        bb.0.get_internal(),
+        func.0.get_internal()
+    );
+    let (_info, stderr) = get_stderr::<M>(mark_block(prog, func, bb, &marker));
+    assert!(!stderr.contains(&marker), "block was reached");
+}
+
+/// Check that `transform` preserves the semantics of each of the `progs`.
+///
+/// Every program is run once per scheduler seed in `seeds` (see `Config::schedule_seed`),
+/// before and after the transformation. The transformed program must only show behaviors that were
+/// also observed for the original with any of the seeds, unless the original can have UB or is ill-formed,
+/// in which case any behavior is fine. The transformed program must never be ill-formed.
+/// A failure names the program and the seed of the offending run of the transformed program.
+/// This is a test and not a proof: behaviors that the runs of the original miss are reported
+/// as differences, so non-deterministic programs need enough seeds.
+#[track_caller]
+pub fn assert_preserves_semantics<M: Memory>(
+    transform: impl Fn(Program) -> Program,
+    progs: &[Program],
+    seeds: &[usize],
+) {
+    let run = |prog: Program, seed: usize| {
+        let mut config = default_config();
+        config.schedule_seed = Some(Int::from(seed));
+        observe_with_config::<M>(prog, config)
+    };
+
+    for (idx, prog) in progs.iter().enumerate() {
+        let transformed = transform(*prog);
+
+        let mut original = Vec::new();
+        for &seed in seeds {
+            let behavior = run(*prog, seed);
+            if !original.contains(&behavior) {
+                original.push(behavior);
+            }
+        }
+        let original_ub = original.iter().any(|b| {
+            matches!(b.termination, TerminationInfo::Ub(_) | TerminationInfo::IllFormed(_))
+        });
+
+        for &seed in seeds {
+            let behavior = run(transformed, seed);
+            if let TerminationInfo::IllFormed(msg) = behavior.termination {
+                panic!(
+                    "program {idx} is ill-formed after the transformation (seed {seed}): {}",
+                    msg.get_internal()
+                );
+            }
+            if !original_ub && !original.contains(&behavior) {
+                panic!(
+                    "program {idx} changed behavior under the transformation with seed {seed}:\n\
+                     before: {original:?}\n\
+                     after: {behavior:?}"
+                );
+            }
+        }
+    }
+}
+
 #[track_caller]
 pub fn assert_ill_formed<M: Memory>(prog: Program, msg: &str) {
     let TerminationInfo::IllFormed(info) = run_program::<M>(prog) else {
@@ -99,6 +362,79 @@ pub fn assert_memory_leak<M: Memory>(prog: Program) {
     assert_eq!(run_program::<M>(prog), TerminationInfo::MemoryLeak);
 }
 
+#[track_caller]
+pub fn assert_stop_on_all_targets<M: Retarget>(prog: Program) {
+    for (target, info) in run_program_on_all_targets::<M>(prog) {
+        assert_eq!(info, TerminationInfo::MachineStop, "unexpected outcome on target `{target}`");
+    }
+}
+
+#[track_caller]
+pub fn assert_abort_on_all_targets<M: Retarget>(prog: Program) {
+    for (target, info) in run_program_on_all_targets::<M>(prog) {
+        assert_eq!(info, TerminationInfo::Abort, "unexpected outcome on target `{target}`");
+    }
+}
+
+#[track_caller]
+pub fn assert_ub_on_all_targets<M: Retarget>(prog: Program, msg: &str) {
+    let msg = minirust_rs::prelude::String::from_internal(msg.to_string());
+    for (target, info) in run_program_on_all_targets::<M>(prog) {
+        assert_eq!(info, TerminationInfo::Ub(msg), "unexpected outcome on target `{target}`");
+    }
+}
+
+#[track_caller]
+pub fn assert_ill_formed_on_all_targets<M: Retarget>(prog: Program, msg: &str) {
+    for (target, info) in run_program_on_all_targets::<M>(prog) {
+        let TerminationInfo::IllFormed(info) = info else {
+            panic!("program is not ill formed on target `{target}`!")
+        };
+        assert_eq!(
+            info.get_internal(),
+            msg,
+            "program is ill-formed with a different error message on target `{target}`"
+        );
+    }
+}
+
+/// Run the program with the stack depth limited to `max_stack_depth` frames,
+/// and check that it exhausts the stack.
+#[track_caller]
+pub fn assert_stack_overflow<M: Memory>(prog: Program, max_stack_depth: usize) {
+    let mut config = default_config();
+    config.max_stack_depth = Some(Int::from(max_stack_depth));
+    assert_eq!(run_program_with_config::<M>(prog, config), TerminationInfo::StackExhausted);
+}
+
+/// Run the program with each thread's stack limited to `max_stack_size` bytes,
+/// and check that it exhausts the stack.
+#[track_caller]
+pub fn assert_stack_size_exhausted<M: Memory>(prog: Program, max_stack_size: usize) {
+    let mut config = default_config();
+    config.max_stack_size = Some(Size::from_bytes(max_stack_size).unwrap());
+    assert_eq!(run_program_with_config::<M>(prog, config), TerminationInfo::StackExhausted);
+}
+
+/// Run the program with varying targets, schedules and other non-deterministic choices for `duration`,
+/// and check that it always behaves the same. See `chaos`.
+#[track_caller]
+pub fn chaos_test<M: Retarget>(prog: Program, duration: std::time::Duration) {
+    let report = chaos::<M>(prog, duration);
+    if !report.is_deterministic() {
+        let behaviors: Vec<String> = report
+            .behaviors
+            .iter()
+            .map(|(behavior, run)| format!("{run:?}: {behavior:?}"))
+            .collect();
+        panic!(
+            "the program behaved differently across {} runs:\n{}",
+            report.runs,
+            behaviors.join("\n")
+        );
+    }
+}
+
 /// Run the program multiple times. Checks if we get a data race in some execution
 /// This automatically fails if the program does not terminate correctly if the data race did not occur.
 #[track_caller]
@@ -0,0 +1,190 @@
+use crate::*;
+
+fn compatible(caller_ty: Type, callee_ty: Type) -> bool {
+    check_abi_compatibility::<miniutil::DefaultTarget>(caller_ty, callee_ty)
+}
+
+/// Calls a function with argument type `callee_ty` that prints `print` (an expression on its argument),
+/// passing `arg` for that argument.
+fn call_with(
+    callee_ty: Type,
+    arg: impl Fn(&mut FunctionBuilder) -> ValueExpr,
+    print: fn(PlaceExpr) -> ValueExpr,
+) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let callee = {
+        let mut f = p.declare_function();
+        let x = f.declare_arg_with_ty(callee_ty);
+        f.print(print(x));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let arg = arg(&mut f);
+        f.call_ignoreret(fn_ptr(callee), &[by_value(arg)]);
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+#[test]
+fn transparent_struct_arg() {
+    let wrapper = transparent_ty(<u32>::get_type(), &[<()>::get_type(), <[u8; 0]>::get_type()]);
+    let p = call_with(
+        <u32>::get_type(),
+        |_f| tuple(&[const_int(7u32), unit(), array(&[], <u8>::get_type())], wrapper),
+        load,
+    );
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7"]);
+}
+
+#[test]
+fn transparent_struct_param() {
+    let wrapper = transparent_ty(<u32>::get_type(), &[]);
+    let p = call_with(wrapper, |_f| const_int(7u32), |x| load(field(x, 0)));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7"]);
+}
+
+/// A struct with two fields is not a wrapper, even if one of them is a ZST with a larger alignment.
+#[test]
+fn struct_with_two_fields() {
+    let pair = tuple_ty(
+        &[(offset(0), <u16>::get_type()), (offset(2), <u16>::get_type())],
+        size(4),
+        align(2),
+    );
+    assert!(!compatible(pair, <u32>::get_type()));
+    let aligned_zst = tuple_ty(&[], size(0), align(4));
+    let over_aligned =
+        tuple_ty(&[(offset(0), <u16>::get_type()), (offset(0), aligned_zst)], size(4), align(4));
+    assert!(!compatible(over_aligned, <u16>::get_type()));
+}
+
+#[test]
+fn nested_wrappers() {
+    let inner = transparent_ty(<u64>::get_type(), &[]);
+    let outer = transparent_ty(inner, &[<()>::get_type()]);
+    assert!(compatible(outer, <u64>::get_type()));
+    assert!(compatible(<u64>::get_type(), outer));
+    assert!(compatible(outer, transparent_ty(<u64>::get_type(), &[])));
+    assert!(compatible(outer, <i64>::get_type()));
+    assert!(!compatible(outer, <u32>::get_type()));
+}
+
+/// An enum with a single untagged variant, like `NonZeroU32`, is passed like its field.
+#[test]
+fn nonzero_int() {
+    let u32_int = IntType { signed: Signedness::Unsigned, size: size(4) };
+    assert!(compatible(nonzero_ty(u32_int), <u32>::get_type()));
+    assert!(compatible(nonzero_ty(u32_int), <i32>::get_type()));
+    assert!(!compatible(nonzero_ty(u32_int), <u64>::get_type()));
+}
+
+#[test]
+fn nullable_ref_arg() {
+    let ref_ty = ref_ty_default_markers_for(<u32>::get_type());
+    let option_ty = nullable_ptr_ty(ref_ty);
+    let p = call_with(
+        ref_ty,
+        |f| {
+            let x = f.declare_local::<u32>();
+            f.storage_live(x);
+            f.assign(x, const_int(7u32));
+            variant(0, addr_of(x, ref_ty), option_ty)
+        },
+        |x| load(deref(load(x), <u32>::get_type())),
+    );
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7"]);
+}
+
+/// `Option<&T>` is also compatible with raw pointers, and `None` is passed as the null pointer.
+#[test]
+fn nullable_ref_none_as_raw_ptr() {
+    let option_ty = nullable_ptr_ty(ref_ty_default_markers_for(<u32>::get_type()));
+    let p = call_with(
+        <*const u32>::get_type(),
+        |_f| variant(1, tuple(&[], tuple_ty(&[], size(8), align(8))), option_ty),
+        |x| eq(ptr_addr(load(x)), const_int(0usize)),
+    );
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+}
+
+#[test]
+fn nullable_fn_ptr() {
+    let fn_ptr_ty = Type::Ptr(PtrType::FnPtr);
+    assert!(compatible(nullable_ptr_ty(fn_ptr_ty), fn_ptr_ty));
+    assert!(compatible(transparent_ty(nullable_ptr_ty(fn_ptr_ty), &[]), fn_ptr_ty));
+}
+
+/// Raw pointers can be null, so `Option<*const T>` needs a tag and is not a wrapper.
+/// Neither is `Option<bool>`, which uses a niche other than zero.
+#[test]
+fn not_nullable() {
+    let raw_ptr = <*const u32>::get_type();
+    let option_raw_ptr = enum_ty::<u8>(
+        &[
+            (
+                0,
+                enum_variant(
+                    tuple_ty(&[], size(16), align(8)),
+                    &[(offset(0), (IntType::U8, Int::ZERO))],
+                ),
+            ),
+            (
+                1,
+                enum_variant(
+                    tuple_ty(&[(offset(8), raw_ptr)], size(16), align(8)),
+                    &[(offset(0), (IntType::U8, Int::ONE))],
+                ),
+            ),
+        ],
+        discriminator_branch::<u8>(
+            offset(0),
+            discriminator_invalid(),
+            &[((0, 1), discriminator_known(0)), ((1, 2), discriminator_known(1))],
+        ),
+        size(16),
+        align(8),
+    );
+    assert!(!compatible(option_raw_ptr, raw_ptr));
+
+    let option_bool = niche_enum_ty(<bool>::get_type(), Niche::bool(offset(0)), 1);
+    assert!(!compatible(option_bool, <bool>::get_type()));
+    assert!(!compatible(option_bool, <u8>::get_type()));
+}
+
+/// Integers of the same size are compatible no matter their sign; the callee sees the same bytes.
+#[test]
+fn int_signedness_arg() {
+    let p = call_with(<i32>::get_type(), |_f| const_int(u32::MAX), load);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["-1"]);
+    assert!(compatible(transparent_ty(<u32>::get_type(), &[]), <i32>::get_type()));
+    assert!(!compatible(<u32>::get_type(), <i64>::get_type()));
+}
+
+/// Thin pointers are compatible with pointer-sized integers; the callee gets the address.
+#[test]
+fn ptr_and_usize_arg() {
+    let p =
+        call_with(<usize>::get_type(), |_f| ptr_to_ptr(unit_ptr(), <*const u8>::get_type()), load);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+    assert!(compatible(<usize>::get_type(), <*const u8>::get_type()));
+    assert!(compatible(<*const u8>::get_type(), <isize>::get_type()));
+    assert!(compatible(ref_ty_default_markers_for(<u8>::get_type()), <usize>::get_type()));
+    assert!(!compatible(<*const u8>::get_type(), <u32>::get_type()));
+    assert!(!compatible(<*const [u8]>::get_type(), <usize>::get_type()));
+}
+
+/// Thin pointers are compatible with pointer-sized integers also on 32-bit targets.
+#[test]
+fn ptr_and_usize_32bit() {
+    let compatible = check_abi_compatibility::<arm>;
+    assert!(compatible(<*const u8>::get_type(), <u32>::get_type()));
+    assert!(!compatible(<*const u8>::get_type(), <u64>::get_type()));
+}
@@ -120,10 +120,8 @@ fn store_place_misaligned() {
 fn deref_misaligned_ref() {
     let locals = [<*const i32>::get_type(), <*const u8>::get_type()];
     let b0 = block!(storage_live(0), allocate(const_int(4usize), const_int(4usize), local(0), 1,));
-    let u8ptr = ptr_to_ptr(load(local(0)), <*const u8>::get_type());
     // make the pointer definitely not 2-aligned
-    let nonaligned = ptr_offset(u8ptr, const_int(1usize), InBounds::Yes);
-    let u16ptr = ptr_to_ptr(nonaligned, <*const u16>::get_type());
+    let u16ptr = misaligned(load(local(0)), 1, <*const u16>::get_type());
     let u16ref = transmute(u16ptr, <&u16>::get_type());
     let b1 = block!(
         storage_live(1),
@@ -174,10 +172,8 @@ fn addr_of_misaligned_ref() {
         assign(local(1), addr_of(local(1), <*const i32>::get_type()),),
         goto(1),
     );
-    let u8ptr = ptr_to_ptr(load(local(1)), <*const u8>::get_type());
     // make the pointer definitely not 2-aligned
-    let nonaligned = ptr_offset(u8ptr, const_int(1usize), InBounds::Yes);
-    let u16ptr = ptr_to_ptr(nonaligned, <*const u16>::get_type());
+    let u16ptr = misaligned(load(local(1)), 1, <*const u16>::get_type());
     let b1 = block!(
         storage_live(2),
         assign(
@@ -203,10 +199,8 @@ fn addr_of_misaligned_ptr() {
         assign(local(1), addr_of(local(1), <*const i32>::get_type()),),
         goto(1),
     );
-    let u8ptr = ptr_to_ptr(load(local(1)), <*const u8>::get_type());
     // make the pointer definitely not 2-aligned
-    let nonaligned = ptr_offset(u8ptr, const_int(1usize), InBounds::Yes);
-    let u16ptr = ptr_to_ptr(nonaligned, <*const u16>::get_type());
+    let u16ptr = misaligned(load(local(1)), 1, <*const u16>::get_type());
     let b1 = block!(
         storage_live(2),
         assign(
@@ -221,3 +215,80 @@ fn addr_of_misaligned_ptr() {
     let p = program(&[f]);
     assert_stop::<BasicMem>(p);
 }
+
+#[test]
+fn misaligned_load() {
+    assert_misaligned_load_ub::<u16, BasicMem>();
+    assert_misaligned_load_ub::<u32, BasicMem>();
+    assert_misaligned_load_ub::<u64, BasicMem>();
+}
+
+#[test]
+fn misaligned_store() {
+    assert_misaligned_store_ub::<u16, BasicMem>(const_int(0u16));
+    assert_misaligned_store_ub::<u32, BasicMem>(const_int(0u32));
+    assert_misaligned_store_ub::<u64, BasicMem>(const_int(0u64));
+}
+
+#[test]
+fn misaligned_ref() {
+    assert_misaligned_ref_ub::<u16, BasicMem>();
+    assert_misaligned_ref_ub::<u32, BasicMem>();
+    assert_misaligned_ref_ub::<u64, BasicMem>();
+}
+
+/// Misaligned raw pointers are fine as long as they are not used for an access.
+#[test]
+fn misaligned_raw_ptr() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.misaligned_ptr::<u32>(3);
+    let raw = f.declare_local::<*const u32>();
+    f.storage_live(raw);
+    f.assign(raw, addr_of(deref(ptr, <u32>::get_type()), <*const u32>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop::<BasicMem>(p);
+}
+
+/// An over-aligned byte buffer can be accessed at the larger alignment.
+#[test]
+fn overaligned_local() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let buf =
+        f.declare_local_with_align::<miniutil::DefaultTarget>(<[u8; 8]>::get_type(), align(64));
+    f.storage_live_aligned(buf);
+    f.assert_eq(
+        rem(ptr_addr(addr_of(buf, <*const u8>::get_type())), const_int(64_usize)),
+        const_int(0_usize),
+    );
+    f.assign(buf, array(&[const_int(0_u8); 8], <u8>::get_type()));
+    // Access the buffer as a `u64`.
+    let as_u64 = deref(addr_of(buf, <*const u64>::get_type()), <u64>::get_type());
+    f.assign(as_u64, const_int(42_u64));
+    f.print(load(as_u64));
+    f.storage_dead_aligned(buf);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    for _ in 0..8 {
+        assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+    }
+}
+
+/// `storage_live` only accepts plain locals, not the field that `declare_local_with_align` returns.
+#[test]
+#[should_panic(expected = "PlaceExpr is not a local")]
+fn overaligned_local_plain_storage_live() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let buf = f.declare_local_with_align::<miniutil::DefaultTarget>(<u8>::get_type(), align(64));
+    f.storage_live(buf);
+}
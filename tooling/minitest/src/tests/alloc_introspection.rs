@@ -0,0 +1,254 @@
+use crate::*;
+
+#[test]
+fn dereferenceable_local() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<[u32; 4]>();
+    let b = f.declare_local::<bool>();
+    let size = f.declare_local::<usize>();
+    f.storage_live(x);
+    f.storage_live(b);
+    f.storage_live(size);
+    let second = addr_of(index(x, const_int(1_usize)), <*const u32>::get_type());
+    f.is_dereferenceable(b, second, const_int(12_usize));
+    f.print(load(b));
+    f.is_dereferenceable(b, second, const_int(13_usize));
+    f.print(load(b));
+    f.allocation_size(size, second);
+    f.print(load(size));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true", "false", "16"]);
+}
+
+#[test]
+fn dereferenceable_after_free() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*const u8>();
+    let b = f.declare_local::<bool>();
+    f.storage_live(ptr);
+    f.storage_live(b);
+    f.allocate(const_int(8_usize), const_int(1_usize), ptr);
+    f.is_dereferenceable(b, load(ptr), const_int(8_usize));
+    f.print(load(b));
+    f.deallocate(load(ptr), const_int(8_usize), const_int(1_usize));
+    f.is_dereferenceable(b, load(ptr), const_int(8_usize));
+    f.print(load(b));
+    // Zero-sized accesses are always fine.
+    f.is_dereferenceable(b, load(ptr), const_int(0_usize));
+    f.print(load(b));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true", "false", "true"]);
+}
+
+#[test]
+fn allocation_size_without_provenance() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let size = f.declare_local::<usize>();
+    f.storage_live(size);
+    f.allocation_size(size, null());
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(
+        p,
+        "`AllocationSize` intrinsic called on pointer without live allocation",
+    );
+}
+
+/// Prints the init mask of the `N` bytes `ptr` points to, one byte per line.
+fn print_init_mask<const N: usize>(f: &mut FunctionBuilder, ptr: ValueExpr) {
+    let mask = f.declare_local::<[u8; N]>();
+    f.storage_live(mask);
+    f.init_mask(mask, ptr);
+    for i in 0..N {
+        f.print(load(index(mask, const_int(i))));
+    }
+}
+
+#[test]
+fn init_mask_padding() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let pair_ty =
+        tuple_ty(&[(size(0), <u8>::get_type()), (size(2), <u16>::get_type())], size(4), align(2));
+    let pair = f.declare_local_with_ty(pair_ty);
+    f.storage_live(pair);
+    print_init_mask::<4>(&mut f, addr_of(pair, <*const u8>::get_type()));
+    // Storing the pair leaves the padding byte uninitialized.
+    f.assign(pair, tuple(&[const_int(1u8), const_int(2u16)], pair_ty));
+    print_init_mask::<4>(&mut f, addr_of(pair, <*const u8>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "0", "0", "0", "1", "0", "1", "1"]);
+}
+
+#[test]
+fn init_mask_partial_and_ptr() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let ptr = f.declare_local::<*const u32>();
+    f.storage_live(x);
+    f.storage_live(ptr);
+    f.assign(ptr, addr_of(x, <*const u32>::get_type()));
+    f.assign(u8_at(load(ptr), 2), const_int(7u8));
+    print_init_mask::<4>(&mut f, load(ptr));
+    // All bytes of a pointer carry its provenance.
+    print_init_mask::<8>(&mut f, addr_of(ptr, <*const u8>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(
+        get_stdout::<BasicMem>(p).unwrap(),
+        &["0", "0", "1", "0", "2", "2", "2", "2", "2", "2", "2", "2"]
+    );
+}
+
+/// Looking at the bytes is not a read, so it is allowed even through a reference
+/// that the aliasing model no longer allows reading through.
+#[test]
+fn init_mask_is_not_an_access() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let r = f.declare_local::<&mut u32>();
+    f.storage_live(x);
+    f.storage_live(r);
+    f.assign(x, const_int(0u32));
+    f.assign(r, addr_of(x, <&mut u32>::get_type()));
+    f.validate(r, false);
+    // Writing to `x` directly disables `r`.
+    f.assign(x, const_int(1u32));
+    print_init_mask::<4>(&mut f, load(r));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<TreeBorrowMem>(p).unwrap(), &["1", "1", "1", "1"]);
+}
+
+#[test]
+fn init_mask_wrong_return_type() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let mask = f.declare_local::<[u16; 4]>();
+    f.storage_live(x);
+    f.storage_live(mask);
+    f.init_mask(mask, addr_of(x, <*const u32>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `InitMask`: expected an array of `u8`",
+    );
+}
+
+#[test]
+fn provenance_history_retag_and_expose() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let r = f.declare_local_with_ty(ref_mut_ty_default_markers_for(<u32>::get_type()));
+    let addr = f.declare_local::<usize>();
+    let ptr = f.declare_local::<*mut u32>();
+    f.storage_live(x);
+    f.storage_live(r);
+    f.storage_live(addr);
+    f.storage_live(ptr);
+    f.assign(r, addr_of(x, ref_mut_ty_default_markers_for(<u32>::get_type())));
+    f.expose_provenance(addr, load(r));
+    f.with_exposed_provenance(ptr, load(addr));
+    f.print_provenance_history(load(ptr));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    // The cast guesses the provenance demonically, so it may also pick no provenance,
+    // which has no history. Try until the exposed provenance gets picked.
+    let mut config = default_config();
+    config.provenance_choice = ProvenanceChoice::Demonic;
+    config.record_provenance_history = true;
+    let lines = (0..32)
+        .map(|_| observe_with_config::<TreeBorrowMem>(p, config))
+        .inspect(|behavior| assert_eq!(behavior.termination, TerminationInfo::MachineStop))
+        .map(|behavior| behavior.stderr)
+        .find(|lines| lines != &["pointer without provenance"])
+        .expect("the exposed provenance was never picked");
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].ends_with(": allocated (Stack)"));
+    assert!(lines[1].contains(": retagged from "));
+    assert!(lines[2].ends_with(": exposed"));
+    assert!(lines[3].ends_with(": picked for an integer-to-pointer cast"));
+}
+
+#[test]
+fn provenance_history_transmute() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*const u8>();
+    let copy = f.declare_local::<*const u8>();
+    f.storage_live(ptr);
+    f.storage_live(copy);
+    f.allocate(const_int(4_usize), const_int(1_usize), ptr);
+    f.assign(copy, transmute(load(ptr), <*const u8>::get_type()));
+    f.print_provenance_history(load(copy));
+    f.deallocate(load(ptr), const_int(4_usize), const_int(1_usize));
+    // Pointers without provenance have no history.
+    f.print_provenance_history(null());
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    let mut config = default_config();
+    config.record_provenance_history = true;
+    let behavior = observe_with_config::<BasicMem>(p, config);
+    assert_eq!(behavior.termination, TerminationInfo::MachineStop);
+    let lines = behavior.stderr;
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(": allocated (Heap)"));
+    assert!(lines[1].ends_with(": transmuted"));
+    assert_eq!(lines[2], "pointer without provenance");
+}
+
+/// Without `record_provenance_history`, there is no history to print.
+#[test]
+fn provenance_history_not_recorded() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.print_provenance_history(addr_of(x, <*const u32>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    let (termination, lines) = get_stderr::<BasicMem>(p);
+    assert_eq!(termination, TerminationInfo::MachineStop);
+    assert_eq!(lines, &["provenance history is not recorded"]);
+}
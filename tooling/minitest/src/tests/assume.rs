@@ -32,7 +32,10 @@ fn assume_wrong_argnum() {
     let b1 = block!(exit());
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Assume` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Assume`",
+    );
 }
 
 #[test]
@@ -47,5 +50,42 @@ fn assume_wrong_argty() {
     let b1 = block!(exit());
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid argument for `Assume` intrinsic: not a Boolean");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Assume`: expected a Boolean",
+    );
+}
+
+#[test]
+fn assume_in_range_true() {
+    let locals = [];
+    let b0 = block!(assume_in_range(const_int(5u8), const_int(5u8), const_int(10u8), 1));
+    let b1 = block!(assume_in_range(const_int(-3i8), const_int(-3i32), const_int(10u64), 2));
+    let b2 = block!(exit());
+    let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
+    let p = program(&[f]);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn assume_out_of_range() {
+    let locals = [];
+    let b0 = block!(assume_in_range(const_int(11u8), const_int(5u8), const_int(10u8), 1));
+    let b1 = block!(exit());
+    let f = function(Ret::No, 0, &locals, &[b0, b1]);
+    let p = program(&[f]);
+    assert_ub::<BasicMem>(p, "`AssumeInRange` intrinsic called on value outside of the range");
+}
+
+#[test]
+fn assume_in_range_wrong_argty() {
+    let locals = [];
+    let b0 = block!(assume_in_range(const_bool(true), const_int(0u8), const_int(1u8), 1));
+    let b1 = block!(exit());
+    let f = function(Ret::No, 0, &locals, &[b0, b1]);
+    let p = program(&[f]);
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `AssumeInRange`: expected an integer",
+    );
 }
@@ -34,7 +34,10 @@ fn atomic_store_arg_count() {
 
     let f = function(Ret::No, 0, &[], &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `AtomicStore` intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `AtomicStore`",
+    )
 }
 
 #[test]
@@ -49,9 +52,9 @@ fn atomic_store_arg_type1() {
 
     let f = function(Ret::No, 0, &[], &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         p,
-        "invalid first argument to `AtomicStore` intrinsic: not a thin pointer",
+        "Terminator::Intrinsic: invalid argument 0 for `AtomicStore`: expected a thin pointer",
     )
 }
 
@@ -125,7 +128,10 @@ fn atomic_store_ret_type() {
 
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid return type for `AtomicStore` intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `AtomicStore`: expected the unit type",
+    )
 }
 
 #[test]
@@ -167,7 +173,10 @@ fn atomic_load_arg_count() {
 
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `AtomicLoad` intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `AtomicLoad`",
+    )
 }
 
 #[test]
@@ -187,7 +196,10 @@ fn atomic_load_arg_type() {
 
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid first argument to `AtomicLoad` intrinsic: not a thin pointer")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `AtomicLoad`: expected a thin pointer",
+    )
 }
 
 #[test]
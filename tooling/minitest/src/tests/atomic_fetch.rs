@@ -46,7 +46,10 @@ fn atomic_fetch_arg_count() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `AtomicFetchAndOp` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `AtomicFetchAndOp(Add)`",
+    );
 }
 
 #[test]
@@ -64,9 +67,9 @@ fn atomic_fetch_arg_1() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         p,
-        "invalid first argument to `AtomicFetchAndOp` intrinsic: not a thin pointer",
+        "Terminator::Intrinsic: invalid argument 0 for `AtomicFetchAndOp(Add)`: expected a thin pointer",
     );
 }
 
@@ -112,9 +115,9 @@ fn atomic_fetch_ret_ty() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         p,
-        "invalid return type for `AtomicFetchAndOp` intrinsic: only works with integers",
+        "Terminator::Intrinsic: invalid argument 1 for `AtomicFetchAndOp(Add)`: expected an integer",
     );
 }
 
@@ -0,0 +1,108 @@
+use crate::*;
+
+/// A pointer stored and loaded atomically keeps its provenance.
+#[test]
+fn atomic_store_load_ptr() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let slot = f.declare_local::<*const u32>();
+    let loaded = f.declare_local::<*const u32>();
+    f.storage_live(x);
+    f.storage_live(slot);
+    f.storage_live(loaded);
+    f.assign(x, const_int(42u32));
+    let slot_ptr = addr_of(slot, <*const *const u32>::get_type());
+    f.atomic_store(slot_ptr, addr_of(x, <*const u32>::get_type()));
+    f.atomic_load(loaded, slot_ptr);
+    f.print(load(deref(load(loaded), <u32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+/// Compare-exchange on a pointer location stores the new pointer, with its provenance, only on success.
+#[test]
+fn compare_exchange_ptr() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let y = f.declare_local::<u32>();
+    let slot = f.declare_local::<*const u32>();
+    let old = f.declare_local::<*const u32>();
+    f.storage_live(x);
+    f.storage_live(y);
+    f.storage_live(slot);
+    f.storage_live(old);
+    f.assign(x, const_int(1u32));
+    f.assign(y, const_int(2u32));
+    let ptr_ty = <*const u32>::get_type();
+    let slot_ptr = addr_of(slot, <*const *const u32>::get_type());
+    f.assign(slot, addr_of(x, ptr_ty));
+    // Fails: the slot does not point to `y`.
+    f.compare_exchange(old, slot_ptr, addr_of(y, ptr_ty), addr_of(y, ptr_ty));
+    f.print(load(deref(load(slot), <u32>::get_type())));
+    // Succeeds, and returns the old pointer.
+    f.compare_exchange(old, slot_ptr, addr_of(x, ptr_ty), addr_of(y, ptr_ty));
+    f.print(load(deref(load(slot), <u32>::get_type())));
+    f.print(load(deref(load(old), <u32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "2", "1"]);
+}
+
+/// The expected pointer is compared by address only, so a pointer without provenance
+/// can be used to replace a pointer with provenance, and the returned pointer keeps its provenance.
+#[test]
+fn compare_exchange_ptr_ignores_provenance() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let slot = f.declare_local::<*const u32>();
+    let old = f.declare_local::<*const u32>();
+    f.storage_live(x);
+    f.storage_live(slot);
+    f.storage_live(old);
+    f.assign(x, const_int(7u32));
+    let ptr_ty = <*const u32>::get_type();
+    f.assign(slot, addr_of(x, ptr_ty));
+    let addr_only = int_to_ptr(ptr_to_addr::<usize>(addr_of(x, ptr_ty)), ptr_ty);
+    f.compare_exchange(old, addr_of(slot, <*const *const u32>::get_type()), addr_only, null());
+    f.print(eq(ptr_to_addr::<usize>(load(slot)), const_int(0usize)));
+    f.print(load(deref(load(old), <u32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true", "7"]);
+}
+
+#[test]
+fn compare_exchange_wide_ptr() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let arr = f.declare_local::<[u32; 2]>();
+    let slot = f.declare_local::<*const [u32]>();
+    let old = f.declare_local::<*const [u32]>();
+    f.storage_live(arr);
+    f.storage_live(slot);
+    f.storage_live(old);
+    let wide = construct_wide_pointer(
+        addr_of(arr, <*const u32>::get_type()),
+        const_int(2usize),
+        <*const [u32]>::get_type(),
+    );
+    f.assign(slot, wide);
+    f.compare_exchange(old, addr_of(slot, <*const *const [u32]>::get_type()), wide, wide);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(
+        p,
+        "invalid return type for `AtomicCompareExchange` intrinsic: only works with integers and thin raw pointers",
+    );
+}
@@ -0,0 +1,104 @@
+use crate::*;
+
+#[test]
+fn auto_storage_builder() {
+    let mut p = ProgramBuilder::new();
+
+    let mut callee = p.declare_function();
+    callee.auto_storage();
+    let ret = callee.declare_ret::<u32>();
+    let var = callee.declare_local::<u32>();
+    callee.assign(var, const_int(42u32));
+    callee.assign(ret, load(var));
+    callee.return_();
+    let callee = p.finish_function(callee);
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    // Call the function twice to make sure it cleans up after itself.
+    f.call_nounwind(x, fn_ptr(callee), &[]);
+    f.call_nounwind(x, fn_ptr(callee), &[]);
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+#[test]
+fn auto_storage_keeps_explicit_markers() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    f.auto_storage();
+    let var = f.declare_local::<u32>();
+    f.storage_live(var);
+    f.storage_dead(var);
+    // `var` is managed explicitly, so the pass must not make it live for the entire function.
+    f.assign(var, const_int(42u32));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+}
+
+#[test]
+fn insert_storage_markers_raw() {
+    let locals = [<u32>::get_type()];
+    let b0 = block!(assign(local(0), const_int(42u32)), exit());
+    let f = function(Ret::No, 0, &locals, &[b0]);
+
+    let p = program(&[f]);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+
+    let p = program(&[insert_storage_markers(f)]);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn insert_storage_markers_use_range() {
+    let locals = [<u32>::get_type(), <u32>::get_type()];
+    let b0 = block!(assign(local(0), const_int(1u32)), goto(1));
+    let b1 = block!(assign(local(1), load(local(0))), goto(2));
+    let b2 = block!(print(load(local(1)), 3));
+    let b3 = block!(exit());
+    let f = insert_storage_markers(function(Ret::No, 0, &locals, &[b0, b1, b2, b3]));
+
+    // Both locals are used after the start block, so no new start block is needed.
+    assert_eq!(f.start, BbName(Name::from_internal(0)));
+    let statements = |bb: u32| f.blocks.get(BbName(Name::from_internal(bb))).unwrap().statements;
+    // `_0` is live from its first to its last use, and `_1` too.
+    assert_eq!(statements(0).first(), Some(storage_live(0)));
+    assert_eq!(statements(1).first(), Some(storage_live(1)));
+    assert_eq!(statements(2).first(), Some(storage_dead(0)));
+    assert_eq!(statements(3).first(), Some(storage_dead(1)));
+
+    let p = program(&[f]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+}
+
+#[test]
+fn auto_storage_loop() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    f.auto_storage();
+    let i = f.declare_local::<u32>();
+    let sum = f.declare_local::<u32>();
+    f.assign(i, const_int(0u32));
+    f.assign(sum, const_int(0u32));
+    // The locals keep their values across iterations, so they must not be made live in the loop.
+    f.while_(lt(load(i), const_int(4u32)), |f, _| {
+        f.assign(sum, add(load(sum), load(i)));
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+    f.print(load(sum));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["6"]);
+}
@@ -3,9 +3,10 @@ use crate::*;
 /// This test checks that using `goto` to jump to a block of a different kind results in an ill-formed program.
 #[test]
 fn goto_wrong_blockkind() {
-    let bb0 = block!(goto(1));
-    let bb1 = block(&[], exit(), BbKind::Cleanup);
-    let f = function(Ret::No, 0, &[], &[bb0, bb1]);
+    let f = function!(Ret::No, 0, &[], {
+        start: block!(goto(cleanup)),
+        cleanup: block!(kind = BbKind::Cleanup; exit()),
+    });
     let p = program(&[f]);
     dump_program(p);
     assert_ill_formed::<BasicMem>(p, "Terminator: next block has the wrong block kind");
@@ -57,7 +58,7 @@ fn call_nextblock_wrong_kind() {
         next_block: Some(BbName(Name::from_internal(1))),
         unwind_block: None,
     });
-    let bb1 = block(&[], exit(), BbKind::Terminate);
+    let bb1 = block(&[], terminate(), BbKind::Terminate);
     let f0 = function(Ret::No, 0, &[], &[bb0, bb1]);
 
     let f1 = function(Ret::No, 0, &[], &[block!(return_())]);
@@ -88,9 +89,10 @@ fn call_unwindblock_wrong_kind() {
 /// This test checks that using `StartUnwind` to jump to a regular block results in an ill-formed program.
 #[test]
 fn start_unwind_wrong_nextblock() {
-    let bb0 = block!(start_unwind(unit_ptr(), BbName(Name::from_internal(1))));
-    let bb1 = block!(exit());
-    let f = function(Ret::No, 0, &[], &[bb0, bb1]);
+    let f = function!(Ret::No, 0, &[], {
+        start: block!(start_unwind(unit_ptr(), bb(next))),
+        next: block!(exit()),
+    });
     let p = program(&[f]);
     dump_program(p);
     assert_ill_formed::<BasicMem>(p, "Terminator: unwind block has the wrong block kind");
@@ -259,3 +261,68 @@ fn goto_from_cleanup_to_catch() {
     dump_program(p);
     assert_ill_formed::<BasicMem>(p, "Terminator: next block has the wrong block kind");
 }
+
+/// Blocks built with `function!` can refer to each other by label.
+#[test]
+fn labeled_blocks() {
+    let f = function!(Ret::No, 0, &[], {
+        start: block!(if_(const_bool(false), dead, live)),
+        dead: block!(unreachable()),
+        live: block!(print(const_int(42u32), done)),
+        done: block!(exit()),
+    });
+    let p = program(&[f]);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+/// A block is put aside with `save_cursor` until the block it jumps to exists.
+#[test]
+fn save_and_restore_cursor() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.let_::<u32>(const_int(1u32));
+    let cursor = f.save_cursor();
+
+    let target = f.declare_block();
+    f.set_cur_block(target, BbKind::Regular);
+    f.print(load(x));
+    f.exit();
+
+    f.restore_cursor(cursor);
+    f.assign(x, const_int(2u32));
+    f.goto(target);
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["2"]);
+}
+
+#[test]
+#[should_panic(expected = "restore_cursor: the current block is unfinished")]
+fn restore_cursor_over_unfinished_block() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let cursor = f.save_cursor();
+    let other = f.declare_block();
+    f.set_cur_block(other, BbKind::Regular);
+    f.restore_cursor(cursor);
+}
+
+/// A block declared up front is built in the middle of building another one.
+#[test]
+fn build_block_later() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let done = f.declare_block();
+    let x = f.let_::<u32>(const_int(1u32));
+    f.build_block_later(done, |f| {
+        f.print(const_int(3u32));
+        f.exit();
+    });
+    f.print(load(x));
+    f.goto(done);
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "3"]);
+}
@@ -59,6 +59,110 @@ fn arg_and_ret_var() {
     assert_stop::<BasicMem>(p);
 }
 
+/// `return_value` returns early from the middle of the function.
+#[test]
+fn return_value() {
+    let mut p = ProgramBuilder::new();
+
+    let clamp_to_10 = {
+        let mut f = p.declare_function();
+        let var = f.declare_arg::<u32>();
+        f.declare_ret::<u32>();
+        f.if_(gt(load(var), const_int(10u32)), |f| f.return_value(const_int(10u32)), |_| {});
+        f.return_value(load(var));
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let ret_place = f.declare_local::<u32>();
+    f.storage_live(ret_place);
+    for arg in [3u32, 42] {
+        f.call_nounwind(ret_place, fn_ptr(clamp_to_10), &[by_value(const_int(arg))]);
+        f.print(load(ret_place));
+    }
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["3", "10"]);
+}
+
+#[test]
+#[should_panic(expected = "the function has no return place")]
+fn return_value_without_ret() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.return_value(const_int(0u32));
+}
+
+#[test]
+#[should_panic(expected = "returning a value of type")]
+fn return_value_wrong_type() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.declare_ret::<u32>();
+    f.return_value(const_bool(true));
+}
+
+/// Builds `fn(n: u32) -> bool { if n == 0 { zero } else { other(n - 1) } }`.
+fn parity_fn(f: &mut FunctionBuilder, zero: bool, other: FnName) {
+    let n = f.declare_arg::<u32>();
+    f.declare_ret::<bool>();
+    f.if_(eq(load(n), const_int(0u32)), |f| f.return_value(const_bool(zero)), |_| {});
+    let result = f.declare_local::<bool>();
+    f.storage_live(result);
+    f.call_nounwind(result, fn_ptr(other), &[by_value(sub(load(n), const_int(1u32)))]);
+    f.return_value(load(result));
+}
+
+/// `is_even` and `is_odd` call each other, so one of them has to be declared before it is built.
+#[test]
+fn forward_declare_function() {
+    let mut p = ProgramBuilder::new();
+    let is_odd = p.forward_declare_function();
+    let is_even = {
+        let mut f = p.declare_function();
+        parity_fn(&mut f, true, is_odd);
+        p.finish_function(f)
+    };
+    p.define_function(is_odd, |f| parity_fn(f, false, is_even));
+
+    let mut f = p.declare_function();
+    let result = f.declare_local::<bool>();
+    f.storage_live(result);
+    f.call_nounwind(result, fn_ptr(is_even), &[by_value(const_int(7u32))]);
+    f.print(load(result));
+    f.call_nounwind(result, fn_ptr(is_odd), &[by_value(const_int(7u32))]);
+    f.print(load(result));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["false", "true"]);
+}
+
+#[test]
+#[should_panic(expected = "was forward declared but never defined")]
+fn forward_declared_function_not_defined() {
+    let mut p = ProgramBuilder::new();
+    p.forward_declare_function();
+    let mut f = p.declare_function();
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f);
+}
+
+#[test]
+#[should_panic(expected = "the function was not forward declared or is already defined")]
+fn define_function_twice() {
+    let mut p = ProgramBuilder::new();
+    let f = p.forward_declare_function();
+    p.define_function(f, |f| f.return_());
+    p.define_function(f, |f| f.return_());
+}
+
 #[test]
 fn switch_int() {
     let mut p = ProgramBuilder::new();
@@ -85,6 +189,55 @@ fn switch_int() {
     assert_stop::<BasicMem>(p);
 }
 
+/// Prints which of the ranges of `ranges` contains each of `values`, by index, or `none`.
+fn switch_range_program(ranges: &[(i32, i32)], values: &[i32]) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let branches: Vec<_> = (0..ranges.len())
+        .map(|idx| move |f: &mut FunctionBuilder| f.print(const_int(idx as u32)))
+        .collect();
+    let cases: Vec<_> = ranges
+        .iter()
+        .zip(&branches)
+        .map(|(range, branch)| (*range, branch as &dyn Fn(&mut FunctionBuilder)))
+        .collect();
+    for value in values {
+        f.switch_range(const_int(*value), &cases, |f| f.print(const_str("none")));
+    }
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn switch_range() {
+    let ranges = [(10, 99), (-5, -1), (0, 9), (1000, 1000), (200, 299)];
+    let values = [-6, -5, -1, 0, 9, 10, 50, 99, 100, 150, 200, 299, 999, 1000, 1001];
+    let p = switch_range_program(&ranges, &values);
+    dump_program(p);
+    let expected =
+        ["none", "1", "1", "2", "2", "0", "0", "0", "none", "none", "4", "4", "none", "3", "none"];
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &expected);
+}
+
+/// Few enough values are handled by a single switch.
+#[test]
+fn switch_range_dense() {
+    let ranges = [(1, 2), (4, 4), (5, 7)];
+    let p = switch_range_program(&ranges, &[0, 1, 2, 3, 4, 5, 7, 8]);
+    assert_eq!(
+        get_stdout::<BasicMem>(p).unwrap(),
+        &["none", "0", "0", "none", "1", "2", "2", "none"]
+    );
+    assert_eq!(fmt_program(p).matches("switch(").count(), 8);
+}
+
+#[test]
+#[should_panic(expected = "switch_range: the ranges 0..=5 and 5..=10 overlap")]
+fn switch_range_overlap() {
+    switch_range_program(&[(5, 10), (0, 5)], &[0]);
+}
+
 #[test]
 fn while_() {
     let mut p = ProgramBuilder::new();
@@ -95,7 +248,7 @@ fn while_() {
     f.storage_live(counter);
     f.assign(var, const_int(0u32));
     f.assign(counter, const_int(0u32));
-    f.while_(lt(load(counter), const_int(42u32)), |f| {
+    f.while_(lt(load(counter), const_int(42u32)), |f, _| {
         f.assign(counter, add(load(counter), const_int(1u32)));
         f.assign(var, add(load(var), const_int(2u32)));
     });
@@ -105,6 +258,280 @@ fn while_() {
     assert_stop::<BasicMem>(p);
 }
 
+/// Prints the odd numbers below 10 until it finds one divisible by 7.
+#[test]
+fn while_break_continue() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.assign(counter, const_int(0u32));
+    f.while_(lt(load(counter), const_int(10u32)), |f, ctrl| {
+        f.assign(counter, add(load(counter), const_int(1u32)));
+        f.if_(
+            eq(rem(load(counter), const_int(2u32)), const_int(0u32)),
+            |f| ctrl.continue_(f),
+            |_| {},
+        );
+        f.print(load(counter));
+        f.if_(eq(rem(load(counter), const_int(7u32)), const_int(0u32)), |f| ctrl.break_(f), |_| {});
+    });
+    f.print(const_int(100u32));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "3", "5", "7", "100"]);
+}
+
+/// Prints the counter and increments it while it is below `limit`, but at least once.
+fn do_while_program(limit: u32) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.assign(counter, const_int(0u32));
+    f.do_while_(
+        |f, _| {
+            f.print(load(counter));
+            f.assign(counter, add(load(counter), const_int(1u32)));
+        },
+        lt(load(counter), const_int(limit)),
+    );
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn do_while_() {
+    let p = do_while_program(3);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "1", "2"]);
+    // The body runs once even if the condition never holds.
+    assert_eq!(get_stdout::<BasicMem>(do_while_program(0)).unwrap(), &["0"]);
+}
+
+/// `continue_` in a do-while loop still checks the condition.
+#[test]
+fn do_while_continue() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.assign(counter, const_int(0u32));
+    f.do_while_(
+        |f, ctrl| {
+            f.assign(counter, add(load(counter), const_int(1u32)));
+            f.if_(eq(load(counter), const_int(2u32)), |f| ctrl.continue_(f), |_| {});
+            f.print(load(counter));
+        },
+        lt(load(counter), const_int(4u32)),
+    );
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "3", "4"]);
+}
+
+#[test]
+fn loop_break() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.assign(counter, const_int(0u32));
+    f.loop_(|f, ctrl| {
+        f.if_(eq(load(counter), const_int(3u32)), |f| ctrl.break_(f), |_| {});
+        f.print(load(counter));
+        f.assign(counter, add(load(counter), const_int(1u32)));
+    });
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "1", "2"]);
+}
+
+#[test]
+fn loop_continue() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.assign(counter, const_int(0u32));
+    f.loop_(|f, ctrl| {
+        f.assign(counter, add(load(counter), const_int(1u32)));
+        f.if_(eq(load(counter), const_int(8u32)), |f| ctrl.break_(f), |_| {});
+        // Skip the even numbers.
+        f.if_(
+            eq(rem(load(counter), const_int(2u32)), const_int(0u32)),
+            |f| ctrl.continue_(f),
+            |_| {},
+        );
+        f.print(load(counter));
+    });
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "3", "5", "7"]);
+}
+
+/// Without a `break_`, there is no block after the loop.
+#[test]
+fn loop_without_break() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.loop_(|f, _ctrl| {
+        f.print(const_int(1u32));
+        f.exit();
+    });
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+}
+
+/// Prints every index of `for_(start, end, ..)`.
+fn for_program(start: ValueExpr, end: ValueExpr) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.for_(start, end, |f, idx| f.print(idx));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn for_() {
+    let p = for_program(const_int(3i8), const_int(6i8));
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["3", "4", "5"]);
+
+    let p = for_program(const_int(-2i8), const_int(1i8));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["-2", "-1", "0"]);
+
+    // Going right up to the maximum does not overflow the counter.
+    let p = for_program(const_int(254u8), const_int(255u8));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["254"]);
+}
+
+#[test]
+fn for_empty_range() {
+    let p = for_program(const_int(5u32), const_int(5u32));
+    assert!(get_stdout::<BasicMem>(p).unwrap().is_empty());
+
+    let p = for_program(const_int(5u32), const_int(2u32));
+    assert!(get_stdout::<BasicMem>(p).unwrap().is_empty());
+}
+
+/// The end of the range is evaluated once, so changing it in the body does not affect the loop.
+#[test]
+fn for_end_evaluated_once() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let end = f.declare_local::<u32>();
+    let sum = f.declare_local::<u32>();
+    f.storage_live(end);
+    f.storage_live(sum);
+    f.assign(end, const_int(4u32));
+    f.assign(sum, const_int(0u32));
+    f.for_(const_int(0u32), load(end), |f, idx| {
+        f.assign(sum, add(load(sum), idx));
+        f.assign(end, const_int(100u32));
+    });
+    f.print(load(sum));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["6"]);
+}
+
+#[test]
+#[should_panic(expected = "expected an integer")]
+fn for_non_int() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.for_(const_bool(false), const_bool(true), |_f, _idx| {});
+}
+
+/// Prints `100 / x` if `x` is not zero and `0` otherwise.
+fn if_value_program(x: u32) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = const_int(x);
+    // The division is only evaluated if `x` is not zero, so it cannot cause UB.
+    let quotient =
+        f.if_value::<u32>(ne(x, const_int(0u32)), div(const_int(100u32), x), const_int(0u32));
+    f.print(load(quotient));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn if_value() {
+    let p = if_value_program(4);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["25"]);
+
+    let p = if_value_program(0);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0"]);
+}
+
+/// Inside a scope, the result is a temporary that is dead after the scope.
+#[test]
+fn if_value_in_scope() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let result = std::cell::Cell::new(None);
+    f.scope(|f| {
+        let r = f.if_value::<bool>(const_bool(true), const_bool(false), const_bool(true));
+        f.print(load(r));
+        result.set(Some(r));
+    });
+    f.print(load(result.get().unwrap()));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+}
+
+/// Prints `lhs && rhs` or `lhs || rhs`; building the right-hand side prints `"rhs"` first.
+fn short_circuit_program(or: bool, lhs: bool, rhs: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let rhs = |f: &mut FunctionBuilder| {
+        f.print(const_str("rhs"));
+        const_bool(rhs)
+    };
+    let result = if or { f.or_(const_bool(lhs), rhs) } else { f.and_(const_bool(lhs), rhs) };
+    f.print(load(result));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn and_() {
+    let p = short_circuit_program(false, true, true);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["rhs", "true"]);
+    let p = short_circuit_program(false, true, false);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["rhs", "false"]);
+    let p = short_circuit_program(false, false, true);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["false"]);
+}
+
+#[test]
+fn or_() {
+    let p = short_circuit_program(true, false, true);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["rhs", "true"]);
+    let p = short_circuit_program(true, false, false);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["rhs", "false"]);
+    let p = short_circuit_program(true, true, false);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+}
+
 #[test]
 #[should_panic(expected = "PlaceExpr is not a local")]
 fn storage_live_with_non_local() {
@@ -171,3 +598,45 @@ fn no_exit() {
     let p = p.finish_program(f);
     assert_stop::<BasicMem>(p);
 }
+
+/// Adds `a` and `b` and asserts that the sum is 42.
+fn assert_sum_program(a: u32, b: u32) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let var = f.declare_local::<u32>();
+    f.storage_live(var);
+    f.assign(var, add(const_int(a), const_int(b)));
+    f.assert_eq_msg(load(var), const_int(42_u32), "the sum should be 42");
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn assert_eq_success() {
+    assert_stop::<BasicMem>(assert_sum_program(40, 2));
+}
+
+#[test]
+fn assert_eq_failure() {
+    assert_abort::<BasicMem>(assert_sum_program(40, 3));
+    let (info, stderr) = get_stderr::<BasicMem>(assert_sum_program(40, 3));
+    assert_eq!(info, TerminationInfo::Abort);
+    assert_eq!(stderr, &["assertion `left == right` failed: the sum should be 42", "43", "42"]);
+}
+
+#[test]
+fn assert_eq_without_message() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.assert_eq(const_int(1_i8), neg(const_int(1_i8)));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let (info, stderr) = get_stderr::<BasicMem>(p);
+    assert_eq!(info, TerminationInfo::Abort);
+    assert_eq!(stderr, &["assertion `left == right` failed", "1", "-1"]);
+}
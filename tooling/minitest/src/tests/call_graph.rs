@@ -0,0 +1,125 @@
+use crate::*;
+
+fn empty_function(p: &mut ProgramBuilder) -> FnName {
+    let mut f = p.declare_function();
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Functions that are never called from the start function are unreachable.
+#[test]
+fn direct_calls() {
+    let mut p = ProgramBuilder::new();
+    let leaf = empty_function(&mut p);
+    let dead = empty_function(&mut p);
+
+    let middle = {
+        let mut f = p.declare_function();
+        f.call_ignoreret(fn_ptr(leaf), &[]);
+        f.return_();
+        p.finish_function(f)
+    };
+    // Calls `middle`, but is dead itself.
+    let dead_caller = {
+        let mut f = p.declare_function();
+        f.call_ignoreret(fn_ptr(middle), &[]);
+        f.call_ignoreret(fn_ptr(dead), &[]);
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(middle), &[]);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let graph = CallGraph::new(p);
+    assert_eq!(graph.callees(main), vec![middle]);
+    assert_eq!(graph.callees(middle), vec![leaf]);
+    assert_eq!(graph.callees(dead_caller), vec![dead, middle]);
+    assert_eq!(graph.reachable(), vec![leaf, middle, main]);
+    assert_eq!(graph.unreachable(), vec![dead, dead_caller]);
+    assert_eq!(graph.reachable_from(dead_caller), vec![leaf, dead, middle, dead_caller]);
+    assert!(graph.escaped().is_empty());
+}
+
+/// Functions whose pointer is stored somewhere may be called indirectly, so they are reachable.
+#[test]
+fn escaping_fn_ptr() {
+    let mut p = ProgramBuilder::new();
+    let target = empty_function(&mut p);
+    let dead = empty_function(&mut p);
+
+    let mut f = p.declare_function();
+    let ptr = f.declare_local_with_ty(Type::Ptr(PtrType::FnPtr));
+    f.storage_live(ptr);
+    f.assign(ptr, fn_ptr(target));
+    f.call_ignoreret(load(ptr), &[]);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let graph = CallGraph::new(p);
+    assert!(graph.callees(main).is_empty());
+    assert!(graph.calls_indirectly(main));
+    assert_eq!(graph.escaped(), vec![target]);
+    assert_eq!(graph.may_call(main), vec![target]);
+    assert_eq!(graph.unreachable(), vec![dead]);
+}
+
+/// The start function of a spawned thread is reachable from the spawning function.
+#[test]
+fn spawned_thread() {
+    let mut p = ProgramBuilder::new();
+    let thread = {
+        let mut f = p.declare_function();
+        let _data = f.declare_arg::<*const ()>();
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let id = f.declare_local::<u32>();
+    f.storage_live(id);
+    f.spawn(thread, null(), id);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let graph = CallGraph::new(p);
+    assert!(!graph.calls_indirectly(main));
+    assert_eq!(graph.reachable(), vec![thread, main]);
+}
+
+/// Using a vtable makes all its methods reachable.
+#[test]
+fn vtable_methods() {
+    let mut p = ProgramBuilder::new();
+
+    let mut trait_a = p.declare_trait();
+    let method_a = trait_a.declare_method();
+    let trait_a = p.finish_trait(trait_a);
+
+    let impl_a = {
+        let mut f = p.declare_function();
+        let _self = f.declare_arg::<&u8>();
+        f.return_();
+        p.finish_function(f)
+    };
+    let mut vtable = p.declare_vtable_for_frozen_ty(trait_a, <u8>::get_type());
+    vtable.add_method(method_a, impl_a);
+    let vtable = p.finish_vtable(vtable);
+
+    let mut f = p.declare_function();
+    let meta = f.declare_local_with_ty(Type::Ptr(PtrType::VTablePtr(trait_a)));
+    f.storage_live(meta);
+    f.assign(meta, const_vtable(vtable, trait_a));
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let graph = CallGraph::new(p);
+    assert_eq!(graph.escaped(), vec![impl_a]);
+    assert!(graph.unreachable().is_empty());
+}
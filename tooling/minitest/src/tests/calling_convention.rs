@@ -70,3 +70,65 @@ fn spawn_wrong_conv() {
     dump_program(p);
     assert_ub::<BasicMem>(p, "call ABI violation: calling conventions are not the same");
 }
+
+/// Builds a program where `main` calls a function with the calling convention `conv`, which panics.
+/// The cleanup block of `main` prints 1 and exits.
+/// If `abort_on_unwind` is set, the callee guards the panic with a terminate block, like a Rust
+/// function with the C calling convention does.
+fn unwind_through(conv: CallingConvention, abort_on_unwind: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let panic_fn = {
+        let mut f = p.declare_function();
+        let resume = f.cleanup_block(|f| f.resume_unwind());
+        f.start_unwind(unit_ptr(), resume);
+        p.finish_function(f)
+    };
+
+    let extern_fn = {
+        let mut f = p.declare_function();
+        f.set_conv(conv);
+        let unwind = if abort_on_unwind {
+            f.terminate_block(|f| f.terminate())
+        } else {
+            f.cleanup_block(|f| f.resume_unwind())
+        };
+        f.call(unit_place(), fn_ptr(panic_fn), &[], unwind);
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main_fn = {
+        let mut f = p.declare_function();
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(1u32));
+            f.exit();
+        });
+        f.call_with_conv(unit_place(), fn_ptr(extern_fn), &[], conv, cleanup);
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main_fn)
+}
+
+#[test]
+fn unwind_out_of_c() {
+    let p = unwind_through(CallingConvention::C, false);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "unwinding out of a function with the C calling convention");
+}
+
+#[test]
+fn unwind_out_of_c_unwind() {
+    let p = unwind_through(CallingConvention::CUnwind, false);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+}
+
+#[test]
+fn abort_in_c() {
+    let p = unwind_through(CallingConvention::C, true);
+    dump_program(p);
+    assert_abort::<BasicMem>(p);
+}
@@ -88,3 +88,67 @@ fn catch_no_unwind() {
     dump_program(p);
     assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "2", "4", "6"]);
 }
+
+/// Calls a function built by `panicking`, which panics.
+/// Catches the panic and prints the payload, which has type `ty`.
+fn catch_panic_program(ty: Type, panicking: &dyn Fn(&mut FunctionBuilder)) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let panicking_fn = {
+        let mut f = p.declare_function();
+        panicking(&mut f);
+        p.finish_function(f)
+    };
+
+    let main_fn = {
+        let mut f = p.declare_function();
+        let payload = f.declare_local::<*mut u8>();
+        let cont = f.declare_block();
+        let layout = ty.layout::<miniutil::DefaultTarget>();
+        let size = const_usize_on::<miniutil::DefaultTarget>(
+            layout.expect_size("payloads are sized").bytes(),
+        );
+        let align = const_usize_on::<miniutil::DefaultTarget>(
+            layout.expect_align("payloads are sized").bytes(),
+        );
+        let catch_block = f.catch_block(|f| {
+            f.storage_live(payload);
+            f.get_unwind_payload(payload);
+            f.print(load(deref(load(payload), ty)));
+            f.deallocate(load(payload), size, align);
+            f.stop_unwind(cont);
+        });
+        f.call(unit_place(), fn_ptr(panicking_fn), &[], catch_block);
+        f.print(const_str("not reached"));
+        f.goto(cont);
+        f.set_cur_block(cont, BbKind::Regular);
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main_fn)
+}
+
+#[test]
+fn panic() {
+    let p = catch_panic_program(<i32>::get_type(), &|f| f.panic::<i32>(const_int(42i32)));
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+#[test]
+fn panic_str() {
+    let p = catch_panic_program(array_ty(<u8>::get_type(), 4), &|f| f.panic_str("oops"));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["oops"]);
+}
+
+/// A panic that is not caught unwinds out of the bottom of the stack.
+#[test]
+fn panic_uncaught() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.panic::<u8>(const_int(0u8));
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "the function at the bottom of the stack must not unwind");
+}
@@ -0,0 +1,59 @@
+use crate::*;
+
+use std::time::Duration;
+
+/// The main thread spawns a thread that prints `1`, and prints `0` itself.
+/// If `join_first` is set, it waits for the other thread before printing, so the output is always the same.
+fn two_printers(join_first: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let printer = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<()>();
+        f.print(const_int(1u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        f.spawn(printer, unit(), thread_id);
+        if join_first {
+            f.join(load(thread_id));
+            f.print(const_int(0u32));
+        } else {
+            f.print(const_int(0u32));
+            f.join(load(thread_id));
+        }
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+#[test]
+fn chaos_deterministic() {
+    let p = two_printers(true);
+    dump_program(p);
+    chaos_test::<BasicMem>(p, Duration::from_millis(200));
+}
+
+#[test]
+fn chaos_finds_race() {
+    let report = chaos::<BasicMem>(two_printers(false), Duration::from_millis(200));
+    assert!(!report.is_deterministic());
+    let mut outputs: Vec<Vec<String>> =
+        report.behaviors.iter().map(|(behavior, _run)| behavior.stdout.clone()).collect();
+    outputs.sort();
+    assert_eq!(outputs, vec![vec!["0", "1"], vec!["1", "0"]]);
+}
+
+#[test]
+#[should_panic(expected = "the program behaved differently")]
+fn chaos_test_reports_race() {
+    chaos_test::<BasicMem>(two_printers(false), Duration::from_millis(200));
+}
@@ -0,0 +1,72 @@
+use crate::*;
+
+/// `main` calls `a`, which calls `b`, which panics. Every function has a cleanup that prints its number.
+/// Returns the program, the functions `[b, a, main]`, and their cleanup blocks.
+fn nested_unwind() -> (Program, [FnName; 3], [BbName; 3]) {
+    let mut p = ProgramBuilder::new();
+
+    let (b, b_cleanup) = {
+        let mut f = p.declare_function();
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(1u32));
+            f.resume_unwind();
+        });
+        f.start_unwind(unit_ptr(), cleanup);
+        (p.finish_function(f), cleanup)
+    };
+
+    let (a, a_cleanup) = {
+        let mut f = p.declare_function();
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(2u32));
+            f.resume_unwind();
+        });
+        f.call(unit_place(), fn_ptr(b), &[], cleanup);
+        f.return_();
+        (p.finish_function(f), cleanup)
+    };
+
+    let (main, main_cleanup) = {
+        let mut f = p.declare_function();
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(3u32));
+            f.exit();
+        });
+        f.call(unit_place(), fn_ptr(a), &[], cleanup);
+        f.exit();
+        (p.finish_function(f), cleanup)
+    };
+
+    (p.finish_program(main), [b, a, main], [b_cleanup, a_cleanup, main_cleanup])
+}
+
+#[test]
+fn cleanups_run_innermost_first() {
+    let (prog, [b, a, main], _cleanups) = nested_unwind();
+    dump_program(prog);
+    let mut interp = Interpreter::<BasicMem>::new(prog);
+    assert_eq!(interp.run(), TerminationInfo::MachineStop);
+    assert_eq!(interp.state().stdout, &["1", "2", "3"]);
+
+    // Each cleanup consists of two blocks: the one that prints, and the one after it.
+    let frames: Vec<(FnName, usize)> =
+        interp.cleanups().iter().map(|ev| (ev.function, ev.depth)).collect();
+    assert_eq!(frames, &[(b, 3), (b, 3), (a, 2), (a, 2), (main, 1), (main, 1)]);
+    assert!(interp.cleanups().iter().all(|ev| ev.thread == 0));
+}
+
+#[test]
+fn cleanup_sequence_matches_execution() {
+    let (prog, fns, cleanups) = nested_unwind();
+    let mut interp = Interpreter::<BasicMem>::new(prog);
+    interp.run();
+
+    // `main` exits in its cleanup, so only `b` and `a` resume unwinding.
+    for (f, cleanup) in fns.into_iter().zip(cleanups).take(2) {
+        let expected = cleanup_sequence(prog.functions.get(f).unwrap(), cleanup).unwrap();
+        let executed: Vec<BbName> =
+            interp.cleanups().iter().filter(|ev| ev.function == f).map(|ev| ev.block).collect();
+        assert_eq!(executed, expected);
+    }
+    assert_eq!(cleanup_sequence(prog.functions.get(fns[2]).unwrap(), cleanups[2]), None);
+}
@@ -70,7 +70,10 @@ fn compare_exchange_arg_count() {
 
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `AtomicCompareExchange` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `AtomicCompareExchange`",
+    );
 }
 
 #[test]
@@ -91,9 +94,9 @@ fn compare_exchange_arg_1_value() {
 
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         p,
-        "invalid first argument to `AtomicCompareExchange` intrinsic: not a thin pointer",
+        "Terminator::Intrinsic: invalid argument 0 for `AtomicCompareExchange`: expected a thin pointer",
     );
 }
 
@@ -117,7 +120,7 @@ fn compare_exchange_ret_type() {
     let p = program(&[f]);
     assert_ub::<BasicMem>(
         p,
-        "invalid return type for `Intrinis::AtomicCompareExchange`: only works with integers",
+        "invalid return type for `AtomicCompareExchange` intrinsic: only works with integers and thin raw pointers",
     );
 }
 
@@ -1,13 +1,13 @@
 use crate::*;
 
 /// Helper which builds code to assert that compute_align with the given sized type returns the expected align.
-fn assume_align_of_ty(f: &mut FunctionBuilder, align: usize, ty: Type) {
-    f.assume(eq(compute_align(ty, unit()), const_int(align)));
+fn assert_align_of_ty(f: &mut FunctionBuilder, align: usize, ty: Type) {
+    f.assert_eq(compute_align(ty, unit()), const_int(align));
 }
 
-/// Helper to call [`assume_align_of_ty`] when [`TypeConv`] is available.
-fn assume_align_of_ty_conv<T: TypeConv>(f: &mut FunctionBuilder, align: usize) {
-    assume_align_of_ty(f, align, T::get_type());
+/// Helper to call [`assert_align_of_ty`] when [`TypeConv`] is available.
+fn assert_align_of_ty_conv<T: TypeConv>(f: &mut FunctionBuilder, align: usize) {
+    assert_align_of_ty(f, align, T::get_type());
 }
 
 /// Tests compute_align for integers.
@@ -18,17 +18,17 @@ fn align_of_ints() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_align_of_ty_conv::<u8>(&mut f, 1);
-        assume_align_of_ty_conv::<i8>(&mut f, 1);
-        assume_align_of_ty_conv::<u16>(&mut f, 2);
-        assume_align_of_ty_conv::<i16>(&mut f, 2);
-        assume_align_of_ty_conv::<u32>(&mut f, 4);
-        assume_align_of_ty_conv::<i32>(&mut f, 4);
-        assume_align_of_ty_conv::<u64>(&mut f, 8);
-        assume_align_of_ty_conv::<i64>(&mut f, 8);
+        assert_align_of_ty_conv::<u8>(&mut f, 1);
+        assert_align_of_ty_conv::<i8>(&mut f, 1);
+        assert_align_of_ty_conv::<u16>(&mut f, 2);
+        assert_align_of_ty_conv::<i16>(&mut f, 2);
+        assert_align_of_ty_conv::<u32>(&mut f, 4);
+        assert_align_of_ty_conv::<i32>(&mut f, 4);
+        assert_align_of_ty_conv::<u64>(&mut f, 8);
+        assert_align_of_ty_conv::<i64>(&mut f, 8);
         // we are using a 64bit target in these tests
-        assume_align_of_ty_conv::<usize>(&mut f, 8);
-        assume_align_of_ty_conv::<isize>(&mut f, 8);
+        assert_align_of_ty_conv::<usize>(&mut f, 8);
+        assert_align_of_ty_conv::<isize>(&mut f, 8);
 
         f.exit();
         p.finish_function(f)
@@ -46,12 +46,12 @@ fn align_of_ptr() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_align_of_ty_conv::<&u8>(&mut f, 8);
-        assume_align_of_ty_conv::<&bool>(&mut f, 8);
-        assume_align_of_ty_conv::<&()>(&mut f, 8);
-        assume_align_of_ty_conv::<&[u8]>(&mut f, 8);
-        assume_align_of_ty_conv::<&mut [u16]>(&mut f, 8);
-        assume_align_of_ty_conv::<&[u8; 2]>(&mut f, 8);
+        assert_align_of_ty_conv::<&u8>(&mut f, 8);
+        assert_align_of_ty_conv::<&bool>(&mut f, 8);
+        assert_align_of_ty_conv::<&()>(&mut f, 8);
+        assert_align_of_ty_conv::<&[u8]>(&mut f, 8);
+        assert_align_of_ty_conv::<&mut [u16]>(&mut f, 8);
+        assert_align_of_ty_conv::<&[u8; 2]>(&mut f, 8);
 
         f.exit();
         p.finish_function(f)
@@ -69,8 +69,8 @@ fn align_of_zst() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_align_of_ty(&mut f, 1, <()>::get_type());
-        assume_align_of_ty(&mut f, 4, <[u32; 0]>::get_type());
+        assert_align_of_ty(&mut f, 1, <()>::get_type());
+        assert_align_of_ty(&mut f, 4, <[u32; 0]>::get_type());
 
         f.exit();
         p.finish_function(f)
@@ -87,7 +87,7 @@ fn align_of_struct() {
 
     let f = {
         let mut f = p.declare_function();
-        assume_align_of_ty(
+        assert_align_of_ty(
             &mut f,
             8,
             tuple_ty(
@@ -120,10 +120,10 @@ fn align_of_slice() {
             const_int(3_usize),
             <&[u32]>::get_type(),
         );
-        f.assume(eq(
+        f.assert_eq(
             compute_align(<[u32]>::get_type(), get_metadata(slice_ptr)),
             const_int(4_usize),
-        ));
+        );
 
         let arr = f.declare_local::<[u32; 0]>();
         f.storage_live(arr);
@@ -132,10 +132,10 @@ fn align_of_slice() {
             const_int(0_usize),
             <&[u32]>::get_type(),
         );
-        f.assume(eq(
+        f.assert_eq(
             compute_align(<[u32]>::get_type(), get_metadata(slice_ptr)),
             const_int(4_usize),
-        ));
+        );
 
         let arr = f.declare_local::<[u8; 312]>();
         f.storage_live(arr);
@@ -144,10 +144,7 @@ fn align_of_slice() {
             const_int(312_usize),
             <&[u8]>::get_type(),
         );
-        f.assume(eq(
-            compute_align(<[u8]>::get_type(), get_metadata(slice_ptr)),
-            const_int(1_usize),
-        ));
+        f.assert_eq(compute_align(<[u8]>::get_type(), get_metadata(slice_ptr)), const_int(1_usize));
 
         f.exit();
         p.finish_function(f)
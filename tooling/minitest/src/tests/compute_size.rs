@@ -1,13 +1,13 @@
 use crate::*;
 
 /// Helper which builds code to assert that compute_size with the given sized type returns the expected size.
-fn assume_size_of_ty(f: &mut FunctionBuilder, size: usize, ty: Type) {
-    f.assume(eq(compute_size(ty, unit()), const_int(size)));
+fn assert_size_of_ty(f: &mut FunctionBuilder, size: usize, ty: Type) {
+    f.assert_eq(compute_size(ty, unit()), const_int(size));
 }
 
-/// Helper to call [`assume_size_of_ty`] when [`TypeConv`] is available.
-fn assume_size_of_ty_conv<T: TypeConv>(f: &mut FunctionBuilder, size: usize) {
-    assume_size_of_ty(f, size, T::get_type());
+/// Helper to call [`assert_size_of_ty`] when [`TypeConv`] is available.
+fn assert_size_of_ty_conv<T: TypeConv>(f: &mut FunctionBuilder, size: usize) {
+    assert_size_of_ty(f, size, T::get_type());
 }
 
 /// Tests compute_size for integers.
@@ -18,17 +18,17 @@ fn size_of_ints() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_size_of_ty_conv::<u8>(&mut f, 1);
-        assume_size_of_ty_conv::<i8>(&mut f, 1);
-        assume_size_of_ty_conv::<u16>(&mut f, 2);
-        assume_size_of_ty_conv::<i16>(&mut f, 2);
-        assume_size_of_ty_conv::<u32>(&mut f, 4);
-        assume_size_of_ty_conv::<i32>(&mut f, 4);
-        assume_size_of_ty_conv::<u64>(&mut f, 8);
-        assume_size_of_ty_conv::<i64>(&mut f, 8);
+        assert_size_of_ty_conv::<u8>(&mut f, 1);
+        assert_size_of_ty_conv::<i8>(&mut f, 1);
+        assert_size_of_ty_conv::<u16>(&mut f, 2);
+        assert_size_of_ty_conv::<i16>(&mut f, 2);
+        assert_size_of_ty_conv::<u32>(&mut f, 4);
+        assert_size_of_ty_conv::<i32>(&mut f, 4);
+        assert_size_of_ty_conv::<u64>(&mut f, 8);
+        assert_size_of_ty_conv::<i64>(&mut f, 8);
         // we are using a 64bit target in these tests
-        assume_size_of_ty_conv::<usize>(&mut f, 8);
-        assume_size_of_ty_conv::<isize>(&mut f, 8);
+        assert_size_of_ty_conv::<usize>(&mut f, 8);
+        assert_size_of_ty_conv::<isize>(&mut f, 8);
 
         f.exit();
         p.finish_function(f)
@@ -46,12 +46,12 @@ fn size_of_ptr() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_size_of_ty_conv::<&u8>(&mut f, 8);
-        assume_size_of_ty_conv::<&bool>(&mut f, 8);
-        assume_size_of_ty_conv::<&()>(&mut f, 8);
-        assume_size_of_ty_conv::<&[u8]>(&mut f, 16);
-        assume_size_of_ty_conv::<&mut [u16]>(&mut f, 16);
-        assume_size_of_ty_conv::<&[u8; 2]>(&mut f, 8);
+        assert_size_of_ty_conv::<&u8>(&mut f, 8);
+        assert_size_of_ty_conv::<&bool>(&mut f, 8);
+        assert_size_of_ty_conv::<&()>(&mut f, 8);
+        assert_size_of_ty_conv::<&[u8]>(&mut f, 16);
+        assert_size_of_ty_conv::<&mut [u16]>(&mut f, 16);
+        assert_size_of_ty_conv::<&[u8; 2]>(&mut f, 8);
 
         f.exit();
         p.finish_function(f)
@@ -69,8 +69,8 @@ fn size_of_zst() {
     let f = {
         let mut f = p.declare_function();
 
-        assume_size_of_ty(&mut f, 0, <()>::get_type());
-        assume_size_of_ty(&mut f, 0, <[u32; 0]>::get_type());
+        assert_size_of_ty(&mut f, 0, <()>::get_type());
+        assert_size_of_ty(&mut f, 0, <[u32; 0]>::get_type());
 
         f.exit();
         p.finish_function(f)
@@ -87,7 +87,7 @@ fn size_of_struct() {
 
     let f = {
         let mut f = p.declare_function();
-        assume_size_of_ty(
+        assert_size_of_ty(
             &mut f,
             16,
             tuple_ty(
@@ -120,10 +120,10 @@ fn size_of_slice() {
             const_int(3_usize),
             <&[u32]>::get_type(),
         );
-        f.assume(eq(
+        f.assert_eq(
             compute_size(<[u32]>::get_type(), get_metadata(slice_ptr)),
             const_int(12_usize),
-        ));
+        );
 
         let arr = f.declare_local::<[u32; 0]>();
         f.storage_live(arr);
@@ -132,10 +132,7 @@ fn size_of_slice() {
             const_int(0_usize),
             <&[u32]>::get_type(),
         );
-        f.assume(eq(
-            compute_size(<[u32]>::get_type(), get_metadata(slice_ptr)),
-            const_int(0_usize),
-        ));
+        f.assert_eq(compute_size(<[u32]>::get_type(), get_metadata(slice_ptr)), const_int(0_usize));
 
         let arr = f.declare_local::<[u8; 312]>();
         f.storage_live(arr);
@@ -144,10 +141,10 @@ fn size_of_slice() {
             const_int(312_usize),
             <&[u8]>::get_type(),
         );
-        f.assume(eq(
+        f.assert_eq(
             compute_size(<[u8]>::get_type(), get_metadata(slice_ptr)),
             const_int(312_usize),
-        ));
+        );
 
         f.exit();
         p.finish_function(f)
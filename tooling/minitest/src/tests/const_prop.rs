@@ -0,0 +1,77 @@
+use crate::*;
+
+/// The number of `Switch` terminators in the start function.
+fn switches(prog: Program) -> usize {
+    let f = prog.functions.get(prog.start).unwrap();
+    f.blocks.iter().filter(|(_name, bb)| matches!(bb.terminator, Terminator::Switch { .. })).count()
+}
+
+/// A program that computes with constants and branches on the result.
+fn arithmetic() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u8>();
+    f.storage_live(x);
+    f.assign(x, add(const_int(200u8), const_int(100u8)));
+    f.assign(x, mul(load(x), neg(const_int(3u8))));
+    // Branch before printing: printing ends the block, and constants are only propagated within a block.
+    f.if_(lt(load(x), const_int(10u8)), |f| f.print(const_int(1u32)), |f| f.print(const_int(2u32)));
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// A program whose locals change through a pointer, which must not be propagated.
+fn through_pointer() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    f.storage_live(x);
+    f.assign(x, const_int(1i32));
+    f.assign(deref(addr_of(x, <*mut i32>::get_type()), <i32>::get_type()), const_int(2i32));
+    f.if_(eq(load(x), const_int(2i32)), |f| f.exit(), |f| f.unreachable());
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// A program that divides by zero.
+fn div_by_zero() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.assign(x, const_int(0u32));
+    f.assign(x, div(const_int(1u32), load(x)));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn preserves_semantics() {
+    let progs = [arithmetic(), through_pointer(), div_by_zero()];
+    assert_preserves_semantics::<BasicMem>(const_prop, &progs, &[0, 1, 2, 3]);
+}
+
+#[test]
+fn folds_switch() {
+    let prog = arithmetic();
+    assert_eq!(switches(prog), 1);
+    let prog = const_prop(prog);
+    assert_eq!(switches(prog), 0);
+    assert_eq!(get_stdout::<BasicMem>(prog).unwrap(), vec!["2", "124"]);
+}
+
+#[test]
+fn keeps_address_taken_locals() {
+    let prog = const_prop(through_pointer());
+    assert_eq!(switches(prog), 1);
+    assert_stop::<BasicMem>(prog);
+}
+
+/// Division by zero is UB, so it must not be folded away.
+#[test]
+fn keeps_ub() {
+    assert_ub::<BasicMem>(const_prop(div_by_zero()), "division by zero");
+}
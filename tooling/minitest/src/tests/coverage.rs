@@ -0,0 +1,55 @@
+use crate::*;
+
+fn branch_program(cond: bool) -> Program {
+    let f = function!(Ret::No, 0, &[], {
+        start: block!(if_(const_bool(cond), then_bb, else_bb)),
+        then_bb: block!(print(const_int(1u32), done)),
+        else_bb: block!(print(const_int(2u32), done)),
+        done: block!(exit()),
+    });
+    program(&[f])
+}
+
+#[test]
+fn fallback_not_taken() {
+    let p = branch_program(true);
+    dump_program(p);
+    assert_block_never_reached::<BasicMem>(p, FnName(Name::from_internal(0)), bb(2));
+}
+
+#[test]
+#[should_panic(expected = "block was reached")]
+fn fallback_taken() {
+    let p = branch_program(false);
+    assert_block_never_reached::<BasicMem>(p, FnName(Name::from_internal(0)), bb(2));
+}
+
+#[test]
+fn marking_preserves_behavior() {
+    let p = mark_block(branch_program(false), FnName(Name::from_internal(0)), bb(2), "marker");
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["2"]);
+    assert_eq!(
+        get_stderr::<BasicMem>(p),
+        (TerminationInfo::MachineStop, vec!["marker".to_string()])
+    );
+}
+
+/// Cleanup blocks are only reached when unwinding.
+#[test]
+fn cleanup_not_reached() {
+    let mut p = ProgramBuilder::new();
+    let callee = {
+        let mut f = p.declare_function();
+        f.return_();
+        p.finish_function(f)
+    };
+    let mut f = p.declare_function();
+    let cleanup = f.cleanup_block(|f| f.abort());
+    f.call(unit_place(), fn_ptr(callee), &[], cleanup);
+    f.exit();
+    let main_fn = p.finish_function(f);
+    let p = p.finish_program(main_fn);
+    dump_program(p);
+    assert_block_never_reached::<BasicMem>(p, main_fn, cleanup);
+}
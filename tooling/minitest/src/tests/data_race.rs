@@ -151,3 +151,22 @@ fn non_atomic_store_non_atomic_store() {
 
     assert!(has_data_race::<BasicMem>(p))
 }
+
+/// With a fixed set of scheduler seeds, whether the race shows up does not depend on luck.
+#[test]
+fn race_with_some_seed() {
+    let p = racy_program(
+        AccessPattern(AccessType::Store, Atomicity::None),
+        AccessPattern(AccessType::Store, Atomicity::None),
+    );
+    assert_ub_sometimes::<BasicMem>(p, 32, "Data race");
+}
+
+#[test]
+fn no_race_with_any_seed() {
+    let p = racy_program(
+        AccessPattern(AccessType::Store, Atomicity::Atomic),
+        AccessPattern(AccessType::Store, Atomicity::Atomic),
+    );
+    assert_stop_always::<BasicMem>(p, 32);
+}
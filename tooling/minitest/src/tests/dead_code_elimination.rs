@@ -0,0 +1,141 @@
+use crate::*;
+
+fn statement_count(prog: Program) -> usize {
+    let f = prog.functions.get(prog.start).unwrap();
+    f.blocks.iter().map(|(_name, bb)| bb.statements.iter().count()).sum()
+}
+
+/// A program with an unused function and a local that is written but never read.
+fn dead_code() -> Program {
+    let mut p = ProgramBuilder::new();
+    let unused = {
+        let mut f = p.declare_function();
+        f.print(const_int(1u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let y = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.storage_live(y);
+    f.assign(x, add(const_int(1u32), const_int(2u32)));
+    f.assign(y, const_int(7u32));
+    f.print(load(y));
+    f.storage_dead(x);
+    f.storage_dead(y);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+    assert!(p.functions.contains_key(unused));
+    p
+}
+
+#[test]
+fn removes_dead_code() {
+    let p = dead_code();
+    let optimized = dead_code_elimination(p);
+    assert_eq!(optimized.functions.iter().count(), 1);
+    // Only the statements for `y` remain.
+    assert_eq!(statement_count(p), 6);
+    assert_eq!(statement_count(optimized), 3);
+    assert_preserves_semantics::<BasicMem>(dead_code_elimination, &[p], &[0]);
+    assert_eq!(get_stdout::<BasicMem>(optimized).unwrap(), vec!["7"]);
+}
+
+/// Writing to a dead local is UB even if the value is never read.
+#[test]
+fn keeps_store_to_dead_local() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.assign(x, const_int(1u32));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(dead_code_elimination(p), "access to a dead local");
+}
+
+/// An unused division by zero is still UB.
+#[test]
+fn keeps_failing_operation() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.assign(x, div(const_int(1u32), const_int(0u32)));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(dead_code_elimination(p), "division by zero");
+}
+
+/// An unused load of uninitialized memory is still UB.
+#[test]
+fn keeps_uninit_load() {
+    let locals = vec![<bool>::get_type(); 2];
+    let stmts = vec![storage_live(0), storage_live(1), assign(local(0), load(local(1)))];
+    let p = small_program(&locals, &stmts);
+    assert_ub::<BasicMem>(
+        dead_code_elimination(p),
+        "load at type Bool but the data in memory violates the language invariant",
+    );
+}
+
+/// A value that is overwritten on every path before it is read is dead,
+/// even though the local is read elsewhere.
+#[test]
+fn removes_overwritten_store() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.assign(x, const_int(1u32));
+    f.if_(const_bool(true), |f| f.assign(x, const_int(2u32)), |f| f.assign(x, const_int(3u32)));
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let optimized = dead_code_elimination(p);
+    let stores = |prog: Program| {
+        let f = prog.functions.get(prog.start).unwrap();
+        f.blocks
+            .iter()
+            .flat_map(|(_name, bb)| bb.statements.iter())
+            .filter(|st| matches!(st, Statement::Assign { .. }))
+            .count()
+    };
+    assert_eq!(stores(p), 3);
+    assert_eq!(stores(optimized), 2);
+    assert_preserves_semantics::<BasicMem>(dead_code_elimination, &[p], &[0]);
+}
+
+/// The methods of a vtable stay, even if nothing uses the vtable.
+#[test]
+fn keeps_vtable_methods() {
+    let mut p = ProgramBuilder::new();
+
+    let mut trait_a = p.declare_trait();
+    let method = trait_a.declare_method();
+    let trait_a = p.finish_trait(trait_a);
+    let method_impl = {
+        let mut f = p.declare_function();
+        f.declare_arg::<&u32>();
+        f.return_();
+        p.finish_function(f)
+    };
+    let mut vtable = p.declare_vtable_for_frozen_ty(trait_a, <u32>::get_type());
+    vtable.add_method(method, method_impl);
+    p.finish_vtable(vtable);
+
+    let mut f = p.declare_function();
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let optimized = dead_code_elimination(p);
+    assert!(optimized.functions.contains_key(method_impl));
+    assert_stop::<BasicMem>(optimized);
+}
@@ -0,0 +1,67 @@
+use crate::*;
+
+#[test]
+fn loop_and_call() {
+    let p = minirust! {
+        fn double(x: u32) -> u32 {
+            return mul(load(x), const_int(2u32));
+        }
+
+        fn main() {
+            let i: u32 = const_int(0u32);
+            let y: u32;
+            while (lt(load(i), const_int(3u32))) {
+                y = call double(load(i));
+                print(load(y));
+                i = add(load(i), const_int(1u32));
+            }
+            exit;
+        }
+    };
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "2", "4"]);
+}
+
+#[test]
+fn recursion() {
+    let p = minirust! {
+        fn fib(n: u32) -> u32 {
+            if (lt(load(n), const_int(2u32))) {
+                return load(n);
+            }
+            let a: u32;
+            let b: u32;
+            a = call fib(sub(load(n), const_int(1u32)));
+            b = call fib(sub(load(n), const_int(2u32)));
+            return add(load(a), load(b));
+        }
+
+        fn main() {
+            let x: u32;
+            x = call fib(const_int(10u32));
+            print(load(x));
+            exit;
+        }
+    };
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["55"]);
+}
+
+#[test]
+fn if_else_and_ub() {
+    let p = minirust! {
+        fn check(b: bool) {
+            if (load(b)) {
+                print(const_int(1u32));
+            } else {
+                unreachable;
+            }
+            return;
+        }
+
+        fn main() {
+            check(const_bool(true));
+            check(const_bool(false));
+            exit;
+        }
+    };
+    assert_ub::<BasicMem>(p, "reached unreachable code");
+}
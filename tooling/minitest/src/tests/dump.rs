@@ -0,0 +1,187 @@
+use crate::*;
+
+fn small() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// Dumps are kept until the test fails (or something takes them).
+#[test]
+fn dumps_are_deferred() {
+    if std::env::var_os("MINIRUST_DUMP").is_some() {
+        // Dumps are printed right away.
+        return;
+    }
+    let p = small();
+    dump_program(p);
+    dump_program(p);
+    assert_eq!(take_pending_dumps(), vec![fmt_program(p); 2]);
+    assert!(take_pending_dumps().is_empty());
+}
+
+/// Dumps are printed to the writer.
+#[test]
+fn dump_to_writer() {
+    let p = small();
+    let mut out = Vec::new();
+    dump_program_to(p, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format!("{}\n", fmt_program(p)));
+}
+
+#[test]
+fn colors() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let cleanup = f.cleanup_block(|f| f.resume_unwind());
+    f.start_unwind(unit_ptr(), cleanup);
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let plain = fmt_program(p);
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.contains("(Cleanup):"));
+    assert!(!plain.contains("(Regular)"));
+
+    let colored = fmt_program_with(p, FmtOptions { color: true });
+    assert!(colored.contains("\x1b[33m(Cleanup)\x1b[0m:"));
+    assert!(colored.contains("\x1b[32m(Regular)\x1b[0m:"));
+    // Colors do not change the indentation.
+    assert!(colored.contains("\n    \x1b[35mresume;\x1b[0m\n"));
+}
+
+#[test]
+fn field_names() {
+    let mut p = ProgramBuilder::new();
+    let point = p.declare_struct::<miniutil::DefaultTarget>(&[
+        ("x", <i32>::get_type()),
+        ("y", <u8>::get_type()),
+    ]);
+    assert_eq!(point.layout::<miniutil::DefaultTarget>().expect_size("structs are sized"), size(8));
+
+    let mut f = p.declare_function();
+    let pt = f.declare_local_with_ty(point);
+    let ptr = f.declare_local_with_ty(<*const u8>::get_type());
+    f.storage_live(pt);
+    f.storage_live(ptr);
+    f.assign(field(pt, 0), const_int(1_i32));
+    f.assign(ptr, addr_of(field(deref(load(ptr), point), 1), <*const u8>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+
+    let named = fmt_program_with_field_names(prog, FmtOptions::default(), p.field_names());
+    assert!(named.contains("_0.x = 1;"));
+    assert!(named.contains(").y"));
+    // Without the names, fields are printed by index.
+    let plain = fmt_program(prog);
+    assert!(plain.contains("_0.0 = 1;"));
+    assert!(!plain.contains(".x"));
+}
+
+/// Two structs with the same layout have the same type, so only the field they agree on is printed by name.
+#[test]
+fn field_names_same_layout() {
+    let mut p = ProgramBuilder::new();
+    let point = p.declare_struct::<miniutil::DefaultTarget>(&[
+        ("x", <i32>::get_type()),
+        ("y", <i32>::get_type()),
+    ]);
+    let range = p.declare_struct::<miniutil::DefaultTarget>(&[
+        ("x", <i32>::get_type()),
+        ("len", <i32>::get_type()),
+    ]);
+    assert_eq!(point, range);
+
+    let mut f = p.declare_function();
+    let pt = f.declare_local_with_ty(point);
+    f.storage_live(pt);
+    f.assign(field(pt, 0), const_int(1_i32));
+    f.assign(field(pt, 1), const_int(2_i32));
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+
+    let named = fmt_program_with_field_names(prog, FmtOptions::default(), p.field_names());
+    assert!(named.contains("_0.x = 1;"));
+    assert!(named.contains("_0.1 = 2;"));
+}
+
+#[test]
+fn local_and_block_names() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let count = f.declare_local_named::<u32>("count");
+    let other = f.declare_local::<u32>();
+    f.storage_live(count);
+    f.storage_live(other);
+    let head = f.declare_block_named("loop_head");
+    f.goto(head);
+    f.set_cur_block(head, BbKind::Regular);
+    f.assign(count, load(other));
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+    dump_program_with_debug_info(prog, &p);
+
+    let named =
+        fmt_program_with_debug_info(prog, FmtOptions::default(), p.field_names(), p.debug_info());
+    assert!(named.contains("let _0_count: u32;"));
+    assert!(named.contains("goto -> bb1_loop_head;"));
+    assert!(named.contains("  bb1_loop_head:\n"));
+    assert!(named.contains("_0_count = load(_1);"));
+    // Without the names, only the numbers are printed.
+    let plain = fmt_program(prog);
+    assert!(plain.contains("let _0: u32;"));
+    assert!(plain.contains("goto -> bb1;"));
+}
+
+#[test]
+fn comments() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let counter = f.declare_local::<u32>();
+    f.storage_live(counter);
+    f.comment("increment counter");
+    f.assign(counter, const_int(1u32));
+    f.comment("done\nfor now");
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+    dump_program_with_debug_info(prog, &p);
+
+    let commented =
+        fmt_program_with_debug_info(prog, FmtOptions::default(), p.field_names(), p.debug_info());
+    assert!(commented.contains("    storage_live(_0);\n    // increment counter\n    _0 = 1;\n"));
+    // Comments on terminators work the same way, and a multi-line comment is printed line by line.
+    let done = commented.find("    // done\n    // for now\n").unwrap();
+    assert!(commented[done..].lines().nth(2).unwrap().contains("exit()"));
+    assert!(!fmt_program(prog).contains("//"));
+}
+
+#[test]
+fn function_names() {
+    let mut p = ProgramBuilder::new();
+    let helper = {
+        let mut f = p.declare_function_named("helper");
+        f.return_();
+        p.finish_function(f)
+    };
+    let mut f = p.declare_function_named("main");
+    f.call_nounwind(unit_place(), fn_ptr(helper), &[]);
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+    dump_program_with_debug_info(prog, &p);
+
+    let named =
+        fmt_program_with_debug_info(prog, FmtOptions::default(), p.field_names(), p.debug_info());
+    assert!(named.contains("fn f0_helper("));
+    assert!(named.contains("start fn f1_main("));
+    assert!(named.contains("f0_helper()"));
+    let plain = fmt_program(prog);
+    assert!(plain.contains("start fn f1("));
+    assert!(!plain.contains("helper"));
+}
@@ -0,0 +1,51 @@
+use crate::*;
+use miniutil::DefaultTarget;
+
+#[test]
+fn encode_decode_roundtrip() {
+    let bytes = encode_int::<x86_64, u32>(0x01020304);
+    assert_eq!(bytes, list![4, 3, 2, 1]);
+    let bytes: Vec<u8> = bytes.iter().collect();
+    assert_eq!(decode_int::<x86_64, u32>(&bytes), Int::from(0x01020304));
+    assert_eq!(
+        decode_int::<arm, i16>(&encode_int::<arm, i16>(-2).iter().collect::<Vec<_>>()),
+        Int::from(-2)
+    );
+}
+
+#[test]
+fn global_int_value() {
+    let mut p = ProgramBuilder::new();
+    let g = p.declare_global_int::<DefaultTarget, u16>(0x0102);
+    let f = {
+        let mut f = p.declare_function();
+        f.print(load(g));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["258"]);
+}
+
+#[test]
+fn union_from_bytes() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let union_ty = union_ty(
+            &[(offset(0), <[u8; 4]>::get_type()), (offset(0), <u32>::get_type())],
+            size(4),
+            align(4),
+        );
+        let u = f.declare_local_with_ty(union_ty);
+        f.storage_live(u);
+        f.assign(field(u, 0), const_int_bytes::<DefaultTarget, u32>(0xdeadbeefu32));
+        f.print(load(field(u, 1)));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["3735928559"]);
+}
@@ -0,0 +1,251 @@
+use crate::*;
+
+/// How many random layouts `enum_layout_fuzz` checks.
+const LAYOUTS: u64 = 64;
+
+/// A small xorshift generator, so that every seed gives the same layout on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // The state must never be zero.
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `start..end`.
+    fn range(&mut self, start: u64, end: u64) -> u64 {
+        start + self.next() % (end - start)
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next() % 2 == 0
+    }
+
+    /// `n` distinct numbers in `start..end`, in random order.
+    fn distinct(&mut self, n: usize, start: u64, end: u64) -> Vec<u64> {
+        let mut all: Vec<u64> = (start..end).collect();
+        for i in 0..n {
+            let j = self.range(i as u64, all.len() as u64) as usize;
+            all.swap(i, j);
+        }
+        all.truncate(n);
+        all
+    }
+}
+
+/// A payload field of a variant: a `u8`, or a `bool` (only used for the niche).
+#[derive(Clone, Copy)]
+enum Field {
+    U8(u8),
+    Bool(bool),
+}
+
+impl Field {
+    fn ty(self) -> Type {
+        match self {
+            Field::U8(_) => <u8>::get_type(),
+            Field::Bool(_) => <bool>::get_type(),
+        }
+    }
+
+    fn value(self) -> ValueExpr {
+        match self {
+            Field::U8(v) => const_int(v),
+            Field::Bool(b) => const_bool(b),
+        }
+    }
+
+    fn printed(self) -> String {
+        match self {
+            Field::U8(v) => v.to_string(),
+            Field::Bool(b) => b.to_string(),
+        }
+    }
+}
+
+/// A randomly generated enum, together with one value for each of its variants.
+struct Layout {
+    ty: Type,
+    /// The discriminant of each variant and the values of its fields.
+    variants: Vec<(Int, Vec<Field>)>,
+}
+
+/// The variant type of `len` bytes with `fields` at `offsets`.
+fn variant_ty(fields: &[Field], offsets: &[u64], len: u64) -> Type {
+    let fields: Vec<(Offset, Type)> =
+        fields.iter().zip(offsets).map(|(f, o)| (offset(*o), f.ty())).collect();
+    tuple_ty(&fields, size(len), align(1))
+}
+
+/// A discriminator that branches on the `u8` at `offset`, rejecting all values not in `children`.
+fn branch(offset: Offset, children: Vec<(u64, Discriminator)>) -> Discriminator {
+    Discriminator::Branch {
+        offset,
+        value_type: IntType::U8,
+        fallback: GcCow::new(discriminator_invalid()),
+        children: children
+            .into_iter()
+            .map(|(value, disc)| ((Int::from(value), Int::from(value + 1)), disc))
+            .collect(),
+    }
+}
+
+/// An enum whose variants are told apart by a tag of one or two bytes at random offsets.
+/// A two-byte tag makes the discriminator a tree of depth two.
+fn tagged_layout(rng: &mut Rng) -> Layout {
+    let len = rng.range(2, 6);
+    let tag_len = if len > 2 && rng.bool() { 2 } else { 1 };
+    // The tag bytes come first, the rest is room for the payload.
+    let bytes = rng.distinct(len as usize, 0, len);
+    let (tag_offsets, data_offsets) = bytes.split_at(tag_len);
+
+    let n_variants = rng.range(1, 5) as usize;
+    let discriminants = rng.distinct(n_variants, 0, 16);
+    // Tag values never use 255, so that the range of each value fits into a `u8`.
+    let tags: Vec<u64> = rng.distinct(n_variants, 0, 255_u64.pow(tag_len as u32));
+
+    let mut variants = Vec::new();
+    let mut enum_variants = Vec::new();
+    for (disc, tag) in discriminants.iter().zip(tags.iter()) {
+        let n_fields = rng.range(0, data_offsets.len() as u64 + 1) as usize;
+        let offsets = rng.distinct(n_fields, 0, data_offsets.len() as u64);
+        let offsets: Vec<u64> = offsets.iter().map(|i| data_offsets[*i as usize]).collect();
+        let fields: Vec<Field> = offsets.iter().map(|_| Field::U8(rng.next() as u8)).collect();
+
+        let tag_bytes = [tag % 255, tag / 255];
+        let tagger: Vec<(Offset, (IntType, Int))> = tag_offsets
+            .iter()
+            .zip(tag_bytes)
+            .map(|(o, byte)| (offset(*o), (IntType::U8, Int::from(byte))))
+            .collect();
+        let ty = variant_ty(&fields, &offsets, len);
+        enum_variants.push((*disc as u8, enum_variant(ty, &tagger)));
+        variants.push((Int::from(*disc), fields));
+    }
+
+    let discriminator = if tag_len == 1 {
+        branch(
+            offset(tag_offsets[0]),
+            discriminants.iter().zip(&tags).map(|(d, t)| (*t, discriminator_known(*d))).collect(),
+        )
+    } else {
+        // Branch on the first tag byte, then on the second one.
+        let mut first: Vec<u64> = tags.iter().map(|t| t % 255).collect();
+        first.sort();
+        first.dedup();
+        let children = first
+            .into_iter()
+            .map(|lo| {
+                let inner = discriminants
+                    .iter()
+                    .zip(&tags)
+                    .filter(|(_, t)| *t % 255 == lo)
+                    .map(|(d, t)| (*t / 255, discriminator_known(*d)))
+                    .collect();
+                (lo, branch(offset(tag_offsets[1]), inner))
+            })
+            .collect();
+        branch(offset(tag_offsets[0]), children)
+    };
+
+    let ty = enum_ty::<u8>(&enum_variants, discriminator, size(len), align(1));
+    Layout { ty, variants }
+}
+
+/// An enum with one data variant and up to three data-less variants stored in the niche of a `bool`.
+fn niche_layout(rng: &mut Rng) -> Layout {
+    let len = rng.range(1, 5);
+    let bytes = rng.distinct(len as usize, 0, len);
+    let niche_offset = bytes[0];
+
+    let mut offsets = vec![niche_offset];
+    let mut fields = vec![Field::Bool(rng.bool())];
+    for o in &bytes[1..] {
+        if rng.bool() {
+            offsets.push(*o);
+            fields.push(Field::U8(rng.next() as u8));
+        }
+    }
+    let payload = variant_ty(&fields, &offsets, len);
+
+    let n_empty = rng.range(1, 4) as u32;
+    let ty = niche_enum_ty(payload, Niche::bool(offset(niche_offset)), n_empty);
+    let mut variants = vec![(Int::ZERO, fields)];
+    variants.extend((1..=n_empty).map(|d| (Int::from(d), Vec::new())));
+    Layout { ty, variants }
+}
+
+/// For each variant, the program
+/// - constructs a value of the variant and copies it, so it gets encoded and decoded again, and
+/// - writes the fields through a downcast and then sets the discriminant, and copies that, too.
+///
+/// After both, it prints the discriminant and the fields of the copy.
+fn layout_program(layout: &Layout) -> (Program, Vec<String>) {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let mut expected = Vec::new();
+
+    for (disc, fields) in &layout.variants {
+        let disc = *disc;
+        let Type::Enum { variants, .. } = layout.ty else { unreachable!() };
+        let variant_ty = variants.get(disc).unwrap().ty;
+        let original = f.declare_local_with_ty(layout.ty);
+        let written = f.declare_local_with_ty(layout.ty);
+        let copy = f.declare_local_with_ty(layout.ty);
+        f.storage_live(original);
+        f.storage_live(written);
+        f.storage_live(copy);
+
+        let data: Vec<ValueExpr> = fields.iter().map(|field| field.value()).collect();
+        f.assign(original, variant(disc, tuple(&data, variant_ty), layout.ty));
+        f.assign(copy, load(original));
+        f.print(get_discriminant(copy));
+        for i in 0..fields.len() {
+            f.print(load(field(downcast(copy, disc), i)));
+        }
+
+        for (i, data) in data.iter().enumerate() {
+            f.assign(field(downcast(written, disc), i), *data);
+        }
+        f.set_discriminant(written, disc);
+        f.assign(copy, load(written));
+        f.print(get_discriminant(copy));
+        for i in 0..fields.len() {
+            f.print(load(field(downcast(copy, disc), i)));
+        }
+
+        for _ in 0..2 {
+            expected.push(disc.to_string());
+            expected.extend(fields.iter().map(|field| field.printed()));
+        }
+    }
+
+    f.exit();
+    let f = p.finish_function(f);
+    (p.finish_program(f), expected)
+}
+
+/// Generates random enum layouts and checks that encoding, decoding, `GetDiscriminant` and
+/// `SetDiscriminant` agree on every variant.
+#[test]
+fn enum_layout_fuzz() {
+    for seed in 0..LAYOUTS {
+        let mut rng = Rng::new(seed);
+        let layout = if rng.bool() { tagged_layout(&mut rng) } else { niche_layout(&mut rng) };
+        let (p, expected) = layout_program(&layout);
+        match get_stdout::<BasicMem>(p) {
+            Ok(out) => assert_eq!(out, expected, "wrong output for the layout with seed {seed}"),
+            Err(info) => {
+                dump_program(p);
+                panic!("the layout with seed {seed} failed: {info:?}");
+            }
+        }
+    }
+}
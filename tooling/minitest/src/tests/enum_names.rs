@@ -0,0 +1,257 @@
+use crate::*;
+
+/// Sets `x` to each variant of `enum Shape { Dot, Line(u8), Rect(u16) }` in turn
+/// and prints what the `switch_int` on its discriminant finds.
+#[test]
+fn switch_on_variant_names() {
+    let mut p = ProgramBuilder::new();
+    let shape = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("Dot", <()>::get_type()),
+        ("Line", <u8>::get_type()),
+        ("Rect", <u16>::get_type()),
+    ]);
+    let dot = p.discriminant(shape, "Dot");
+    let line = p.discriminant(shape, "Line");
+    let rect = p.discriminant(shape, "Rect");
+
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(shape);
+    f.storage_live(x);
+    let check = |f: &mut FunctionBuilder| {
+        f.switch_int(
+            get_discriminant(x),
+            &[
+                (dot, &|f| f.print(const_int(0u8))),
+                (line, &|f| f.print(load(field(downcast(x, line), 0)))),
+                (rect, &|f| f.print(load(field(downcast(x, rect), 0)))),
+            ],
+            |f| f.unreachable(),
+        );
+    };
+
+    f.set_discriminant(x, dot);
+    check(&mut f);
+    f.assign(field(downcast(x, line), 0), const_int(7u8));
+    f.set_discriminant(x, line);
+    check(&mut f);
+    f.assign(field(downcast(x, rect), 0), const_int(515u16));
+    f.set_discriminant(x, rect);
+    check(&mut f);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "7", "515"]);
+}
+
+/// Like `switch_on_variant_names`, but with `match_enum` doing the downcasts.
+#[test]
+fn match_enum() {
+    let mut p = ProgramBuilder::new();
+    let shape = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("Dot", <()>::get_type()),
+        ("Line", <u8>::get_type()),
+        ("Rect", <u16>::get_type()),
+    ]);
+    let dot = p.discriminant(shape, "Dot");
+    let line = p.discriminant(shape, "Line");
+    let rect = p.discriminant(shape, "Rect");
+
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(shape);
+    f.storage_live(x);
+    let check = |f: &mut FunctionBuilder| {
+        f.match_enum(
+            x,
+            &[
+                (line, &|f, line| f.print(load(field(line, 0)))),
+                (rect, &|f, rect| f.print(load(field(rect, 0)))),
+            ],
+            |f| f.print(const_int(0u8)),
+        );
+    };
+
+    f.set_discriminant(x, dot);
+    check(&mut f);
+    f.assign(field(downcast(x, line), 0), const_int(7u8));
+    f.set_discriminant(x, line);
+    check(&mut f);
+    f.assign(field(downcast(x, rect), 0), const_int(515u16));
+    f.set_discriminant(x, rect);
+    check(&mut f);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "7", "515"]);
+}
+
+#[test]
+#[should_panic(expected = "match_enum: the enum has no variant with discriminant 2")]
+fn match_enum_unknown_variant() {
+    let mut p = ProgramBuilder::new();
+    let ty = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(ty);
+    f.match_enum(x, &[(2, &|_, _| {})], |_| {});
+}
+
+/// `if let Some(v) = x { print(v) } else { print(0) }` for `x` being `None` and then `Some(7)`.
+#[test]
+fn if_variant() {
+    let mut p = ProgramBuilder::new();
+    let option = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    let none = p.discriminant(option, "None");
+    let some = p.discriminant(option, "Some");
+
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(option);
+    f.storage_live(x);
+    let check = |f: &mut FunctionBuilder| {
+        f.if_variant(x, some, |f, some| f.print(load(field(some, 0))), |f| f.print(const_int(0u8)));
+    };
+
+    f.set_discriminant(x, none);
+    check(&mut f);
+    f.assign(field(downcast(x, some), 0), const_int(7u8));
+    f.set_discriminant(x, some);
+    check(&mut f);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "7"]);
+}
+
+#[test]
+#[should_panic(expected = "if_variant: the enum has no variant with discriminant 2")]
+fn if_variant_unknown_variant() {
+    let mut p = ProgramBuilder::new();
+    let ty = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(ty);
+    f.if_variant(x, 2, |_, _| {}, |_| {});
+}
+
+/// The tag comes first, and the data is aligned after it.
+#[test]
+fn declared_enum_layout() {
+    let mut p = ProgramBuilder::new();
+    let ty = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("A", <u8>::get_type()),
+        ("B", <u32>::get_type()),
+    ]);
+    assert_eq!(ty.layout::<miniutil::DefaultTarget>(), LayoutStrategy::Sized(size(8), align(4)));
+    let b = p.discriminant(ty, "B");
+    let bytes = encode_value::<BasicMem>(ty, variant_value(b, tuple_value(&[to_value(1u32)])));
+    let bytes: Vec<Option<u8>> = bytes.iter().collect();
+    assert_eq!(bytes, &[Some(1), None, None, None, Some(1), Some(0), Some(0), Some(0)]);
+}
+
+#[test]
+#[should_panic(expected = "the enum has no variant called `Nothing`")]
+fn unknown_variant_name() {
+    let mut p = ProgramBuilder::new();
+    let ty = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    p.discriminant(ty, "Nothing");
+}
+
+/// `Option<u8>` and `Result<u8, ()>` have the same layout, so they are the same type,
+/// but each keeps the names of its own variants.
+#[test]
+fn same_layout_enums() {
+    let mut p = ProgramBuilder::new();
+    let option = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    let result = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("Err", <()>::get_type()),
+        ("Ok", <u8>::get_type()),
+    ]);
+    assert_eq!(option, result);
+    assert_eq!(p.discriminant(option, "Some"), Int::from(1));
+    assert_eq!(p.discriminant(result, "Ok"), Int::from(1));
+    assert_eq!(p.discriminant(result, "Err"), Int::from(0));
+}
+
+#[test]
+#[should_panic(
+    expected = "the variant `A` has different discriminants in enums with the same layout"
+)]
+fn same_layout_enums_ambiguous_variant() {
+    let mut p = ProgramBuilder::new();
+    let ab = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("A", <u8>::get_type()),
+        ("B", <u8>::get_type()),
+    ]);
+    p.declare_enum::<miniutil::DefaultTarget>(&[("B", <u8>::get_type()), ("A", <u8>::get_type())]);
+    p.discriminant(ab, "A");
+}
+
+/// Like `switch_on_variant_names` and `match_enum`, but with the variants given by name.
+#[test]
+fn switch_and_match_by_name() {
+    let mut p = ProgramBuilder::new();
+    let shape = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("Dot", <()>::get_type()),
+        ("Line", <u8>::get_type()),
+        ("Rect", <u16>::get_type()),
+    ]);
+    let dot = p.discriminant(shape, "Dot");
+    let line = p.discriminant(shape, "Line");
+
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(shape);
+    f.storage_live(x);
+    let check = |f: &mut FunctionBuilder| {
+        f.switch_variant_by_name(
+            x,
+            &[("Dot", &|f| f.print(const_int(0u8))), ("Rect", &|f| f.print(const_int(2u8)))],
+            |f| f.print(const_int(1u8)),
+        );
+        f.match_enum_by_name(x, &[("Line", &|f, line| f.print(load(field(line, 0))))], |f| {
+            f.print(const_int(0u8))
+        });
+    };
+
+    f.set_discriminant(x, dot);
+    check(&mut f);
+    f.assign(field(downcast(x, line), 0), const_int(7u8));
+    f.set_discriminant(x, line);
+    check(&mut f);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "0", "1", "7"]);
+}
+
+#[test]
+#[should_panic(expected = "the enum has no variant called `Nothing`")]
+fn match_enum_by_unknown_name() {
+    let mut p = ProgramBuilder::new();
+    let ty = p.declare_enum::<miniutil::DefaultTarget>(&[
+        ("None", <()>::get_type()),
+        ("Some", <u8>::get_type()),
+    ]);
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(ty);
+    f.match_enum_by_name(x, &[("Nothing", &|_, _| {})], |_| {});
+}
@@ -328,3 +328,59 @@ fn negative_discriminants_work() {
     let prog = small_program(locals, statements);
     assert_stop::<BasicMem>(prog)
 }
+
+/// The data-less variant `discriminant` of the niche-encoded enum `enum_ty`.
+fn empty_variant(enum_ty: Type, discriminant: u32) -> ValueExpr {
+    let Type::Enum { variants, .. } = enum_ty else { unreachable!() };
+    let ty = variants.get(Int::from(discriminant)).unwrap().ty;
+    variant(discriminant, tuple(&[], ty), enum_ty)
+}
+
+/// `Option<&u32>` stores `None` as the null pointer.
+#[test]
+fn niche_enum_null_ptr() {
+    let ref_ty = <&u32>::get_type();
+    let option_ty = niche_enum_ty(ref_ty, Niche::null_ptr(offset(0)), 1);
+    assert_eq!(
+        option_ty.layout::<miniutil::DefaultTarget>(),
+        ref_ty.layout::<miniutil::DefaultTarget>()
+    );
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    let opt = f.declare_local_with_ty(option_ty);
+    f.storage_live(x);
+    f.storage_live(opt);
+    f.assign(opt, empty_variant(option_ty, 1));
+    f.print(transmute(load(opt), <usize>::get_type()));
+    f.assign(opt, variant(0, addr_of(x, ref_ty), option_ty));
+    f.print(get_discriminant(opt));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "0"]);
+}
+
+/// Several data-less variants use the values after the valid range of the niche.
+#[test]
+fn niche_enum_bool() {
+    let enum_ty = niche_enum_ty(<bool>::get_type(), Niche::bool(offset(0)), 3);
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let e = f.declare_local_with_ty(enum_ty);
+    f.storage_live(e);
+    f.assign(e, empty_variant(enum_ty, 3));
+    f.print(transmute(load(e), <u8>::get_type()));
+    f.assign(e, transmute(const_int(3_u8), enum_ty));
+    f.print(get_discriminant(e));
+    f.assign(e, transmute(const_int(1_u8), enum_ty));
+    f.print(get_discriminant(e));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["4", "2", "0"]);
+}
@@ -20,7 +20,7 @@ fn pointer_works() {
     assert_stop::<BasicMem>(program);
 }
 
-/// Test if `expose` called with non-pointer is UB
+/// Test if `expose` called with non-pointer is ill-formed
 #[test]
 fn requires_pointer() {
     let locals = [<usize>::get_type()];
@@ -30,8 +30,63 @@ fn requires_pointer() {
     ];
 
     let program = program(&[function(Ret::No, 0, &locals, &blocks)]);
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         program,
-        "invalid argument for `PointerExposeProvenance` intrinsic: not a thin pointer",
+        "Terminator::Intrinsic: invalid argument 0 for `PointerExposeProvenance`: expected a thin pointer",
     );
 }
+
+/// Casts the address of `x` back to a pointer and loads `x` through that pointer.
+/// `expose` determines whether the provenance of `x` is exposed first.
+fn roundtrip_program(expose: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    let addr = f.declare_local::<usize>();
+    let ptr = f.declare_local::<*const i32>();
+    let val = f.declare_local::<i32>();
+    f.storage_live(x);
+    f.storage_live(addr);
+    f.storage_live(ptr);
+    f.storage_live(val);
+    f.assign(x, const_int(42i32));
+    if expose {
+        f.expose_provenance(addr, addr_of(x, <*const i32>::get_type()));
+    } else {
+        f.assign(addr, ptr_addr(addr_of(x, <*const i32>::get_type())));
+    }
+    f.with_exposed_provenance(ptr, load(addr));
+    f.assign(val, load(deref(load(ptr), <i32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+/// With demonic choice, the guess might not pick the exposed provenance.
+#[test]
+fn demonic_guess_can_fail() {
+    let p = roundtrip_program(true);
+    assert_ub_eventually_demonic_provenance::<BasicMem>(
+        p,
+        32,
+        "dereferencing pointer without provenance",
+    );
+}
+
+/// Without any exposed provenance, there is nothing to guess.
+#[test]
+fn demonic_guess_without_expose() {
+    let p = roundtrip_program(false);
+    for _ in 0..8 {
+        let mut config = Config::default();
+        config.provenance_choice = ProvenanceChoice::Demonic;
+        assert_eq!(
+            run_program_with_config::<BasicMem>(p, config),
+            TerminationInfo::Ub(minirust_rs::prelude::String::from_internal(
+                "dereferencing pointer without provenance".to_string()
+            ))
+        );
+    }
+}
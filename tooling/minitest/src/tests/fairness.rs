@@ -0,0 +1,100 @@
+use crate::*;
+
+/// The main thread spawns a thread that exits the program, and then spins forever.
+/// The program only terminates if the second thread eventually gets to run.
+fn spin_program() -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let exit = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<()>();
+        f.print(const_int(1u32));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        f.spawn(exit, unit(), thread_id);
+        let spin = f.declare_block();
+        f.goto(spin);
+        f.set_cur_block(spin, BbKind::Regular);
+        f.goto(spin);
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+fn config(bound: usize, schedule: &[usize]) -> Config {
+    let mut config = Config::default();
+    config.fairness_bound = Some(Int::from(bound));
+    config.replay_schedule = Some(schedule.iter().map(|id| Int::from(*id)).collect());
+    config
+}
+
+#[test]
+fn spin_loop_terminates() {
+    let p = spin_program();
+    dump_program(p);
+    for seed in 0..8 {
+        assert_eq!(run_program_with_seed::<BasicMem>(p, seed), TerminationInfo::MachineStop);
+    }
+}
+
+#[test]
+fn fair_replay() {
+    // Two steps to spawn the thread, then the main thread spins for as long as the bound allows.
+    let schedule = [0, 0, 0, 0, 0, 1];
+    let mut interp = Interpreter::<BasicMem>::with_config(spin_program(), config(3, &schedule));
+    assert_eq!(interp.run_steps(2), None);
+    assert_eq!(interp.state().waiting, &[0, 0]);
+    assert_eq!(interp.run_steps(3), None);
+    assert_eq!(interp.state().waiting, &[0, 3]);
+    assert_eq!(interp.run_steps(1), None);
+    assert_eq!(interp.state().waiting, &[1, 0]);
+    assert_eq!(interp.state().stdout, &["1"]);
+}
+
+/// However the threads are picked, the second thread never waits for more steps than the bound allows.
+#[test]
+fn starving_thread_runs_next() {
+    let mut config = Config::default();
+    config.fairness_bound = Some(Int::from(3));
+    let mut interp = Interpreter::<BasicMem>::with_config(spin_program(), config);
+    while interp.state().stdout.is_empty() {
+        assert_eq!(interp.run_steps(1), None);
+        assert!(interp.state().waiting.iter().all(|n| *n <= 3));
+    }
+}
+
+#[test]
+fn unfair_replay() {
+    let info = run_program_with_config::<BasicMem>(spin_program(), config(3, &[0; 6]));
+    let msg = "the replayed schedule is unfair: thread 1 has been waiting for 3 steps in step 5";
+    let msg = minirust_rs::prelude::String::from_internal(msg.to_string());
+    assert_eq!(info, TerminationInfo::ReplayMismatch(msg));
+}
+
+/// Without a fairness bound, the check reports the first thread that waits for longer than the given bound.
+#[test]
+fn check_fairness_reports_starvation() {
+    let mut config = Config::default();
+    config.replay_schedule = Some([0, 0, 0, 0, 0, 1].into_iter().map(Int::from).collect());
+    let starvation = check_fairness::<BasicMem>(spin_program(), config, 2).unwrap_err();
+    assert_eq!(starvation, Starvation { thread: 1, waiting: 3, step: 5 });
+}
+
+/// Runs with a fairness bound never exceed it.
+#[test]
+fn check_fairness_accepts_fair_runs() {
+    let mut config = Config::default();
+    config.fairness_bound = Some(Int::from(3));
+    for _ in 0..8 {
+        let info = check_fairness::<BasicMem>(spin_program(), config, 3);
+        assert_eq!(info, Ok(TerminationInfo::MachineStop));
+    }
+}
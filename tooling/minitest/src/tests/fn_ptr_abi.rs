@@ -0,0 +1,88 @@
+use crate::*;
+
+/// Calls `callee` indirectly: its address is first stored in a local of type `fn()`,
+/// which is then loaded to perform the call.
+fn call_indirect(p: &mut ProgramBuilder, callee: FnName, ret: Type, args: &[ValueExpr]) -> FnName {
+    let mut f = p.declare_function();
+    let fn_local = f.declare_local_with_ty(Type::Ptr(PtrType::FnPtr));
+    let ret_local = f.declare_local_with_ty(ret);
+    f.storage_live(fn_local);
+    f.storage_live(ret_local);
+    f.assign(fn_local, fn_ptr(callee));
+    let args: Vec<ArgumentExpr> = args.iter().map(|arg| by_value(*arg)).collect();
+    f.call_nounwind(ret_local, load(fn_local), &args);
+    f.exit();
+    p.finish_function(f)
+}
+
+#[test]
+fn indirect_call_matching() {
+    let mut p = ProgramBuilder::new();
+    let callee = p.declare_exit_function(
+        &[<u32>::get_type(), <bool>::get_type()],
+        <u8>::get_type(),
+        CallingConvention::Rust,
+    );
+    let main =
+        call_indirect(&mut p, callee, <u8>::get_type(), &[const_int(0u32), const_bool(true)]);
+    let p = p.finish_program(main);
+    dump_program(p);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn indirect_call_arg_count() {
+    let mut p = ProgramBuilder::new();
+    let callee =
+        p.declare_exit_function(&[<u32>::get_type()], <()>::get_type(), CallingConvention::Rust);
+    let main = call_indirect(&mut p, callee, <()>::get_type(), &[]);
+    let p = p.finish_program(main);
+    assert_ub::<BasicMem>(p, "call ABI violation: number of arguments does not agree");
+}
+
+#[test]
+fn indirect_call_second_arg_signedness() {
+    let mut p = ProgramBuilder::new();
+    let callee = p.declare_exit_function(
+        &[<u32>::get_type(), <u32>::get_type()],
+        <()>::get_type(),
+        CallingConvention::Rust,
+    );
+    // The second argument has the right size, but a different sign, which does not matter.
+    let main = call_indirect(&mut p, callee, <()>::get_type(), &[const_int(0u32), const_int(0i32)]);
+    let p = p.finish_program(main);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn indirect_call_ret_ty() {
+    let mut p = ProgramBuilder::new();
+    let callee = p.declare_exit_function(&[], <u64>::get_type(), CallingConvention::Rust);
+    let main = call_indirect(&mut p, callee, <u32>::get_type(), &[]);
+    let p = p.finish_program(main);
+    assert_ub::<BasicMem>(p, "call ABI violation: return types are not compatible");
+}
+
+#[test]
+fn indirect_call_conv() {
+    let mut p = ProgramBuilder::new();
+    let callee = p.declare_exit_function(&[], <()>::get_type(), CallingConvention::C);
+    let main = call_indirect(&mut p, callee, <()>::get_type(), &[]);
+    let p = p.finish_program(main);
+    assert_ub::<BasicMem>(p, "call ABI violation: calling conventions are not the same");
+}
+
+/// Raw pointers with the same metadata kind are compatible, no matter their pointee.
+#[test]
+fn indirect_call_ptr_pointee() {
+    let mut p = ProgramBuilder::new();
+    let callee = p.declare_exit_function(
+        &[<*const u64>::get_type()],
+        <()>::get_type(),
+        CallingConvention::Rust,
+    );
+    let arg = ptr_to_ptr(unit_ptr(), <*const u8>::get_type());
+    let main = call_indirect(&mut p, callee, <()>::get_type(), &[arg]);
+    let p = p.finish_program(main);
+    assert_stop::<BasicMem>(p);
+}
@@ -0,0 +1,86 @@
+use crate::*;
+
+fn fn_ptr_ty() -> Type {
+    Type::Ptr(PtrType::FnPtr)
+}
+
+fn empty_function(p: &mut ProgramBuilder) -> FnName {
+    let mut f = p.declare_function();
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Within one stack frame, pointers to the same function are always equal.
+#[test]
+fn same_fn_same_frame() {
+    let mut p = ProgramBuilder::new();
+    let target = empty_function(&mut p);
+
+    let mut f = p.declare_function();
+    f.if_(eq(fn_ptr(target), fn_ptr(target)), |f| f.exit(), |f| f.unreachable());
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop_always::<BasicMem>(p, 16);
+}
+
+/// Pointers to different functions are never equal.
+#[test]
+fn different_fns() {
+    let mut p = ProgramBuilder::new();
+    let a = empty_function(&mut p);
+    let b = empty_function(&mut p);
+
+    let mut f = p.declare_function();
+    f.if_(ne(fn_ptr(a), fn_ptr(b)), |f| f.exit(), |f| f.unreachable());
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop_always::<BasicMem>(p, 16);
+}
+
+/// Pointers to the same function created in different stack frames can differ.
+#[test]
+fn same_fn_different_frames() {
+    let mut p = ProgramBuilder::new();
+    let target = empty_function(&mut p);
+
+    let compare = {
+        let mut f = p.declare_function();
+        let arg = f.declare_arg_with_ty(fn_ptr_ty());
+        f.if_(eq(load(arg), fn_ptr(target)), |f| f.return_(), |f| f.unreachable());
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(compare), &[by_value(fn_ptr(target))]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub_eventually::<BasicMem>(p, 32, "reached unreachable code");
+}
+
+/// No matter which instance we get, we can call it.
+#[test]
+fn call_any_instance() {
+    let mut p = ProgramBuilder::new();
+    let target = empty_function(&mut p);
+
+    let call = {
+        let mut f = p.declare_function();
+        let arg = f.declare_arg_with_ty(fn_ptr_ty());
+        f.call_ignoreret(load(arg), &[]);
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(call), &[by_value(fn_ptr(target))]);
+    f.call_ignoreret(fn_ptr(target), &[]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop_always::<BasicMem>(p, 16);
+}
@@ -0,0 +1,109 @@
+use crate::*;
+
+/// Creates two mutable references to the same local without `Validate` statements,
+/// writes through the second and then through the first one.
+/// The references are transmuted from a raw pointer, so that only assigning them can retag them.
+fn two_mut_refs() -> Program {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let ref_ty = ref_mut_ty_default_markers_for(<u32>::get_type());
+        let x = f.declare_local::<u32>();
+        let raw = f.declare_local::<*mut u32>();
+        let r1 = f.declare_local_with_ty(ref_ty);
+        let r2 = f.declare_local_with_ty(ref_ty);
+        f.storage_live(x);
+        f.storage_live(raw);
+        f.storage_live(r1);
+        f.storage_live(r2);
+        f.assign(x, const_int(0u32));
+        f.assign(raw, addr_of(x, <*mut u32>::get_type()));
+        f.assign(r1, transmute(load(raw), ref_ty));
+        f.assign(r2, transmute(load(raw), ref_ty));
+        f.assign(deref(load(r2), <u32>::get_type()), const_int(1u32));
+        f.assign(deref(load(r1), <u32>::get_type()), const_int(2u32));
+        f.exit();
+        p.finish_function(f)
+    };
+    p.finish_program(f)
+}
+
+#[test]
+fn assignments_not_validated_by_default() {
+    let p = two_mut_refs();
+    dump_program(p);
+    let mut config = Config::default();
+    config.full_validation = false;
+    assert_eq!(run_program_with_config::<TreeBorrowMem>(p, config), TerminationInfo::MachineStop);
+}
+
+#[test]
+fn full_validation_retags_assignments() {
+    let p = two_mut_refs();
+    dump_program(p);
+    let mut config = Config::default();
+    config.full_validation = true;
+    assert_eq!(
+        run_program_with_config::<TreeBorrowMem>(p, config),
+        TerminationInfo::Ub(minirust_rs::prelude::String::from_internal(
+            "Tree Borrows: writing to the local of a pointer with Disabled permission".to_string()
+        ))
+    );
+}
+
+/// Passes two mutable references to the same local to a function, which writes through the second
+/// and then through the first one. As in `two_mut_refs`, the references are transmuted from a raw pointer,
+/// so that only passing them as arguments can retag them.
+fn two_mut_ref_args() -> Program {
+    let mut p = ProgramBuilder::new();
+    let ref_ty = ref_mut_ty_default_markers_for(<u32>::get_type());
+
+    let callee = {
+        let mut f = p.declare_function();
+        let r1 = f.declare_arg_with_ty(ref_ty);
+        let r2 = f.declare_arg_with_ty(ref_ty);
+        f.assign(deref(load(r2), <u32>::get_type()), const_int(1u32));
+        f.assign(deref(load(r1), <u32>::get_type()), const_int(2u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        let raw = f.declare_local::<*mut u32>();
+        f.storage_live(x);
+        f.storage_live(raw);
+        f.assign(x, const_int(0u32));
+        f.assign(raw, addr_of(x, <*mut u32>::get_type()));
+        f.call_nounwind(
+            unit_place(),
+            fn_ptr(callee),
+            &[by_value(transmute(load(raw), ref_ty)), by_value(transmute(load(raw), ref_ty))],
+        );
+        f.exit();
+        p.finish_function(f)
+    };
+    p.finish_program(f)
+}
+
+#[test]
+fn call_arguments_not_validated_by_default() {
+    let p = two_mut_ref_args();
+    let mut config = Config::default();
+    config.full_validation = false;
+    assert_eq!(run_program_with_config::<TreeBorrowMem>(p, config), TerminationInfo::MachineStop);
+}
+
+#[test]
+fn full_validation_retags_call_arguments() {
+    let p = two_mut_ref_args();
+    let mut config = Config::default();
+    config.full_validation = true;
+    assert_eq!(
+        run_program_with_config::<TreeBorrowMem>(p, config),
+        TerminationInfo::Ub(minirust_rs::prelude::String::from_internal(
+            "Tree Borrows: writing to the local of a pointer with Disabled permission".to_string()
+        ))
+    );
+}
@@ -41,7 +41,10 @@ fn alloc_argcount() {
     let f = function(Ret::No, 0, &locals, &[b0]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Allocate` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Allocate`",
+    );
 }
 
 #[test]
@@ -105,7 +108,10 @@ fn alloc_wrongarg1() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid first argument to `Allocate` intrinsic: not an integer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Allocate`: expected an integer",
+    );
 }
 
 #[test]
@@ -127,7 +133,10 @@ fn alloc_wrongarg2() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid second argument to `Allocate` intrinsic: not an integer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 1 for `Allocate`: expected an integer",
+    );
 }
 
 #[test]
@@ -148,7 +157,10 @@ fn alloc_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid return type for `Allocate` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Allocate`: expected a thin pointer",
+    );
 }
 
 #[test]
@@ -188,7 +200,10 @@ fn dealloc_argcount() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Deallocate` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Deallocate`",
+    );
 }
 
 #[test]
@@ -254,9 +269,9 @@ fn dealloc_wrongarg1() {
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(
+    assert_ill_formed::<BasicMem>(
         p,
-        "invalid first argument to `Deallocate` intrinsic: not a thin pointer",
+        "Terminator::Intrinsic: invalid argument 0 for `Deallocate`: expected a thin pointer",
     );
 }
 
@@ -279,7 +294,10 @@ fn dealloc_wrongarg2() {
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid second argument to `Deallocate` intrinsic: not an integer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 1 for `Deallocate`: expected an integer",
+    );
 }
 
 #[test]
@@ -301,7 +319,10 @@ fn dealloc_wrongarg3() {
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid third argument to `Deallocate` intrinsic: not an integer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 2 for `Deallocate`: expected an integer",
+    );
 }
 
 #[test]
@@ -323,7 +344,10 @@ fn dealloc_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid return type for `Deallocate` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Deallocate`: expected the unit type",
+    );
 }
 
 #[test]
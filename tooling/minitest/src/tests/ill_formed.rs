@@ -36,3 +36,31 @@ fn type_mismatch() {
     let p = small_program(locals, stmts);
     assert_ill_formed::<BasicMem>(p, "Statement::Assign: destination and source type differ");
 }
+
+/// A function with a type mismatch in its first block.
+fn mismatched_fn() -> Function {
+    let locals = [<i32>::get_type()];
+    let b0 = block!(storage_live(0), assign(local(0), const_int::<u32>(0)), exit());
+    function(Ret::No, 0, &locals, &[b0])
+}
+
+/// A function with a local of negative array size.
+fn neg_array_fn() -> Function {
+    let locals = [array_ty(<()>::get_type(), -1)];
+    let b0 = block!(exit());
+    function(Ret::No, 0, &locals, &[b0])
+}
+
+/// When several functions are ill-formed, the error of the one with the lowest name is reported,
+/// no matter how the function map happens to be laid out.
+#[test]
+fn first_error_is_deterministic() {
+    let start = function(Ret::No, 0, &[], &[block!(exit())]);
+    for _ in 0..16 {
+        let p = program(&[start, mismatched_fn(), neg_array_fn()]);
+        assert_ill_formed::<BasicMem>(p, "Statement::Assign: destination and source type differ");
+
+        let p = program(&[start, neg_array_fn(), mismatched_fn()]);
+        assert_ill_formed::<BasicMem>(p, "Type::Array: negative amount of elements");
+    }
+}
@@ -0,0 +1,106 @@
+use crate::*;
+
+/// The number of `Call` terminators in the start function.
+fn calls(prog: Program) -> usize {
+    let f = prog.functions.get(prog.start).unwrap();
+    f.blocks.iter().filter(|(_name, bb)| matches!(bb.terminator, Terminator::Call { .. })).count()
+}
+
+/// Calls a function that adds one to its argument twice, printing 42 and 43.
+fn add_one_twice(hint: InlineHint) -> (Program, InlineHints) {
+    let mut p = ProgramBuilder::new();
+
+    let add_one = {
+        let mut f = p.declare_function();
+        f.set_inline(hint);
+        let x = f.declare_arg::<u32>();
+        let ret = f.declare_ret::<u32>();
+        f.assign(ret, add(load(x), const_int(1u32)));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let y = f.declare_local::<u32>();
+        f.storage_live(y);
+        f.call_nounwind(y, fn_ptr(add_one), &[by_value(const_int(41u32))]);
+        f.print(load(y));
+        f.call_nounwind(y, fn_ptr(add_one), &[by_value(load(y))]);
+        f.print(load(y));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    (p.finish_program(main), p.inline_hints().clone())
+}
+
+/// Calls a function that unwinds through its own cleanup block into the cleanup block of the caller,
+/// printing 0, 1 and 2.
+fn unwind_into_caller() -> (Program, InlineHints) {
+    let mut p = ProgramBuilder::new();
+
+    let panics = {
+        let mut f = p.declare_function();
+        f.set_inline(InlineHint::Always);
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(1u32));
+            f.resume_unwind();
+        });
+        f.print(const_int(0u32));
+        f.start_unwind(unit_ptr(), cleanup);
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let cleanup = f.cleanup_block(|f| {
+            f.print(const_int(2u32));
+            f.exit();
+        });
+        f.call(unit_place(), fn_ptr(panics), &[], cleanup);
+        f.exit();
+        p.finish_function(f)
+    };
+
+    (p.finish_program(main), p.inline_hints().clone())
+}
+
+#[test]
+fn preserves_semantics() {
+    for (prog, hints) in [add_one_twice(InlineHint::Always), unwind_into_caller()] {
+        assert_preserves_semantics::<BasicMem>(
+            |prog| inline_functions(prog, &hints),
+            &[prog],
+            &[0],
+        );
+    }
+}
+
+#[test]
+fn inlines_calls() {
+    let (prog, hints) = add_one_twice(InlineHint::Always);
+    let inlined = inline_functions(prog, &hints);
+    assert_eq!(calls(prog), 2);
+    assert_eq!(calls(inlined), 0);
+    assert_eq!(get_stdout::<BasicMem>(inlined).unwrap(), &["42", "43"]);
+}
+
+/// Small functions are inlined with `InlineHint::Hint`, and nothing is inlined with `InlineHint::Never`.
+#[test]
+fn respects_hints() {
+    let (prog, hints) = add_one_twice(InlineHint::Hint);
+    assert_eq!(calls(inline_functions(prog, &hints)), 0);
+
+    let (prog, hints) = add_one_twice(InlineHint::Never);
+    assert_eq!(calls(inline_functions(prog, &hints)), 2);
+}
+
+/// `ResumeUnwind` in the callee continues in the cleanup block of the caller.
+#[test]
+fn inlines_unwinding_call() {
+    let (prog, hints) = unwind_into_caller();
+    let inlined = inline_functions(prog, &hints);
+    assert_eq!(calls(inlined), 0);
+    assert_eq!(get_stdout::<BasicMem>(inlined).unwrap(), &["0", "1", "2"]);
+}
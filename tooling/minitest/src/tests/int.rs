@@ -6,21 +6,21 @@ fn arith_works() {
 
     let mut f = p.declare_function();
 
-    f.assume(eq(add(const_int(1u8), const_int(7u8)), const_int(8u8)));
-    f.assume(eq(sub(const_int(1u8), const_int(7u8)), const_int(250u8)));
-    f.assume(eq(mul(const_int(1u8), const_int(7u8)), const_int(7u8)));
-    f.assume(eq(div(const_int(1u8), const_int(7u8)), const_int(0u8)));
-    f.assume(eq(rem(const_int(1u8), const_int(7u8)), const_int(1u8)));
+    f.assert_eq(add(const_int(1u8), const_int(7u8)), const_int(8u8));
+    f.assert_eq(sub(const_int(1u8), const_int(7u8)), const_int(250u8));
+    f.assert_eq(mul(const_int(1u8), const_int(7u8)), const_int(7u8));
+    f.assert_eq(div(const_int(1u8), const_int(7u8)), const_int(0u8));
+    f.assert_eq(rem(const_int(1u8), const_int(7u8)), const_int(1u8));
 
     // Division is odd around signs...
-    f.assume(eq(div(const_int(7i8), const_int(3i8)), const_int(2i8)));
-    f.assume(eq(rem(const_int(7i8), const_int(3i8)), const_int(1i8)));
-    f.assume(eq(div(const_int(7i8), const_int(-3i8)), const_int(-2i8)));
-    f.assume(eq(rem(const_int(7i8), const_int(-3i8)), const_int(1i8)));
-    f.assume(eq(div(const_int(-7i8), const_int(3i8)), const_int(-2i8)));
-    f.assume(eq(rem(const_int(-7i8), const_int(3i8)), const_int(-1i8)));
-    f.assume(eq(div(const_int(-7i8), const_int(-3i8)), const_int(2i8)));
-    f.assume(eq(rem(const_int(-7i8), const_int(-3i8)), const_int(-1i8)));
+    f.assert_eq(div(const_int(7i8), const_int(3i8)), const_int(2i8));
+    f.assert_eq(rem(const_int(7i8), const_int(3i8)), const_int(1i8));
+    f.assert_eq(div(const_int(7i8), const_int(-3i8)), const_int(-2i8));
+    f.assert_eq(rem(const_int(7i8), const_int(-3i8)), const_int(1i8));
+    f.assert_eq(div(const_int(-7i8), const_int(3i8)), const_int(-2i8));
+    f.assert_eq(rem(const_int(-7i8), const_int(3i8)), const_int(-1i8));
+    f.assert_eq(div(const_int(-7i8), const_int(-3i8)), const_int(2i8));
+    f.assert_eq(rem(const_int(-7i8), const_int(-3i8)), const_int(-1i8));
 
     f.exit();
 
@@ -100,8 +100,8 @@ fn div_exact_arith() {
 
     let mut f = p.declare_function();
 
-    f.assume(eq(div_exact(const_int(4i32), const_int(2i32)), const_int(2i32)));
-    f.assume(eq(div_exact(const_int(4i32), const_int(-1i32)), const_int(-4i32)));
+    f.assert_eq(div_exact(const_int(4i32), const_int(2i32)), const_int(2i32));
+    f.assert_eq(div_exact(const_int(4i32), const_int(-1i32)), const_int(-4i32));
 
     f.exit();
     let f = p.finish_function(f);
@@ -239,7 +239,7 @@ fn count_ones_works() {
     let mut f = p.declare_function();
 
     fn check<T: TypeConv + Into<Int>>(f: &mut FunctionBuilder, val: T, expect: u32) {
-        f.assume(eq(count_ones(const_int(val)), const_int(expect)));
+        f.assert_eq(count_ones(const_int(val)), const_int(expect));
     }
 
     check(&mut f, 0_u8, 0_u8.count_ones());
@@ -260,21 +260,21 @@ fn shl_works() {
 
     let mut f = p.declare_function();
 
-    f.assume(eq(shl(const_int(1u8), const_int(7u8)), const_int(128u8)));
-    f.assume(eq(shl(const_int(1u8), const_int(0u8)), const_int(1u8)));
-    f.assume(eq(shl(const_int(-1i32), const_int(1i32)), const_int(-2i32)));
-    f.assume(eq(shl(const_int(i32::MAX), const_int(1)), const_int(-2i32)));
+    f.assert_eq(shl(const_int(1u8), const_int(7u8)), const_int(128u8));
+    f.assert_eq(shl(const_int(1u8), const_int(0u8)), const_int(1u8));
+    f.assert_eq(shl(const_int(-1i32), const_int(1i32)), const_int(-2i32));
+    f.assert_eq(shl(const_int(i32::MAX), const_int(1)), const_int(-2i32));
 
     // Shl should allow for different integer types for left and right operands
-    f.assume(eq(shl(const_int(1u16), const_int(7i32)), const_int(128u16)));
+    f.assert_eq(shl(const_int(1u16), const_int(7i32)), const_int(128u16));
 
     // Test if shift calculates the euclidean modulo of right operand with number of bits of left
     // 8 % 8 = 0; shift by 0
-    f.assume(eq(shl(const_int(1u8), const_int(8)), const_int(1u8)));
+    f.assert_eq(shl(const_int(1u8), const_int(8)), const_int(1u8));
     // 9 % 8 = 1; shift by 1
-    f.assume(eq(shl(const_int(1u8), const_int(9)), const_int(2u8)));
+    f.assert_eq(shl(const_int(1u8), const_int(9)), const_int(2u8));
     // -1 % 8 = 7; (for solution in 0..8) shift by 7
-    f.assume(eq(shl(const_int(1u8), const_int(-1)), const_int(128u8)));
+    f.assert_eq(shl(const_int(1u8), const_int(-1)), const_int(128u8));
     f.exit();
 
     let f = p.finish_function(f);
@@ -290,27 +290,27 @@ fn shr_works() {
     let mut f = p.declare_function();
 
     // Logical shr for unsigned integers
-    f.assume(eq(shr(const_int(u8::MAX), const_int(7u8)), const_int(1u8)));
-    f.assume(eq(shr(const_int(1u8), const_int(0)), const_int(1u8)));
+    f.assert_eq(shr(const_int(u8::MAX), const_int(7u8)), const_int(1u8));
+    f.assert_eq(shr(const_int(1u8), const_int(0)), const_int(1u8));
 
     // Arithmetic shr for signed integers
-    f.assume(eq(shr(const_int(-4i16), const_int(1u16)), const_int(-2i16)));
-    f.assume(eq(shr(const_int(-1i32), const_int(1)), const_int(-1i32)));
-    f.assume(eq(shr(const_int(1i32), const_int(1)), const_int(0i32)));
-    f.assume(eq(shr(const_int(i32::MAX), const_int(1)), const_int(i32::MAX / 2)));
-    f.assume(eq(shr(const_int(i32::MIN), const_int(1)), const_int(i32::MIN / 2)));
+    f.assert_eq(shr(const_int(-4i16), const_int(1u16)), const_int(-2i16));
+    f.assert_eq(shr(const_int(-1i32), const_int(1)), const_int(-1i32));
+    f.assert_eq(shr(const_int(1i32), const_int(1)), const_int(0i32));
+    f.assert_eq(shr(const_int(i32::MAX), const_int(1)), const_int(i32::MAX / 2));
+    f.assert_eq(shr(const_int(i32::MIN), const_int(1)), const_int(i32::MIN / 2));
 
     // Shr should allow for different integer types for left and right operands
-    f.assume(eq(shr(const_int(u8::MAX), const_int(7i32)), const_int(1u8)));
-    f.assume(eq(shr(const_int(-4i16), const_int(1u8)), const_int(-2i16)));
+    f.assert_eq(shr(const_int(u8::MAX), const_int(7i32)), const_int(1u8));
+    f.assert_eq(shr(const_int(-4i16), const_int(1u8)), const_int(-2i16));
 
     // Test if shift calculates the euclidean modulo of right operand with number of bits of left
     // 8 % 8 = 0; shift by 0
-    f.assume(eq(shr(const_int(1u8), const_int(8)), const_int(1u8)));
+    f.assert_eq(shr(const_int(1u8), const_int(8)), const_int(1u8));
     // 9 % 8 = 1; shift by 1
-    f.assume(eq(shr(const_int(2u8), const_int(9)), const_int(1u8)));
+    f.assert_eq(shr(const_int(2u8), const_int(9)), const_int(1u8));
     // -1 % 8 = 7; (for solution in 0..8) shift by 7
-    f.assume(eq(shr(const_int(u8::MAX), const_int(-1)), const_int(1u8)));
+    f.assert_eq(shr(const_int(u8::MAX), const_int(-1)), const_int(1u8));
     f.exit();
 
     let f = p.finish_function(f);
@@ -337,9 +337,9 @@ fn unchecked_add_works() {
     let mut p = ProgramBuilder::new();
 
     let mut f = p.declare_function();
-    f.assume(eq(add_unchecked(const_int(i8::MIN), const_int(i8::MAX)), const_int(-1_i8)));
-    f.assume(eq(add_unchecked(const_int(0_i8), const_int(-i8::MAX)), const_int(i8::MIN + 1_i8)));
-    f.assume(eq(add_unchecked(const_int(30), const_int(12)), const_int(42)));
+    f.assert_eq(add_unchecked(const_int(i8::MIN), const_int(i8::MAX)), const_int(-1_i8));
+    f.assert_eq(add_unchecked(const_int(0_i8), const_int(-i8::MAX)), const_int(i8::MIN + 1_i8));
+    f.assert_eq(add_unchecked(const_int(30), const_int(12)), const_int(42));
     f.exit();
     let f = p.finish_function(f);
 
@@ -365,9 +365,9 @@ fn unchecked_sub_works() {
     let mut p = ProgramBuilder::new();
 
     let mut f = p.declare_function();
-    f.assume(eq(sub_unchecked(const_int(-1_i8), const_int(i8::MAX)), const_int(i8::MIN)));
-    f.assume(eq(sub_unchecked(const_int(-1_i8), const_int(i8::MIN)), const_int(i8::MAX)));
-    f.assume(eq(sub_unchecked(const_int(53), const_int(11)), const_int(42)));
+    f.assert_eq(sub_unchecked(const_int(-1_i8), const_int(i8::MAX)), const_int(i8::MIN));
+    f.assert_eq(sub_unchecked(const_int(-1_i8), const_int(i8::MIN)), const_int(i8::MAX));
+    f.assert_eq(sub_unchecked(const_int(53), const_int(11)), const_int(42));
     f.exit();
     let f = p.finish_function(f);
 
@@ -392,9 +392,9 @@ fn unchecked_mul_works() {
     let mut p = ProgramBuilder::new();
 
     let mut f = p.declare_function();
-    f.assume(eq(mul_unchecked(const_int(-1_i8), const_int(i8::MAX)), const_int(i8::MIN + 1)));
-    f.assume(eq(mul_unchecked(const_int(-8_i8), const_int(16_i8)), const_int(i8::MIN)));
-    f.assume(eq(mul_unchecked(const_int(6), const_int(7)), const_int(42)));
+    f.assert_eq(mul_unchecked(const_int(-1_i8), const_int(i8::MAX)), const_int(i8::MIN + 1));
+    f.assert_eq(mul_unchecked(const_int(-8_i8), const_int(16_i8)), const_int(i8::MIN));
+    f.assert_eq(mul_unchecked(const_int(6), const_int(7)), const_int(42));
     f.exit();
     let f = p.finish_function(f);
 
@@ -425,13 +425,13 @@ fn unchecked_shl_works() {
 
     let mut f = p.declare_function();
 
-    f.assume(eq(shl_unchecked(const_int(1u8), const_int(7u8)), const_int(128u8)));
-    f.assume(eq(shl_unchecked(const_int(1u8), const_int(0u8)), const_int(1u8)));
-    f.assume(eq(shl_unchecked(const_int(-1i32), const_int(1i32)), const_int(-2i32)));
-    f.assume(eq(shl_unchecked(const_int(i32::MAX), const_int(1)), const_int(-2i32)));
+    f.assert_eq(shl_unchecked(const_int(1u8), const_int(7u8)), const_int(128u8));
+    f.assert_eq(shl_unchecked(const_int(1u8), const_int(0u8)), const_int(1u8));
+    f.assert_eq(shl_unchecked(const_int(-1i32), const_int(1i32)), const_int(-2i32));
+    f.assert_eq(shl_unchecked(const_int(i32::MAX), const_int(1)), const_int(-2i32));
 
     // Unchecked shl should allow for different integer types for left and right operands
-    f.assume(eq(shl_unchecked(const_int(1u16), const_int(7i32)), const_int(128u16)));
+    f.assert_eq(shl_unchecked(const_int(1u16), const_int(7i32)), const_int(128u16));
     f.exit();
 
     let f = p.finish_function(f);
@@ -468,19 +468,19 @@ fn unchecked_shr_works() {
     let mut f = p.declare_function();
 
     // Logical shr for unsigned integers
-    f.assume(eq(shr_unchecked(const_int(u8::MAX), const_int(7u8)), const_int(1u8)));
-    f.assume(eq(shr_unchecked(const_int(1u8), const_int(0)), const_int(1u8)));
+    f.assert_eq(shr_unchecked(const_int(u8::MAX), const_int(7u8)), const_int(1u8));
+    f.assert_eq(shr_unchecked(const_int(1u8), const_int(0)), const_int(1u8));
 
     // Arithmetic shr for signed integers
-    f.assume(eq(shr_unchecked(const_int(-4i16), const_int(1u16)), const_int(-2i16)));
-    f.assume(eq(shr_unchecked(const_int(-1i32), const_int(1)), const_int(-1i32)));
-    f.assume(eq(shr_unchecked(const_int(1i32), const_int(1)), const_int(0i32)));
-    f.assume(eq(shr_unchecked(const_int(i32::MAX), const_int(1)), const_int(i32::MAX / 2)));
-    f.assume(eq(shr_unchecked(const_int(i32::MIN), const_int(1)), const_int(i32::MIN / 2)));
+    f.assert_eq(shr_unchecked(const_int(-4i16), const_int(1u16)), const_int(-2i16));
+    f.assert_eq(shr_unchecked(const_int(-1i32), const_int(1)), const_int(-1i32));
+    f.assert_eq(shr_unchecked(const_int(1i32), const_int(1)), const_int(0i32));
+    f.assert_eq(shr_unchecked(const_int(i32::MAX), const_int(1)), const_int(i32::MAX / 2));
+    f.assert_eq(shr_unchecked(const_int(i32::MIN), const_int(1)), const_int(i32::MIN / 2));
 
     // Unchecked shr should allow for different integer types for left and right operands
-    f.assume(eq(shr_unchecked(const_int(u8::MAX), const_int(7i32)), const_int(1u8)));
-    f.assume(eq(shr_unchecked(const_int(-4i16), const_int(1u8)), const_int(-2i16)));
+    f.assert_eq(shr_unchecked(const_int(u8::MAX), const_int(7i32)), const_int(1u8));
+    f.assert_eq(shr_unchecked(const_int(-4i16), const_int(1u8)), const_int(-2i16));
     f.exit();
 
     let f = p.finish_function(f);
@@ -495,12 +495,12 @@ fn cmp_works() {
 
     let mut f = p.declare_function();
 
-    f.assume(eq(cmp(const_int(0), const_int(-1)), const_int(1_i8)));
-    f.assume(eq(cmp(const_int(-1), const_int(0)), const_int(-1_i8)));
-    f.assume(eq(cmp(const_int(0), const_int(1)), const_int(-1_i8)));
-    f.assume(eq(cmp(const_int(1), const_int(0)), const_int(1_i8)));
-    f.assume(eq(cmp(const_int(0), const_int(0)), const_int(0_i8)));
-    f.assume(eq(cmp(const_int(i128::MAX), const_int(i128::MIN)), const_int(1_i8)));
+    f.assert_eq(cmp(const_int(0), const_int(-1)), const_int(1_i8));
+    f.assert_eq(cmp(const_int(-1), const_int(0)), const_int(-1_i8));
+    f.assert_eq(cmp(const_int(0), const_int(1)), const_int(-1_i8));
+    f.assert_eq(cmp(const_int(1), const_int(0)), const_int(1_i8));
+    f.assert_eq(cmp(const_int(0), const_int(0)), const_int(0_i8));
+    f.assert_eq(cmp(const_int(i128::MAX), const_int(i128::MIN)), const_int(1_i8));
     f.exit();
 
     let f = p.finish_function(f);
@@ -551,7 +551,7 @@ fn overflow_add_works() {
     f.storage_live(loc);
     let mut test = |l: i32, r: i32, result: (i32, bool)| {
         f.assign(loc, overflow_add(const_int(l), const_int(r)));
-        f.assume(eq(load(field(loc, 0)), const_int(result.0)));
+        f.assert_eq(load(field(loc, 0)), const_int(result.0));
         f.assume(not(bool_xor(load(field(loc, 1)), const_bool(result.1))));
     };
     // test the non-overflowing addition
@@ -580,7 +580,7 @@ fn overflow_sub_works() {
     f.storage_live(loc);
     let mut test = |l: i32, r: i32, result: (i32, bool)| {
         f.assign(loc, overflow_sub(const_int(l), const_int(r)));
-        f.assume(eq(load(field(loc, 0)), const_int(result.0)));
+        f.assert_eq(load(field(loc, 0)), const_int(result.0));
         f.assume(not(bool_xor(load(field(loc, 1)), const_bool(result.1))));
     };
     // test the non-overflowing subtraction
@@ -609,7 +609,7 @@ fn overflow_mul_works() {
     f.storage_live(loc);
     let mut test = |l: i32, r: i32, result: (i32, bool)| {
         f.assign(loc, overflow_mul(const_int(l), const_int(r)));
-        f.assume(eq(load(field(loc, 0)), const_int(result.0)));
+        f.assert_eq(load(field(loc, 0)), const_int(result.0));
         f.assume(not(bool_xor(load(field(loc, 1)), const_bool(result.1))));
     };
     // test the non-overflowing multiplication
@@ -661,3 +661,72 @@ fn overflow_ill_formed_right() {
     let p = p.finish_program(f);
     assert_ill_formed::<BasicMem>(p, "BinOp::IntWithOverflow: invalid right type");
 }
+
+#[test]
+fn overflow_mode_wrap() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+
+    let wrap = OverflowMode::Wrap;
+    f.assert_eq(add_with(wrap, const_int(255u8), const_int(1u8)), const_int(0u8));
+    f.assert_eq(sub_with(wrap, const_int(0u8), const_int(1u8)), const_int(255u8));
+    f.assert_eq(mul_with(wrap, const_int(16u8), const_int(16u8)), const_int(0u8));
+    f.assert_eq(shl_with(wrap, const_int(1u8), const_int(9u8)), const_int(2u8));
+    f.assert_eq(shr_with(wrap, const_int(4u8), const_int(9u8)), const_int(2u8));
+    f.exit();
+
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn overflow_mode_ub() {
+    let ub = OverflowMode::Ub;
+    assert_ub_expr::<u8, BasicMem>(
+        add_with(ub, const_int(255u8), const_int(1u8)),
+        "overflow in unchecked add",
+    );
+    assert_ub_expr::<u8, BasicMem>(
+        sub_with(ub, const_int(0u8), const_int(1u8)),
+        "overflow in unchecked sub",
+    );
+    assert_ub_expr::<u8, BasicMem>(
+        mul_with(ub, const_int(16u8), const_int(16u8)),
+        "overflow in unchecked mul",
+    );
+    assert_ub_expr::<u8, BasicMem>(
+        shl_with(ub, const_int(1u8), const_int(9u8)),
+        "overflow in unchecked shift",
+    );
+}
+
+fn checked_program(build: impl Fn(&mut FunctionBuilder, PlaceExpr)) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i8>();
+    f.storage_live(x);
+    build(&mut f, x);
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn checked_arith_in_range() {
+    let p = checked_program(|f, x| {
+        f.add_checked(x, const_int(100i8), const_int(27i8));
+        f.sub_checked(x, load(x), const_int(1i8));
+        f.mul_checked(x, load(x), const_int(-1i8));
+    });
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["-126"]);
+}
+
+#[test]
+fn checked_arith_overflow() {
+    let p = checked_program(|f, x| f.add_checked(x, const_int(127i8), const_int(1i8)));
+    dump_program(p);
+    assert_abort::<BasicMem>(p);
+}
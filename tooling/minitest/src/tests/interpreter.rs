@@ -0,0 +1,80 @@
+use crate::*;
+
+fn print_twice() -> (Program, FnName) {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(const_int(1u32));
+    f.print(const_int(2u32));
+    f.exit();
+    let f = p.finish_function(f);
+    (p.finish_program(f), f)
+}
+
+#[test]
+fn step_through_program() {
+    let (prog, main) = print_twice();
+    let mut interp = Interpreter::<BasicMem>::new(prog);
+
+    let state = interp.state();
+    assert_eq!(state.steps, 0);
+    assert_eq!(state.termination, None);
+    let location = state.threads[0].clone().unwrap();
+    assert_eq!(location.function, main);
+    assert_eq!(location.index, 0);
+
+    assert_eq!(interp.step(), Ok(()));
+    let state = interp.state();
+    assert_eq!(state.steps, 1);
+    assert_eq!(state.stdout, &["1"]);
+    assert_ne!(state.threads[0].clone().unwrap().block, location.block);
+
+    assert_eq!(interp.run(), TerminationInfo::MachineStop);
+    let state = interp.state();
+    assert_eq!(state.steps, 2);
+    assert_eq!(state.termination, Some(TerminationInfo::MachineStop));
+    assert_eq!(state.stdout, &["1", "2"]);
+
+    // Stepping a terminated program does nothing.
+    assert_eq!(interp.step(), Err(TerminationInfo::MachineStop));
+    assert_eq!(interp.state().steps, 2);
+}
+
+#[test]
+fn run_limited_steps() {
+    let (prog, _main) = print_twice();
+    let mut interp = Interpreter::<BasicMem>::new(prog);
+    assert_eq!(interp.run_steps(1), None);
+    assert_eq!(interp.run_steps(10), Some(TerminationInfo::MachineStop));
+    assert_eq!(interp.state().stdout, &["1", "2"]);
+}
+
+#[test]
+fn ill_formed_program() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(get_metadata(const_int(0u32)));
+    f.exit();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+
+    let mut interp = Interpreter::<BasicMem>::new(prog);
+    assert!(matches!(interp.state().termination, Some(TerminationInfo::IllFormed(_))));
+    assert!(interp.state().threads.is_empty());
+    assert!(matches!(interp.step(), Err(TerminationInfo::IllFormed(_))));
+}
+
+#[test]
+fn run_fast() {
+    let (prog, _main) = print_twice();
+    assert_eq!(run_program_fast::<BasicMem>(prog, Config::default()), TerminationInfo::MachineStop);
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.unreachable();
+    let f = p.finish_function(f);
+    let prog = p.finish_program(f);
+    assert!(matches!(
+        run_program_fast::<BasicMem>(prog, Config::default()),
+        TerminationInfo::Ub(_)
+    ));
+}
@@ -0,0 +1,90 @@
+use crate::*;
+
+#[test]
+fn max_size_per_target() {
+    assert_eq!(max_size::<x86_64>().bytes(), Int::from(i64::MAX));
+    assert_eq!(max_size::<arm>().bytes(), Int::from(i32::MAX));
+}
+
+/// The largest valid array is fine as a local; one more byte makes the program ill-formed.
+#[test]
+fn boundary_local() {
+    let p = small_program(&[boundary_array_ty::<arm>(0)], &[]);
+    assert_stop::<BasicMemory<arm>>(p);
+
+    let p = small_program(&[boundary_array_ty::<arm>(1)], &[]);
+    dump_program(p);
+    assert_ill_formed::<BasicMemory<arm>>(p, "LayoutStrategy: size not valid");
+    // The 32-bit boundary is no problem for a 64-bit target.
+    assert_stop::<BasicMemory<x86_64>>(p);
+}
+
+/// Allocates `size` bytes on the heap of target `T`.
+fn allocate_program<T: Target>(size: Int) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*mut u8>();
+    f.storage_live(ptr);
+    f.allocate(const_usize_on::<T>(size), const_usize_on::<T>(1), ptr);
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn boundary_allocation() {
+    let p = allocate_program::<arm>(max_size::<arm>().bytes() + Int::ONE);
+    dump_program(p);
+    assert_ub::<BasicMemory<arm>>(p, "asking for a too large allocation");
+}
+
+/// Prints the size of a `[elem]` with `count` elements on target `T`.
+fn compute_size_program<T: Target>(elem: Type, count: Int) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(compute_size(slice_ty(elem), const_usize_on::<T>(count)));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn compute_size_at_boundary() {
+    let max = max_size::<arm>().bytes();
+
+    let p = compute_size_program::<arm>(<u8>::get_type(), max);
+    assert_eq!(get_stdout::<BasicMemory<arm>>(p).unwrap(), &[max.to_string()]);
+
+    let p = compute_size_program::<arm>(<u8>::get_type(), max + Int::ONE);
+    dump_program(p);
+    assert_ub::<BasicMemory<arm>>(p, "ComputeSize: total size exceeds isize::MAX");
+}
+
+/// A slice with `usize::MAX` elements of size 2 has a size that does not even fit into a `usize`.
+#[test]
+fn compute_size_beyond_usize() {
+    let p = compute_size_program::<x86_64>(<u16>::get_type(), Int::from(usize::MAX));
+    assert_ub::<BasicMem>(p, "ComputeSize: total size exceeds isize::MAX");
+
+    let p = compute_size_program::<x86_64>(<u16>::get_type(), Int::from(usize::MAX / 4));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &[(usize::MAX / 2 - 1).to_string()]);
+}
+
+/// Vtables of trait objects are subject to the same size limit as all other types.
+#[test]
+fn boundary_vtable() {
+    let mut p = ProgramBuilder::new();
+    let trait_ = p.declare_trait();
+    let trait_ = p.finish_trait(trait_);
+    let size = Size::from_bytes(max_size::<arm>().bytes() + Int::ONE).unwrap();
+    let vtable = p.declare_vtable(trait_, size, Align::ONE, List::new());
+    p.finish_vtable(vtable);
+
+    let mut f = p.declare_function();
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    assert_stop::<BasicMemory<x86_64>>(p);
+    assert_ill_formed::<BasicMemory<arm>>(p, "Program: size stored in vtable not valid");
+}
@@ -0,0 +1,91 @@
+use crate::*;
+
+fn min_sig() -> Signature {
+    Signature {
+        args: vec![<u32>::get_type(), <u32>::get_type()],
+        ret: <u32>::get_type(),
+        conv: CallingConvention::Rust,
+    }
+}
+
+fn counter_sig() -> Signature {
+    Signature { args: vec![], ret: <u32>::get_type(), conv: CallingConvention::Rust }
+}
+
+/// A library exporting `min` and `next`, which returns the next value of a counter in a global.
+/// `next` uses `min` through an extern declaration, to be resolved when linking.
+fn library() -> ProgramBuilder {
+    let mut p = ProgramBuilder::new();
+    let counter = p.declare_global_int::<miniutil::DefaultTarget, u32>(10);
+    let min = p.declare_extern_function("min", min_sig());
+
+    let mut f = p.declare_function();
+    let ret = f.declare_ret::<u32>();
+    f.assign(counter, add(load(counter), const_int(1u32)));
+    f.call_nounwind(ret, fn_ptr(min), &[by_value(load(counter)), by_value(const_int(12u32))]);
+    f.return_();
+    let next = p.finish_function(f);
+    p.export_function("next", next);
+    p
+}
+
+/// Another library, defining `min`.
+fn min_library() -> ProgramBuilder {
+    let mut p = ProgramBuilder::new();
+    let min = stdlib::declare_min::<u32>(&mut p);
+    p.export_function("min", min);
+    p
+}
+
+/// Calls `next` three times; prints 11, 12, 12.
+fn main_program(libs: Vec<ProgramBuilder>) -> Program {
+    let mut p = ProgramBuilder::new();
+    // Our own global must not get mixed up with the one of the library.
+    let x = p.declare_global_int::<miniutil::DefaultTarget, u32>(0);
+    let next = p.declare_extern_function("next", counter_sig());
+
+    let mut f = p.declare_function();
+    for _ in 0..3 {
+        f.call_nounwind(x, fn_ptr(next), &[]);
+        f.print(load(x));
+    }
+    f.exit();
+    let f = p.finish_function(f);
+
+    for lib in libs {
+        p.link(lib);
+    }
+    p.finish_program(f)
+}
+
+#[test]
+fn link_libraries() {
+    let p = main_program(vec![library(), min_library()]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["11", "12", "12"]);
+}
+
+/// Externs of a library can also be resolved by a library linked earlier.
+#[test]
+fn link_order() {
+    let p = main_program(vec![min_library(), library()]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["11", "12", "12"]);
+}
+
+#[test]
+#[should_panic(expected = "extern function `min` was never linked")]
+fn unresolved_extern() {
+    main_program(vec![library()]);
+}
+
+#[test]
+#[should_panic(expected = "extern function `next` does not match its definition")]
+fn signature_mismatch() {
+    let mut lib = ProgramBuilder::new();
+    let mut f = lib.declare_function();
+    f.declare_arg::<u32>();
+    f.declare_ret::<u32>();
+    f.return_();
+    let next = lib.finish_function(f);
+    lib.export_function("next", next);
+    main_program(vec![lib]);
+}
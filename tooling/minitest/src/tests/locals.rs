@@ -29,3 +29,120 @@ fn assign_dead() {
     let p = p.finish_program(f);
     assert_ub::<BasicMem>(p, "access to a dead local");
 }
+
+#[test]
+fn scoped_temp() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let i = f.let_::<u32>(const_int(0u32));
+    f.while_(lt(load(i), const_int(3u32)), |f, _| {
+        f.scope(|f| {
+            let tmp = f.declare_temp::<u32>();
+            f.assign(tmp, add(load(i), const_int(1u32)));
+            f.assign(i, load(tmp));
+        });
+    });
+    f.print(load(i));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["3"]);
+}
+
+#[test]
+fn scoped_temp_dead_after_scope() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let tmp = std::cell::Cell::new(None);
+    f.scope(|f| {
+        let t = f.declare_temp::<u32>();
+        f.assign(t, const_int(42u32));
+        tmp.set(Some(t));
+    });
+    f.assign(tmp.get().unwrap(), const_int(42u32));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+}
+
+/// Any local declared and marked live in a scope is dead after it.
+#[test]
+fn scoped_let_dead_after_scope() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = std::cell::Cell::new(None);
+    f.scope(|f| {
+        let y = f.let_::<u32>(const_int(1u32));
+        x.set(Some(f.let_::<u32>(load(y))));
+    });
+    f.print(load(x.get().unwrap()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+}
+
+/// Locals declared outside of a scope are not affected by it, even if they are marked live inside.
+#[test]
+fn scope_keeps_outer_locals() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.scope(|f| {
+        f.storage_live(x);
+        f.assign(x, const_int(1u32));
+    });
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+}
+
+/// Breaking out of a loop from within a scope in its body also marks the scope's locals dead.
+#[test]
+fn scoped_temp_dead_after_break() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let tmp = std::cell::Cell::new(None);
+    f.loop_(|f, ctrl| {
+        f.scope(|f| {
+            let t = f.let_::<u32>(const_int(42u32));
+            tmp.set(Some(t));
+            ctrl.break_(f);
+        });
+    });
+    f.print(load(tmp.get().unwrap()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "access to a dead local");
+}
+
+#[test]
+fn let_() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.let_::<u32>(const_int(20u32));
+    let y = f.let_with_ty(<u32>::get_type(), add(load(x), const_int(1u32)));
+    f.print(mul(load(y), const_int(2u32)));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
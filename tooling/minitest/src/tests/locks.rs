@@ -22,7 +22,7 @@ fn lock_handover() {
         critical.storage_live(val);
         critical.assign(val, const_int(0u32));
         critical.lock_acquire(load(lock));
-        critical.while_(ne(load(val), const_int(256_u32)), |f| {
+        critical.while_(ne(load(val), const_int(256_u32)), |f, _| {
             f.assign(val, add(load(val), const_int(1u32)));
         });
         critical.lock_release(load(lock));
@@ -148,7 +148,28 @@ fn acquire_arg_count() {
     let f = function(Ret::No, 0, &[], &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Acquire` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Lock(Acquire)`",
+    )
+}
+
+#[test]
+fn acquire_too_many_args() {
+    let b0 = block!(Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::Lock(IntrinsicLockOp::Acquire),
+        arguments: list![const_int::<u32>(0), null()],
+        ret: unit_place(),
+        next_block: Some(BbName(Name::from_internal(1))),
+    });
+    let b1 = block!(exit());
+    let f = function(Ret::No, 0, &[], &[b0, b1]);
+
+    let p = program(&[f]);
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Lock(Acquire)`",
+    )
 }
 
 #[test]
@@ -160,7 +181,10 @@ fn acquire_arg_value() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid first argument to `Acquire` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Lock(Acquire)`: expected an integer",
+    )
 }
 
 #[test]
@@ -180,7 +204,10 @@ fn acquire_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid return type for `Acquire` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Lock(Acquire)`: expected the unit type or a Boolean",
+    )
 }
 
 #[test]
@@ -199,6 +226,42 @@ fn acquire_non_existent() {
     assert_ub::<BasicMem>(p, "acquiring non-existing lock")
 }
 
+#[test]
+fn acquire_reentrant() {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+
+    let mut f = p.declare_function();
+    f.lock_create(lock);
+    f.lock_acquire(load(lock));
+    f.lock_acquire(load(lock));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "acquiring a lock that is already held by the current thread");
+}
+
+/// Acquiring a lock again after releasing it is fine.
+#[test]
+fn acquire_after_release() {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+
+    let mut f = p.declare_function();
+    f.lock_create(lock);
+    f.lock_acquire(load(lock));
+    f.lock_release(load(lock));
+    f.lock_acquire(load(lock));
+    f.lock_release(load(lock));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop::<BasicMem>(p);
+}
+
 // UB Tests for Release
 
 #[test]
@@ -215,7 +278,10 @@ fn release_arg_count() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Release` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Lock(Release)`",
+    )
 }
 
 #[test]
@@ -227,7 +293,10 @@ fn release_arg_value() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid first argument to `Release` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Lock(Release)`: expected an integer",
+    )
 }
 
 #[test]
@@ -247,7 +316,10 @@ fn release_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid return type for `Release` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Lock(Release)`: expected the unit type",
+    )
 }
 
 #[test]
@@ -298,7 +370,10 @@ fn create_arg_count() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Create` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Lock(Create)`",
+    )
 }
 
 #[test]
@@ -318,7 +393,10 @@ fn create_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid return type for `Create` lock intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Lock(Create)`: expected an integer",
+    )
 }
 
 // Other errors
@@ -362,3 +440,95 @@ fn deadlock() {
     let p = p.finish_program(main);
     assert_deadlock::<BasicMem>(p);
 }
+
+// Poisoning
+
+/// Acquires and releases a lock, unwinding in between if `unwind` is set,
+/// and then checks whether acquiring the lock again reports it as poisoned.
+fn poison_program(unwind: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+
+    let mut f = p.declare_function();
+    let poisoned = f.declare_local::<bool>();
+    f.storage_live(poisoned);
+    f.lock_create(lock);
+    f.lock_acquire(load(lock));
+    let cont = f.declare_block();
+    if unwind {
+        let catch = f.catch_block(|f| {
+            f.lock_release(load(lock));
+            f.stop_unwind(cont);
+        });
+        f.start_unwind(unit_ptr(), catch);
+    } else {
+        f.lock_release(load(lock));
+        f.goto(cont);
+    }
+    f.set_cur_block(cont, BbKind::Regular);
+    f.lock_acquire_poisoned(load(lock), poisoned);
+    f.print(load(poisoned));
+    f.lock_release(load(lock));
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn poison_on_unwind() {
+    let p = poison_program(true);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+}
+
+#[test]
+fn no_poison_without_unwind() {
+    let p = poison_program(false);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["false"]);
+}
+
+/// A thread blocked on a lock learns that it is poisoned when it gets the lock,
+/// even though the lock was not yet poisoned when the thread started waiting.
+#[test]
+fn poison_on_handover() {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+
+    let second = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<*const ()>();
+        let poisoned = f.declare_local::<bool>();
+        f.storage_live(poisoned);
+        f.lock_acquire_poisoned(load(lock), poisoned);
+        f.print(load(poisoned));
+        f.lock_release(load(lock));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        f.lock_create(lock);
+        // The second thread can only get the lock after the main thread unwound.
+        f.lock_acquire(load(lock));
+        f.spawn(second, null(), thread_id);
+        let cont = f.declare_block();
+        let catch = f.catch_block(|f| {
+            f.lock_release(load(lock));
+            f.stop_unwind(cont);
+        });
+        f.start_unwind(unit_ptr(), catch);
+        f.set_cur_block(cont, BbKind::Regular);
+        f.join(load(thread_id));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+}
@@ -1,27 +1,50 @@
+mod abi_compatibility;
 mod abort;
 mod align;
+mod alloc_introspection;
 mod assume;
 mod atomic;
 mod atomic_fetch;
+mod atomic_ptr;
+mod auto_storage;
 mod blocks;
 mod bool;
 mod builder_api;
 mod call;
+mod call_graph;
 mod calling_convention;
 mod catch_unwind;
+mod chaos;
+mod cleanup_order;
 mod compare_exchange;
 mod compute_align;
 mod compute_size;
 mod concurrency;
+mod const_prop;
+mod coverage;
 mod data_race;
+mod dead_code_elimination;
 mod dereferenceable;
+mod dsl;
+mod dump;
+mod endianness;
 mod enum_discriminant;
 mod enum_downcast;
+mod enum_layout_fuzz;
+mod enum_names;
 mod enum_representation;
 mod expose;
+mod fairness;
+mod fn_ptr_abi;
+mod fn_ptr_eq;
+mod full_validation;
 mod heap_intrinsics;
 mod ill_formed;
+mod inline;
 mod int;
+mod interpreter;
+mod isize_max;
+mod link;
 mod locals;
 mod locks;
 mod main;
@@ -31,17 +54,42 @@ mod no_preserve_prov;
 mod null;
 mod packed;
 mod place_mention;
+mod preserve_semantics;
 mod print;
+mod project;
+mod promela;
 mod ptr;
+mod ptr_fragments;
+mod ptr_guaranteed_cmp;
+mod ptr_int_cast;
 mod ptr_offset;
 mod ptr_offset_from;
+mod range_int;
+mod raw_bytes;
 mod raw_eq;
+mod rc;
+mod record_replay;
+mod repeat;
+mod report;
+mod resource_limits;
 mod return_;
+mod send_unique;
+mod simplify_cfg;
 mod slice;
 mod spawn_join;
+mod stack_allocate;
+mod stack_overflow;
+mod stdlib;
+mod swap;
 mod switch;
+mod symbolic;
+mod targets;
+mod template;
+mod to_value;
 mod too_large_alloc;
 mod trait_object;
+mod typed_copy;
+mod unchecked;
 mod uninit_read;
 mod unreachable;
 mod unsized_struct;
@@ -43,3 +43,51 @@ fn no_preserve_padding() {
         "load at type Int(IntType { signed: Unsigned, size: Size(1 bytes) }) but the data in memory violates the language invariant",
     );
 }
+
+/// `union_ty_auto` does not preserve bytes that are padding in every field.
+#[test]
+fn union_auto_chunks() {
+    // type Pair = (u8, u16);
+    // #[repr(align(4))]
+    // union Union { f0: Pair, f1: [u8; 0] }
+    //
+    // let _0: Union;
+    // let _1: Union;
+    // let _2: u8;
+    //
+    // *(&raw _0 as *mut u32) = 0;
+    // _1 = _0;
+    // _2 = *(&raw _1 as *const u8);
+    // _2 = *(&raw _1 as *const u8).offset(1);
+
+    let pair_ty =
+        tuple_ty(&[(size(0), u8::get_type()), (size(2), u16::get_type())], size(4), align(2));
+    let union_ty =
+        union_ty_auto(&[(size(0), pair_ty), (size(0), <[u8; 0]>::get_type())], size(4), align(4));
+    let Type::Union { chunks, .. } = union_ty else { unreachable!() };
+    assert_eq!(chunks, list![(size(0), size(1)), (size(2), size(2))]);
+
+    let locals = vec![union_ty, union_ty, <u8>::get_type()];
+    let byte_ptr = addr_of(local(1), <*const u8>::get_type());
+    let stmts = vec![
+        storage_live(0),
+        storage_live(1),
+        storage_live(2),
+        assign(
+            deref(addr_of(local(0), <*mut u32>::get_type()), <u32>::get_type()),
+            const_int(0_u32),
+        ),
+        assign(local(1), load(local(0))),
+        assign(local(2), load(deref(byte_ptr, <u8>::get_type()))),
+        assign(
+            local(2),
+            load(deref(ptr_offset(byte_ptr, const_int(1_u32), InBounds::Yes), <u8>::get_type())),
+        ),
+    ];
+
+    let p = small_program(&locals, &stmts);
+    assert_ub::<BasicMem>(
+        p,
+        "load at type Int(IntType { signed: Unsigned, size: Size(1 bytes) }) but the data in memory violates the language invariant",
+    );
+}
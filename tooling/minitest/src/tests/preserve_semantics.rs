@@ -0,0 +1,81 @@
+use crate::*;
+
+/// A program that prints `val` and stops.
+fn print_program(val: u32) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(const_int(val));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// A program that prints 0 and then has UB.
+fn ub_program() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(const_int(0u32));
+    f.unreachable();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// Renames the blocks of the start function in reverse order, which does not change anything observable.
+fn rename_blocks(mut prog: Program) -> Program {
+    let mut f = prog.functions.get(prog.start).unwrap();
+    let max = f.blocks.iter().map(|(BbName(name), _bb)| name.get_internal()).max().unwrap();
+    let rename = |BbName(name): BbName| BbName(Name::from_internal(max - name.get_internal()));
+    let mut blocks = Map::new();
+    for (bb_name, mut bb) in f.blocks.iter() {
+        bb.terminator = match bb.terminator {
+            Terminator::Goto(next) => Terminator::Goto(rename(next)),
+            Terminator::Intrinsic { intrinsic, arguments, ret, next_block } =>
+                Terminator::Intrinsic {
+                    intrinsic,
+                    arguments,
+                    ret,
+                    next_block: next_block.map(rename),
+                },
+            term => term,
+        };
+        blocks.insert(rename(bb_name), bb);
+    }
+    f.blocks = blocks;
+    f.start = rename(f.start);
+    prog.functions.insert(prog.start, f);
+    prog
+}
+
+#[test]
+fn identity() {
+    let progs = [print_program(1), ub_program()];
+    assert_preserves_semantics::<BasicMem>(|prog| prog, &progs, &[0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn renaming_blocks() {
+    let progs = [print_program(1), ub_program()];
+    assert_preserves_semantics::<BasicMem>(rename_blocks, &progs, &[0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+#[should_panic(expected = "program 0 changed behavior under the transformation with seed 3")]
+fn changed_output() {
+    assert_preserves_semantics::<BasicMem>(|_prog| print_program(2), &[print_program(1)], &[3]);
+}
+
+/// Programs with UB may be replaced by anything.
+#[test]
+fn ub_allows_anything() {
+    assert_preserves_semantics::<BasicMem>(|_prog| print_program(2), &[ub_program()], &[0]);
+}
+
+#[test]
+#[should_panic(expected = "program 0 is ill-formed after the transformation")]
+fn ill_formed_result() {
+    let break_program = |mut prog: Program| {
+        prog.functions = Map::new();
+        prog
+    };
+    assert_preserves_semantics::<BasicMem>(break_program, &[ub_program()], &[0]);
+}
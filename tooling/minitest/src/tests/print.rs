@@ -15,6 +15,37 @@ fn print_success() {
     assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
 }
 
+#[test]
+fn print_str() {
+    let locals = [];
+
+    let b0 = block!(
+        print(const_str("Hello, world!"), 1), // arrays of `u8` are printed as text
+    );
+    let b1 = block!(exit());
+
+    let f = function(Ret::No, 0, &locals, &[b0, b1]);
+    let p = program(&[f]);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["Hello, world!"]);
+}
+
+#[test]
+fn print_str_utf8() {
+    let locals = [];
+
+    let b0 = block!(
+        print(const_str("Grüße, 世界!"), 1), // the bytes are decoded as UTF-8
+    );
+    let invalid = [const_int(b'a'), const_int(0xffu8), const_int(b'b')];
+    let b1 = block!(print(array(&invalid, <u8>::get_type()), 2));
+    let b2 = block!(exit());
+
+    let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
+    let p = program(&[f]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["Grüße, 世界!", "a\u{fffd}b"]);
+}
+
 #[test]
 fn print_fail() {
     let locals = [];
@@ -27,7 +58,10 @@ fn print_fail() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "unsupported value for printing");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `PrintStdout`: expected an integer, a Boolean, or an array of `u8`",
+    );
 }
 
 #[test]
@@ -48,5 +82,8 @@ fn print_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid return type for `PrintStdout` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `PrintStdout`: expected the unit type",
+    );
 }
@@ -0,0 +1,59 @@
+use crate::*;
+
+use Projection::*;
+
+#[test]
+fn project_field_index() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        // struct { a: u8, b: [u16; 3] }
+        let ty = tuple_ty(
+            &[(offset(0), <u8>::get_type()), (offset(2), <[u16; 3]>::get_type())],
+            size(8),
+            align(2),
+        );
+        let x = f.declare_local_with_ty(ty);
+        f.storage_live(x);
+        let elem = project(x, &[Field(1), Index(const_int(2usize))]);
+        f.assign(elem, const_int(42u16));
+        f.print(load(index(field(x, 1), const_int(2usize))));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+#[test]
+fn project_deref_downcast() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        // enum { A(u8) } behind a raw pointer
+        let enum_ty = enum_ty::<u8>(
+            &[(0, enum_variant(<u8>::get_type(), &[]))],
+            discriminator_known(0),
+            size(1),
+            align(1),
+        );
+        let e = f.declare_local_with_ty(enum_ty);
+        let ptr = f.declare_local::<*const u8>();
+        f.storage_live(e);
+        f.storage_live(ptr);
+        f.assign(e, variant(0, const_int(7u8), enum_ty));
+        f.assign(ptr, addr_of(e, <*const u8>::get_type()));
+        f.print(load(project(ptr, &[Deref(enum_ty), Downcast(0)])));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7"]);
+}
+
+#[test]
+fn project_empty_path() {
+    assert_eq!(project(local(3), &[]), local(3));
+}
@@ -0,0 +1,114 @@
+use crate::*;
+
+/// Two threads increment a counter while holding a lock.
+fn locked_counter() -> Program {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+    let counter = p.declare_global_int::<miniutil::DefaultTarget, u32>(5);
+
+    let increment = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        let _data = f.declare_arg::<*const ()>();
+        f.lock_acquire(load(lock));
+        f.assign(counter, add(load(counter), const_int(1u32)));
+        f.lock_release(load(lock));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let id = f.declare_local::<u32>();
+    f.storage_live(id);
+    f.lock_create(lock);
+    f.spawn(increment, null(), id);
+    f.lock_acquire(load(lock));
+    f.assign(counter, add(load(counter), const_int(1u32)));
+    f.lock_release(load(lock));
+    f.join(load(id));
+    f.assume(eq(load(counter), const_int(7u32)));
+    f.print(load(counter));
+    f.exit();
+    let main = p.finish_function(f);
+    p.finish_program(main)
+}
+
+#[test]
+fn locks_and_threads() {
+    let p = locked_counter();
+    assert_stop::<BasicMem>(p);
+
+    let model = to_promela(p).unwrap();
+    assert!(model.contains("int g0 = 0;\nint g1 = 5;\n"));
+    assert!(model.contains("\nproctype f0() {\n"));
+    assert!(model.contains("\nactive proctype f1() {\n  pid child;\n  int _0;\n"));
+    assert!(model.contains("atomic { g0 = nlocks; nlocks++ }"));
+    assert!(model.contains("atomic { child = run f0(); done[child] = false };\n  _0 = child;"));
+    assert!(model.contains("atomic { !locked[g0] -> locked[g0] = true }"));
+    assert!(model.contains("g1 = (g1 + 1);"));
+    assert!(model.contains("(done[_0]);"));
+    assert!(model.contains("assert((g1 == 7));"));
+    assert!(model.contains("printf(\"%d\\n\", g1);"));
+    assert!(model.contains("exited = true;\n  goto finish;"));
+}
+
+#[test]
+fn atomics() {
+    let mut p = ProgramBuilder::new();
+    let x = p.declare_global_zero_initialized::<u32>();
+    let ptr = addr_of(x, <*const u32>::get_type());
+
+    let mut f = p.declare_function();
+    let old = f.declare_local::<u32>();
+    f.storage_live(old);
+    f.atomic_store(ptr, const_int(1u32));
+    f.compare_exchange(old, ptr, const_int(1u32), const_int(2u32));
+    f.atomic_fetch(FetchBinOp::Add, old, ptr, const_int(3u32));
+    f.atomic_load(old, ptr);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let model = to_promela(p).unwrap();
+    assert!(model.contains("g0 = 1;"));
+    assert!(model.contains(
+        "atomic {\n    _0 = g0;\n    if\n    :: (_0 == 1) -> g0 = 2\n    :: else -> skip\n    fi\n  };"
+    ));
+    assert!(model.contains("atomic { _0 = g0; g0 = g0 + 3 };"));
+    assert!(model.contains("_0 = g0;\n"));
+}
+
+/// Without unwinding, locks never get poisoned.
+#[test]
+fn acquire_poisoned() {
+    let mut p = ProgramBuilder::new();
+    let lock = p.declare_global_zero_initialized::<u32>();
+
+    let mut f = p.declare_function();
+    let poisoned = f.declare_local::<bool>();
+    f.storage_live(poisoned);
+    f.lock_create(lock);
+    f.lock_acquire_poisoned(load(lock), poisoned);
+    f.lock_release(load(lock));
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let model = to_promela(p).unwrap();
+    assert!(model.contains("atomic { !locked[g0] -> locked[g0] = true; _0 = false }"));
+}
+
+/// Heap memory is outside the supported subset.
+#[test]
+fn unsupported() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*mut u32>();
+    f.storage_live(ptr);
+    f.allocate(const_int(4usize), const_int(4usize), ptr);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    assert_eq!(to_promela(p).unwrap_err(), "this intrinsic in f0 is not supported");
+}
@@ -0,0 +1,70 @@
+use crate::*;
+
+fn ptr_size() -> usize {
+    <*const i32>::get_size().bytes().try_to_usize().unwrap()
+}
+
+/// Builds a program that copies `&raw x` into a second pointer local using `copy`,
+/// and then loads `x` through the copy.
+fn copy_and_deref(copy: impl Fn(&mut FunctionBuilder, ValueExpr, ValueExpr)) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    let src = f.declare_local::<*const i32>();
+    let dst = f.declare_local::<*const i32>();
+    let val = f.declare_local::<i32>();
+    f.storage_live(x);
+    f.storage_live(src);
+    f.storage_live(dst);
+    f.storage_live(val);
+    f.assign(x, const_int(42i32));
+    f.assign(src, addr_of(x, <*const i32>::get_type()));
+    copy(
+        &mut f,
+        addr_of(dst, <*mut *const i32>::get_type()),
+        addr_of(src, <*const *const i32>::get_type()),
+    );
+    f.assign(val, load(deref(load(dst), <i32>::get_type())));
+    f.if_(eq(load(val), const_int(42i32)), |f| f.exit(), |f| f.unreachable());
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn bytewise_copy_keeps_provenance() {
+    let p = copy_and_deref(|f, dst, src| f.copy_bytes(dst, src, ptr_size()));
+    dump_program(p);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn u8_copy_strips_provenance() {
+    let p = copy_and_deref(|f, dst, src| f.copy_bytes_as_u8(dst, src, ptr_size()));
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "dereferencing pointer without provenance");
+}
+
+/// All bytes have the right address, but one of them lost its provenance.
+#[test]
+fn mixed_fragments_lose_provenance() {
+    let p = copy_and_deref(|f, dst, src| {
+        f.copy_bytes(dst, src, ptr_size() - 1);
+        f.assign(u8_at(dst, ptr_size() - 1), load(u8_at(src, ptr_size() - 1)));
+    });
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "dereferencing pointer without provenance");
+}
+
+/// Copying the bytes in a different order does not matter, as long as each ends up in the right place.
+#[test]
+fn bytewise_copy_reversed_order() {
+    let p = copy_and_deref(|f, dst, src| {
+        for i in (0..ptr_size()).rev() {
+            f.assign(byte_at(dst, i), load(byte_at(src, i)));
+        }
+    });
+    dump_program(p);
+    assert_stop::<BasicMem>(p);
+}
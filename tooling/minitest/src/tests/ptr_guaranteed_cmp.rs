@@ -0,0 +1,80 @@
+use crate::*;
+
+/// Prints the result of `ptr_guaranteed_cmp` for the pointers returned by `ptrs`,
+/// which gets two fresh `u32` locals.
+fn guaranteed_cmp_program(
+    ptrs: impl Fn(PlaceExpr, PlaceExpr) -> (ValueExpr, ValueExpr),
+) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<[u32; 2]>();
+    let y = f.declare_local::<u32>();
+    let res = f.declare_local::<u8>();
+    f.storage_live(x);
+    f.storage_live(y);
+    f.storage_live(res);
+    let (left, right) = ptrs(x, y);
+    f.ptr_guaranteed_cmp(res, left, right);
+    f.print(load(res));
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+fn ptr(place: PlaceExpr) -> ValueExpr {
+    addr_of(place, <*const u32>::get_type())
+}
+
+#[test]
+fn same_allocation() {
+    let p = guaranteed_cmp_program(|x, _| {
+        (ptr(index(x, const_int(0_usize))), ptr(index(x, const_int(0_usize))))
+    });
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+
+    let p = guaranteed_cmp_program(|x, _| {
+        (ptr(index(x, const_int(0_usize))), ptr(index(x, const_int(1_usize))))
+    });
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0"]);
+}
+
+#[test]
+fn without_provenance() {
+    let p = guaranteed_cmp_program(|_, _| (null(), null()));
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1"]);
+}
+
+/// Comparing pointers to different allocations may or may not give a known result.
+#[test]
+fn different_allocations() {
+    let p = guaranteed_cmp_program(|x, y| (ptr(x), ptr(y)));
+
+    let mut results = Vec::new();
+    for _ in 0..32 {
+        let out = get_stdout::<BasicMem>(p).unwrap();
+        results.push(out[0].clone());
+    }
+    assert!(results.iter().all(|r| r == "0" || r == "2"));
+    assert!(results.iter().any(|r| r == "0"));
+    assert!(results.iter().any(|r| r == "2"));
+}
+
+#[test]
+fn wrong_return_type() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let res = f.declare_local::<bool>();
+    f.storage_live(res);
+    f.ptr_guaranteed_cmp(res, null(), null());
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `PtrGuaranteedCmp`: expected a 1-byte unsigned integer",
+    );
+}
@@ -0,0 +1,93 @@
+use crate::*;
+
+/// `ptr_to_addr` yields the same address as exposing the provenance.
+#[test]
+fn ptr_to_addr_matches_expose() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    let exposed = f.declare_local::<usize>();
+    f.storage_live(x);
+    f.storage_live(exposed);
+    f.expose_provenance(exposed, addr_of(x, <*const i32>::get_type()));
+    f.print(eq(ptr_to_addr::<usize>(addr_of(x, <*const i32>::get_type())), load(exposed)));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+}
+
+/// Unlike a pointer-to-integer `as` cast, `ptr_to_addr` does not expose the provenance,
+/// so casting the address back cannot pick it up.
+#[test]
+fn ptr_to_addr_does_not_expose() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    let addr = f.declare_local::<usize>();
+    let ptr = f.declare_local::<*const i32>();
+    f.storage_live(x);
+    f.storage_live(addr);
+    f.storage_live(ptr);
+    f.assign(x, const_int(42i32));
+    f.assign(addr, ptr_to_addr::<usize>(addr_of(x, <*const i32>::get_type())));
+    f.with_exposed_provenance(ptr, load(addr));
+    f.print(load(deref(load(ptr), <i32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    // Nothing is exposed, so the only provenance that can be picked is none at all.
+    assert_ub_eventually_demonic_provenance::<BasicMem>(
+        p,
+        1,
+        "dereferencing pointer without provenance",
+    );
+}
+
+/// Like `as` casts, the conversions truncate and sign-extend.
+#[test]
+fn casts_convert_like_as() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let ptr = int_to_ptr(const_int(0x1234usize), <*const u8>::get_type());
+    f.print(ptr_to_addr::<u8>(ptr));
+    let ptr = int_to_ptr(const_int(-1i32), <*const u8>::get_type());
+    f.print(ptr_to_addr::<usize>(ptr));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["52".to_string(), usize::MAX.to_string()]);
+}
+
+/// A pointer made from an integer has no provenance, even if an allocation lives at that address.
+#[test]
+fn int_to_ptr_has_no_provenance() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    f.storage_live(x);
+    f.assign(x, const_int(42i32));
+    let addr = ptr_to_addr::<usize>(addr_of(x, <*const i32>::get_type()));
+    let ptr = int_to_ptr(addr, <*const i32>::get_type());
+    f.print(load(deref(ptr, <i32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "dereferencing pointer without provenance");
+}
+
+#[test]
+fn int_to_wide_ptr() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(ptr_to_addr::<usize>(int_to_ptr(const_int(8usize), <*const [u8]>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ill_formed::<BasicMem>(p, "Cast::IntToPtr: wide pointer target type");
+}
@@ -13,10 +13,10 @@ fn inbounds_success() {
     f.storage_live(ptr2);
     f.assign(ptr1, addr_of(index(arr, const_int(0i32)), <*const i32>::get_type()));
     f.assign(ptr2, addr_of(index(arr, const_int(1i32)), <*const i32>::get_type()));
-    f.assume(eq(ptr_offset_from(load(ptr2), load(ptr1), InBounds::Yes), const_int(4isize)));
-    f.assume(eq(ptr_offset_from(load(ptr2), load(ptr1), InBounds::No), const_int(4isize)));
-    f.assume(eq(ptr_offset_from(load(ptr1), load(ptr2), InBounds::Yes), const_int(-4isize)));
-    f.assume(eq(ptr_offset_from(load(ptr1), load(ptr2), InBounds::No), const_int(-4isize)));
+    f.assert_eq(ptr_offset_from(load(ptr2), load(ptr1), InBounds::Yes), const_int(4isize));
+    f.assert_eq(ptr_offset_from(load(ptr2), load(ptr1), InBounds::No), const_int(4isize));
+    f.assert_eq(ptr_offset_from(load(ptr1), load(ptr2), InBounds::Yes), const_int(-4isize));
+    f.assert_eq(ptr_offset_from(load(ptr1), load(ptr2), InBounds::No), const_int(-4isize));
     f.exit();
     let f = p.finish_function(f);
 
@@ -38,7 +38,7 @@ fn oob_success() {
     f.storage_live(var2);
     f.storage_live(diff);
     f.assign(diff, ptr_offset_from(var1_addr, var2_addr, InBounds::No));
-    f.assume(eq(ptr_offset_from(var2_addr, var1_addr, InBounds::No), neg(load(diff))));
+    f.assert_eq(ptr_offset_from(var2_addr, var1_addr, InBounds::No), neg(load(diff)));
     f.exit();
     let f = p.finish_function(f);
 
@@ -59,9 +59,9 @@ fn nonneg() {
     f.storage_live(ptr2);
     f.assign(ptr1, addr_of(index(arr, const_int(0i32)), <*const i32>::get_type()));
     f.assign(ptr2, addr_of(index(arr, const_int(1i32)), <*const i32>::get_type()));
-    f.assume(eq(ptr_offset_from_nonneg(load(ptr2), load(ptr1), InBounds::Yes), const_int(4isize)));
-    f.assume(eq(ptr_offset_from_nonneg(load(ptr2), load(ptr1), InBounds::No), const_int(4isize)));
-    f.assume(eq(ptr_offset_from_nonneg(load(ptr1), load(ptr2), InBounds::Yes), const_int(-4isize)));
+    f.assert_eq(ptr_offset_from_nonneg(load(ptr2), load(ptr1), InBounds::Yes), const_int(4isize));
+    f.assert_eq(ptr_offset_from_nonneg(load(ptr2), load(ptr1), InBounds::No), const_int(4isize));
+    f.assert_eq(ptr_offset_from_nonneg(load(ptr1), load(ptr2), InBounds::Yes), const_int(-4isize));
     f.exit();
     let f = p.finish_function(f);
 
@@ -105,7 +105,7 @@ fn inbounds_cross_alloc_same_addr() {
     f.storage_live(diff);
     f.assign(diff, ptr_offset_from(var1_addr, var2_addr, InBounds::No));
     let var2_addr_offset = ptr_offset(var2_addr, load(diff), InBounds::No); // var2_addr + (var1_addr - var2_addr)
-    f.assume(eq(ptr_offset_from(var1_addr, var2_addr_offset, InBounds::Yes), const_int(0isize)));
+    f.assert_eq(ptr_offset_from(var1_addr, var2_addr_offset, InBounds::Yes), const_int(0isize));
     f.exit();
     let f = p.finish_function(f);
 
@@ -0,0 +1,63 @@
+use crate::*;
+
+use std::num::{NonZeroI8, NonZeroU32};
+
+#[test]
+fn nonzero_roundtrip() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<NonZeroU32>();
+    let y = f.declare_local::<NonZeroU32>();
+    let z = f.declare_local::<NonZeroI8>();
+    f.storage_live(x);
+    f.storage_live(y);
+    f.storage_live(z);
+    f.assign(x, range_int(const_int(7_u32), <NonZeroU32>::get_type()));
+    f.assign(y, load(x));
+    f.print(load(range_int_value(y)));
+    f.assign(z, transmute(const_int(-1_i8), <NonZeroI8>::get_type()));
+    f.print(load(range_int_value(z)));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7", "-1"]);
+}
+
+#[test]
+fn nonzero_transmute_zero() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<NonZeroU32>();
+    f.storage_live(x);
+    f.assign(x, transmute(const_int(0_u32), <NonZeroU32>::get_type()));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "transmuted value is not valid at new type");
+}
+
+/// Writing an out-of-range integer into the place is fine, but loading it is UB.
+#[test]
+fn range_int_load_out_of_range() {
+    let digit_ty = range_int_ty(IntType::U8, &[(Int::ZERO, Int::from(10))]);
+    let program = |value: u8| {
+        let mut p = ProgramBuilder::new();
+        let mut f = p.declare_function();
+        let x = f.declare_local_with_ty(digit_ty);
+        let y = f.declare_local_with_ty(digit_ty);
+        f.storage_live(x);
+        f.storage_live(y);
+        f.assign(range_int_value(x), const_int(value));
+        f.assign(y, load(x));
+        f.exit();
+        let f = p.finish_function(f);
+        p.finish_program(f)
+    };
+
+    assert_stop::<BasicMem>(program(9));
+    assert!(matches!(run_program::<BasicMem>(program(10)), TerminationInfo::Ub(_)));
+}
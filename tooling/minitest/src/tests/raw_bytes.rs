@@ -0,0 +1,73 @@
+use crate::*;
+
+#[test]
+fn bytes_of_int() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.assign(x, const_int(0x01020304u32));
+        let bytes = f.bytes_of(x);
+        for i in 0..4usize {
+            f.print(load(index(bytes, const_int(i))));
+        }
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    // `DefaultTarget` is little-endian.
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["4", "3", "2", "1"]);
+}
+
+#[test]
+fn bytes_of_write_back() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u16>();
+        f.storage_live(x);
+        let bytes = f.bytes_of(x);
+        f.assign(bytes, array(&[const_int(1u8), const_int(2u8)], <u8>::get_type()));
+        f.print(load(x));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["513"]);
+}
+
+#[test]
+fn bytes_of_padding() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let pair_ty = tuple_ty(
+            &[(offset(0), <u8>::get_type()), (offset(2), <u16>::get_type())],
+            size(4),
+            align(2),
+        );
+        let x = f.declare_local_with_ty(pair_ty);
+        let copy = f.declare_local_with_ty(pair_ty);
+        f.storage_live(x);
+        f.storage_live(copy);
+        f.assign(x, tuple(&[const_int(1u8), const_int(2u16)], pair_ty));
+        // The raw bytes can be copied including the uninitialized padding byte...
+        let (src, dst) = (f.raw_bytes_of(x), f.raw_bytes_of(copy));
+        f.assign(dst, load(src));
+        f.print(load(field(copy, 1)));
+        // ... but loading the padding byte as `u8` is UB.
+        let bytes = f.bytes_of(x);
+        f.print(load(index(bytes, const_int(1usize))));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ub::<BasicMem>(
+        p,
+        "load at type Int(IntType { signed: Unsigned, size: Size(1 bytes) }) but the data in memory violates the language invariant",
+    );
+}
@@ -22,7 +22,7 @@ fn true_raw_eq() {
 
     f.raw_eq(dest, left_ptr, right_ptr);
 
-    f.assume(eq(bool_to_int::<u8>(load(dest)), bool_to_int::<u8>(const_bool(true))));
+    f.assert_eq(load(dest), const_bool(true));
     f.exit();
 
     let f = p.finish_function(f);
@@ -52,7 +52,7 @@ fn false_raw_eq() {
 
     f.raw_eq(dest, left_ptr, right_ptr);
 
-    f.assume(eq(bool_to_int::<u8>(load(dest)), bool_to_int::<u8>(const_bool(false))));
+    f.assert_eq(load(dest), const_bool(false));
     f.exit();
 
     let f = p.finish_function(f);
@@ -132,7 +132,10 @@ fn invalid_ret_ty_raw_eq() {
 
     let f = p.finish_function(f);
     let p = p.finish_program(f);
-    assert_ub::<BasicMem>(p, "invalid return type for `RawEq` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `RawEq`: expected a Boolean",
+    );
 }
 
 #[test]
@@ -177,5 +180,59 @@ fn invalid_arg_ty_raw_eq() {
 
     let f = p.finish_function(f);
     let p = p.finish_program(f);
-    assert_ub::<BasicMem>(p, "invalid first argument to `RawEq` intrinsic: not a pointer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `RawEq`: expected a pointer",
+    );
+}
+
+/// Builds a `&[u8]` to the first `len` elements of the array local `arr`.
+fn slice_ref(arr: PlaceExpr, len: usize) -> ValueExpr {
+    construct_wide_pointer(
+        addr_of(arr, <&[u8; 3]>::get_type()),
+        const_int(len),
+        <&[u8]>::get_type(),
+    )
+}
+
+/// Runs `raw_eq` on slice references with the given contents and lengths.
+fn slice_raw_eq(left: [u8; 3], left_len: usize, right: [u8; 3], right_len: usize) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let dest = f.declare_local::<bool>();
+    let l = f.declare_local::<[u8; 3]>();
+    let r = f.declare_local::<[u8; 3]>();
+
+    f.storage_live(dest);
+    f.storage_live(l);
+    f.storage_live(r);
+
+    f.assign(l, array(&left.map(const_int::<u8>), <u8>::get_type()));
+    f.assign(r, array(&right.map(const_int::<u8>), <u8>::get_type()));
+
+    f.raw_eq(dest, slice_ref(l, left_len), slice_ref(r, right_len));
+    f.print(load(dest));
+    f.exit();
+
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn slice_raw_eq_uses_metadata() {
+    // Only the first two bytes are compared.
+    let p = slice_raw_eq([1, 2, 3], 2, [1, 2, 4], 2);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true"]);
+
+    let p = slice_raw_eq([1, 2, 3], 3, [1, 2, 4], 3);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["false"]);
+}
+
+#[test]
+fn slice_raw_eq_different_len() {
+    let p = slice_raw_eq([1, 2, 3], 2, [1, 2, 3], 3);
+    assert_ub::<BasicMem>(
+        p,
+        "invalid arguments to `RawEq` intrinsic: pointees have different sizes",
+    );
 }
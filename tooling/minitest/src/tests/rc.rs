@@ -0,0 +1,120 @@
+use crate::*;
+
+/// Creates an `Rc`-like pointer, clones it, and drops both copies.
+/// If `concurrent`, the clone is dropped by a second thread while the main thread drops the original.
+fn rc_program(kind: stdlib::RcKind, concurrent: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+    let rc_new = stdlib::declare_rc_new::<u32>(&mut p);
+    let rc_clone = stdlib::declare_rc_clone::<u32>(&mut p, kind);
+    let rc_drop = stdlib::declare_rc_drop::<u32>(&mut p, kind);
+
+    let thread = {
+        let mut f = p.declare_function();
+        let rc = f.declare_arg::<*mut ()>();
+        f.set_conv(CallingConvention::C);
+        f.call_ignoreret(fn_ptr(rc_drop), &[by_value(load(rc))]);
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let rc = f.declare_local::<*mut ()>();
+    let thread_id = f.declare_local::<u32>();
+    f.storage_live(rc);
+    f.storage_live(thread_id);
+    f.call_nounwind(rc, fn_ptr(rc_new), &[by_value(const_int(42_u32))]);
+    f.call_ignoreret(fn_ptr(rc_clone), &[by_value(load(rc))]);
+    f.print(load(stdlib::rc_count::<u32>(load(rc))));
+    if concurrent {
+        f.spawn(thread, load(rc), thread_id);
+        f.call_ignoreret(fn_ptr(rc_drop), &[by_value(load(rc))]);
+        f.join(load(thread_id));
+    } else {
+        f.call_ignoreret(fn_ptr(rc_drop), &[by_value(load(rc))]);
+        f.print(load(stdlib::rc_payload::<u32>(load(rc))));
+        f.call_ignoreret(fn_ptr(rc_drop), &[by_value(load(rc))]);
+    }
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn rc_clone_drop() {
+    for kind in [stdlib::RcKind::Rc, stdlib::RcKind::Arc] {
+        let p = rc_program(kind, false);
+        assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["2", "42"]);
+    }
+}
+
+/// With a non-atomic count, racing drops are a data race. The race detector only catches
+/// accesses in adjacent steps, so some executions instead lose an update and leak the allocation.
+#[test]
+fn rc_racing_drops() {
+    let p = rc_program(stdlib::RcKind::Rc, true);
+
+    let mut misbehaved = false;
+    for _ in 0..32 {
+        match run_program::<BasicMem>(p) {
+            TerminationInfo::MachineStop => {}
+            TerminationInfo::Ub(ub) if ub.get_internal() == "Data race" => misbehaved = true,
+            TerminationInfo::MemoryLeak => misbehaved = true,
+            termination_info => panic!("unexpected outcome: {termination_info:?}"),
+        }
+    }
+    assert!(misbehaved);
+}
+
+#[test]
+fn arc_racing_drops() {
+    let p = rc_program(stdlib::RcKind::Arc, true);
+    for _ in 0..32 {
+        assert_stop::<BasicMem>(p);
+    }
+}
+
+#[test]
+fn rc_use_after_drop() {
+    let mut p = ProgramBuilder::new();
+    let rc_new = stdlib::declare_rc_new::<u32>(&mut p);
+    let rc_drop = stdlib::declare_rc_drop::<u32>(&mut p, stdlib::RcKind::Rc);
+
+    let mut f = p.declare_function();
+    let rc = f.declare_local::<*mut ()>();
+    f.storage_live(rc);
+    f.call_nounwind(rc, fn_ptr(rc_new), &[by_value(const_int(42_u32))]);
+    f.call_ignoreret(fn_ptr(rc_drop), &[by_value(load(rc))]);
+    f.print(load(stdlib::rc_payload::<u32>(load(rc))));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "dereferencing pointer to dead allocation");
+}
+
+#[test]
+fn rc_count_overflow() {
+    for kind in [stdlib::RcKind::Rc, stdlib::RcKind::Arc] {
+        let mut p = ProgramBuilder::new();
+        let rc_new = stdlib::declare_rc_new::<u32>(&mut p);
+        let rc_clone = stdlib::declare_rc_clone::<u32>(&mut p, kind);
+
+        let mut f = p.declare_function();
+        let rc = f.declare_local::<*mut ()>();
+        f.storage_live(rc);
+        f.call_nounwind(rc, fn_ptr(rc_new), &[by_value(const_int(42_u32))]);
+        // Pretend that the pointer was leaked so many times that one more clone is too many.
+        let leaked = match kind {
+            stdlib::RcKind::Rc => usize::MAX,
+            stdlib::RcKind::Arc => isize::MAX as usize + 1,
+        };
+        f.assign(stdlib::rc_count::<u32>(load(rc)), const_int(leaked));
+        f.call_ignoreret(fn_ptr(rc_clone), &[by_value(load(rc))]);
+        f.exit();
+        let f = p.finish_function(f);
+
+        let p = p.finish_program(f);
+        assert_abort::<BasicMem>(p);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::*;
+
+/// The main thread and a second thread both write to a global without synchronization.
+/// Whether this is detected as a data race depends on the scheduling.
+fn racy_program() -> Program {
+    let mut p = ProgramBuilder::new();
+    let global = p.declare_global_zero_initialized::<u32>();
+
+    let second = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<*const ()>();
+        f.assign(global, const_int(2u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        f.spawn(second, null(), thread_id);
+        f.assign(global, const_int(1u32));
+        f.join(load(thread_id));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+#[test]
+fn replay_data_race() {
+    let p = racy_program();
+    dump_program(p);
+    // The outcomes are compared as text: the strings inside `TerminationInfo` are garbage collected,
+    // so they do not survive running another machine.
+    let data_race = format!(
+        "{:?}",
+        TerminationInfo::Ub(minirust_rs::prelude::String::from_internal("Data race".to_string()))
+    );
+    let mut seen = Vec::new();
+    for _ in 0..64 {
+        let (info, trace) = record::<BasicMem>(p);
+        let info = format!("{info:?}");
+        assert!(
+            info == format!("{:?}", TerminationInfo::MachineStop) || info == data_race,
+            "unexpected outcome: {info}"
+        );
+        // Replaying the trace always leads to the same outcome.
+        let trace: Trace = trace.to_string().parse().unwrap();
+        for _ in 0..4 {
+            assert_eq!(format!("{:?}", replay::<BasicMem>(p, &trace)), info);
+        }
+        if !seen.contains(&info) {
+            seen.push(info);
+        }
+        if seen.len() == 2 {
+            return;
+        }
+    }
+    panic!("did not see both a data race and a clean run after 64 attempts");
+}
+
+#[test]
+fn trace_roundtrip() {
+    let trace = Trace { schedule: vec![0, 0, 1, 0] };
+    assert_eq!(trace.to_string(), "0,0,1,0");
+    assert_eq!("0,0,1,0".parse::<Trace>().unwrap(), trace);
+    assert_eq!("".parse::<Trace>().unwrap(), Trace::default());
+}
+
+#[test]
+fn replay_mismatch() {
+    let p = racy_program();
+    let mismatch = |msg: &str| {
+        TerminationInfo::ReplayMismatch(minirust_rs::prelude::String::from_internal(
+            msg.to_string(),
+        ))
+    };
+    // Only the main thread exists in the first step.
+    let trace = Trace { schedule: vec![1] };
+    assert_eq!(
+        replay::<BasicMem>(p, &trace),
+        mismatch(
+            "the replayed schedule does not match this execution: thread 1 cannot run in step 0"
+        )
+    );
+    let trace = Trace { schedule: vec![0] };
+    assert_eq!(
+        replay::<BasicMem>(p, &trace),
+        mismatch("the replayed schedule is exhausted in step 1")
+    );
+}
+
+/// Replaying only fixes the scheduling: other non-deterministic choices, like the address of a local,
+/// are made afresh, so a program that prints an address can print something else when it is replayed.
+#[test]
+fn replay_is_schedule_only() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u32>();
+    f.storage_live(x);
+    f.print(ptr_addr(addr_of(x, <*const u32>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let (info, trace) = record::<BasicMem>(p);
+    assert_eq!(info, TerminationInfo::MachineStop);
+    let mut config = default_config();
+    config.replay_schedule = Some(trace.schedule.iter().map(|id| Int::from(*id)).collect());
+    let mut addresses = Vec::new();
+    for _ in 0..8 {
+        let behavior = observe_with_config::<BasicMem>(p, config);
+        // The schedule always matches, but the address is picked again.
+        assert_eq!(behavior.termination, TerminationInfo::MachineStop);
+        if !addresses.contains(&behavior.stdout) {
+            addresses.push(behavior.stdout);
+        }
+    }
+    assert!(addresses.len() > 1, "the local got the same address in every replay");
+}
@@ -0,0 +1,36 @@
+use crate::*;
+
+/// Stores `[7u16; n]` in a local and prints its length and its last element.
+fn repeat_program(n: usize) -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let arr = f.declare_local_with_ty(array_ty_dyn(<u16>::get_type(), n));
+    f.storage_live(arr);
+    f.assign(arr, repeat(const_int(7u16), <u16>::get_type(), n));
+    f.print(const_int(n));
+    f.print(load(index(arr, const_int(n - 1))));
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn repeat_many_lengths() {
+    for n in [1, 2, 5, 16, 100] {
+        let p = repeat_program(n);
+        assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &[n.to_string(), "7".to_string()]);
+    }
+}
+
+#[test]
+fn huge_zst_array() {
+    // Arrays of zero-sized types never take up any memory, no matter their length.
+    let n = 1 << 40;
+    assert_eq!(array_ty_dyn(<()>::get_type(), n), array_ty(<()>::get_type(), n));
+}
+
+#[test]
+#[should_panic(expected = "is too large")]
+fn array_too_large() {
+    array_ty_dyn(<u64>::get_type(), usize::MAX / 4);
+}
@@ -0,0 +1,80 @@
+use crate::*;
+
+/// The main thread spawns a thread that prints 1, prints 2 itself, and joins.
+fn two_threads() -> Program {
+    let mut p = ProgramBuilder::new();
+    let thread = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C); // thread function needs to use "C" ABI
+        let _data = f.declare_arg::<*const ()>();
+        f.print(const_int(1u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut f = p.declare_function();
+    let id = f.declare_local::<u32>();
+    f.storage_live(id);
+    f.spawn(thread, null(), id);
+    f.print(const_int(2u32));
+    f.join(load(id));
+    f.exit();
+    let main = p.finish_function(f);
+    p.finish_program(main)
+}
+
+#[test]
+fn trace() {
+    let p = two_threads();
+    let trace = trace_execution::<BasicMem>(p);
+    assert_eq!(trace.termination, TerminationInfo::MachineStop);
+    let mut stdout = trace.stdout.clone();
+    stdout.sort();
+    assert_eq!(stdout, vec!["1", "2"]);
+
+    // Both threads ran, and the first step is the `storage_live`, which is a memory event.
+    assert!(trace.steps.iter().any(|step| step.thread == 1));
+    let first = &trace.steps[0];
+    assert_eq!((first.thread, first.function, first.index), (0, p.start, 0));
+    assert!(first.memory_event);
+    assert_eq!(trace.steps.iter().filter(|step| step.memory_event).count(), 1);
+}
+
+#[test]
+fn html() {
+    let p = two_threads();
+    let trace = trace_execution::<BasicMem>(p);
+    let html = html_report(p, &trace);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<th>thread 1</th>"));
+    assert!(
+        html.contains(
+            "<span class=\"executed\" title=\"executed 1 times\">storage_live(_0);</span>"
+        )
+    );
+    assert!(html.contains("MachineStop"));
+}
+
+/// Steps are attributed to the function that was called, even if another function has the same body.
+#[test]
+fn identical_bodies() {
+    let mut p = ProgramBuilder::new();
+    let mut callees = Vec::new();
+    for _ in 0..2 {
+        let mut f = p.declare_function();
+        f.print(const_int(1u32));
+        f.return_();
+        callees.push(p.finish_function(f));
+    }
+
+    let mut f = p.declare_function();
+    f.call_nounwind(unit_place(), fn_ptr(callees[1]), &[]);
+    f.exit();
+    let main = p.finish_function(f);
+    let p = p.finish_program(main);
+
+    let trace = trace_execution::<BasicMem>(p);
+    assert_eq!(trace.termination, TerminationInfo::MachineStop);
+    assert!(trace.steps.iter().any(|step| step.function == callees[1]));
+    assert!(!trace.steps.iter().any(|step| step.function == callees[0]));
+}
@@ -0,0 +1,114 @@
+use crate::*;
+
+/// The main thread spawns `count` threads. If `join` is set, it joins each of them before spawning the next one;
+/// otherwise the threads spin forever.
+fn threads_program(count: usize, join: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let thread = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<()>();
+        if join {
+            f.return_();
+        } else {
+            let spin = f.declare_block();
+            f.goto(spin);
+            f.set_cur_block(spin, BbKind::Regular);
+            f.goto(spin);
+        }
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        for _ in 0..count {
+            f.spawn(thread, unit(), thread_id);
+            if join {
+                f.join(load(thread_id));
+            }
+        }
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+/// Allocates 16 bytes on the heap `count` times, and frees each allocation right away if `free` is set.
+/// Besides the heap allocations, the start function has two live locals: its unit return place and an 8-byte pointer.
+fn heap_program(count: usize, free: bool) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*mut u8>();
+    f.storage_live(ptr);
+    for _ in 0..count {
+        f.allocate(const_int(16_usize), const_int(8_usize), ptr);
+        if free {
+            f.deallocate(load(ptr), const_int(16_usize), const_int(8_usize));
+        }
+    }
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+fn run_with_limits(
+    prog: Program,
+    max_threads: Option<usize>,
+    max_allocations: Option<usize>,
+    max_memory: Option<usize>,
+) -> TerminationInfo {
+    let mut config = default_config();
+    config.max_threads = max_threads.map(Int::from);
+    config.max_allocations = max_allocations.map(Int::from);
+    config.max_memory = max_memory.map(|bytes| Size::from_bytes(bytes).unwrap());
+    run_program_with_config::<BasicMem>(prog, config)
+}
+
+#[test]
+fn thread_limit() {
+    let p = threads_program(3, false);
+    dump_program(p);
+    // The main thread and two spawned threads fit, the third spawned thread does not.
+    assert_eq!(run_with_limits(p, Some(3), None, None), TerminationInfo::ThreadsExhausted);
+}
+
+#[test]
+fn terminated_threads_do_not_count() {
+    let p = threads_program(3, true);
+    assert_eq!(run_with_limits(p, Some(2), None, None), TerminationInfo::MachineStop);
+}
+
+#[test]
+fn allocation_limit() {
+    let p = heap_program(3, false);
+    dump_program(p);
+    assert_eq!(run_with_limits(p, None, Some(4), None), TerminationInfo::AllocationsExhausted);
+}
+
+#[test]
+fn freed_allocations_do_not_count() {
+    let p = heap_program(3, true);
+    assert_eq!(run_with_limits(p, None, Some(3), None), TerminationInfo::MachineStop);
+}
+
+#[test]
+fn memory_limit() {
+    // The pointer and two heap allocations take 40 bytes, the third heap allocation does not fit.
+    let p = heap_program(3, false);
+    assert_eq!(run_with_limits(p, None, None, Some(40)), TerminationInfo::MemoryExhausted);
+    // With freeing, only 24 bytes are ever in use.
+    let p = heap_program(3, true);
+    assert_eq!(run_with_limits(p, None, None, Some(24)), TerminationInfo::MachineStop);
+}
+
+#[test]
+fn no_limits() {
+    let p = heap_program(3, true);
+    assert_eq!(run_with_limits(p, None, None, None), TerminationInfo::MachineStop);
+}
@@ -0,0 +1,154 @@
+use crate::*;
+
+/// Spins until the `u32` global `flag` is set, reading it atomically.
+fn wait_for(f: &mut FunctionBuilder, flag: PlaceExpr) {
+    let flag_ptr = addr_of(flag, <*const u32>::get_type());
+    let val = f.declare_local::<u32>();
+    f.storage_live(val);
+    f.atomic_load(val, flag_ptr);
+    f.while_(eq(load(val), const_int(0u32)), |f, _| f.atomic_load(val, flag_ptr));
+}
+
+/// Sets the `u32` global `flag` atomically.
+fn set(f: &mut FunctionBuilder, flag: PlaceExpr) {
+    f.atomic_store(addr_of(flag, <*const u32>::get_type()), const_int(1u32));
+}
+
+/// The main thread sends a `Box` to another thread, which writes through it,
+/// and reads the value through the raw pointer the `Box` was made from after joining.
+#[test]
+fn sent_box_usable_after_join() {
+    let box_ty = box_ty_default_markers_for(<u32>::get_type());
+    let mut p = ProgramBuilder::new();
+
+    let receiver = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        let b = f.declare_received_arg_with_ty(box_ty);
+        f.assign(deref(load(b), <u32>::get_type()), const_int(7u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let raw = f.declare_local::<*mut u32>();
+        let b = f.declare_local_with_ty(box_ty);
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(raw);
+        f.storage_live(b);
+        f.storage_live(thread_id);
+        f.allocate(const_int(4_usize), const_int(4_usize), raw);
+        f.assign(b, transmute(load(raw), box_ty));
+        f.spawn_sending(receiver, b, thread_id);
+        f.join(load(thread_id));
+        f.print(load(deref(load(raw), <u32>::get_type())));
+        f.deallocate(load(raw), const_int(4_usize), const_int(4_usize));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    dump_program(p);
+    assert_eq!(get_stdout::<TreeBorrowMem>(p).unwrap(), &["7"]);
+}
+
+/// The main thread sends a mutable reference to `x` to another thread and, once that thread
+/// has written through it, writes to `x` itself while the other thread still holds the reference.
+#[test]
+fn sender_writes_while_protected() {
+    let mut p = ProgramBuilder::new();
+    let written = p.declare_global_zero_initialized::<u32>();
+
+    let receiver = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        let r = f.declare_received_arg::<&mut u32>();
+        f.assign(deref(load(r), <u32>::get_type()), const_int(1u32));
+        set(&mut f, written);
+        // Keep the reference protected forever.
+        f.while_(const_bool(true), |_, _| {});
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        let r = f.declare_local::<&mut u32>();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.storage_live(r);
+        f.storage_live(thread_id);
+        f.assign(x, const_int(0u32));
+        f.assign(r, addr_of(x, <&mut u32>::get_type()));
+        f.spawn_sending(receiver, r, thread_id);
+        wait_for(&mut f, written);
+        f.assign(x, const_int(2u32));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    dump_program(p);
+    assert_ub::<TreeBorrowMem>(
+        p,
+        "Tree Borrows: a protected pointer with Unique permission becomes Disabled",
+    );
+}
+
+/// The main thread reads `x` while another thread holds a protected mutable reference to it,
+/// which makes the later write through that reference UB.
+#[test]
+fn sender_reads_while_protected() {
+    let mut p = ProgramBuilder::new();
+    let received = p.declare_global_zero_initialized::<u32>();
+    let read = p.declare_global_zero_initialized::<u32>();
+
+    let receiver = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        let r = f.declare_received_arg::<&mut u32>();
+        set(&mut f, received);
+        wait_for(&mut f, read);
+        f.assign(deref(load(r), <u32>::get_type()), const_int(1u32));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        let y = f.declare_local::<u32>();
+        let r = f.declare_local::<&mut u32>();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.storage_live(y);
+        f.storage_live(r);
+        f.storage_live(thread_id);
+        f.assign(x, const_int(0u32));
+        f.assign(r, addr_of(x, <&mut u32>::get_type()));
+        f.spawn_sending(receiver, r, thread_id);
+        wait_for(&mut f, received);
+        f.assign(y, load(x));
+        set(&mut f, read);
+        f.join(load(thread_id));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    dump_program(p);
+    assert_ub::<TreeBorrowMem>(
+        p,
+        "Tree Borrows: writing to the local of a protected pointer with Conflicted Reserved permission",
+    );
+}
+
+#[test]
+#[should_panic(expected = "only a `Box` or mutable reference can be received")]
+fn receive_raw_ptr() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.declare_received_arg::<*mut u32>();
+}
@@ -0,0 +1,63 @@
+use crate::*;
+
+fn block_count(prog: Program) -> usize {
+    prog.functions.get(prog.start).unwrap().blocks.iter().count()
+}
+
+/// Prints 0 to 4, and whether each number is even.
+fn loop_with_branches() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let i = f.declare_local::<u32>();
+    f.storage_live(i);
+    f.assign(i, const_int(0u32));
+    f.while_(lt(load(i), const_int(5u32)), |f, _| {
+        f.print(load(i));
+        f.if_(
+            eq(rem(load(i), const_int(2u32)), const_int(0u32)),
+            |f| f.print(const_bool(true)),
+            |f| f.print(const_bool(false)),
+        );
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// Unwinds into a cleanup block that jumps through an empty cleanup block.
+fn unwind_through_empty_block() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let cleanup = f.cleanup_block(|f| {
+        f.print(const_int(1u32));
+        f.exit();
+    });
+    let empty = f.cleanup_block(|f| f.goto(cleanup));
+    f.print(const_int(0u32));
+    f.start_unwind(unit_ptr(), empty);
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+#[test]
+fn preserves_semantics() {
+    let progs = [loop_with_branches(), unwind_through_empty_block()];
+    assert_preserves_semantics::<BasicMem>(simplify_cfg, &progs, &[0]);
+}
+
+#[test]
+fn removes_blocks() {
+    let prog = loop_with_branches();
+    let simplified = simplify_cfg(prog);
+    assert!(block_count(simplified) < block_count(prog));
+    assert_eq!(get_stdout::<BasicMem>(simplified), get_stdout::<BasicMem>(prog));
+}
+
+/// Unwind edges to empty blocks get redirected as well.
+#[test]
+fn skips_empty_cleanup_block() {
+    let simplified = simplify_cfg(unwind_through_empty_block());
+    assert_eq!(block_count(simplified), 4);
+    assert_eq!(get_stdout::<BasicMem>(simplified).unwrap(), vec!["0", "1"]);
+}
@@ -10,10 +10,7 @@ fn ref_as_transmuted_slice<T: TypeConv + Freeze>(
 ) -> PlaceExpr {
     // construct fake wide ptr
     let arr_ref = addr_of(arr, <*const T>::get_type());
-    let pair_ty = PtrType::Raw { meta_kind: PointerMetaKind::ElementCount }
-        .as_wide_pair::<miniutil::DefaultTarget>()
-        .expect("PtrType is wide");
-    let fake_ptr = f.declare_local_with_ty(pair_ty);
+    let fake_ptr = f.declare_local_with_ty(wide_pair_ty(<*const [T]>::get_type()));
     f.storage_live(fake_ptr);
     f.assign(field(fake_ptr, 0), arr_ref);
     f.assign(field(fake_ptr, 1), const_int(known_len));
@@ -171,7 +168,7 @@ fn index_to_transmuted_slice() {
         let slice_ptr = ref_as_transmuted_slice::<u32>(&mut f, arr, 3);
         // Print slice[1]
         let loaded_val = load(index(deref(load(slice_ptr), <[u32]>::get_type()), const_int(1)));
-        f.assume(eq(loaded_val, const_int(43_u32)));
+        f.assert_eq(loaded_val, const_int(43_u32));
         f.exit();
         p.finish_function(f)
     };
@@ -214,7 +211,7 @@ fn index_to_slice() {
         );
         // Load and check slice[1]
         f.assign(elem, load(index(deref(load(slice), <[u32]>::get_type()), const_int(1))));
-        f.assume(eq(load(elem), const_int(43_u32)));
+        f.assert_eq(load(elem), const_int(43_u32));
         f.exit();
         p.finish_function(f)
     };
@@ -336,7 +333,7 @@ fn get_metadata_correct() {
         );
         // Get the metadata again && assert it to be correct
         let loaded_len = get_metadata(load(slice));
-        f.assume(eq(loaded_len, const_int(3_usize)));
+        f.assert_eq(loaded_len, const_int(3_usize));
         f.exit();
         p.finish_function(f)
     };
@@ -367,7 +364,7 @@ fn get_thin_pointer_is_first_elem() {
         );
         // Get the thin pointer & load an u32 at this address, should be the first element
         let loaded_start = get_thin_pointer(slice_ptr);
-        f.assume(eq(load(deref(loaded_start, <u32>::get_type())), const_int(42_u32)));
+        f.assert_eq(load(deref(loaded_start, <u32>::get_type())), const_int(42_u32));
         f.exit();
         p.finish_function(f)
     };
@@ -375,3 +372,98 @@ fn get_thin_pointer_is_first_elem() {
     let p = p.finish_program(f);
     assert_stop::<BasicMem>(p);
 }
+
+/// Indexes into `[42, 43]`, both directly and through a slice, with bounds checks.
+fn index_checked_program(idx: usize) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let arr = f.declare_local::<[u32; 2]>();
+    f.storage_live(arr);
+    f.assign(arr, array(&[const_int(42_u32), const_int(43_u32)], <u32>::get_type()));
+    let slice_ptr = construct_wide_pointer(
+        addr_of(arr, <&[u32; 2]>::get_type()),
+        const_int(2_usize),
+        <&[u32]>::get_type(),
+    );
+
+    let elem = f.index_checked(arr, const_int(idx), |f| f.abort());
+    f.print(load(elem));
+    let elem =
+        f.index_checked(deref(slice_ptr, <[u32]>::get_type()), const_int(idx), |f| f.abort());
+    f.print(load(elem));
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn index_checked_in_bounds() {
+    let p = index_checked_program(1);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["43", "43"]);
+}
+
+#[test]
+fn index_checked_out_of_bounds() {
+    let p = index_checked_program(2);
+    assert_abort::<BasicMem>(p);
+}
+
+/// Matches the first `len` elements of `[1, 2, 3, 4, 5]` against `pattern`, printing the bound
+/// elements and then the length and first element of the rest, or `0` if the pattern does not match.
+fn match_slice_program(len: usize, pattern: &[SlicePat]) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let arr = f.declare_local::<[u32; 5]>();
+    f.storage_live(arr);
+    let elems: Vec<_> = (1..=5).map(|i| const_int(i as u32)).collect();
+    f.assign(arr, array(&elems, <u32>::get_type()));
+    let slice_ptr = construct_wide_pointer(
+        addr_of(arr, <&[u32; 5]>::get_type()),
+        const_int(len),
+        <&[u32]>::get_type(),
+    );
+
+    f.match_slice(
+        deref(slice_ptr, <[u32]>::get_type()),
+        pattern,
+        |f, bindings| {
+            for elem in &bindings.elems {
+                f.print(load(*elem));
+            }
+            if let Some(rest) = bindings.rest {
+                f.print(get_metadata(rest));
+                let first = index(deref(rest, <[u32]>::get_type()), const_int(0_usize));
+                f.if_(gt(get_metadata(rest), const_int(0_usize)), |f| f.print(load(first)), |_| {});
+            }
+        },
+        |f| f.print(const_int(0_u32)),
+    );
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn match_slice_with_rest() {
+    use SlicePat::*;
+    let p = match_slice_program(5, &[Elem, Rest, Elem, Elem]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "4", "5", "2", "2"]);
+    // The rest can be empty.
+    let p = match_slice_program(2, &[Elem, Elem, Rest]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "2", "0"]);
+    let p = match_slice_program(1, &[Elem, Elem, Rest]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0"]);
+}
+
+#[test]
+fn match_slice_exact() {
+    use SlicePat::*;
+    let p = match_slice_program(2, &[Elem, Elem]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "2"]);
+    let p = match_slice_program(3, &[Elem, Elem]);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0"]);
+}
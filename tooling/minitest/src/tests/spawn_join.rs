@@ -77,7 +77,10 @@ fn spawn_arg_count() {
     let f = function(Ret::No, 0, &[], &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Spawn` intrinsic")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Spawn`",
+    )
 }
 
 #[test]
@@ -93,7 +96,10 @@ fn spawn_arg_value() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
 
     let p = program(&[f]);
-    assert_ub::<BasicMem>(p, "trying to look up a function based on a non-pointer value")
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Spawn`: expected a thin pointer",
+    )
 }
 
 fn no_args() -> Function {
@@ -141,21 +147,60 @@ fn spawn_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
 
     let p = program(&[f, dummy_function()]);
-    assert_ub::<BasicMem>(p, "invalid return type for `Spawn` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Spawn`: expected an integer",
+    );
 }
 
+/// The argument is passed like a call argument, so it has to fit the spawned function.
 #[test]
-fn spawn_data_ptr() {
-    let locals = [<()>::get_type()];
+fn spawn_arg_abi_mismatch() {
+    let locals = [<u32>::get_type()];
 
-    let b0 =
-        block!(storage_live(0), spawn(fn_ptr_internal(1), const_int::<usize>(0), unit_place(), 1),);
+    let b0 = block!(storage_live(0), spawn(fn_ptr_internal(1), const_int::<u32>(0), local(0), 1),);
     let b1 = block!(join(load(local(0)), 2),);
     let b2 = block!(exit());
     let f = function(Ret::No, 0, &locals, &[b0, b1, b2]);
 
     let p = program(&[f, dummy_function()]);
-    assert_ub::<BasicMem>(p, "invalid second argument to `Spawn` intrinsic: not a pointer");
+    assert_ub::<BasicMem>(p, "call ABI violation: argument types are not compatible");
+}
+
+/// Spawned functions can take arguments of any sized type, which are passed by copy.
+#[test]
+fn spawn_typed_arg() {
+    let mut p = ProgramBuilder::new();
+    let pair_ty =
+        tuple_ty(&[(size(0), <u32>::get_type()), (size(8), <u64>::get_type())], size(16), align(8));
+
+    let child = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        let pair = f.declare_arg_with_ty(pair_ty);
+        f.print(load(field(pair, 0)));
+        f.print(load(field(pair, 1)));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let pair = f.declare_local_with_ty(pair_ty);
+        let id = f.declare_local::<u32>();
+        f.storage_live(pair);
+        f.storage_live(id);
+        f.assign(pair, tuple(&[const_int(7u32), const_int(8u64)], pair_ty));
+        f.spawn(child, load(pair), id);
+        // The child got a copy, so this does not affect it.
+        f.assign(field(pair, 0), const_int(0u32));
+        f.join(load(id));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7", "8"]);
 }
 
 fn wrongarg() -> Function {
@@ -192,7 +237,10 @@ fn join_arg_count() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(p, "invalid number of arguments for `Join` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid number of arguments for `Join`",
+    );
 }
 
 #[test]
@@ -205,7 +253,10 @@ fn join_arg_value() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(p, "invalid first argument to `Join` intrinsic: not an integer");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid argument 0 for `Join`: expected an integer",
+    );
 }
 
 #[test]
@@ -226,7 +277,10 @@ fn join_wrongreturn() {
     let f = function(Ret::No, 0, &locals, &[b0, b1]);
     let p = program(&[f]);
 
-    assert_ub::<BasicMem>(p, "invalid return type for `Join` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `Join`: expected the unit type",
+    );
 }
 
 #[test]
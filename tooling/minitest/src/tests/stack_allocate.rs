@@ -0,0 +1,74 @@
+use crate::*;
+
+/// Declares a function that allocates a buffer of `n` bytes (its argument) on the stack,
+/// stores `42` in the first byte, and saves the pointer in `global`.
+/// If `unwind`, the function then starts unwinding; otherwise it returns.
+fn declare_alloca_fn(p: &mut ProgramBuilder, global: PlaceExpr, unwind: bool) -> FnName {
+    let mut f = p.declare_function();
+    let n = f.declare_arg::<usize>();
+    let ptr = f.declare_local::<*mut u8>();
+    f.storage_live(ptr);
+    f.stack_allocate(load(n), const_int(1_usize), ptr);
+    f.assign(deref(load(ptr), <u8>::get_type()), const_int(42_u8));
+    f.assign(global, load(ptr));
+    if unwind {
+        let resume = f.cleanup_block(|f| f.resume_unwind());
+        f.start_unwind(unit_ptr(), resume);
+    } else {
+        f.return_();
+    }
+    p.finish_function(f)
+}
+
+#[test]
+fn use_within_frame() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*mut u8>();
+    f.storage_live(ptr);
+    f.stack_allocate(const_int(16_usize), const_int(8_usize), ptr);
+    f.assign(deref(load(ptr), <u64>::get_type()), const_int(42_u64));
+    f.print(load(deref(load(ptr), <u64>::get_type())));
+    // No need to free the memory.
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42"]);
+}
+
+#[test]
+fn freed_on_return() {
+    let mut p = ProgramBuilder::new();
+    let global = p.declare_global_zero_initialized::<*mut u8>();
+    let alloca_fn = declare_alloca_fn(&mut p, global, false);
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(alloca_fn), &[by_value(const_int(4_usize))]);
+    f.print(load(deref(load(global), <u8>::get_type())));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "dereferencing pointer to dead allocation");
+}
+
+#[test]
+fn freed_on_unwind() {
+    let mut p = ProgramBuilder::new();
+    let global = p.declare_global_zero_initialized::<*mut u8>();
+    let alloca_fn = declare_alloca_fn(&mut p, global, true);
+
+    let mut f = p.declare_function();
+    let catch = f.catch_block(|f| {
+        f.print(load(deref(load(global), <u8>::get_type())));
+        f.exit();
+    });
+    f.call(unit_place(), fn_ptr(alloca_fn), &[by_value(const_int(4_usize))], catch);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub::<BasicMem>(p, "dereferencing pointer to dead allocation");
+}
@@ -0,0 +1,154 @@
+use crate::*;
+
+/// A function that calls itself `depth` times (counting down its argument), then returns.
+fn recursive_program(depth: u32) -> Program {
+    let mut p = ProgramBuilder::new();
+
+    let mut rec = p.declare_function();
+    let n = rec.declare_arg::<u32>();
+    let rec_name = rec.name();
+    rec.if_(
+        eq(load(n), const_int(0u32)),
+        |f| f.return_(),
+        |f| {
+            f.call_ignoreret(fn_ptr(rec_name), &[by_value(sub(load(n), const_int(1u32)))]);
+            f.return_();
+        },
+    );
+    let rec = p.finish_function(rec);
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(rec), &[by_value(const_int(depth))]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    p.finish_program(f)
+}
+
+#[test]
+fn unbounded_recursion() {
+    let mut p = ProgramBuilder::new();
+
+    let mut rec = p.declare_function();
+    let rec_name = rec.name();
+    rec.call_ignoreret(fn_ptr(rec_name), &[]);
+    rec.return_();
+    let rec = p.finish_function(rec);
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(rec), &[]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stack_overflow::<BasicMem>(p, 64);
+}
+
+#[test]
+fn recursion_too_deep() {
+    // The start function plus 11 frames of `rec`.
+    let p = recursive_program(10);
+    assert_stack_overflow::<BasicMem>(p, 11);
+}
+
+#[test]
+fn recursion_within_limit() {
+    // The start function plus 11 frames of `rec`.
+    let p = recursive_program(10);
+    let mut config = Config::default();
+    config.max_stack_depth = Some(Int::from(12));
+    assert_eq!(run_program_with_config::<BasicMem>(p, config), TerminationInfo::MachineStop);
+}
+
+fn stack_size_config(max_stack_size: usize) -> Config {
+    let mut config = default_config();
+    config.max_stack_size = Some(Size::from_bytes(max_stack_size).unwrap());
+    config
+}
+
+#[test]
+fn recursion_too_large() {
+    // Each frame of `rec` has a `u32` argument, so 11 frames take 44 bytes.
+    let p = recursive_program(10);
+    assert_stack_size_exhausted::<BasicMem>(p, 43);
+    let p = recursive_program(10);
+    assert_eq!(
+        run_program_with_config::<BasicMem>(p, stack_size_config(44)),
+        TerminationInfo::MachineStop
+    );
+}
+
+#[test]
+fn local_too_large() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<[u8; 100]>();
+    f.storage_live(x);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_stack_size_exhausted::<BasicMem>(p, 99);
+}
+
+/// Dead locals do not count towards the stack size.
+#[test]
+fn storage_dead_frees_stack() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local::<[u8; 60]>();
+    let y = f.declare_local::<[u8; 60]>();
+    f.storage_live(x);
+    f.storage_dead(x);
+    f.storage_live(y);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(
+        run_program_with_config::<BasicMem>(p, stack_size_config(100)),
+        TerminationInfo::MachineStop
+    );
+}
+
+#[test]
+fn stack_allocation_too_large() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let ptr = f.declare_local::<*mut u8>();
+    f.storage_live(ptr);
+    f.stack_allocate(const_int(64usize), const_int(8usize), ptr);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    // The pointer takes another 8 bytes.
+    assert_stack_size_exhausted::<BasicMem>(p, 71);
+}
+
+#[test]
+fn frame_sizes() {
+    let mut p = ProgramBuilder::new();
+    let callee = {
+        let mut f = p.declare_function();
+        let _arg = f.declare_arg::<u16>();
+        let x = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.exit();
+        p.finish_function(f)
+    };
+    let mut f = p.declare_function();
+    let x = f.declare_local::<u64>();
+    f.storage_live(x);
+    f.call_ignoreret(fn_ptr(callee), &[by_value(const_int(0u16))]);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    let mut interp = Interpreter::<BasicMem>::with_config(p, stack_size_config(13));
+    assert_eq!(interp.state().frame_sizes, vec![vec![0]]);
+    assert_eq!(interp.step(), Ok(()));
+    assert_eq!(interp.state().frame_sizes, vec![vec![8]]);
+    // The call pushes a frame with the argument.
+    assert_eq!(interp.step(), Ok(()));
+    assert_eq!(interp.state().frame_sizes, vec![vec![8, 2]]);
+    // Making `x` live in the callee exceeds the budget.
+    assert_eq!(interp.step(), Err(TerminationInfo::StackExhausted));
+}
@@ -0,0 +1,93 @@
+use crate::*;
+
+#[test]
+fn min_max() {
+    let mut p = ProgramBuilder::new();
+    let min = stdlib::declare_min::<i32>(&mut p);
+    let max = stdlib::declare_max::<i32>(&mut p);
+
+    let mut f = p.declare_function();
+    let x = f.declare_local::<i32>();
+    f.storage_live(x);
+    let args = [by_value(const_int(-3_i32)), by_value(const_int(7_i32))];
+    f.call_nounwind(x, fn_ptr(min), &args);
+    f.print(load(x));
+    f.call_nounwind(x, fn_ptr(max), &args);
+    f.print(load(x));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["-3", "7"]);
+}
+
+#[test]
+fn memswap() {
+    let mut p = ProgramBuilder::new();
+    let memswap = stdlib::declare_memswap::<u32>(&mut p);
+
+    let mut f = p.declare_function();
+    let a = f.declare_local::<u32>();
+    let b = f.declare_local::<u32>();
+    f.storage_live(a);
+    f.storage_live(b);
+    f.assign(a, const_int(1_u32));
+    f.assign(b, const_int(2_u32));
+    let args = [
+        by_value(addr_of(a, <*mut u32>::get_type())),
+        by_value(addr_of(b, <*mut u32>::get_type())),
+    ];
+    f.call_ignoreret(fn_ptr(memswap), &args);
+    f.print(load(a));
+    f.print(load(b));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["2", "1"]);
+}
+
+#[test]
+fn panic_handler() {
+    let mut p = ProgramBuilder::new();
+    let panic = stdlib::declare_panic_handler(&mut p, "explicit panic");
+
+    let mut f = p.declare_function();
+    f.call_ignoreret(fn_ptr(panic), &[]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_abort::<BasicMem>(p);
+}
+
+/// Pushes 0..10 into a vector (which has to grow twice), prints its contents and drops it.
+#[test]
+fn vec_push() {
+    let mut p = ProgramBuilder::new();
+    let push = stdlib::declare_vec_push::<u32>(&mut p);
+    let drop = stdlib::declare_vec_drop::<u32>(&mut p);
+
+    let mut f = p.declare_function();
+    let v = f.declare_local_with_ty(stdlib::vec_ty::<u32>());
+    let i = f.declare_local::<u32>();
+    f.storage_live(v);
+    f.storage_live(i);
+    f.assign(v, stdlib::vec_new::<u32>());
+    let v_ptr = addr_of(v, <*mut ()>::get_type());
+
+    f.assign(i, const_int(0_u32));
+    f.while_(lt(load(i), const_int(10_u32)), |f, _| {
+        f.call_ignoreret(fn_ptr(push), &[by_value(v_ptr), by_value(load(i))]);
+        f.assign(i, add(load(i), const_int(1_u32)));
+    });
+    f.print(load(stdlib::vec_len(v)));
+    f.print(load(stdlib::vec_cap(v)));
+    f.print(load(stdlib::vec_elem::<u32>(v, const_int(9_usize))));
+    f.call_ignoreret(fn_ptr(drop), &[by_value(v_ptr)]);
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["10", "16", "9"]);
+}
@@ -0,0 +1,80 @@
+use crate::*;
+
+#[test]
+fn swap_locals() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let a = f.declare_local::<u32>();
+        let b = f.declare_local::<u32>();
+        f.storage_live(a);
+        f.storage_live(b);
+        f.assign(a, const_int(1u32));
+        f.assign(b, const_int(2u32));
+        f.swap(a, b);
+        f.print(load(a));
+        f.print(load(b));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["2", "1"]);
+}
+
+#[test]
+fn swap_array_elements() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let arr = f.declare_local::<[u8; 2]>();
+        f.storage_live(arr);
+        f.assign(arr, array(&[const_int(3u8), const_int(4u8)], <u8>::get_type()));
+        f.swap(index(arr, const_int(0usize)), index(arr, const_int(1usize)));
+        f.print(load(index(arr, const_int(0usize))));
+        f.print(load(index(arr, const_int(1usize))));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["4", "3"]);
+}
+
+#[test]
+fn swap_overlapping() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let arr = f.declare_local::<[u8; 2]>();
+        f.storage_live(arr);
+        f.assign(arr, array(&[const_int(3u8), const_int(4u8)], <u8>::get_type()));
+        f.swap(index(arr, const_int(1usize)), index(arr, const_int(1usize)));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "`Assume` intrinsic called on condition that is violated");
+}
+
+#[test]
+fn replace_local() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        let old = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.storage_live(old);
+        f.assign(x, const_int(1u32));
+        f.replace(x, const_int(5u32), old);
+        f.print(load(x));
+        f.print(load(old));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["5", "1"]);
+}
@@ -0,0 +1,194 @@
+use crate::*;
+
+/// A program whose function `f(x: u8, y: u8)` is built by `body`; `main` never calls it.
+fn with_function(body: impl Fn(&mut FunctionBuilder, PlaceExpr, PlaceExpr)) -> (Program, FnName) {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_arg::<u8>();
+        let y = f.declare_arg::<u8>();
+        body(&mut f, x, y);
+        f.return_();
+        p.finish_function(f)
+    };
+    let mut main = p.declare_function();
+    main.exit();
+    let main = p.finish_function(main);
+    (p.finish_program(main), f)
+}
+
+#[test]
+fn finds_overflow() {
+    let (p, f) = with_function(|f, x, y| {
+        let z = f.declare_local::<u8>();
+        f.storage_live(z);
+        f.assign(z, add_unchecked(load(x), load(y)));
+    });
+    let Verdict::Ub { message, inputs } =
+        check_function(p, f, 10, &mut BruteForceSolver::default())
+    else {
+        panic!("the overflow was not found")
+    };
+    assert_eq!(message, "overflow in unchecked add");
+    // The counterexample really has UB.
+    assert_ub::<BasicMem>(counterexample_program(p, f, &inputs), "overflow in unchecked add");
+}
+
+#[test]
+fn guarded_arithmetic_is_fine() {
+    let (p, f) = with_function(|f, x, y| {
+        let z = f.declare_local::<u8>();
+        f.storage_live(z);
+        f.if_(
+            lt(load(x), const_int(100u8)),
+            |f| {
+                f.if_(
+                    lt(load(y), const_int(100u8)),
+                    |f| f.assign(z, add_unchecked(load(x), load(y))),
+                    |f| f.assign(z, div(load(x), load(y))),
+                );
+            },
+            |_| {},
+        );
+    });
+    assert!(matches!(
+        check_function(p, f, 10, &mut BruteForceSolver::default()),
+        Verdict::NoUb { complete: true }
+    ));
+}
+
+#[test]
+fn finds_division_by_zero() {
+    let (p, f) = with_function(|f, x, y| {
+        let z = f.declare_local::<u8>();
+        f.storage_live(z);
+        f.if_(ne(load(x), const_int(0u8)), |f| f.assign(z, rem(load(x), load(y))), |_| {});
+    });
+    let Verdict::Ub { message, inputs } =
+        check_function(p, f, 10, &mut BruteForceSolver::default())
+    else {
+        panic!("the division by zero was not found")
+    };
+    assert_eq!(message, "modulus of remainder is zero");
+    assert_eq!(inputs[1], const_int(0u8));
+    assert_ub::<BasicMem>(counterexample_program(p, f, &inputs), "modulus of remainder is zero");
+}
+
+#[test]
+fn finds_value_outside_assumed_range() {
+    let (p, f) = with_function(|f, x, _y| {
+        f.if_(
+            lt(load(x), const_int(200u8)),
+            |f| f.assume_in_range(load(x), const_int(10u8), const_int(20u8)),
+            |_| {},
+        );
+    });
+    let Verdict::Ub { message, inputs } =
+        check_function(p, f, 10, &mut BruteForceSolver::default())
+    else {
+        panic!("the value outside of the range was not found")
+    };
+    assert_eq!(message, "`AssumeInRange` intrinsic called on value outside of the range");
+    assert_ub::<BasicMem>(counterexample_program(p, f, &inputs), &message);
+}
+
+#[test]
+fn uninit_read() {
+    let (p, f) = with_function(|f, x, _y| {
+        let z = f.declare_local::<u8>();
+        f.storage_live(z);
+        f.if_(eq(load(x), const_int(42u8)), |f| f.print(load(z)), |_| {});
+    });
+    let Verdict::Ub { inputs, .. } = check_function(p, f, 10, &mut BruteForceSolver::default())
+    else {
+        panic!("the uninitialized read was not found")
+    };
+    assert_eq!(inputs[0], const_int(42u8));
+}
+
+/// The loop runs up to 255 times, so a small bound does not explore all executions.
+#[test]
+fn loop_bound() {
+    let (p, f) = with_function(|f, x, _y| {
+        let i = f.declare_local::<u8>();
+        f.storage_live(i);
+        f.assign(i, const_int(0u8));
+        f.while_(lt(load(i), load(x)), |f, _| f.assign(i, add_unchecked(load(i), const_int(1u8))));
+    });
+    assert!(matches!(
+        check_function(p, f, 4, &mut BruteForceSolver::default()),
+        Verdict::NoUb { complete: false }
+    ));
+}
+
+#[test]
+fn smtlib() {
+    let x = Term::Var("x".to_string(), Sort::BitVec(8));
+    let cond = Term::app(Op::BvUlt, &[x.clone(), Term::bv(Int::from(-1), 8)])
+        .and(x.equals(Term::bv(Int::from(3), 8)));
+    assert_eq!(
+        smtlib_script(&[cond]),
+        "(set-logic QF_BV)\n\
+         (declare-const x (_ BitVec 8))\n\
+         (assert (and (bvult x (_ bv255 8)) (= x (_ bv3 8))))\n\
+         (check-sat)\n\
+         (get-model)\n"
+    );
+}
+
+/// Checks that the operands of every operation have matching sorts, like an SMT solver would,
+/// before passing the assertions on to `BruteForceSolver`.
+struct SortChecker(BruteForceSolver);
+
+impl Solver for SortChecker {
+    fn check(&mut self, assertions: &[Term]) -> SolverResult {
+        fn check_sorts(term: &Term) {
+            let Term::App(op, args) = term else { return };
+            let operands = if matches!(op, Op::Ite) { &args[1..] } else { &args[..] };
+            assert!(
+                operands.iter().all(|arg| arg.sort() == operands[0].sort()),
+                "ill-sorted term: {}",
+                term.to_smtlib()
+            );
+            args.iter().for_each(check_sorts);
+        }
+        assertions.iter().for_each(check_sorts);
+        self.0.check(assertions)
+    }
+}
+
+/// The shift amount can be narrower than the value being shifted.
+#[test]
+fn shift_by_narrower_amount() {
+    let (p, f) = with_function(|f, x, y| {
+        let z = f.declare_local::<u32>();
+        f.storage_live(z);
+        f.assign(z, shl(int_cast::<u32>(load(x)), load(y)));
+        f.assign(z, shr_unchecked(load(z), load(y)));
+    });
+    let Verdict::Ub { message, inputs } =
+        check_function(p, f, 10, &mut SortChecker(BruteForceSolver::default()))
+    else {
+        panic!("the overlong shift was not found")
+    };
+    assert_eq!(message, "overflow in unchecked shift");
+    assert_ub::<BasicMem>(counterexample_program(p, f, &inputs), "overflow in unchecked shift");
+}
+
+/// A solver that claims satisfiability but gives a broken model yields `Unknown`.
+#[test]
+fn truncated_model() {
+    let mut solver = ExternalSolver {
+        command: "sh".to_string(),
+        args: vec![
+            "-c".to_string(),
+            "cat > /dev/null; echo sat; echo '(model (define-fun x'".to_string(),
+        ],
+    };
+    let x = Term::Var("x".to_string(), Sort::BitVec(8));
+    let SolverResult::Unknown(message) = solver.check(&[x.equals(Term::bv(Int::from(3), 8))])
+    else {
+        panic!("the broken model was accepted")
+    };
+    assert!(message.starts_with("cannot parse the model"), "{message}");
+}
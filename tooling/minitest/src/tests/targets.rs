@@ -0,0 +1,79 @@
+use crate::*;
+
+#[test]
+fn arith_on_all_targets() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    let var = f.declare_local::<u32>();
+    f.storage_live(var);
+    f.assign(var, mul(const_int(6u32), const_int(7u32)));
+    f.if_(eq(load(var), const_int(42u32)), |f| f.exit(), |f| f.unreachable());
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_stop_on_all_targets::<BasicMem>(p);
+}
+
+#[test]
+fn abort_on_all_targets() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    f.abort();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_abort_on_all_targets::<BasicMem>(p);
+}
+
+#[test]
+fn unreachable_on_all_targets() {
+    let mut p = ProgramBuilder::new();
+
+    let mut f = p.declare_function();
+    f.unreachable();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_ub_on_all_targets::<BasicMem>(p, "reached unreachable code");
+}
+
+/// A 4 GiB local is fine on a 64-bit target, but too large for a 32-bit one.
+#[test]
+fn local_size_depends_on_target() {
+    let ty = array_ty(<u8>::get_type(), 1u64 << 32);
+    let locals = &[ty];
+    let stmts = &[];
+
+    let p = small_program(locals, stmts);
+    assert_stop::<BasicMemory<x86_64>>(p);
+    assert_ill_formed::<BasicMemory<arm>>(p, "LayoutStrategy: size not valid");
+}
+
+/// Declared structs, enums and integer globals are laid out for the target they are declared for.
+#[test]
+fn declarations_depend_on_target() {
+    let mut p = ProgramBuilder::new();
+    let size_on = |ty: Type, on_arm: bool| {
+        let layout = if on_arm { ty.layout::<arm>() } else { ty.layout::<x86_64>() };
+        layout.expect_size("declared types are sized").bytes()
+    };
+    let usize_x86 = <usize>::get_type_on::<x86_64>();
+    let usize_arm = <usize>::get_type_on::<arm>();
+    assert_eq!(size_on(p.declare_struct::<x86_64>(&[("len", usize_x86)]), false), 8);
+    assert_eq!(size_on(p.declare_struct::<arm>(&[("len", usize_arm)]), true), 4);
+    let variants_x86 = [("A", <u8>::get_type()), ("B", usize_x86)];
+    let variants_arm = [("A", <u8>::get_type()), ("B", usize_arm)];
+    assert_eq!(size_on(p.declare_enum::<x86_64>(&variants_x86), false), 16);
+    assert_eq!(size_on(p.declare_enum::<arm>(&variants_arm), true), 8);
+
+    let global = p.declare_global_int::<arm, usize>(7);
+    let mut f = p.declare_function();
+    f.print(load(global));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(p.globals.iter().map(|(_name, g)| g.bytes.len()).collect::<Vec<_>>(), [4]);
+    assert_eq!(get_stdout::<BasicMemory<arm>>(p).unwrap(), &["7"]);
+}
@@ -0,0 +1,54 @@
+use crate::*;
+
+/// `fn double<T>(x: T) -> T`, with wrapping addition.
+fn double_template() -> FnTemplate<impl Fn(&mut ProgramBuilder, &mut FunctionBuilder, &[Type])> {
+    FnTemplate::new(|_p, f, tys| {
+        let x = f.declare_arg_with_ty(tys[0]);
+        let ret = f.declare_ret_with_ty(tys[0]);
+        f.assign(ret, add(load(x), load(x)));
+        f.return_();
+    })
+}
+
+/// Calls `double::<T>(200)` and prints the result.
+fn call_double<T: TypeConv + Freeze + From<u8> + Into<Int>>(
+    f: &mut FunctionBuilder,
+    double: FnName,
+) {
+    let x = f.declare_local::<T>();
+    f.storage_live(x);
+    f.call_nounwind(x, fn_ptr(double), &[by_value(const_int(T::from(200u8)))]);
+    f.print(load(x));
+}
+
+#[test]
+fn instantiate_for_each_type() {
+    let mut p = ProgramBuilder::new();
+    let mut double = double_template();
+    let double_u8 = double.instantiate(&mut p, &[<u8>::get_type()]);
+    let double_u32 = double.instantiate(&mut p, &[<u32>::get_type()]);
+    let double_u128 = double.instantiate(&mut p, &[<u128>::get_type()]);
+
+    let mut f = p.declare_function();
+    call_double::<u8>(&mut f, double_u8);
+    call_double::<u32>(&mut f, double_u32);
+    call_double::<u128>(&mut f, double_u128);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["144", "400", "400"]);
+}
+
+/// Asking for the same type arguments again gives the same instance.
+#[test]
+fn instances_are_shared() {
+    let mut p = ProgramBuilder::new();
+    let mut double = double_template();
+    let first = double.instantiate(&mut p, &[<u32>::get_type()]);
+    let other = double.instantiate(&mut p, &[<i32>::get_type()]);
+    let second = double.instantiate(&mut p, &[<u32>::get_type()]);
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    assert_eq!(double.instances().len(), 2);
+}
@@ -0,0 +1,80 @@
+use crate::*;
+
+/// A struct `{ a: u8, b: u16 }` with one byte of padding after `a`.
+fn padded_struct_ty() -> Type {
+    tuple_ty(&[(offset(0), <u8>::get_type()), (offset(2), <u16>::get_type())], size(4), align(2))
+}
+
+#[test]
+fn global_with_array_value() {
+    let mut p = ProgramBuilder::new();
+    let arr = p.declare_global_with_value([7u16, 8, 9]);
+
+    let mut f = p.declare_function();
+    for i in 0..3 {
+        f.print(load(index(arr, const_int(i as usize))));
+    }
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7", "8", "9"]);
+}
+
+#[test]
+fn global_with_enum_value() {
+    let option_ty = niche_enum_ty(<bool>::get_type(), Niche::bool(offset(0)), 1);
+
+    let mut p = ProgramBuilder::new();
+    let some = p.declare_global_with_value_of_type(option_ty, variant_value(0, to_value(true)));
+    let none = p.declare_global_with_value_of_type(option_ty, variant_value(1, to_value(())));
+
+    let mut f = p.declare_function();
+    f.print(get_discriminant(some));
+    f.print(load(downcast(some, 0)));
+    f.print(get_discriminant(none));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["0", "true", "1"]);
+}
+
+#[test]
+fn const_struct_value() {
+    let ty = padded_struct_ty();
+    let val = tuple_value(&[to_value::<BasicMem, _>(1u8), to_value(515u16)]);
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(ty);
+    f.storage_live(x);
+    f.assign(x, const_value(ty, val));
+    f.print(load(field(x, 0)));
+    f.print(load(field(x, 1)));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["1", "515"]);
+}
+
+/// Padding is uninitialized when encoding, and decoding gives back the same value.
+#[test]
+fn encode_decode_round_trip() {
+    let ty = padded_struct_ty();
+    let val = tuple_value(&[to_value::<BasicMem, _>(1u8), to_value(515u16)]);
+
+    let bytes = encode_value(ty, val);
+    assert_eq!(bytes.len(), Int::from(4));
+    assert_eq!(bytes.get(Int::ONE), Some(None));
+    let bytes: Vec<Option<u8>> = bytes.iter().collect();
+    assert!(decode_value::<BasicMem>(ty, &bytes) == Some(val));
+}
+
+#[test]
+fn decode_invalid_bool() {
+    assert!(decode_value::<BasicMem>(<bool>::get_type(), &[Some(2)]).is_none());
+    assert!(decode_value::<BasicMem>(<bool>::get_type(), &[None]).is_none());
+}
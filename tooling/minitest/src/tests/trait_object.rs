@@ -63,7 +63,7 @@ fn dynamic_dispatch() {
             vtable_method_lookup(get_metadata(load(y)), method_a_foo),
             &[by_value(ptr_to_ptr(get_thin_pointer(load(y)), <&usize>::get_type()))],
         );
-        main.assume(eq(load(x), load(foo_ret)));
+        main.assert_eq(load(x), load(foo_ret));
 
         main.exit();
         p.finish_function(main)
@@ -501,3 +501,69 @@ fn ill_vtables_unaligned_size() {
 
     assert_ill_formed::<BasicMem>(p, "Program: size stored in vtable not a multiple of alignment");
 }
+
+/// Drops `Box<dyn Any>` values of two types with different layouts.
+/// The drop glue prints the value; the boxes have to be deallocated with the right layout,
+/// and the leak check at exit ensures both of them were deallocated.
+#[test]
+fn drop_box_dyn() {
+    let mut p = ProgramBuilder::new();
+
+    let mut trait_any = p.declare_trait();
+    let drop_glue = trait_any.declare_method();
+    let trait_any = p.finish_trait(trait_any);
+    let box_dyn_ty = box_ty_default_markers_for(trait_object_ty(trait_any));
+
+    let drop_u32 = {
+        let mut f = p.declare_function();
+        let self_ = f.declare_arg::<*mut u32>();
+        f.print(load(deref(load(self_), <u32>::get_type())));
+        f.return_();
+        p.finish_function(f)
+    };
+    let drop_bytes = {
+        let mut f = p.declare_function();
+        let self_ = f.declare_arg::<*mut [u8; 3]>();
+        f.print(load(index(deref(load(self_), <[u8; 3]>::get_type()), const_int(2_usize))));
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let mut u32_vtable = p.declare_vtable_for_frozen_ty(trait_any, <u32>::get_type());
+    u32_vtable.add_method(drop_glue, drop_u32);
+    let u32_vtable = p.finish_vtable(u32_vtable);
+    let mut bytes_vtable = p.declare_vtable_for_frozen_ty(trait_any, <[u8; 3]>::get_type());
+    bytes_vtable.add_method(drop_glue, drop_bytes);
+    let bytes_vtable = p.finish_vtable(bytes_vtable);
+
+    let main = {
+        let mut f = p.declare_function();
+        let ptr = f.declare_local::<*mut u8>();
+        let boxed = f.declare_local_with_ty(box_dyn_ty);
+        f.storage_live(ptr);
+        f.storage_live(boxed);
+
+        f.allocate(const_int(4_usize), const_int(4_usize), ptr);
+        f.assign(deref(load(ptr), <u32>::get_type()), const_int(42_u32));
+        f.assign(
+            boxed,
+            construct_wide_pointer(load(ptr), const_vtable(u32_vtable, trait_any), box_dyn_ty),
+        );
+        f.drop_box_dyn(load(boxed), trait_any, drop_glue);
+
+        f.allocate(const_int(3_usize), const_int(1_usize), ptr);
+        let bytes = array(&[const_int(1_u8), const_int(2_u8), const_int(3_u8)], <u8>::get_type());
+        f.assign(deref(load(ptr), <[u8; 3]>::get_type()), bytes);
+        f.assign(
+            boxed,
+            construct_wide_pointer(load(ptr), const_vtable(bytes_vtable, trait_any), box_dyn_ty),
+        );
+        f.drop_box_dyn(load(boxed), trait_any, drop_glue);
+
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(main);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["42", "3"]);
+}
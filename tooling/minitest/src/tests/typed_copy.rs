@@ -0,0 +1,60 @@
+use crate::*;
+
+#[test]
+fn typed_copy_works() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        let x = f.declare_local::<u32>();
+        let y = f.declare_local::<u32>();
+        f.storage_live(x);
+        f.storage_live(y);
+        f.assign(x, const_int(7u32));
+        f.typed_copy(y, x);
+        f.print(load(y));
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["7"]);
+}
+
+#[test]
+fn typed_copy_overlap() {
+    let locals = [<u32>::get_type()];
+    let stmts =
+        [storage_live(0), assign(local(0), const_int(7u32)), typed_copy(local(0), local(0))];
+    let p = small_program(&locals, &stmts);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "`CopyPlace` with overlapping source and destination");
+}
+
+#[test]
+fn typed_copy_zst_no_overlap() {
+    let locals = [<()>::get_type()];
+    let stmts = [storage_live(0), typed_copy(local(0), local(0))];
+    let p = small_program(&locals, &stmts);
+    dump_program(p);
+    assert_stop::<BasicMem>(p);
+}
+
+#[test]
+fn typed_copy_invalid() {
+    let locals = [<bool>::get_type(); 2];
+    let stmts = [storage_live(0), storage_live(1), typed_copy(local(0), local(1))];
+    let p = small_program(&locals, &stmts);
+    dump_program(p);
+    assert_ub::<BasicMem>(
+        p,
+        "load at type Bool but the data in memory violates the language invariant",
+    );
+}
+
+#[test]
+fn typed_copy_type_mismatch() {
+    let locals = [<u32>::get_type(), <i32>::get_type()];
+    let stmts = [storage_live(0), storage_live(1), typed_copy(local(0), local(1))];
+    let p = small_program(&locals, &stmts);
+    assert_ill_formed::<BasicMem>(p, "Statement::CopyPlace: destination and source type differ");
+}
@@ -0,0 +1,46 @@
+use crate::*;
+
+/// `StopUnwind` outside of a catch block is ill-formed, but if that check is skipped,
+/// the machine still notices that we are not unwinding.
+#[test]
+fn stop_unwind_outside_catch() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let next = f.declare_block();
+    f.stop_unwind(next);
+    f.set_cur_block(next, BbKind::Regular);
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ill_formed::<BasicMem>(p, "Terminator::StopUnwind has to be called in a catch block");
+    assert_ub_unchecked::<BasicMem>(p, "StopUnwind: the payload stack is empty");
+}
+
+/// `ResumeUnwind` in a regular block of the start function.
+#[test]
+fn resume_unwind_in_regular_block() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.resume_unwind();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ill_formed::<BasicMem>(p, "Terminator::ResumeUnwind: has to be called in cleanup block");
+    assert_ub_unchecked::<BasicMem>(p, "the function at the bottom of the stack must not unwind");
+}
+
+/// Skipping the check does not change the behavior of well-formed programs.
+#[test]
+fn well_formed_program() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.print(const_int(1u32));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    assert_eq!(
+        run_program_with_config::<BasicMem>(p, unchecked_config()),
+        TerminationInfo::MachineStop
+    );
+}
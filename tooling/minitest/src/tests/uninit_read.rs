@@ -10,3 +10,66 @@ fn uninit_read() {
         "load at type Bool but the data in memory violates the language invariant",
     );
 }
+
+fn ub_message(p: Program) -> String {
+    let TerminationInfo::Ub(msg) = run_program::<BasicMem>(p) else {
+        panic!("program did not cause UB");
+    };
+    msg.get_internal().to_string()
+}
+
+/// Loading a partially initialized aggregate reports which part is uninitialized.
+#[test]
+fn uninit_read_array_of_structs() {
+    let pair_ty = tuple_ty(
+        &[(offset(0), <u8>::get_type()), (offset(2), <u16>::get_type())],
+        size(4),
+        align(2),
+    );
+    let arr_ty = array_ty(pair_ty, 2);
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let arr = f.declare_local_with_ty(arr_ty);
+    let copy = f.declare_local_with_ty(arr_ty);
+    f.storage_live(arr);
+    f.storage_live(copy);
+    f.assign(index(arr, const_int(0usize)), tuple(&[const_int(1u8), const_int(2u16)], pair_ty));
+    f.assign(field(index(arr, const_int(1usize)), 0), const_int(3u8));
+    f.assign(copy, load(arr));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    let msg = ub_message(p);
+    assert!(msg.ends_with("violates the language invariant at `[1].1` (byte 6)"), "{msg}");
+}
+
+/// If the discriminant of an enum is valid, the report points into the data of the variant.
+#[test]
+fn uninit_read_enum_data() {
+    let payload_ty = tuple_ty(
+        &[(offset(0), <bool>::get_type()), (offset(1), <u8>::get_type())],
+        size(2),
+        align(1),
+    );
+    let enum_ty = niche_enum_ty(payload_ty, Niche::bool(offset(0)), 1);
+
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let x = f.declare_local_with_ty(enum_ty);
+    let copy = f.declare_local_with_ty(enum_ty);
+    f.storage_live(x);
+    f.storage_live(copy);
+    f.assign(field(downcast(x, 0), 0), const_bool(true));
+    f.assign(copy, load(x));
+    f.exit();
+    let f = p.finish_function(f);
+
+    let p = p.finish_program(f);
+    let msg = ub_message(p);
+    assert!(
+        msg.ends_with("violates the language invariant at `.<enum-variant(0)>.1` (byte 1)"),
+        "{msg}"
+    );
+}
@@ -11,3 +11,29 @@ fn reach_unreachable() {
     dump_program(p);
     assert_ub::<BasicMem>(p, "reached unreachable code");
 }
+
+#[test]
+fn reach_unreachable_unchecked() {
+    let locals = [];
+
+    let b0 = block!(unreachable_unchecked());
+
+    let f = function(Ret::No, 0, &locals, &[b0]);
+    let p = program(&[f]);
+    dump_program(p);
+    assert_ub::<BasicMem>(p, "`UnreachableUnchecked` intrinsic called");
+}
+
+/// `UnreachableUnchecked` is only UB when it is actually executed.
+#[test]
+fn unreachable_unchecked_not_taken() {
+    let mut p = ProgramBuilder::new();
+    let f = {
+        let mut f = p.declare_function();
+        f.if_(const_bool(true), |f| f.exit(), |f| f.unreachable_unchecked());
+        p.finish_function(f)
+    };
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_stop::<BasicMem>(p);
+}
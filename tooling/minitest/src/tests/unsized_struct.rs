@@ -98,12 +98,12 @@ fn unsized_tail() {
             vtable_method_lookup(get_metadata(load(g_b)), method_bar_get),
             &[by_value(get_thin_pointer(load(g_b)))],
         );
-        main.assume(eq(const_int(11_usize), load(get_ret)));
+        main.assert_eq(const_int(11_usize), load(get_ret));
 
         // `assert_eq!(core::mem::size_of_val(g), 16);`
         // `assert_eq!(core::mem::align_of_val(g), 8);`
-        main.assume(eq(const_int(16_usize), compute_size(g_ty, get_metadata(load(g)))));
-        main.assume(eq(const_int(8_usize), compute_align(g_ty, get_metadata(load(g)))));
+        main.assert_eq(const_int(16_usize), compute_size(g_ty, get_metadata(load(g))));
+        main.assert_eq(const_int(8_usize), compute_align(g_ty, get_metadata(load(g))));
 
         main.exit();
         p.finish_function(main)
@@ -251,9 +251,9 @@ fn size_of_nested_struct() {
 
     let main = {
         let mut f = p.declare_function();
-        f.assume(eq(compute_size(nested_ty, const_int(0_usize)), const_int(12_usize)));
-        f.assume(eq(compute_size(nested_ty, const_int(1_usize)), const_int(20_usize)));
-        f.assume(eq(compute_align(nested_ty, const_int(0_usize)), const_int(4_usize)));
+        f.assert_eq(compute_size(nested_ty, const_int(0_usize)), const_int(12_usize));
+        f.assert_eq(compute_size(nested_ty, const_int(1_usize)), const_int(20_usize));
+        f.assert_eq(compute_align(nested_ty, const_int(0_usize)), const_int(4_usize));
         f.exit();
         p.finish_function(f)
     };
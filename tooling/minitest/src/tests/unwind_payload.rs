@@ -31,11 +31,11 @@ fn basic_test() {
         // assume(x_ptr == ret)
         f.assume(eq(load(x_ptr), load(ret)));
         // assume(5 == *ret)
-        f.assume(eq(const_int(5), load(deref(load(ret), <i32>::get_type()))));
+        f.assert_eq(const_int(5), load(deref(load(ret), <i32>::get_type())));
         // *ret = 3;
         f.assign(deref(load(ret), <i32>::get_type()), const_int(3));
         // assume(x == 3);
-        f.assume(eq(load(x), const_int(3)));
+        f.assert_eq(load(x), const_int(3));
         f.exit();
         p.finish_function(f)
     };
@@ -100,7 +100,7 @@ fn recursive_payload_test() {
             );
             f.storage_live(payload);
             f.get_unwind_payload(payload);
-            f.assume(eq(load(deref(load(payload), Type::Int(IntType::I32))), load(x)));
+            f.assert_eq(load(deref(load(payload), Type::Int(IntType::I32))), load(x));
             f.print(load(deref(load(payload), Type::Int(IntType::I32))));
             f.stop_unwind(bb2);
         });
@@ -157,7 +157,7 @@ fn empty_stack() {
     assert_ub::<BasicMem>(p, "GetUnwindPayload: the payload stack is empty");
 }
 
-/// In this test the return place of `get_unwind_payload()` has the wrong type. Results in ub.
+/// In this test the return place of `get_unwind_payload()` has the wrong type. The program is ill-formed.
 #[test]
 fn wrong_ret_ty() {
     let mut p = ProgramBuilder::new();
@@ -176,7 +176,10 @@ fn wrong_ret_ty() {
 
     let p = p.finish_program(f);
     dump_program(p);
-    assert_ub::<BasicMem>(p, "invalid return type for `GetUnwindPayload` intrinsic");
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator::Intrinsic: invalid return type for `GetUnwindPayload`: expected a thin pointer",
+    );
 }
 
 /// In this test the unwind payload has the wrong type. Results in ub.
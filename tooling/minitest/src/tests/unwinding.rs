@@ -14,8 +14,8 @@ fn reach_terminate_block() {
 
     let main_fn = {
         let mut f = p.declare_function();
-        let terminate = f.terminating_block(|f| {
-            f.exit();
+        let terminate = f.terminate_block(|f| {
+            f.terminate();
         });
         let cleanup = f.cleanup_block(|f| {
             f.call(unit_place(), fn_ptr(panic_fn), &[], terminate);
@@ -28,7 +28,66 @@ fn reach_terminate_block() {
     };
     let p = p.finish_program(main_fn);
     dump_program(p);
-    assert_stop::<BasicMem>(p);
+    assert_abort::<BasicMem>(p);
+}
+
+/// A call that unwinds directly to a terminate block, like a call to a function that must not unwind.
+#[test]
+fn unwind_into_terminate_block() {
+    let mut p = ProgramBuilder::new();
+
+    let panic_fn = {
+        let mut f = p.declare_function();
+        let resume = f.cleanup_block(|f| f.resume_unwind());
+        f.start_unwind(unit_ptr(), resume);
+        p.finish_function(f)
+    };
+
+    let main_fn = {
+        let mut f = p.declare_function();
+        let terminate = f.terminate_block(|f| f.terminate());
+        f.call(unit_place(), fn_ptr(panic_fn), &[], terminate);
+        f.exit();
+        p.finish_function(f)
+    };
+    let p = p.finish_program(main_fn);
+    dump_program(p);
+    assert_abort::<BasicMem>(p);
+}
+
+/// Terminate blocks must not be left in any way other than aborting.
+#[test]
+fn exit_in_terminate_block() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let terminate = f.terminate_block(|f| f.exit());
+    f.start_unwind(unit_ptr(), terminate);
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ill_formed::<BasicMem>(
+        p,
+        "Terminator: terminate blocks may only use `Goto`, `Switch`, `Unreachable` and `Terminate`",
+    );
+}
+
+#[test]
+#[should_panic(expected = "The terminate block is unfinished")]
+fn unfinished_terminate_block() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.terminate_block(|_| {});
+}
+
+#[test]
+fn terminate_in_regular_block() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    f.terminate();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_ill_formed::<BasicMem>(p, "Terminator::Terminate: has to be called in terminate block");
 }
 
 /// Test a program that starts unwinding and aborts in the cleanup block.
@@ -211,7 +211,7 @@ fn get_thin_of_thin_ptr() {
         f.assign(ptr, addr_of(x, <&u32>::get_type()));
         f.assign(nop, get_thin_pointer(load(ptr)));
         // Make sure the new pointer still has the same provenance
-        f.assume(eq(load(deref(load(nop), <u32>::get_type())), const_int(12_u32)));
+        f.assert_eq(load(deref(load(nop), <u32>::get_type())), const_int(12_u32));
         f.exit();
         p.finish_function(f)
     };
@@ -243,7 +243,7 @@ fn construct_slice() {
 
         // Assert slice[1] == 43
         let loaded_val = load(index(deref(load(slice_ref), <[u32]>::get_type()), const_int(1)));
-        f.assume(eq(loaded_val, const_int(43_u32)));
+        f.assert_eq(loaded_val, const_int(43_u32));
 
         f.exit();
         p.finish_function(f)
@@ -266,7 +266,7 @@ fn construct_thin() {
         let cast_ptr =
             construct_wide_pointer(addr_of(x, <&u8>::get_type()), unit(), <&i8>::get_type());
 
-        f.assume(eq(load(deref(cast_ptr, <i8>::get_type())), const_int(-1_i8)));
+        f.assert_eq(load(deref(cast_ptr, <i8>::get_type())), const_int(-1_i8));
 
         f.exit();
         p.finish_function(f)
@@ -385,3 +385,80 @@ fn compare_trait_obj_ptr() {
     let p = p.finish_program(f);
     assert_stop::<BasicMem>(p);
 }
+
+/// Asserts that comparing wide pointers compares the address first and the metadata second
+#[test]
+fn wide_ptr_rel_compares_metadata() {
+    let mut p = ProgramBuilder::new();
+
+    let f = {
+        let mut f = p.declare_function();
+        let arr = f.declare_local::<[u32; 3]>();
+        f.storage_live(arr);
+        let slice = |len: usize| {
+            construct_wide_pointer(
+                addr_of(arr, <*const [u32; 3]>::get_type()),
+                const_int(len),
+                <*const [u32]>::get_type(),
+            )
+        };
+        f.print(eq(slice(2), slice(2)));
+        f.print(eq(slice(2), slice(3)));
+        f.print(lt(slice(2), slice(3)));
+        // The address takes priority over the metadata.
+        let later = construct_wide_pointer(
+            addr_of(index(arr, const_int(1)), <*const [u32; 3]>::get_type()),
+            const_int(1_usize),
+            <*const [u32]>::get_type(),
+        );
+        f.print(lt(slice(3), later));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    let p = p.finish_program(f);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["true", "false", "true", "true"]);
+}
+
+#[test]
+fn metadata_types() {
+    assert_eq!(metadata_ty(<u32>::get_type()), <()>::get_type());
+    assert_eq!(metadata_ty(<[u32]>::get_type()), <usize>::get_type());
+    let trait_name = TraitName(Name::from_internal(0));
+    assert_eq!(metadata_ty(trait_object_ty(trait_name)), Type::Ptr(PtrType::VTablePtr(trait_name)));
+    assert_eq!(wide_pair_ty(<&[u32]>::get_type()), wide_pair_ty(<*const [u8]>::get_type()));
+}
+
+/// Splits a slice reference and rebuilds it with a shorter length.
+#[test]
+fn split_and_rebuild_slice() {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let arr = f.declare_local::<[u32; 3]>();
+    f.storage_live(arr);
+    f.assign(
+        arr,
+        array(&[const_int(42_u32), const_int(43_u32), const_int(44_u32)], <u32>::get_type()),
+    );
+    let slice_ref = f.declare_local::<&[u32]>();
+    f.storage_live(slice_ref);
+    f.assign(
+        slice_ref,
+        with_metadata(
+            addr_of(arr, <&[u32; 3]>::get_type()),
+            const_int(3_usize),
+            <&[u32]>::get_type(),
+        ),
+    );
+
+    let (thin, len) = split_ptr(load(slice_ref));
+    f.print(len);
+    f.print(load(deref(thin, <u32>::get_type())));
+    let shorter = with_metadata(load(slice_ref), const_int(2_usize), <&[u32]>::get_type());
+    f.print(compute_size(<[u32]>::get_type(), get_metadata(shorter)));
+    f.exit();
+    let f = p.finish_function(f);
+    let p = p.finish_program(f);
+    dump_program(p);
+    assert_eq!(get_stdout::<BasicMem>(p).unwrap(), &["3", "42", "8"]);
+}
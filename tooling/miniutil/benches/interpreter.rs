@@ -0,0 +1,127 @@
+//! Benchmarks for the interpreter, on small programs that stress different parts of the machine.
+//! Run them with `cargo bench -p miniutil`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use miniutil::build::*;
+use miniutil::run::*;
+use miniutil::*;
+
+/// How often the loop of `tight_loop` runs.
+const LOOP_ITERATIONS: u32 = 1000;
+/// The number of nodes in the list of `pointer_chasing`.
+const LIST_NODES: u32 = 64;
+/// How often `pointer_chasing` walks around the list.
+const LIST_ROUNDS: u32 = 8;
+/// How often each thread of `ping_pong` gets the ball.
+const PING_PONG_ROUNDS: u32 = 50;
+
+/// Adds up the numbers below `LOOP_ITERATIONS` in a local.
+fn tight_loop() -> Program {
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let i = f.declare_local::<u32>();
+    let sum = f.declare_local::<u32>();
+    f.storage_live(i);
+    f.storage_live(sum);
+    f.assign(i, const_int(0u32));
+    f.assign(sum, const_int(0u32));
+    f.while_(lt(load(i), const_int(LOOP_ITERATIONS)), |f, _| {
+        f.assign(sum, add(load(sum), load(i)));
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// Builds a circular list in an array, where every element points to the next one,
+/// and then follows the pointers `LIST_ROUNDS` times around the list.
+fn pointer_chasing() -> Program {
+    let ptr_ty = <*const u8>::get_type();
+    let mut p = ProgramBuilder::new();
+    let mut f = p.declare_function();
+    let nodes = f.declare_local_with_ty(array_ty(ptr_ty, LIST_NODES));
+    let i = f.declare_local::<u32>();
+    let cur = f.declare_local_with_ty(ptr_ty);
+    f.storage_live(nodes);
+    f.storage_live(i);
+    f.storage_live(cur);
+
+    f.assign(i, const_int(0u32));
+    f.while_(lt(load(i), const_int(LIST_NODES)), |f, _| {
+        let next = rem(add(load(i), const_int(1u32)), const_int(LIST_NODES));
+        f.assign(index(nodes, load(i)), addr_of(index(nodes, next), ptr_ty));
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+
+    f.assign(cur, addr_of(index(nodes, const_int(0u32)), ptr_ty));
+    f.assign(i, const_int(0u32));
+    f.while_(lt(load(i), const_int(LIST_NODES * LIST_ROUNDS)), |f, _| {
+        f.assign(cur, load(deref(load(cur), ptr_ty)));
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+    f.exit();
+    let f = p.finish_function(f);
+    p.finish_program(f)
+}
+
+/// Plays `PING_PONG_ROUNDS` rounds of `ping_pong` as player `me`:
+/// waits until `ball` holds `me` and then stores `other`.
+fn player(f: &mut FunctionBuilder, ball: ValueExpr, me: u32, other: u32) {
+    let i = f.declare_local::<u32>();
+    let seen = f.declare_local::<u32>();
+    f.storage_live(i);
+    f.storage_live(seen);
+    f.assign(i, const_int(0u32));
+    f.while_(lt(load(i), const_int(PING_PONG_ROUNDS)), |f, _| {
+        f.atomic_load(seen, ball);
+        f.while_(ne(load(seen), const_int(me)), |f, _| f.atomic_load(seen, ball));
+        f.atomic_store(ball, const_int(other));
+        f.assign(i, add(load(i), const_int(1u32)));
+    });
+}
+
+/// Two threads pass a ball back and forth through an atomic global.
+fn ping_pong() -> Program {
+    let mut p = ProgramBuilder::new();
+    let ball = p.declare_global_zero_initialized::<u32>();
+    let ball = addr_of(ball, <*const u32>::get_type());
+
+    let pong = {
+        let mut f = p.declare_function();
+        f.set_conv(CallingConvention::C);
+        f.declare_arg::<()>();
+        player(&mut f, ball, 1, 0);
+        f.return_();
+        p.finish_function(f)
+    };
+
+    let main = {
+        let mut f = p.declare_function();
+        let thread_id = f.declare_local::<u32>();
+        f.storage_live(thread_id);
+        f.spawn(pong, unit(), thread_id);
+        player(&mut f, ball, 0, 1);
+        f.join(load(thread_id));
+        f.exit();
+        p.finish_function(f)
+    };
+
+    p.finish_program(main)
+}
+
+fn bench_program(c: &mut Criterion, name: &str, prog: Program) {
+    // Make sure the benchmark measures a program that works.
+    assert_eq!(run_program_fast::<BasicMem>(prog, Config::default()), TerminationInfo::MachineStop);
+    c.bench_function(name, |b| b.iter(|| run_program_fast::<BasicMem>(prog, Config::default())));
+}
+
+fn interpreter(c: &mut Criterion) {
+    bench_program(c, "tight_loop", tight_loop());
+    bench_program(c, "pointer_chasing", pointer_chasing());
+    bench_program(c, "ping_pong", ping_pong());
+}
+
+criterion_group!(benches, interpreter);
+criterion_main!(benches);
@@ -0,0 +1,214 @@
+use super::*;
+
+/// The call graph of a program.
+///
+/// A call whose callee is a constant function pointer is a direct call edge.
+/// Every other use of a function pointer (and every vtable use, for all its methods) makes
+/// that function *escape*: it might get called through a non-constant callee at any later point,
+/// possibly from a different function or thread. The graph treats such uses conservatively,
+/// i.e. escaped functions are reachable as soon as the function using their pointer is.
+pub struct CallGraph {
+    start: FnName,
+    /// The functions of the program, sorted by name.
+    functions: Vec<FnName>,
+    /// The functions each function calls directly.
+    direct: Map<FnName, Set<FnName>>,
+    /// The functions whose pointer each function uses other than as a direct callee.
+    escapes: Map<FnName, Set<FnName>>,
+    /// The functions that contain a call through a non-constant callee.
+    indirect_callers: Set<FnName>,
+}
+
+impl CallGraph {
+    pub fn new(prog: Program) -> CallGraph {
+        let mut functions: Vec<FnName> = prog.functions.iter().map(|(name, _f)| name).collect();
+        functions.sort_by_key(|FnName(name)| name.get_internal());
+
+        let mut direct = Map::new();
+        let mut escapes = Map::new();
+        let mut indirect_callers = Set::new();
+        for (name, f) in prog.functions.iter() {
+            let mut uses =
+                FnUses { prog, direct: Set::new(), escapes: Set::new(), indirect: false };
+            for (_bb_name, bb) in f.blocks.iter() {
+                for st in bb.statements.iter() {
+                    uses.statement(st);
+                }
+                uses.terminator(bb.terminator);
+            }
+            direct.insert(name, uses.direct);
+            escapes.insert(name, uses.escapes);
+            if uses.indirect {
+                indirect_callers.insert(name);
+            }
+        }
+
+        CallGraph { start: prog.start, functions, direct, escapes, indirect_callers }
+    }
+
+    /// The functions that `f` calls directly.
+    pub fn callees(&self, f: FnName) -> Vec<FnName> {
+        let direct = self.direct.get(f).expect("`callees`: function does not exist");
+        self.sorted(|g| direct.contains(g))
+    }
+
+    /// The functions whose pointer escapes anywhere in the program.
+    pub fn escaped(&self) -> Vec<FnName> {
+        self.sorted(|g| self.escapes.iter().any(|(_f, escapes)| escapes.contains(g)))
+    }
+
+    /// Whether `f` contains calls through a non-constant callee.
+    pub fn calls_indirectly(&self, f: FnName) -> bool {
+        self.indirect_callers.contains(f)
+    }
+
+    /// The functions that `f` might call: its direct callees, and all escaped functions if
+    /// `f` calls through a non-constant callee.
+    pub fn may_call(&self, f: FnName) -> Vec<FnName> {
+        let direct = self.direct.get(f).expect("`may_call`: function does not exist");
+        let escaped = self.escaped();
+        let indirect = self.calls_indirectly(f);
+        self.sorted(|g| direct.contains(g) || (indirect && escaped.contains(&g)))
+    }
+
+    /// The functions that might get executed when starting at `start`.
+    pub fn reachable_from(&self, start: FnName) -> Vec<FnName> {
+        let mut reached = Set::new();
+        let mut todo = vec![start];
+        while let Some(f) = todo.pop() {
+            if reached.contains(f) {
+                continue;
+            }
+            reached.insert(f);
+            let direct = self.direct.get(f).expect("`reachable_from`: function does not exist");
+            let escapes = self.escapes.get(f).unwrap();
+            todo.extend(direct.iter());
+            todo.extend(escapes.iter());
+        }
+        self.sorted(|g| reached.contains(g))
+    }
+
+    /// The functions that might get executed when running the program.
+    pub fn reachable(&self) -> Vec<FnName> {
+        self.reachable_from(self.start)
+    }
+
+    /// The functions that can never get executed when running the program.
+    pub fn unreachable(&self) -> Vec<FnName> {
+        let reachable = self.reachable();
+        self.sorted(|g| !reachable.contains(&g))
+    }
+
+    /// The functions satisfying `filter`, sorted by name so the result does not depend on
+    /// the map iteration order.
+    fn sorted(&self, filter: impl Fn(FnName) -> bool) -> Vec<FnName> {
+        self.functions.iter().copied().filter(|g| filter(*g)).collect()
+    }
+}
+
+/// Collects the function pointer uses in the body of a function.
+struct FnUses {
+    prog: Program,
+    direct: Set<FnName>,
+    escapes: Set<FnName>,
+    indirect: bool,
+}
+
+impl FnUses {
+    fn statement(&mut self, st: Statement) {
+        match st {
+            Statement::Assign { destination, source } => {
+                self.place(destination);
+                self.value(source);
+            }
+            Statement::CopyPlace { destination, source } => {
+                self.place(destination);
+                self.place(source);
+            }
+            Statement::PlaceMention(place)
+            | Statement::SetDiscriminant { destination: place, .. }
+            | Statement::Validate { place, .. }
+            | Statement::Deinit { place } => self.place(place),
+            Statement::StorageLive(_) | Statement::StorageDead(_) => {}
+        }
+    }
+
+    fn terminator(&mut self, term: Terminator) {
+        match term {
+            Terminator::Switch { value, .. } => self.value(value),
+            Terminator::Intrinsic { arguments, ret, .. } => {
+                for arg in arguments.iter() {
+                    self.value(arg);
+                }
+                self.place(ret);
+            }
+            Terminator::Call { callee, arguments, ret, .. } => {
+                match callee {
+                    ValueExpr::Constant(Constant::FnPointer(f), _) => {
+                        self.direct.insert(f);
+                    }
+                    _ => {
+                        self.indirect = true;
+                        self.value(callee);
+                    }
+                }
+                for arg in arguments.iter() {
+                    match arg {
+                        ArgumentExpr::ByValue(value) => self.value(value),
+                        ArgumentExpr::InPlace(place) => self.place(place),
+                    }
+                }
+                self.place(ret);
+            }
+            Terminator::StartUnwind { unwind_payload, .. } => self.value(unwind_payload),
+            Terminator::Goto(_)
+            | Terminator::Unreachable
+            | Terminator::Return
+            | Terminator::StopUnwind(_)
+            | Terminator::ResumeUnwind
+            | Terminator::Terminate => {}
+        }
+    }
+
+    fn value(&mut self, value: ValueExpr) {
+        match value {
+            ValueExpr::Constant(Constant::FnPointer(f), _) => {
+                self.escapes.insert(f);
+            }
+            ValueExpr::Constant(Constant::VTablePointer(vtable), _) => {
+                let vtable = self.prog.vtables.get(vtable).unwrap();
+                for (_method, f) in vtable.methods.iter() {
+                    self.escapes.insert(f);
+                }
+            }
+            ValueExpr::Constant(..) => {}
+            ValueExpr::Tuple(exprs, _) =>
+                for expr in exprs.iter() {
+                    self.value(expr);
+                },
+            ValueExpr::Union { expr, .. } => self.value(expr.extract()),
+            ValueExpr::Variant { data, .. } => self.value(data.extract()),
+            ValueExpr::GetDiscriminant { place } => self.place(place.extract()),
+            ValueExpr::Load { source } => self.place(source.extract()),
+            ValueExpr::AddrOf { target, .. } => self.place(target.extract()),
+            ValueExpr::UnOp { operand, .. } => self.value(operand.extract()),
+            ValueExpr::BinOp { left, right, .. } => {
+                self.value(left.extract());
+                self.value(right.extract());
+            }
+        }
+    }
+
+    fn place(&mut self, place: PlaceExpr) {
+        match place {
+            PlaceExpr::Local(_) => {}
+            PlaceExpr::Deref { operand, .. } => self.value(operand.extract()),
+            PlaceExpr::Field { root, .. } | PlaceExpr::Downcast { root, .. } =>
+                self.place(root.extract()),
+            PlaceExpr::Index { root, index } => {
+                self.place(root.extract());
+                self.value(index.extract());
+            }
+        }
+    }
+}
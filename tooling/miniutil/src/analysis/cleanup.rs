@@ -0,0 +1,27 @@
+use super::*;
+
+/// The cleanup blocks that run, in order, when unwinding enters `f` at `unwind_block`,
+/// up to the `ResumeUnwind` that continues unwinding in the caller.
+/// Calls and intrinsics in cleanup blocks are stepped over: they either come back to their `next_block`,
+/// or unwind into a terminate block, which aborts.
+///
+/// `None` if the order is not fixed (the cleanup branches with a `Switch`),
+/// or if unwinding does not leave the frame through `ResumeUnwind` (it aborts, loops, or gets caught).
+pub fn cleanup_sequence(f: Function, unwind_block: BbName) -> Option<Vec<BbName>> {
+    let mut sequence = Vec::new();
+    let mut cur = unwind_block;
+    loop {
+        let bb = f.blocks.get(cur)?;
+        if bb.kind != BbKind::Cleanup || sequence.contains(&cur) {
+            return None;
+        }
+        sequence.push(cur);
+        cur = match bb.terminator {
+            Terminator::ResumeUnwind => return Some(sequence),
+            Terminator::Goto(next)
+            | Terminator::Call { next_block: Some(next), .. }
+            | Terminator::Intrinsic { next_block: Some(next), .. } => next,
+            _ => return None,
+        };
+    }
+}
@@ -0,0 +1,14 @@
+//! Analyses of MiniRust programs.
+//!
+//! These are useful to validate generated programs and to decide where transformations apply.
+
+use crate::*;
+
+mod call_graph;
+pub use call_graph::*;
+
+mod cleanup;
+pub use cleanup::*;
+
+mod types;
+pub use types::*;
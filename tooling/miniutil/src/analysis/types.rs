@@ -0,0 +1,60 @@
+use super::*;
+
+use crate::build::TypeConv;
+
+/// The type of the place `p` in a function with the given `locals`,
+/// if it can be determined without checking the whole expression for well-formedness.
+pub fn place_type(p: PlaceExpr, locals: Map<LocalName, Type>) -> Option<Type> {
+    match p {
+        PlaceExpr::Local(l) => locals.get(l),
+        PlaceExpr::Deref { ty, .. } => Some(ty),
+        PlaceExpr::Field { root, field } =>
+            match place_type(root.extract(), locals)? {
+                Type::Tuple { sized_fields, unsized_field, .. } =>
+                    match sized_fields.get(field) {
+                        Some((_offset, ty)) => Some(ty),
+                        None => unsized_field.extract(),
+                    },
+                Type::Union { fields, .. } => fields.get(field).map(|(_offset, ty)| ty),
+                _ => None,
+            },
+        PlaceExpr::Index { root, .. } =>
+            match place_type(root.extract(), locals)? {
+                Type::Array { elem, .. } | Type::Slice { elem } => Some(elem.extract()),
+                _ => None,
+            },
+        PlaceExpr::Downcast { root, discriminant } =>
+            match place_type(root.extract(), locals)? {
+                Type::Enum { variants, .. } => variants.get(discriminant).map(|v| v.ty),
+                _ => None,
+            },
+    }
+}
+
+/// The type of the value `v` in a function with the given `locals`,
+/// if it can be determined without checking the whole expression for well-formedness.
+/// This gives up on most operators.
+pub fn value_type(v: ValueExpr, locals: Map<LocalName, Type>) -> Option<Type> {
+    match v {
+        ValueExpr::Constant(_, ty) | ValueExpr::Tuple(_, ty) => Some(ty),
+        ValueExpr::Union { union_ty, .. } => Some(union_ty),
+        ValueExpr::Variant { enum_ty, .. } => Some(enum_ty),
+        ValueExpr::GetDiscriminant { place } =>
+            match place_type(place.extract(), locals)? {
+                Type::Enum { discriminant_ty, .. } => Some(Type::Int(discriminant_ty)),
+                _ => None,
+            },
+        ValueExpr::Load { source } => place_type(source.extract(), locals),
+        ValueExpr::AddrOf { ptr_ty, .. } => Some(Type::Ptr(ptr_ty)),
+        ValueExpr::UnOp { operator: UnOp::Int(IntUnOp::CountOnes), .. } => Some(<u32>::get_type()),
+        ValueExpr::UnOp { operator: UnOp::Int(_), operand } =>
+            value_type(operand.extract(), locals),
+        ValueExpr::BinOp { operator: BinOp::Int(_) | BinOp::PtrOffset { .. }, left, .. } =>
+            value_type(left.extract(), locals),
+        ValueExpr::BinOp { operator: BinOp::Rel(RelOp::Cmp), .. } => Some(<i8>::get_type()),
+        ValueExpr::BinOp { operator: BinOp::Rel(_), .. } => Some(Type::Bool),
+        ValueExpr::BinOp { operator: BinOp::ConstructWidePointer(ptr_ty), .. } =>
+            Some(Type::Ptr(ptr_ty)),
+        _ => None,
+    }
+}
@@ -0,0 +1,40 @@
+//! Encoding integer constants into bytes, for initializing globals and unions byte-wise.
+//! These follow the endianness of the target `Tgt`, so tests do not have to assume little-endian.
+
+use crate::build::*;
+
+#[track_caller]
+fn int_type<Tgt: Target, T: TypeConv>() -> IntType {
+    let Type::Int(int_ty) = T::get_type_on::<Tgt>() else {
+        panic!("expected an integer type");
+    };
+    int_ty
+}
+
+/// The bytes that represent `int` at type `T` on target `Tgt`.
+#[track_caller]
+pub fn encode_int<Tgt: Target, T: TypeConv + Into<Int>>(int: T) -> List<u8> {
+    let int_ty = int_type::<Tgt, T>();
+    Tgt::ENDIANNESS.encode(int_ty.signed, int_ty.size, int.into()).unwrap()
+}
+
+/// The integer of type `T` represented by `bytes` on target `Tgt`.
+#[track_caller]
+pub fn decode_int<Tgt: Target, T: TypeConv>(bytes: &[u8]) -> Int {
+    let int_ty = int_type::<Tgt, T>();
+    assert_eq!(Int::from(bytes.len()), int_ty.size.bytes(), "wrong number of bytes for this type");
+    Tgt::ENDIANNESS.decode(int_ty.signed, bytes.iter().copied().collect())
+}
+
+/// A `[u8; N]` constant holding the bytes of `int` on target `Tgt`.
+pub fn const_int_bytes<Tgt: Target, T: TypeConv + Into<Int>>(int: T) -> ValueExpr {
+    let bytes: Vec<ValueExpr> = encode_int::<Tgt, T>(int).iter().map(const_int::<u8>).collect();
+    array(&bytes, <u8>::get_type())
+}
+
+/// A global of type `T` initialized to `int` on target `Tgt`.
+pub fn global_int_with_value<Tgt: Target, T: TypeConv + Into<Int>>(int: T) -> Global {
+    let bytes = encode_int::<Tgt, T>(int).iter().map(Some).collect();
+    let align = T::get_type_on::<Tgt>().layout::<Tgt>().expect_align("integers are sized");
+    Global { bytes, relocations: list!(), align }
+}
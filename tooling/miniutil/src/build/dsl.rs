@@ -0,0 +1,156 @@
+//! A Rust-like syntax for writing MiniRust programs.
+//!
+//! Example:
+//!
+//! ```rust
+//! use miniutil::build::*;
+//!
+//! let program = minirust! {
+//!     fn double(x: u32) -> u32 {
+//!         return mul(load(x), const_int(2u32));
+//!     }
+//!
+//!     fn main() {
+//!         let i: u32 = const_int(0u32);
+//!         let y: u32;
+//!         while (lt(load(i), const_int(3u32))) {
+//!             y = call double(load(i));
+//!             print(load(y));
+//!             i = add(load(i), const_int(1u32));
+//!         }
+//!         exit;
+//!     }
+//! };
+//! ```
+//!
+//! The program consists of functions, one of which has to be called `main`; it is the start function.
+//! Functions can call themselves and all functions declared before them.
+//! Expressions are written using the builder functions like `load` and `const_int`.
+//! Locals are live from their `let` until the end of the function.
+//!
+//! The function bodies support the following statements:
+//! - `let x: T;` and `let x: T = value;`
+//! - `x = value;` and `assign(place, value);`
+//! - `x = call f(args...);` and `f(args...);`
+//! - `print(value);` and `assume(value);`
+//! - `if (cond) { ... }`, `if (cond) { ... } else { ... }`, and `while (cond) { ... }`
+//! - `return;`, `return value;`, `exit;`, `abort;`, and `unreachable;`
+
+pub macro minirust {
+    // Functions.
+    (@fns $p:ident $start:ident;) => {
+        $p.finish_program($start)
+    },
+    // The start function gets the name that `finish_program` uses.
+    (@fns $p:ident $start:ident; fn main() { $($body:tt)* } $($rest:tt)*) => {
+        minirust!(@fns $p $start; fn $start() { $($body)* } $($rest)*)
+    },
+    (@fns $p:ident $start:ident;
+        fn $name:ident($($arg:ident: $arg_ty:ty),* $(,)?) $(-> $ret_ty:ty)? { $($body:tt)* }
+        $($rest:tt)*
+    ) => {{
+        let $name = {
+            let mut f = $p.declare_function();
+            $(let $arg = f.declare_arg::<$arg_ty>();)*
+            #[allow(unused_variables)]
+            let ret = minirust!(@ret f $($ret_ty)?);
+            #[allow(unused_variables)]
+            let $name = f.name();
+            minirust!(@body f ret; $($body)*);
+            $p.finish_function(f)
+        };
+        minirust!(@fns $p $start; $($rest)*)
+    }},
+
+    // The return place.
+    (@ret $f:ident) => {
+        $f.declare_ret::<()>()
+    },
+    (@ret $f:ident $ret_ty:ty) => {
+        $f.declare_ret::<$ret_ty>()
+    },
+
+    // Statements and terminators.
+    (@body $f:ident $ret:ident;) => {},
+    (@body $f:ident $ret:ident; let $x:ident: $ty:ty; $($rest:tt)*) => {
+        let $x = $f.declare_local::<$ty>();
+        $f.storage_live($x);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; let $x:ident: $ty:ty = $val:expr; $($rest:tt)*) => {
+        let $x = $f.declare_local::<$ty>();
+        $f.storage_live($x);
+        $f.assign($x, $val);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; $x:ident = call $g:ident($($arg:expr),* $(,)?); $($rest:tt)*) => {
+        $f.call_nounwind($x, $crate::build::fn_ptr($g), &[$($crate::build::by_value($arg)),*]);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; $x:ident = $val:expr; $($rest:tt)*) => {
+        $f.assign($x, $val);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; assign($place:expr, $val:expr); $($rest:tt)*) => {
+        $f.assign($place, $val);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; print($val:expr); $($rest:tt)*) => {
+        $f.print($val);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; assume($val:expr); $($rest:tt)*) => {
+        $f.assume($val);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident;
+        if ($cond:expr) { $($then:tt)* } else { $($else:tt)* }
+        $($rest:tt)*
+    ) => {
+        $f.if_(
+            $cond,
+            |$f| { minirust!(@body $f $ret; $($then)*); },
+            |$f| { minirust!(@body $f $ret; $($else)*); },
+        );
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; if ($cond:expr) { $($then:tt)* } $($rest:tt)*) => {
+        $f.if_($cond, |$f| { minirust!(@body $f $ret; $($then)*); }, |_| {});
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; while ($cond:expr) { $($loop_body:tt)* } $($rest:tt)*) => {
+        $f.while_($cond, |$f, _| { minirust!(@body $f $ret; $($loop_body)*); });
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; return; $($rest:tt)*) => {
+        $f.return_();
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; return $val:expr; $($rest:tt)*) => {
+        $f.assign($ret, $val);
+        $f.return_();
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; exit; $($rest:tt)*) => {
+        $f.exit();
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; abort; $($rest:tt)*) => {
+        $f.abort();
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; unreachable; $($rest:tt)*) => {
+        $f.unreachable();
+        minirust!(@body $f $ret; $($rest)*);
+    },
+    (@body $f:ident $ret:ident; $g:ident($($arg:expr),* $(,)?); $($rest:tt)*) => {
+        $f.call_ignoreret($crate::build::fn_ptr($g), &[$($crate::build::by_value($arg)),*]);
+        minirust!(@body $f $ret; $($rest)*);
+    },
+
+    // entry point
+    ($($fns:tt)*) => {{
+        let mut p = $crate::build::ProgramBuilder::new();
+        minirust!(@fns p start; $($fns)*)
+    }},
+}
@@ -9,10 +9,21 @@ pub fn const_int_typed<T: TypeConv>(int: Int) -> ValueExpr {
     ValueExpr::Constant(Constant::Int(int), T::get_type())
 }
 
+/// A `usize` constant on target `T`, for programs that run on a target other than the default one.
+pub fn const_usize_on<T: Target>(int: impl Into<Int>) -> ValueExpr {
+    ValueExpr::Constant(Constant::Int(int.into()), usize_ty_on::<T>())
+}
+
 pub fn const_bool(b: bool) -> ValueExpr {
     ValueExpr::Constant(Constant::Bool(b), Type::Bool)
 }
 
+/// A `[u8; N]` holding the bytes of `s`. The print intrinsics print such arrays as text.
+pub fn const_str(s: &str) -> ValueExpr {
+    let bytes: Vec<ValueExpr> = s.bytes().map(const_int::<u8>).collect();
+    array(&bytes, <u8>::get_type())
+}
+
 #[track_caller]
 pub fn tuple(args: &[ValueExpr], ty: Type) -> ValueExpr {
     let Type::Tuple { sized_fields, .. } = ty else {
@@ -27,6 +38,13 @@ pub fn array(args: &[ValueExpr], elem_ty: Type) -> ValueExpr {
     ValueExpr::Tuple(args.iter().cloned().collect(), ty)
 }
 
+/// `[elem; count]`: an array with `count` copies of `elem`, with a length that may be computed by the test.
+/// `elem` gets evaluated once per element.
+pub fn repeat(elem: ValueExpr, elem_ty: Type, count: usize) -> ValueExpr {
+    let ty = array_ty_dyn(elem_ty, count);
+    ValueExpr::Tuple(std::iter::repeat_n(elem, count).collect(), ty)
+}
+
 pub fn variant(discriminant: impl Into<Int>, data: ValueExpr, enum_ty: Type) -> ValueExpr {
     ValueExpr::Variant { discriminant: discriminant.into(), data: GcCow::new(data), enum_ty }
 }
@@ -98,6 +116,13 @@ pub fn ptr_to_ptr(v: ValueExpr, t: Type) -> ValueExpr {
     transmute(v, t)
 }
 
+/// Offsets the (aligned) pointer `v` by `misalign` bytes and casts it to the raw pointer type `t`.
+/// If `misalign` is smaller than the alignment of the pointee of `t`, the result is misaligned.
+pub fn misaligned(v: ValueExpr, misalign: usize, t: Type) -> ValueExpr {
+    let u8ptr = ptr_to_ptr(v, <*const u8>::get_type());
+    ptr_to_ptr(ptr_offset(u8ptr, const_int(misalign), InBounds::Yes), t)
+}
+
 pub fn bool_to_int<T: TypeConv>(v: ValueExpr) -> ValueExpr {
     // First transmute to `u8`.
     let t_u8 = u8::get_type();
@@ -115,6 +140,24 @@ pub fn transmute(v: ValueExpr, t: Type) -> ValueExpr {
     ValueExpr::UnOp { operator: UnOp::Cast(CastOp::Transmute(t)), operand: GcCow::new(v) }
 }
 
+/// The address of a thin pointer as an integer of type `T`, without exposing its provenance.
+#[track_caller]
+pub fn ptr_to_addr<T: TypeConv>(v: ValueExpr) -> ValueExpr {
+    let Type::Int(t) = T::get_type() else {
+        panic!("ptr_to_addr received non-int type!");
+    };
+    ValueExpr::UnOp { operator: UnOp::Cast(CastOp::PtrToAddr(t)), operand: GcCow::new(v) }
+}
+
+/// A pointer of type `t` with the address `v` and no provenance.
+#[track_caller]
+pub fn int_to_ptr(v: ValueExpr, t: Type) -> ValueExpr {
+    let Type::Ptr(ptr_ty) = t else {
+        panic!("int_to_ptr received non-pointer type!");
+    };
+    ValueExpr::UnOp { operator: UnOp::Cast(CastOp::IntToPtr(ptr_ty)), operand: GcCow::new(v) }
+}
+
 pub fn get_thin_pointer(v: ValueExpr) -> ValueExpr {
     ValueExpr::UnOp { operator: UnOp::GetThinPointer, operand: GcCow::new(v) }
 }
@@ -123,6 +166,18 @@ pub fn get_metadata(v: ValueExpr) -> ValueExpr {
     ValueExpr::UnOp { operator: UnOp::GetMetadata, operand: GcCow::new(v) }
 }
 
+/// Splits a pointer into a thin raw pointer and its metadata, like `<*const T>::to_raw_parts`.
+/// The operand gets evaluated twice.
+pub fn split_ptr(v: ValueExpr) -> (ValueExpr, ValueExpr) {
+    (get_thin_pointer(v), get_metadata(v))
+}
+
+/// A pointer of type `ptr_ty` with the address and provenance of `v` and the metadata `meta`,
+/// like `ptr::from_raw_parts`. `v` can be a pointer of any kind, its own metadata is dropped.
+pub fn with_metadata(v: ValueExpr, meta: ValueExpr, ptr_ty: Type) -> ValueExpr {
+    construct_wide_pointer(get_thin_pointer(v), meta, ptr_ty)
+}
+
 pub fn construct_wide_pointer(ptr: ValueExpr, meta: ValueExpr, ptr_ty: Type) -> ValueExpr {
     let Type::Ptr(ptr_ty) = ptr_ty else {
         panic!("construct_wide_pointer requires Type::Ptr argument!");
@@ -188,6 +243,45 @@ pub fn bit_xor(l: ValueExpr, r: ValueExpr) -> ValueExpr {
     int_binop(IntBinOp::BitXor, l, r)
 }
 
+/// How an arithmetic operation treats results that do not fit its type.
+/// For overflow that should abort, like in debug builds, see `FunctionBuilder::add_checked` and friends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// The result wraps around, like arithmetic in release builds.
+    Wrap,
+    /// Overflow is UB, like the `unchecked_*` methods.
+    Ub,
+}
+
+fn int_binop_with(
+    mode: OverflowMode,
+    wrap: IntBinOp,
+    ub: IntBinOp,
+    l: ValueExpr,
+    r: ValueExpr,
+) -> ValueExpr {
+    match mode {
+        OverflowMode::Wrap => int_binop(wrap, l, r),
+        OverflowMode::Ub => int_binop(ub, l, r),
+    }
+}
+
+pub fn add_with(mode: OverflowMode, l: ValueExpr, r: ValueExpr) -> ValueExpr {
+    int_binop_with(mode, IntBinOp::Add, IntBinOp::AddUnchecked, l, r)
+}
+pub fn sub_with(mode: OverflowMode, l: ValueExpr, r: ValueExpr) -> ValueExpr {
+    int_binop_with(mode, IntBinOp::Sub, IntBinOp::SubUnchecked, l, r)
+}
+pub fn mul_with(mode: OverflowMode, l: ValueExpr, r: ValueExpr) -> ValueExpr {
+    int_binop_with(mode, IntBinOp::Mul, IntBinOp::MulUnchecked, l, r)
+}
+pub fn shl_with(mode: OverflowMode, l: ValueExpr, r: ValueExpr) -> ValueExpr {
+    int_binop_with(mode, IntBinOp::Shl, IntBinOp::ShlUnchecked, l, r)
+}
+pub fn shr_with(mode: OverflowMode, l: ValueExpr, r: ValueExpr) -> ValueExpr {
+    int_binop_with(mode, IntBinOp::Shr, IntBinOp::ShrUnchecked, l, r)
+}
+
 fn int_overflow(op: IntBinOpWithOverflow, l: ValueExpr, r: ValueExpr) -> ValueExpr {
     ValueExpr::BinOp {
         operator: BinOp::IntWithOverflow(op),
@@ -326,6 +420,84 @@ pub fn index(root: PlaceExpr, index: ValueExpr) -> PlaceExpr {
     PlaceExpr::Index { root: GcCow::new(root), index: GcCow::new(index) }
 }
 
+/// The byte at `offset` behind the pointer `ptr`, as a place of type `maybe_uninit_byte_ty()`.
+/// Loading and storing this place preserves initialization and provenance of the byte.
+pub fn byte_at(ptr: ValueExpr, offset: usize) -> PlaceExpr {
+    let u8ptr = ptr_to_ptr(ptr, <*const u8>::get_type());
+    deref(ptr_offset(u8ptr, const_int(offset), InBounds::Yes), maybe_uninit_byte_ty())
+}
+
+/// The byte at `offset` behind the pointer `ptr`, as a place of type `u8`.
+/// Loading this place yields only the integer data of the byte and drops its provenance.
+pub fn u8_at(ptr: ValueExpr, offset: usize) -> PlaceExpr {
+    let u8ptr = ptr_to_ptr(ptr, <*const u8>::get_type());
+    deref(ptr_offset(u8ptr, const_int(offset), InBounds::Yes), <u8>::get_type())
+}
+
+/// One step of a path through a place, see `project`.
+#[derive(Clone, Copy)]
+pub enum Projection {
+    /// Project to the given field, see `field`.
+    Field(u32),
+    /// Project to the element at the given index, see `index`.
+    Index(ValueExpr),
+    /// Downcast to the variant with the given discriminant, see `downcast`.
+    Downcast(u32),
+    /// Load the pointer stored in the place and dereference it at the given type, see `deref`.
+    Deref(Type),
+}
+
+/// Applies the projections in `path` to `root`, from left to right.
+///
+/// For example, `project(local(0), &[Field(1), Index(const_int(2usize)), Field(0)])`
+/// is `field(index(field(local(0), 1), const_int(2usize)), 0)`.
+pub fn project(root: PlaceExpr, path: &[Projection]) -> PlaceExpr {
+    path.iter().fold(root, |place, proj| {
+        match *proj {
+            Projection::Field(f) => field(place, f),
+            Projection::Index(idx) => index(place, idx),
+            Projection::Downcast(discriminant) => downcast(place, discriminant),
+            Projection::Deref(ty) => deref(load(place), ty),
+        }
+    })
+}
+
+/// The `n` bytes behind the pointer `ptr`, as a place of type `[u8; n]`.
+/// Loading this place strips provenance and is UB if any of the bytes is uninitialized.
+pub fn bytes_at(ptr: ValueExpr, n: usize) -> PlaceExpr {
+    let u8ptr = ptr_to_ptr(ptr, <*const u8>::get_type());
+    deref(u8ptr, array_ty(<u8>::get_type(), n))
+}
+
+/// The `n` bytes behind the pointer `ptr`, as a place of type `[maybe_uninit_byte_ty(); n]`.
+/// Loading and storing this place preserves initialization and provenance of every byte.
+pub fn raw_bytes_at(ptr: ValueExpr, n: usize) -> PlaceExpr {
+    let u8ptr = ptr_to_ptr(ptr, <*const u8>::get_type());
+    deref(u8ptr, array_ty(maybe_uninit_byte_ty(), n))
+}
+
+impl FunctionBuilder {
+    /// The bytes of the sized place `place`, as a place of type `[u8; N]` where `N` is the size of `place`.
+    /// Assigning to the returned place writes the bytes back.
+    #[track_caller]
+    pub fn bytes_of(&self, place: PlaceExpr) -> PlaceExpr {
+        bytes_at(addr_of(place, raw_void_ptr_ty()), self.size_of_place(place))
+    }
+
+    /// Like `bytes_of`, but the bytes preserve initialization and provenance.
+    #[track_caller]
+    pub fn raw_bytes_of(&self, place: PlaceExpr) -> PlaceExpr {
+        raw_bytes_at(addr_of(place, raw_void_ptr_ty()), self.size_of_place(place))
+    }
+
+    #[track_caller]
+    fn size_of_place(&self, place: PlaceExpr) -> usize {
+        let size =
+            self.place_ty(place).layout::<DefaultTarget>().expect_size("place must be sized");
+        size.bytes().try_to_usize().unwrap()
+    }
+}
+
 /// An enum downcast into the variant at the specified index.
 pub fn downcast(root: PlaceExpr, discriminant: impl Into<Int>) -> PlaceExpr {
     PlaceExpr::Downcast { root: GcCow::new(root), discriminant: discriminant.into() }
@@ -10,6 +10,27 @@ pub fn fn_ptr(name: FnName) -> ValueExpr {
     ValueExpr::Constant(c, t)
 }
 
+impl ProgramBuilder {
+    /// Declares a function with the given signature whose body immediately exits the program.
+    /// Calls that are not ABI-compatible with this signature are UB before the body even runs,
+    /// so a call to this function either stops the machine or reports the ABI violation.
+    pub fn declare_exit_function(
+        &mut self,
+        args: &[Type],
+        ret: Type,
+        conv: CallingConvention,
+    ) -> FnName {
+        let mut f = self.declare_function();
+        for arg in args {
+            f.declare_arg_with_ty(*arg);
+        }
+        f.declare_ret_with_ty(ret);
+        f.set_conv(conv);
+        f.exit();
+        self.finish_function(f)
+    }
+}
+
 // Whether a function returns or not.
 pub enum Ret {
     Yes,
@@ -89,35 +110,65 @@ pub fn block(statements: &[Statement], terminator: Terminator, kind: BbKind) ->
     BasicBlock { statements: statements.iter().copied().collect(), terminator, kind }
 }
 
+// The name of the block with index `x`, for terminators that take a `BbName`.
+pub fn bb(x: u32) -> BbName {
+    BbName(Name::from_internal(x))
+}
+
 // block!(statement1, statement2, ..., terminator)
 // is syntactic sugar for
 // block(&[statement1, statement2, ...], terminator, BbKind::Regular)
 //
+// block!(kind = BbKind::Cleanup; statement1, ..., terminator)
+// creates a block of the given kind instead.
+//
 // This macro is evaluated as follows:
 // block!(a, b, c)
-// block!(@{} a, b, c)
-// block!(@{a} b, c)
-// block!(@{a, b} c)
+// block!(@BbKind::Regular; {} a, b, c)
+// block!(@BbKind::Regular; {a} b, c)
+// block!(@BbKind::Regular; {a, b} c)
 // block(&[a, b], c, BbKind::Regular)
 //
 // This seems necessary, as macros like this
 // ($($rest:expr),*, $terminator:expr) => { ... }
 // cause `local ambiguity` when called
 pub macro block{
-    // entry point
+    // entry points
+    (kind = $kind:expr; $($rest:expr),* $(,)?) => {
+        block!(@$kind; {} $($rest),*)
+    },
     ($($rest:expr),* $(,)?) => {
-        block!(@{} $($rest),*)
+        block!(@BbKind::Regular; {} $($rest),*)
     },
-    (@{$($stmts:expr),*} $terminator:expr) => {
-        block(&[$($stmts),*], $terminator, BbKind::Regular)
+    (@$kind:expr; {$($stmts:expr),*} $terminator:expr) => {
+        block(&[$($stmts),*], $terminator, $kind)
     },
 
     // This is a specialization of the case below.
-    // This is necessary because the case below adds a separating comma, regardless of whether @{} is empty.
-    (@{} $stmt:expr, $($rest:expr),*) => {
-        block!(@{$stmt} $($rest),*)
+    // This is necessary because the case below adds a separating comma, regardless of whether {} is empty.
+    (@$kind:expr; {} $stmt:expr, $($rest:expr),*) => {
+        block!(@$kind; {$stmt} $($rest),*)
+    },
+    (@$kind:expr; {$($stmts:expr),*} $stmt:expr, $($rest:expr),*) => {
+        block!(@$kind; {$($stmts),*, $stmt} $($rest),*)
     },
-    (@{$($stmts:expr),*} $stmt:expr, $($rest:expr),*) => {
-        block!(@{$($stmts),*, $stmt} $($rest),*)
+}
+
+// function!(ret, num_args, locals, { label1: block1, label2: block2, ... })
+// is syntactic sugar for
+// function(ret, num_args, locals, &[block1, block2, ...])
+// where each label is bound to the index of its block (a `u32`) while evaluating the blocks.
+// This way terminators can refer to blocks by label, e.g. `goto(exit_bb)` or `bb(cleanup)`.
+// The first block is the starting block.
+pub macro function {
+    ($ret:expr, $num_args:expr, $locals:expr, { $($label:ident: $block:expr),* $(,)? }) => {{
+        function!(@labels 0; $($label)*);
+        function($ret, $num_args, $locals, &[$($block),*])
+    }},
+    (@labels $idx:expr;) => {},
+    (@labels $idx:expr; $label:ident $($rest:ident)*) => {
+        #[allow(unused_variables)]
+        let $label: u32 = $idx;
+        function!(@labels $idx + 1; $($rest)*);
     },
 }
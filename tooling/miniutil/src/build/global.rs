@@ -9,6 +9,44 @@ impl ProgramBuilder {
         self.globals.try_insert(name, global).unwrap();
         global_by_name::<T>(name)
     }
+
+    /// Declares a global of type `T` initialized to `int` on target `Tgt`.
+    pub fn declare_global_int<Tgt: Target, T: TypeConv + Into<Int>>(
+        &mut self,
+        int: T,
+    ) -> PlaceExpr {
+        let global = global_int_with_value::<Tgt, T>(int);
+        let name = GlobalName(Name::from_internal(self.next_global));
+        self.next_global += 1;
+        self.globals.try_insert(name, global).unwrap();
+        let relocation = Relocation { name, offset: Size::ZERO };
+        let ptr_type = Type::Ptr(PtrType::Raw { meta_kind: PointerMetaKind::None });
+        deref(
+            ValueExpr::Constant(Constant::GlobalPointer(relocation), ptr_type),
+            T::get_type_on::<Tgt>(),
+        )
+    }
+
+    /// Declares a global of type `T` initialized to `val`.
+    pub fn declare_global_with_value<T: ToValue>(&mut self, val: T) -> PlaceExpr {
+        self.declare_global_with_value_of_type(T::get_type(), val.to_value::<BasicMem>())
+    }
+
+    /// Declares a global of type `ty` initialized to `val`, for types without a Rust counterpart
+    /// such as enums with a custom layout.
+    pub fn declare_global_with_value_of_type(
+        &mut self,
+        ty: Type,
+        val: Value<BasicMem>,
+    ) -> PlaceExpr {
+        let global = global_with_value::<BasicMem>(ty, val);
+        let name = GlobalName(Name::from_internal(self.next_global));
+        self.next_global += 1;
+        self.globals.try_insert(name, global).unwrap();
+        let relocation = Relocation { name, offset: Size::ZERO };
+        let ptr_type = Type::Ptr(PtrType::Raw { meta_kind: PointerMetaKind::None });
+        deref(ValueExpr::Constant(Constant::GlobalPointer(relocation), ptr_type), ty)
+    }
 }
 
 /// Global Int initialized to zero.
@@ -25,3 +63,11 @@ pub fn global_ptr<T: TypeConv + ?Sized>() -> Global {
 
     Global { bytes, relocations: list!(), align: <*const T>::get_align() }
 }
+
+/// A global of type `ty` initialized to `val`.
+#[track_caller]
+pub fn global_with_value<M: Memory>(ty: Type, val: Value<M>) -> Global {
+    let bytes = encode_value::<M>(ty, val);
+    let align = ty.layout::<M::T>().expect_align("only sized types have values");
+    Global { bytes, relocations: list!(), align }
+}
@@ -0,0 +1,142 @@
+use crate::build::*;
+use crate::transform::Rename;
+
+/// What caller and callee need to agree on for a call.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Signature {
+    pub args: Vec<Type>,
+    pub ret: Type,
+    pub conv: CallingConvention,
+}
+
+impl Signature {
+    pub fn of(f: Function) -> Signature {
+        Signature {
+            args: f.args.iter().map(|arg| f.locals.get(arg).unwrap()).collect(),
+            ret: f.locals.get(f.ret).unwrap(),
+            conv: f.calling_convention,
+        }
+    }
+}
+
+impl ProgramBuilder {
+    /// Declares a function that is defined by another builder, which has to be `link`ed into this one
+    /// before the program can be finished. Calls can use the returned name right away.
+    /// The definition is found by `name` among the functions exported with `export_function`.
+    #[track_caller]
+    pub fn declare_extern_function(&mut self, name: &str, sig: Signature) -> FnName {
+        if let Some((_name, f, other)) = self.externs.iter().find(|(other, ..)| other == name) {
+            assert_eq!(
+                *other, sig,
+                "extern function `{name}` is declared with different signatures"
+            );
+            return *f;
+        }
+        let f = self.fresh_fn_name();
+        self.externs.push((name.to_string(), f, sig));
+        f
+    }
+
+    /// Makes the finished function `f` available to `extern` declarations of builders this one gets linked with.
+    #[track_caller]
+    pub fn export_function(&mut self, name: &str, f: FnName) {
+        assert!(
+            !self.exports.iter().any(|(other, _f)| other == name),
+            "function `{name}` is exported twice"
+        );
+        self.exports.push((name.to_string(), f));
+    }
+
+    /// Moves everything declared in `lib` into this builder, giving it fresh names.
+    /// Extern functions of either builder that the other one exports are resolved;
+    /// the remaining ones stay extern and can be resolved by linking further builders.
+    /// The exports of `lib` are exported from this builder afterwards.
+    ///
+    /// `lib` must not declare traits or vtables, since their names are part of types,
+    /// which this does not rename.
+    #[track_caller]
+    pub fn link(&mut self, lib: ProgramBuilder) {
+        assert!(
+            lib.traits.iter().next().is_none() && lib.vtables.iter().next().is_none(),
+            "cannot link builders that declare traits or vtables"
+        );
+        let mut rename = Rename::default();
+
+        // Definitions of our externs take the name the extern was declared with.
+        let mut functions: Vec<FnName> = lib.functions.iter().map(|(f, _body)| f).collect();
+        functions.sort_by_key(|FnName(name)| name.get_internal());
+        for f in functions {
+            let resolved = lib.exports.iter().find_map(|(name, exported)| {
+                if *exported != f {
+                    return None;
+                }
+                self.externs.iter().position(|(other, ..)| other == name)
+            });
+            let new = match resolved {
+                Some(idx) => {
+                    let (name, ext, sig) = self.externs.remove(idx);
+                    assert_eq!(
+                        sig,
+                        Signature::of(lib.functions.get(f).unwrap()),
+                        "extern function `{name}` does not match its definition"
+                    );
+                    ext
+                }
+                None => self.fresh_fn_name(),
+            };
+            rename.functions.insert(f, new);
+        }
+
+        // The externs of `lib` are either defined by us, or become our externs.
+        for (name, ext, sig) in lib.externs.iter() {
+            let new = match self.exports.iter().find(|(other, _f)| other == name) {
+                Some((_name, f)) => {
+                    assert_eq!(
+                        *sig,
+                        Signature::of(self.functions.get(*f).unwrap()),
+                        "extern function `{name}` does not match its definition"
+                    );
+                    *f
+                }
+                None => self.declare_extern_function(name, sig.clone()),
+            };
+            rename.functions.insert(*ext, new);
+        }
+
+        let mut globals: Vec<GlobalName> = lib.globals.iter().map(|(g, _global)| g).collect();
+        globals.sort_by_key(|GlobalName(name)| name.get_internal());
+        for g in globals {
+            rename.globals.insert(g, GlobalName(Name::from_internal(self.next_global)));
+            self.next_global += 1;
+        }
+
+        for (g, mut global) in lib.globals.iter() {
+            global.relocations = global
+                .relocations
+                .iter()
+                .map(|(offset, relocation)| (offset, rename.relocation(relocation)))
+                .collect();
+            self.globals.try_insert(rename.globals.get(g).unwrap(), global).unwrap();
+        }
+        for (f, mut body) in lib.functions.iter() {
+            body.blocks = body
+                .blocks
+                .iter()
+                .map(|(name, mut bb)| {
+                    bb.statements = bb.statements.iter().map(|st| rename.statement(st)).collect();
+                    bb.terminator = rename.terminator(bb.terminator);
+                    (name, bb)
+                })
+                .collect();
+            self.functions.try_insert(rename.function(f), body).unwrap();
+        }
+        for (name, f) in lib.exports.iter() {
+            self.export_function(name, rename.function(*f));
+        }
+        for (f, hint) in lib.inline_hints.iter() {
+            self.inline_hints.insert(rename.function(f), hint);
+        }
+        self.field_names.extend(&lib.field_names);
+        self.debug_info.extend_renamed(&lib.debug_info, |f| rename.function(f));
+    }
+}
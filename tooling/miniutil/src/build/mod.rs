@@ -17,6 +17,8 @@
 //! let program = program(&[f]);
 //! ```
 
+use crate::fmt::{DebugInfo, FieldNames};
+use crate::transform::{InlineHint, InlineHints};
 use crate::*;
 
 mod function;
@@ -40,6 +42,21 @@ pub use ty::*;
 mod ty_conv;
 pub use ty_conv::*;
 
+mod value;
+pub use value::*;
+
+mod dsl;
+pub use dsl::*;
+
+mod bytes;
+pub use bytes::*;
+
+mod link;
+pub use link::*;
+
+mod template;
+pub use template::*;
+
 pub struct ProgramBuilder {
     functions: Map<FnName, Function>,
     globals: Map<GlobalName, Global>,
@@ -49,6 +66,17 @@ pub struct ProgramBuilder {
     next_global: u32,
     next_vtable: u32,
     next_trait: u32,
+    field_names: FieldNames,
+    debug_info: DebugInfo,
+    /// The names of the variants of all enums declared with `declare_enum`.
+    variant_names: VariantNames,
+    inline_hints: InlineHints,
+    /// Functions defined by other builders, see `declare_extern_function`.
+    externs: Vec<(String, FnName, Signature)>,
+    /// Functions made available to other builders, see `export_function`.
+    exports: Vec<(String, FnName)>,
+    /// Functions declared with `forward_declare_function` that are not defined yet.
+    forward_declared: Vec<FnName>,
 }
 
 impl ProgramBuilder {
@@ -62,10 +90,23 @@ impl ProgramBuilder {
             next_global: 0,
             next_vtable: 0,
             next_trait: 0,
+            field_names: FieldNames::default(),
+            debug_info: DebugInfo::default(),
+            variant_names: VariantNames::default(),
+            inline_hints: InlineHints::default(),
+            externs: Vec::new(),
+            exports: Vec::new(),
+            forward_declared: Vec::new(),
         }
     }
 
     pub fn finish_program(&mut self, start_function: FnName) -> Program {
+        if let Some((name, ..)) = self.externs.first() {
+            panic!("extern function `{name}` was never linked");
+        }
+        if let Some(f) = self.forward_declared.first() {
+            panic!("function `f{}` was forward declared but never defined", f.0.get_internal());
+        }
         // Set the start function to use C calling conventions
         if let Some(mut f) = self.functions.get(start_function) {
             f.calling_convention = CallingConvention::C;
@@ -82,15 +123,145 @@ impl ProgramBuilder {
         }
     }
 
-    pub fn declare_function(&mut self) -> FunctionBuilder {
+    /// Declares a struct with the given named fields, laid out in order like a `repr(C)` struct.
+    /// Formatting the program with `fmt_program_with_field_names(prog, options, p.field_names())`
+    /// prints projections to these fields by name. Structs with the same layout have the same type,
+    /// so if several of them name a field differently, it is printed by index instead.
+    /// The layout is computed for target `T`.
+    pub fn declare_struct<T: Target>(&mut self, fields: &[(&str, Type)]) -> Type {
+        let mut offset = Size::ZERO;
+        let mut align = Align::ONE;
+        let mut sized_fields = Vec::new();
+        for (_name, ty) in fields {
+            let layout = ty.layout::<T>();
+            let field_align = layout.expect_align("struct fields must be sized");
+            offset = offset.align_to(field_align);
+            sized_fields.push((offset, *ty));
+            offset = offset + layout.expect_size("struct fields must be sized");
+            align = align.max(field_align);
+        }
+        let ty = tuple_ty(&sized_fields, offset.align_to(align), align);
+        self.field_names.insert(ty, fields.iter().map(|(name, _ty)| name.to_string()).collect());
+        ty
+    }
+
+    /// Declares an enum with the given named variants, each carrying data of the given type.
+    /// The variants get the discriminants `0..variants.len()` in order, which are stored in a `u8` tag
+    /// at offset 0, followed by the data. Field 0 of a downcast to a variant is its data.
+    /// Use `discriminant`, `FunctionBuilder::switch_variant_by_name` and `FunctionBuilder::match_enum_by_name`
+    /// to refer to the variants by name. The layout is computed for target `T`.
+    #[track_caller]
+    pub fn declare_enum<T: Target>(&mut self, variants: &[(&str, Type)]) -> Type {
+        assert!(variants.len() < 256, "the tags of an enum must fit into a `u8`");
+        let mut align = Align::ONE;
+        let mut size = Size::from_bytes_const(1);
+        let mut data = Vec::new();
+        for (_name, ty) in variants {
+            let layout = ty.layout::<T>();
+            let data_align = layout.expect_align("enum variants must be sized");
+            let data_offset = Size::from_bytes_const(1).align_to(data_align);
+            data.push((data_offset, *ty));
+            size = size.max(data_offset + layout.expect_size("enum variants must be sized"));
+            align = align.max(data_align);
+        }
+        let size = size.align_to(align);
+
+        let enum_variants: Vec<(u8, Variant)> = data
+            .into_iter()
+            .enumerate()
+            .map(|(discriminant, (data_offset, ty))| {
+                let tag = (IntType::U8, Int::from(discriminant));
+                let ty = tuple_ty(&[(data_offset, ty)], size, align);
+                (discriminant as u8, enum_variant(ty, &[(Size::ZERO, tag)]))
+            })
+            .collect();
+        let children: Vec<((u8, u8), Discriminator)> = (0..variants.len())
+            .map(|discriminant| {
+                let tag = discriminant as u8;
+                ((tag, tag + 1), discriminator_known(tag))
+            })
+            .collect();
+        let discriminator = discriminator_branch(Size::ZERO, discriminator_invalid(), &children);
+        let ty = enum_ty(&enum_variants, discriminator, size, align);
+        self.variant_names
+            .enums
+            .push((ty, variants.iter().map(|(name, _ty)| name.to_string()).collect()));
+        ty
+    }
+
+    /// The discriminant of the variant called `name` of `enum_ty`, which has to be declared with `declare_enum`.
+    /// Use this instead of writing down discriminants in `switch_int`, `variant` and `downcast`.
+    #[track_caller]
+    pub fn discriminant(&self, enum_ty: Type, name: &str) -> Int {
+        self.variant_names.discriminant(enum_ty, name)
+    }
+
+    /// The names of the fields of all structs declared with `declare_struct`.
+    pub fn field_names(&self) -> &FieldNames {
+        &self.field_names
+    }
+
+    /// The names of the functions declared with `declare_function_named`, the names of the locals and blocks
+    /// declared with `declare_local_named` and `declare_block_named` and the comments made with `comment`
+    /// in all finished functions.
+    /// Format the program with `fmt_program_with_debug_info` to print them.
+    pub fn debug_info(&self) -> &DebugInfo {
+        &self.debug_info
+    }
+
+    /// The inline hints of all functions that were given one with `FunctionBuilder::set_inline`,
+    /// for use with `inline_functions`.
+    pub fn inline_hints(&self) -> &InlineHints {
+        &self.inline_hints
+    }
+
+    fn fresh_fn_name(&mut self) -> FnName {
         let name = FnName(Name::from_internal(self.next_fn));
         self.next_fn += 1;
-        FunctionBuilder::new(name)
+        name
+    }
+
+    pub fn declare_function(&mut self) -> FunctionBuilder {
+        let name = self.fresh_fn_name();
+        FunctionBuilder::new(name, self.variant_names.clone())
+    }
+
+    /// Declares a function without building it yet, so that functions defined before it can call it,
+    /// e.g. for mutual recursion. It has to be built later with `define_function`.
+    pub fn forward_declare_function(&mut self) -> FnName {
+        let name = self.fresh_fn_name();
+        self.forward_declared.push(name);
+        name
+    }
+
+    /// Builds the function `name` declared with `forward_declare_function`, with `body` adding its
+    /// locals and blocks as for a function from `declare_function`.
+    #[track_caller]
+    pub fn define_function<F: FnOnce(&mut FunctionBuilder)>(&mut self, name: FnName, body: F) {
+        let Some(idx) = self.forward_declared.iter().position(|f| *f == name) else {
+            panic!("define_function: the function was not forward declared or is already defined");
+        };
+        self.forward_declared.remove(idx);
+        let mut f = FunctionBuilder::new(name, self.variant_names.clone());
+        body(&mut f);
+        self.finish_function(f);
+    }
+
+    /// Declares a function called `name`. Dumps of the program made with `debug_info`
+    /// show the function as `fN_name`; the name has no other effect.
+    pub fn declare_function_named(&mut self, name: &str) -> FunctionBuilder {
+        let f = self.declare_function();
+        self.debug_info.insert_function(f.name(), name);
+        f
     }
 
     #[track_caller]
     pub fn finish_function(&mut self, f: FunctionBuilder) -> FnName {
         let name = f.name();
+        if let Some(hint) = f.inline {
+            self.inline_hints.insert(name, hint);
+        }
+        self.debug_info.extend_renamed(&f.debug_info, |f| f);
         let f = f.finish_function();
         self.functions.try_insert(name, f).unwrap();
         name
@@ -160,15 +331,64 @@ pub struct FunctionBuilder {
     start: BbName,
     ret: Option<LocalName>,
     conv: CallingConvention,
+    inline: Option<InlineHint>,
 
     cur_block: Option<CurBlock>,
 
     next_block: u32,
     next_local: u32,
+
+    /// Whether to insert storage markers for locals automatically, see `auto_storage`.
+    auto_storage: bool,
+
+    /// The currently open scopes, innermost scope last.
+    scopes: Vec<Scope>,
+
+    /// The names of the locals and blocks declared with a name, and the comments.
+    debug_info: DebugInfo,
+
+    /// The names of the variants of the enums declared before this function.
+    variant_names: VariantNames,
+}
+
+/// The names of the variants of the enums declared with `ProgramBuilder::declare_enum`, one entry per enum.
+/// Types are structural, so different enums with the same layout have the same type.
+#[derive(Clone, Default)]
+struct VariantNames {
+    enums: Vec<(Type, Vec<String>)>,
+}
+
+impl VariantNames {
+    /// The discriminant of the variant called `name` of the declared enum with type `enum_ty`.
+    #[track_caller]
+    fn discriminant(&self, enum_ty: Type, name: &str) -> Int {
+        let mut declared = self.enums.iter().filter(|(ty, _names)| *ty == enum_ty).peekable();
+        if declared.peek().is_none() {
+            panic!("the enum was not declared with `declare_enum`");
+        }
+        let mut found = declared.filter_map(|(_ty, names)| names.iter().position(|v| v == name));
+        let Some(idx) = found.next() else {
+            panic!("the enum has no variant called `{name}`");
+        };
+        if found.any(|other| other != idx) {
+            panic!(
+                "the variant `{name}` has different discriminants in enums with the same layout"
+            );
+        }
+        Int::from(idx)
+    }
+}
+
+/// A scope opened by `FunctionBuilder::scope`.
+struct Scope {
+    /// The locals declared in this scope.
+    declared: Vec<LocalName>,
+    /// The locals declared in this scope that have been marked as live, in the order they were marked.
+    live: Vec<LocalName>,
 }
 
 impl FunctionBuilder {
-    fn new(name: FnName) -> FunctionBuilder {
+    fn new(name: FnName, variant_names: VariantNames) -> FunctionBuilder {
         let mut fb = FunctionBuilder {
             name,
             locals: Default::default(),
@@ -177,9 +397,14 @@ impl FunctionBuilder {
             start: BbName(Name::from_internal(0)),
             ret: None,
             conv: CallingConvention::Rust,
+            inline: None,
             cur_block: None,
             next_block: 0,
             next_local: 0,
+            auto_storage: false,
+            scopes: Vec::new(),
+            debug_info: DebugInfo::default(),
+            variant_names,
         };
         // prepare the starting block
         let start_block = fb.declare_block();
@@ -195,6 +420,14 @@ impl FunctionBuilder {
         name
     }
 
+    /// Declares a block called `name`, which dumps of the program made with the debug info
+    /// of the `ProgramBuilder` show as `bbN_name`.
+    pub fn declare_block_named(&mut self, name: &str) -> BbName {
+        let bb = self.declare_block();
+        self.debug_info.insert_block(self.name, bb, name);
+        bb
+    }
+
     pub fn set_cur_block(&mut self, name: BbName, kind: BbKind) {
         if self.blocks.contains_key(name) {
             panic!("Already inserted a block with this name.")
@@ -225,14 +458,23 @@ impl FunctionBuilder {
             self.declare_ret::<()>();
         }
 
-        Function {
+        let f = Function {
             locals: self.locals,
             args: self.args,
             ret: self.ret.unwrap(),
             calling_convention: self.conv,
             blocks: self.blocks,
             start: self.start,
-        }
+        };
+
+        if self.auto_storage { crate::transform::insert_storage_markers(f) } else { f }
+    }
+
+    /// Lets the builder take care of `storage_live` and `storage_dead` for all locals of this
+    /// function that are never explicitly marked as live: they are live from before their first use
+    /// to after their last use, see `insert_storage_markers`.
+    pub fn auto_storage(&mut self) {
+        self.auto_storage = true;
     }
 
     pub fn name(&self) -> FnName {
@@ -242,9 +484,25 @@ impl FunctionBuilder {
     fn fresh_local_name(&mut self) -> LocalName {
         let name = LocalName(Name::from_internal(self.next_local));
         self.next_local += 1;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.declared.push(name);
+        }
         name
     }
 
+    /// Declares a local called `name`. Dumps of the program made with the debug info of the `ProgramBuilder`
+    /// show the local as `_N_name`; the name has no other effect.
+    pub fn declare_local_named<T: TypeConv + Freeze>(&mut self, name: &str) -> PlaceExpr {
+        self.declare_local_named_with_ty(T::get_type(), name)
+    }
+
+    pub fn declare_local_named_with_ty(&mut self, t: Type, name: &str) -> PlaceExpr {
+        let local = self.fresh_local_name();
+        self.locals.try_insert(local, t).unwrap();
+        self.debug_info.insert_local(self.name, local, name);
+        local_by_name(local)
+    }
+
     pub fn declare_local<T: TypeConv + Freeze>(&mut self) -> PlaceExpr {
         let name = self.fresh_local_name();
         self.locals.try_insert(name, T::get_type()).unwrap();
@@ -257,13 +515,95 @@ impl FunctionBuilder {
         local_by_name(name)
     }
 
+    /// Declares a local of type `T`, marks it live and initializes it with `init`, like `let x: T = init;`.
+    pub fn let_<T: TypeConv + Freeze>(&mut self, init: ValueExpr) -> PlaceExpr {
+        self.let_with_ty(T::get_type(), init)
+    }
+
+    pub fn let_with_ty(&mut self, t: Type, init: ValueExpr) -> PlaceExpr {
+        let local = self.declare_local_with_ty(t);
+        self.storage_live(local);
+        self.assign(local, init);
+        local
+    }
+
+    /// Declares a local of type `t` that is aligned to at least `align` on target `T`.
+    /// The local is wrapped in a tuple with the requested alignment, and the returned place is its only field.
+    /// Use `storage_live_aligned` and `storage_dead_aligned` to mark it as live or dead.
+    pub fn declare_local_with_align<T: Target>(&mut self, t: Type, align: Align) -> PlaceExpr {
+        let layout = t.layout::<T>();
+        let align = align.max(layout.expect_align("locals must be sized"));
+        let size = layout.expect_size("locals must be sized").align_to(align);
+        let wrapper = tuple_ty(&[(Size::ZERO, t)], size, align);
+        field(self.declare_local_with_ty(wrapper), 0)
+    }
+
+    /// Runs `body` in a new scope. Locals that are declared in `body` and marked as live there
+    /// (like the temporaries from `declare_temp`) are marked dead again, in reverse order,
+    /// whenever control flow leaves the scope normally: by falling off the end of `body`,
+    /// or by a `break_` or `continue_` of a loop outside the scope.
+    /// Returning or unwinding from the function deallocates all locals anyway.
+    pub fn scope<F: Fn(&mut Self)>(&mut self, body: F) {
+        self.scopes.push(Scope { declared: Vec::new(), live: Vec::new() });
+        body(self);
+        if self.cur_block.is_some() {
+            self.end_scopes(self.scopes.len() - 1);
+        }
+        self.scopes.pop();
+    }
+
+    /// Marks the live locals of all scopes from `depth` on as dead, innermost scope first,
+    /// without closing those scopes.
+    fn end_scopes(&mut self, depth: usize) {
+        let live: Vec<LocalName> = self.scopes[depth..]
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.live.iter().rev().copied())
+            .collect();
+        for name in live {
+            self.storage_dead(local_by_name(name));
+        }
+    }
+
+    /// Remembers that `name` was marked as live, if it was declared in an open scope.
+    fn track_live(&mut self, name: LocalName) {
+        let Some(scope) = self.scopes.iter_mut().rev().find(|scope| scope.declared.contains(&name))
+        else {
+            return;
+        };
+        if !scope.live.contains(&name) {
+            scope.live.push(name);
+        }
+    }
+
+    /// Declares a local that is live until the end of the innermost `scope`.
+    #[track_caller]
+    pub fn declare_temp<T: TypeConv + Freeze>(&mut self) -> PlaceExpr {
+        self.declare_temp_with_ty(T::get_type())
+    }
+
+    #[track_caller]
+    pub fn declare_temp_with_ty(&mut self, t: Type) -> PlaceExpr {
+        if self.scopes.is_empty() {
+            panic!("Temporaries can only be declared in a scope.")
+        }
+        let local = self.declare_local_with_ty(t);
+        self.storage_live(local);
+        local
+    }
+
     #[track_caller]
     pub fn declare_ret<T: TypeConv>(&mut self) -> PlaceExpr {
+        self.declare_ret_with_ty(T::get_type())
+    }
+
+    #[track_caller]
+    pub fn declare_ret_with_ty(&mut self, ty: Type) -> PlaceExpr {
         let name = match self.ret {
             Some(_) => panic!("Ret local already set."),
             None => self.fresh_local_name(),
         };
-        self.locals.try_insert(name, T::get_type()).unwrap();
+        self.locals.try_insert(name, ty).unwrap();
         self.ret = Some(name);
         local_by_name(name)
     }
@@ -279,11 +619,64 @@ impl FunctionBuilder {
         local_by_name(name)
     }
 
+    /// Declares an argument that receives a unique pointer (a `Box` or mutable reference) sent by another thread
+    /// with `spawn_sending`, and retags it like on function entry, so it is protected until the function returns.
+    /// Must be called while the builder is still in the start block.
+    #[track_caller]
+    pub fn declare_received_arg_with_ty(&mut self, ty: Type) -> PlaceExpr {
+        assert!(
+            matches!(
+                ty,
+                Type::Ptr(PtrType::Box { .. } | PtrType::Ref { mutbl: Mutability::Mutable, .. })
+            ),
+            "declare_received_arg: only a `Box` or mutable reference can be received"
+        );
+        let arg = self.declare_arg_with_ty(ty);
+        self.validate(arg, /* fn_entry */ true);
+        arg
+    }
+
+    #[track_caller]
+    pub fn declare_received_arg<T: TypeConv>(&mut self) -> PlaceExpr {
+        self.declare_received_arg_with_ty(T::get_type())
+    }
+
+    /// Declares a live local that is aligned for `T` and returns a raw pointer of type `*const T`
+    /// into it that is offset by `misalign` bytes, and hence definitely misaligned for `T`.
+    /// The local is large enough that the pointer is still in-bounds for a `T`.
+    #[track_caller]
+    pub fn misaligned_ptr<T: TypeConv>(&mut self, misalign: usize) -> ValueExpr {
+        assert!(
+            0 < misalign && misalign < T::get_align().bytes().try_to_usize().unwrap(),
+            "misaligned_ptr: `misalign` must be between zero and the alignment of the type"
+        );
+        let buf_ty = tuple_ty(&[], T::get_size() + size(T::get_align().bytes()), T::get_align());
+        let buf = self.declare_local_with_ty(buf_ty);
+        self.storage_live(buf);
+        misaligned(addr_of(buf, <*const T>::get_type()), misalign, <*const T>::get_type())
+    }
+
     /// Change the calling convention of the current function.
     pub fn set_conv(&mut self, conv: CallingConvention) {
         self.conv = conv;
     }
 
+    /// Like an `#[inline]` attribute: tells `inline_functions` whether to inline calls to this function.
+    pub fn set_inline(&mut self, hint: InlineHint) {
+        self.inline = Some(hint);
+    }
+
+    /// Attaches `comment` to the next statement or terminator added to the current block.
+    /// Dumps of the program made with the debug info of the `ProgramBuilder` show it as `// comment`
+    /// above that statement or terminator; the comment has no other effect.
+    #[track_caller]
+    pub fn comment(&mut self, comment: &str) {
+        let fn_name = self.name;
+        let cur_block = self.cur_block();
+        let (bb, idx) = (cur_block.name, cur_block.statements.iter().count());
+        self.debug_info.insert_comment(fn_name, bb, idx, comment);
+    }
+
     /// Build one or multiple cleanup blocks. The name of the first block is returned.
     pub fn cleanup_block<F>(&mut self, cleanup_builder: F) -> BbName
     where
@@ -302,14 +695,16 @@ impl FunctionBuilder {
     }
 
     /// Build one or multiple terminate blocks. The name of the first block is returned.
-    pub fn terminating_block<F>(&mut self, terminate_builer: F) -> BbName
+    /// Like in the language, the blocks can only be left with `terminate`, `goto` and `switch` to other
+    /// terminate blocks, or `unreachable`.
+    pub fn terminate_block<F>(&mut self, terminate_builder: F) -> BbName
     where
         F: Fn(&mut Self),
     {
         let old_cur_block = self.cur_block.take();
         let terminate_block = self.declare_block();
         self.set_cur_block(terminate_block, BbKind::Terminate);
-        terminate_builer(self);
+        terminate_builder(self);
 
         if self.cur_block.is_some() {
             panic!("The terminate block is unfinished. The block needs to end with a Terminator.");
@@ -334,6 +729,49 @@ impl FunctionBuilder {
         self.cur_block = old_cur_block;
         catch_block
     }
+
+    /// Takes the current block, with the statements added to it so far, out of the builder,
+    /// so that other blocks can be built before it is finished. Afterwards, there is no current block.
+    /// Continue building the block with `restore_cursor`.
+    #[track_caller]
+    pub fn save_cursor(&mut self) -> Cursor {
+        let block = self.cur_block.take().expect("save_cursor: there is no current block");
+        Cursor { block }
+    }
+
+    /// Makes the block saved in `cursor` the current block again.
+    /// The block that was current before, if any, has to be finished.
+    #[track_caller]
+    pub fn restore_cursor(&mut self, cursor: Cursor) {
+        if self.cur_block.is_some() {
+            panic!("restore_cursor: the current block is unfinished");
+        }
+        self.cur_block = Some(cursor.block);
+    }
+
+    /// Builds the block `bb`, which was declared earlier, with `builder`, which has to finish it.
+    /// The block has the same kind as the current block, or is a regular block if there is none.
+    /// Afterwards, the current block is the same as before.
+    #[track_caller]
+    pub fn build_block_later<F>(&mut self, bb: BbName, builder: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let kind = self.cur_block.as_ref().map_or(BbKind::Regular, |block| block.kind);
+        let old_cur_block = self.cur_block.take();
+        self.set_cur_block(bb, kind);
+        builder(self);
+
+        if self.cur_block.is_some() {
+            panic!("The block is unfinished. The block needs to end with a Terminator.");
+        }
+        self.cur_block = old_cur_block;
+    }
+}
+
+/// The position of a block under construction, see `FunctionBuilder::save_cursor`.
+pub struct Cursor {
+    block: CurBlock,
 }
 
 pub struct VTableBuilder {
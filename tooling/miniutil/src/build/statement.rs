@@ -5,6 +5,11 @@ impl FunctionBuilder {
         self.cur_block().statements.push(Statement::Assign { destination, source });
     }
 
+    /// Copies the value in `source` to `destination` at their type. Overlapping places are UB.
+    pub fn typed_copy(&mut self, destination: PlaceExpr, source: PlaceExpr) {
+        self.cur_block().statements.push(Statement::CopyPlace { destination, source });
+    }
+
     pub fn place_mention(&mut self, place: PlaceExpr) {
         self.cur_block().statements.push(Statement::PlaceMention(place));
     }
@@ -19,21 +24,170 @@ impl FunctionBuilder {
         self.cur_block().statements.push(Statement::Validate { place, fn_entry });
     }
 
+    /// Copies `n` bytes from `src` to `dst` one byte at a time.
+    /// Every byte keeps its provenance, so this can be used to copy pointers.
+    pub fn copy_bytes(&mut self, dst: ValueExpr, src: ValueExpr, n: usize) {
+        for i in 0..n {
+            self.assign(byte_at(dst, i), load(byte_at(src, i)));
+        }
+    }
+
+    /// Copies `n` bytes from `src` to `dst` one byte at a time using `u8` loads.
+    /// This strips the provenance of every byte.
+    pub fn copy_bytes_as_u8(&mut self, dst: ValueExpr, src: ValueExpr, n: usize) {
+        for i in 0..n {
+            self.assign(u8_at(dst, i), load(u8_at(src, i)));
+        }
+    }
+
+    /// Swaps the contents of `a` and `b` through a temporary, like `mem::swap`.
+    /// Like the two `&mut` that `mem::swap` takes, the places must not overlap;
+    /// the emitted code asserts this with `assume`, so overlapping places are UB.
+    #[track_caller]
+    pub fn swap(&mut self, a: PlaceExpr, b: PlaceExpr) {
+        let ty = self.place_ty(a);
+        assert_eq!(ty, self.place_ty(b), "`swap` requires places of the same type");
+        self.assume_disjoint(a, b, ty);
+        self.scope(|f| {
+            let tmp = f.declare_temp_with_ty(ty);
+            f.assign(tmp, load(a));
+            f.assign(a, load(b));
+            f.assign(b, load(tmp));
+        });
+    }
+
+    /// Moves the contents of `place` into `old_dest` and then stores `new_val` in `place`,
+    /// like `mem::replace`. `place` and `old_dest` must not overlap; the emitted code asserts
+    /// this with `assume`, so overlapping places are UB.
+    #[track_caller]
+    pub fn replace(&mut self, place: PlaceExpr, new_val: ValueExpr, old_dest: PlaceExpr) {
+        let ty = self.place_ty(place);
+        assert_eq!(ty, self.place_ty(old_dest), "`replace` requires places of the same type");
+        self.assume_disjoint(place, old_dest, ty);
+        self.assign(old_dest, load(place));
+        self.assign(place, new_val);
+    }
+
+    /// Asserts with `assume` that the places `a` and `b`, both of type `ty`, do not overlap.
+    fn assume_disjoint(&mut self, a: PlaceExpr, b: PlaceExpr, ty: Type) {
+        let size =
+            ty.layout::<DefaultTarget>().expect_size("`swap` and `replace` need sized places");
+        if size == Size::ZERO {
+            return;
+        }
+        let size = const_int_typed::<usize>(size.bytes());
+        let a = ptr_addr(addr_of(a, raw_void_ptr_ty()));
+        let b = ptr_addr(addr_of(b, raw_void_ptr_ty()));
+        self.assume(bool_or(le(add(a, size), b), le(add(b, size), a)));
+    }
+
+    /// The type of `place`, as far as the builder can determine it.
+    #[track_caller]
+    pub(crate) fn place_ty(&self, place: PlaceExpr) -> Type {
+        match place {
+            PlaceExpr::Local(name) => self.locals.get(name).unwrap(),
+            PlaceExpr::Deref { ty, .. } => ty,
+            PlaceExpr::Field { root, field } =>
+                match self.place_ty(root.extract()) {
+                    Type::Tuple { sized_fields, .. } => sized_fields.index_at(field).1,
+                    Type::Union { fields, .. } => fields.index_at(field).1,
+                    ty => panic!("cannot project to a field of type {ty:?}"),
+                },
+            PlaceExpr::Index { root, .. } =>
+                match self.place_ty(root.extract()) {
+                    Type::Array { elem, .. } | Type::Slice { elem } => elem.extract(),
+                    ty => panic!("cannot index into a place of type {ty:?}"),
+                },
+            PlaceExpr::Downcast { root, discriminant } =>
+                match self.place_ty(root.extract()) {
+                    Type::Enum { variants, .. } => variants.get(discriminant).unwrap().ty,
+                    ty => panic!("cannot downcast a place of type {ty:?}"),
+                },
+        }
+    }
+
+    /// The type of `value`, if the builder can easily determine it.
+    pub(crate) fn value_ty(&self, value: ValueExpr) -> Option<Type> {
+        match value {
+            ValueExpr::Constant(_, ty) | ValueExpr::Tuple(_, ty) => Some(ty),
+            ValueExpr::Load { source } => Some(self.place_ty(source.extract())),
+            ValueExpr::UnOp { operator: UnOp::Int(_), operand } => self.value_ty(operand.extract()),
+            ValueExpr::BinOp { operator: BinOp::Int(_), left, .. } => self.value_ty(left.extract()),
+            _ => None,
+        }
+    }
+
+    /// The type of the integer expression `value`, as far as the builder can determine it.
+    #[track_caller]
+    pub(crate) fn int_value_ty(&self, value: ValueExpr) -> Type {
+        let Some(ty) = self.value_ty(value) else {
+            panic!("cannot determine the type of {value:?}")
+        };
+        let Type::Int(_) = ty else { panic!("expected an integer, found a value of type {ty:?}") };
+        ty
+    }
+
+    #[track_caller]
     pub fn storage_live(&mut self, local: PlaceExpr) {
-        let PlaceExpr::Local(name) = local else { panic!("PlaceExpr is not a local") };
-        self.cur_block().statements.push(Statement::StorageLive(name));
+        self.storage_live_name(local_name(local));
     }
 
+    #[track_caller]
     pub fn storage_dead(&mut self, local: PlaceExpr) {
-        let PlaceExpr::Local(name) = local else { panic!("PlaceExpr is not a local") };
+        self.storage_dead_name(local_name(local));
+    }
+
+    /// Like `storage_live`, for a place returned by `declare_local_with_align`.
+    #[track_caller]
+    pub fn storage_live_aligned(&mut self, local: PlaceExpr) {
+        self.storage_live_name(aligned_local_name(local));
+    }
+
+    /// Like `storage_dead`, for a place returned by `declare_local_with_align`.
+    #[track_caller]
+    pub fn storage_dead_aligned(&mut self, local: PlaceExpr) {
+        self.storage_dead_name(aligned_local_name(local));
+    }
+
+    fn storage_live_name(&mut self, name: LocalName) {
+        self.cur_block().statements.push(Statement::StorageLive(name));
+        self.track_live(name);
+    }
+
+    fn storage_dead_name(&mut self, name: LocalName) {
         self.cur_block().statements.push(Statement::StorageDead(name));
     }
 }
 
+#[track_caller]
+fn local_name(place: PlaceExpr) -> LocalName {
+    match place {
+        PlaceExpr::Local(name) => name,
+        _ => panic!("PlaceExpr is not a local"),
+    }
+}
+
+/// The local wrapping the place returned by `declare_local_with_align`, which is its first field.
+#[track_caller]
+fn aligned_local_name(place: PlaceExpr) -> LocalName {
+    match place {
+        PlaceExpr::Field { root, field } if field == 0 =>
+            match root.extract() {
+                PlaceExpr::Local(name) => name,
+                _ => panic!("PlaceExpr is not an aligned local"),
+            },
+        _ => panic!("PlaceExpr is not an aligned local"),
+    }
+}
+
 pub fn assign(destination: PlaceExpr, source: ValueExpr) -> Statement {
     Statement::Assign { destination, source }
 }
 
+pub fn typed_copy(destination: PlaceExpr, source: PlaceExpr) -> Statement {
+    Statement::CopyPlace { destination, source }
+}
+
 pub fn place_mention(place: PlaceExpr) -> Statement {
     Statement::PlaceMention(place)
 }
@@ -0,0 +1,54 @@
+use crate::build::*;
+
+/// A function that is generic over some types, like a generic Rust function.
+///
+/// The body gets the type arguments and builds one instance of the function from them.
+/// Every list of type arguments gets its own instance, which is declared the first time it is asked for,
+/// so the same body can be stamped out for e.g. `u8`, `u32` and `u128` in one program.
+///
+/// Example:
+///
+/// ```rust
+/// use miniutil::build::*;
+///
+/// let mut p = ProgramBuilder::new();
+/// // fn id<T>(x: T) -> T
+/// let mut id = FnTemplate::new(|_p, f, tys| {
+///     let x = f.declare_arg_with_ty(tys[0]);
+///     let ret = f.declare_ret_with_ty(tys[0]);
+///     f.assign(ret, load(x));
+///     f.return_();
+/// });
+/// let id_u8 = id.instantiate(&mut p, &[<u8>::get_type()]);
+/// let id_u32 = id.instantiate(&mut p, &[<u32>::get_type()]);
+/// assert_ne!(id_u8, id_u32);
+/// assert_eq!(id_u8, id.instantiate(&mut p, &[<u8>::get_type()]));
+/// ```
+pub struct FnTemplate<F> {
+    body: F,
+    instances: Vec<(Vec<Type>, FnName)>,
+}
+
+impl<F: Fn(&mut ProgramBuilder, &mut FunctionBuilder, &[Type])> FnTemplate<F> {
+    pub fn new(body: F) -> FnTemplate<F> {
+        FnTemplate { body, instances: Vec::new() }
+    }
+
+    /// The instance of this function for the type arguments `tys`.
+    /// The body can use `p` to instantiate the templates it calls.
+    pub fn instantiate(&mut self, p: &mut ProgramBuilder, tys: &[Type]) -> FnName {
+        if let Some((_tys, f)) = self.instances.iter().find(|(other, _f)| other == tys) {
+            return *f;
+        }
+        let mut f = p.declare_function();
+        (self.body)(p, &mut f, tys);
+        let name = p.finish_function(f);
+        self.instances.push((tys.to_vec(), name));
+        name
+    }
+
+    /// The instances declared so far, with their type arguments.
+    pub fn instances(&self) -> &[(Vec<Type>, FnName)] {
+        &self.instances
+    }
+}
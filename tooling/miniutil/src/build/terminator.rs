@@ -1,5 +1,17 @@
+use std::cell::Cell;
+
+use crate::analysis::value_type;
 use crate::build::*;
 
+/// The largest number of values that `switch_range` handles with a single `Switch` terminator.
+const SWITCH_RANGE_MAX_CASES: u32 = 8;
+
+/// A branch of `switch_int` and the other switches, which builds its code on the function builder.
+type Branch<'a, B> = &'a dyn Fn(&mut B);
+
+/// An arm of `match_enum`, which also gets the place downcast to the variant it matches.
+type Arm<'a, B> = &'a dyn Fn(&mut B, PlaceExpr);
+
 impl FunctionBuilder {
     #[track_caller]
     fn finish_block(&mut self, terminator: Terminator) {
@@ -34,14 +46,42 @@ impl FunctionBuilder {
         self.finish_block(Terminator::Unreachable);
     }
 
+    pub fn unreachable_unchecked(&mut self) {
+        self.finish_block(unreachable_unchecked());
+    }
+
     pub fn return_(&mut self) {
         self.finish_block(Terminator::Return);
     }
 
+    /// Assigns `value` to the return place and returns, like `return value;`.
+    /// Panics if no return place was declared, or if `value` has a type other than the return type.
+    #[track_caller]
+    pub fn return_value(&mut self, value: ValueExpr) {
+        let Some(ret) = self.ret else {
+            panic!("return_value: the function has no return place, use `declare_ret` first")
+        };
+        let ret_ty = self.locals.get(ret).unwrap();
+        if let Some(ty) = self.value_ty(value) {
+            if ty != ret_ty {
+                panic!(
+                    "return_value: returning a value of type {ty:?} from a function returning {ret_ty:?}"
+                );
+            }
+        }
+        self.assign(local_by_name(ret), value);
+        self.return_();
+    }
+
     pub fn resume_unwind(&mut self) {
         self.finish_block(Terminator::ResumeUnwind);
     }
 
+    /// Aborts the program. Only allowed in terminate blocks, see `terminate_block`.
+    pub fn terminate(&mut self) {
+        self.finish_block(Terminator::Terminate);
+    }
+
     // Call terminators
 
     /// This is a helper function that handles function calls.
@@ -127,6 +167,13 @@ impl FunctionBuilder {
         self.finish_with_next_block(|next_block| assume(val, bbname_into_u32(next_block)));
     }
 
+    /// Assumes that the integer `val` is in `start..=end`.
+    pub fn assume_in_range(&mut self, val: ValueExpr, start: ValueExpr, end: ValueExpr) {
+        self.finish_with_next_block(|next_block| {
+            assume_in_range(val, start, end, bbname_into_u32(next_block))
+        });
+    }
+
     pub fn print(&mut self, arg: ValueExpr) {
         self.finish_with_next_block(|next_block| print(arg, bbname_into_u32(next_block)));
     }
@@ -141,18 +188,34 @@ impl FunctionBuilder {
         });
     }
 
+    /// Allocates memory in the current stack frame, which is freed when the function returns or unwinds.
+    pub fn stack_allocate(&mut self, size: ValueExpr, align: ValueExpr, ret_place: PlaceExpr) {
+        self.finish_with_next_block(|next_block| {
+            stack_allocate(size, align, ret_place, bbname_into_u32(next_block))
+        });
+    }
+
     pub fn deallocate(&mut self, ptr: ValueExpr, size: ValueExpr, align: ValueExpr) {
         self.finish_with_next_block(|next_block| {
             deallocate(ptr, size, align, bbname_into_u32(next_block))
         });
     }
 
-    pub fn spawn(&mut self, f: FnName, data_ptr: ValueExpr, ret: PlaceExpr) {
+    pub fn spawn(&mut self, f: FnName, arg: ValueExpr, ret: PlaceExpr) {
         self.finish_with_next_block(|next_block| {
-            spawn(fn_ptr(f), data_ptr, ret, bbname_into_u32(next_block))
+            spawn(fn_ptr(f), arg, ret, bbname_into_u32(next_block))
         });
     }
 
+    /// Spawns a thread running `f` and sends it the unique pointer (a `Box` or mutable reference) in the local `ptr`.
+    /// The pointer is moved: the local is dead afterwards, so this thread can only get at the memory
+    /// through a parent of the sent pointer, which is UB while the other thread holds it.
+    /// The receiving function should declare its argument with `declare_received_arg`.
+    pub fn spawn_sending(&mut self, f: FnName, ptr: PlaceExpr, ret: PlaceExpr) {
+        self.spawn(f, load(ptr), ret);
+        self.storage_dead(ptr);
+    }
+
     pub fn join(&mut self, thread_id: ValueExpr) {
         self.finish_with_next_block(|next_block| join(thread_id, bbname_into_u32(next_block)));
     }
@@ -163,6 +226,40 @@ impl FunctionBuilder {
         });
     }
 
+    /// Stores `1` (definitely equal), `0` (definitely unequal), or `2` (unknown) in the `u8` place `dest`.
+    pub fn ptr_guaranteed_cmp(&mut self, dest: PlaceExpr, left: ValueExpr, right: ValueExpr) {
+        self.finish_with_next_block(|next_block| {
+            ptr_guaranteed_cmp(dest, left, right, bbname_into_u32(next_block))
+        });
+    }
+
+    /// Stores in the `bool` place `dest` whether `ptr` is dereferenceable for `len` (a `usize`) bytes.
+    pub fn is_dereferenceable(&mut self, dest: PlaceExpr, ptr: ValueExpr, len: ValueExpr) {
+        self.finish_with_next_block(|next_block| {
+            is_dereferenceable(dest, ptr, len, bbname_into_u32(next_block))
+        });
+    }
+
+    /// Stores the size of the allocation `ptr` points to in the `usize` place `dest`.
+    pub fn allocation_size(&mut self, dest: PlaceExpr, ptr: ValueExpr) {
+        self.finish_with_next_block(|next_block| {
+            allocation_size(dest, ptr, bbname_into_u32(next_block))
+        });
+    }
+
+    /// Stores in the `[u8; N]` place `dest` whether each of the `N` bytes `ptr` points to is
+    /// uninitialized (0), initialized (1), or initialized with provenance (2).
+    pub fn init_mask(&mut self, dest: PlaceExpr, ptr: ValueExpr) {
+        self.finish_with_next_block(|next_block| init_mask(dest, ptr, bbname_into_u32(next_block)));
+    }
+
+    /// Prints to stderr where the provenance of `ptr` came from.
+    pub fn print_provenance_history(&mut self, ptr: ValueExpr) {
+        self.finish_with_next_block(|next_block| {
+            print_provenance_history(ptr, bbname_into_u32(next_block))
+        });
+    }
+
     pub fn atomic_store(&mut self, ptr: ValueExpr, src: ValueExpr) {
         self.finish_with_next_block(|next_block| {
             atomic_store(ptr, src, bbname_into_u32(next_block))
@@ -221,6 +318,13 @@ impl FunctionBuilder {
         });
     }
 
+    /// Acquires the lock and stores whether it is poisoned in `poisoned`, which must have type `bool`.
+    pub fn lock_acquire_poisoned(&mut self, lock_id: ValueExpr, poisoned: PlaceExpr) {
+        self.finish_with_next_block(|next_block| {
+            lock_acquire_poisoned(lock_id, poisoned, bbname_into_u32(next_block))
+        });
+    }
+
     pub fn lock_release(&mut self, lock_id: ValueExpr) {
         self.finish_with_next_block(|next_block| {
             lock_release(lock_id, bbname_into_u32(next_block))
@@ -251,17 +355,80 @@ impl FunctionBuilder {
         self.switch_int(bool_to_int::<u8>(condition), &[(1, &then_branch)], else_branch);
     }
 
-    pub fn switch_int<T, G>(
+    /// Evaluates to `then_value` if `condition` holds and to `else_value` otherwise,
+    /// like `let x = if condition { then_value } else { else_value };`.
+    /// Only the value of the branch that is taken is evaluated.
+    /// The result is stored in a fresh local, which is a temporary of the innermost `scope` if there is one,
+    /// and otherwise stays live for the rest of the function. Returns that local.
+    pub fn if_value<T: TypeConv + Freeze>(
         &mut self,
-        value: ValueExpr,
-        cases: &[(T, &dyn Fn(&mut Self))],
-        fallback: G,
-    ) where
+        condition: ValueExpr,
+        then_value: ValueExpr,
+        else_value: ValueExpr,
+    ) -> PlaceExpr {
+        self.if_value_with_ty(T::get_type(), condition, then_value, else_value)
+    }
+
+    pub fn if_value_with_ty(
+        &mut self,
+        ty: Type,
+        condition: ValueExpr,
+        then_value: ValueExpr,
+        else_value: ValueExpr,
+    ) -> PlaceExpr {
+        let result = self.declare_result_local(ty);
+        self.if_(condition, |f| f.assign(result, then_value), |f| f.assign(result, else_value));
+        result
+    }
+
+    /// Computes `lhs && rhs`, where the code built by `rhs` only runs if `lhs` is `true`.
+    /// `rhs` returns the value of the right-hand side, a `bool`.
+    /// The result is stored in a fresh `bool` local as described for `if_value`, which is returned.
+    pub fn and_<F: Fn(&mut Self) -> ValueExpr>(&mut self, lhs: ValueExpr, rhs: F) -> PlaceExpr {
+        let result = self.declare_result_local(<bool>::get_type());
+        self.if_(
+            lhs,
+            |f| f.assign_short_circuit(result, &rhs),
+            |f| f.assign(result, const_bool(false)),
+        );
+        result
+    }
+
+    /// Computes `lhs || rhs`, where the code built by `rhs` only runs if `lhs` is `false`.
+    /// Otherwise this works like `and_`.
+    pub fn or_<F: Fn(&mut Self) -> ValueExpr>(&mut self, lhs: ValueExpr, rhs: F) -> PlaceExpr {
+        let result = self.declare_result_local(<bool>::get_type());
+        self.if_(
+            lhs,
+            |f| f.assign(result, const_bool(true)),
+            |f| f.assign_short_circuit(result, &rhs),
+        );
+        result
+    }
+
+    /// Assigns the value built by `rhs` to `result`, unless building it already diverged.
+    fn assign_short_circuit(&mut self, result: PlaceExpr, rhs: &dyn Fn(&mut Self) -> ValueExpr) {
+        let value = rhs(self);
+        if self.cur_block.is_some() {
+            self.assign(result, value);
+        }
+    }
+
+    /// Declares the local holding the result of a value-producing control flow helper:
+    /// a temporary of the innermost `scope` if there is one, and otherwise a local that stays live.
+    fn declare_result_local(&mut self, ty: Type) -> PlaceExpr {
+        let local = self.declare_local_with_ty(ty);
+        self.storage_live(local);
+        local
+    }
+
+    pub fn switch_int<T, G>(&mut self, value: ValueExpr, cases: &[(T, Branch<Self>)], fallback: G)
+    where
         T: Clone + Into<Int>,
         G: Fn(&mut Self),
     {
         // closures + blocks we we run the closures on
-        let mut branches: Vec<(&dyn Fn(&mut Self), BbName)> = Vec::new();
+        let mut branches: Vec<(Branch<Self>, BbName)> = Vec::new();
         // branch map for switch terminator
         let mut branch_map: Map<Int, BbName> = Map::new();
 
@@ -297,26 +464,559 @@ impl FunctionBuilder {
         }
     }
 
-    pub fn while_<F: Fn(&mut Self)>(&mut self, condition: ValueExpr, body: F) {
+    /// Like `switch_int`, but each case is an inclusive range `(lo, hi)` of values, like `lo..=hi` in a `match`.
+    /// The ranges must not be empty and must not overlap. `value` is an integer and is evaluated once.
+    /// The switch is lowered to a binary search over the ranges using comparisons; once few enough values
+    /// are left, they are handled by a single `Switch` terminator with one case per value.
+    #[track_caller]
+    pub fn switch_range<T, G>(
+        &mut self,
+        value: ValueExpr,
+        cases: &[((T, T), Branch<Self>)],
+        fallback: G,
+    ) where
+        T: Clone + Into<Int>,
+        G: Fn(&mut Self),
+    {
+        let ty = self.int_value_ty(value);
+        let mut branches: Vec<(Branch<Self>, BbName)> = Vec::new();
+        let mut ranges: Vec<(Int, Int, BbName)> = Vec::new();
+        for ((lo, hi), branch) in cases {
+            let (lo, hi): (Int, Int) = (lo.clone().into(), hi.clone().into());
+            if lo > hi {
+                panic!("switch_range: the range {lo}..={hi} is empty");
+            }
+            let block = self.declare_block();
+            ranges.push((lo, hi, block));
+            branches.push((branch, block));
+        }
+        ranges.sort_by_key(|(lo, _hi, _block)| *lo);
+        for pair in ranges.windows(2) {
+            let ((lo1, hi1, _), (lo2, hi2, _)) = (pair[0], pair[1]);
+            if hi1 >= lo2 {
+                panic!("switch_range: the ranges {lo1}..={hi1} and {lo2}..={hi2} overlap");
+            }
+        }
+
+        let block_kind = self.cur_block().kind;
+        let fallback_block = self.declare_block();
+        let scrutinee = self.declare_result_local(ty);
+        self.assign(scrutinee, value);
+        self.range_tree(load(scrutinee), ty, &ranges, fallback_block);
+
+        // As in `switch_int`, the blocks that do not finish on their own jump to a common block afterwards.
+        branches.push((&fallback, fallback_block));
+        let mut after_switch_block: Option<BbName> = None;
+        for (branch, block) in branches {
+            self.set_cur_block(block, block_kind);
+            branch(self);
+            if self.cur_block.is_some() {
+                let jump_to_block = *after_switch_block.get_or_insert_with(|| self.declare_block());
+                self.goto(jump_to_block);
+            }
+        }
+        if let Some(after_switch_block) = after_switch_block {
+            self.set_cur_block(after_switch_block, block_kind);
+        }
+    }
+
+    /// Finishes the current block and the blocks it branches to such that `value` ends up in the block
+    /// of the range that contains it, or in `fallback`. `ranges` are sorted and do not overlap.
+    fn range_tree(
+        &mut self,
+        value: ValueExpr,
+        ty: Type,
+        ranges: &[(Int, Int, BbName)],
+        fallback: BbName,
+    ) {
+        let block_kind = self.cur_block().kind;
+        let constant = |int: Int| ValueExpr::Constant(Constant::Int(int), ty);
+        let values =
+            ranges.iter().fold(Int::ZERO, |sum, (lo, hi, _block)| sum + (*hi - *lo) + Int::ONE);
+
+        if values <= Int::from(SWITCH_RANGE_MAX_CASES) {
+            let mut cases: Map<Int, BbName> = Map::new();
+            for (lo, hi, block) in ranges {
+                let mut case = *lo;
+                while case <= *hi {
+                    cases.insert(case, *block);
+                    case += Int::ONE;
+                }
+            }
+            self.finish_block(Terminator::Switch { value, cases, fallback });
+        } else if let [(lo, hi, block)] = ranges {
+            let check_hi = self.declare_block();
+            self.finish_block(if_(
+                ge(value, constant(*lo)),
+                bbname_into_u32(check_hi),
+                bbname_into_u32(fallback),
+            ));
+            self.set_cur_block(check_hi, block_kind);
+            self.finish_block(if_(
+                le(value, constant(*hi)),
+                bbname_into_u32(*block),
+                bbname_into_u32(fallback),
+            ));
+        } else {
+            let mid = ranges.len() / 2;
+            let (below, above) = (self.declare_block(), self.declare_block());
+            let (mid_lo, _mid_hi, _block) = ranges[mid];
+            self.finish_block(if_(
+                lt(value, constant(mid_lo)),
+                bbname_into_u32(below),
+                bbname_into_u32(above),
+            ));
+            self.set_cur_block(below, block_kind);
+            self.range_tree(value, ty, &ranges[..mid], fallback);
+            self.set_cur_block(above, block_kind);
+            self.range_tree(value, ty, &ranges[mid..], fallback);
+        }
+    }
+
+    /// Runs `body` as long as `condition` holds, like `while condition { .. }`.
+    /// As in `loop_`, the body can use `ctrl.break_(f)` and `ctrl.continue_(f)`;
+    /// `continue_` jumps back to the condition.
+    pub fn while_<F: Fn(&mut Self, &LoopCtrl)>(&mut self, condition: ValueExpr, body: F) {
         // goto new block such that condition sits alone in dedicated block
         let cond = self.declare_block();
         let block_kind = self.cur_block().kind;
         self.goto(cond);
         self.set_cur_block(cond, block_kind);
 
+        let ctrl = LoopCtrl { head: cond, exit: Cell::new(None), scope_depth: self.scopes.len() };
         self.if_(
             condition,
             |f| {
-                body(f);
+                body(f, &ctrl);
                 if f.cur_block.is_some() {
                     f.goto(cond);
                 }
             },
+            |f| ctrl.break_(f),
+        );
+        // The loop is left when the condition does not hold, so there always is a block after it.
+        self.set_cur_block(ctrl.exit.get().unwrap(), block_kind);
+    }
+
+    /// Matches on the variant of the enum in `place`: runs the arm for its discriminant,
+    /// which gets `place` downcast to that variant, or `fallback` if there is no arm for it.
+    #[track_caller]
+    pub fn match_enum<T, G>(&mut self, place: PlaceExpr, arms: &[(T, Arm<Self>)], fallback: G)
+    where
+        T: Clone + Into<Int>,
+        G: Fn(&mut Self),
+    {
+        let Type::Enum { variants, .. } = self.place_ty(place) else {
+            panic!("match_enum: the place does not have an enum type")
+        };
+        // All these closures come from the same expression, so they have the same type and fit in one `Vec`.
+        let arms: Vec<_> = arms
+            .iter()
+            .map(|(discriminant, body)| {
+                let discriminant: Int = discriminant.clone().into();
+                if !variants.contains_key(discriminant) {
+                    panic!("match_enum: the enum has no variant with discriminant {discriminant}");
+                }
+                let downcast_place = downcast(place, discriminant);
+                (discriminant, move |f: &mut Self| body(f, downcast_place))
+            })
+            .collect();
+        let cases: Vec<(Int, Branch<Self>)> =
+            arms.iter().map(|(discriminant, body)| (*discriminant, body as Branch<Self>)).collect();
+        self.switch_int(get_discriminant(place), &cases, fallback);
+    }
+
+    /// Like `match_enum`, but the arms are given by the names of the variants, as in `ProgramBuilder::declare_enum`.
+    #[track_caller]
+    pub fn match_enum_by_name<G: Fn(&mut Self)>(
+        &mut self,
+        place: PlaceExpr,
+        arms: &[(&str, Arm<Self>)],
+        fallback: G,
+    ) {
+        let enum_ty = self.place_ty(place);
+        let arms: Vec<(Int, Arm<Self>)> = arms
+            .iter()
+            .map(|(name, body)| (self.variant_names.discriminant(enum_ty, name), *body))
+            .collect();
+        self.match_enum(place, &arms, fallback);
+    }
+
+    /// Switches on the discriminant of the enum in `place`, like `switch_int` with the cases given by the names
+    /// of the variants, as in `ProgramBuilder::declare_enum`.
+    #[track_caller]
+    pub fn switch_variant_by_name<G: Fn(&mut Self)>(
+        &mut self,
+        place: PlaceExpr,
+        cases: &[(&str, Branch<Self>)],
+        fallback: G,
+    ) {
+        let enum_ty = self.place_ty(place);
+        let cases: Vec<(Int, Branch<Self>)> = cases
+            .iter()
+            .map(|(name, branch)| (self.variant_names.discriminant(enum_ty, name), *branch))
+            .collect();
+        self.switch_int(get_discriminant(place), &cases, fallback);
+    }
+
+    /// Runs `then_branch` if the enum in `place` is the variant with `discriminant`, like `if let`.
+    /// `then_branch` gets `place` downcast to that variant; otherwise `else_branch` runs.
+    #[track_caller]
+    pub fn if_variant<T, F, G>(
+        &mut self,
+        place: PlaceExpr,
+        discriminant: T,
+        then_branch: F,
+        else_branch: G,
+    ) where
+        T: Into<Int>,
+        F: Fn(&mut Self, PlaceExpr),
+        G: Fn(&mut Self),
+    {
+        let Type::Enum { variants, .. } = self.place_ty(place) else {
+            panic!("if_variant: the place does not have an enum type")
+        };
+        let discriminant: Int = discriminant.into();
+        if !variants.contains_key(discriminant) {
+            panic!("if_variant: the enum has no variant with discriminant {discriminant}");
+        }
+        let downcast_place = downcast(place, discriminant);
+        self.switch_int(
+            get_discriminant(place),
+            &[(discriminant, &|f| then_branch(f, downcast_place))],
+            else_branch,
+        );
+    }
+
+    /// Runs `body` over and over, like `loop { .. }`.
+    /// The body can leave the loop with `ctrl.break_(f)` and start the next iteration with `ctrl.continue_(f)`.
+    /// If it never breaks, the loop is infinite and there is no current block afterwards.
+    pub fn loop_<F: Fn(&mut Self, &LoopCtrl)>(&mut self, body: F) {
+        let head = self.declare_block();
+        let block_kind = self.cur_block().kind;
+        self.goto(head);
+        self.set_cur_block(head, block_kind);
+
+        let ctrl = LoopCtrl { head, exit: Cell::new(None), scope_depth: self.scopes.len() };
+        body(self, &ctrl);
+        if self.cur_block.is_some() {
+            self.goto(head);
+        }
+
+        if let Some(exit) = ctrl.exit.get() {
+            self.set_cur_block(exit, block_kind);
+        }
+    }
+
+    /// Runs `body` once, and then again as long as `condition` holds, like `do { .. } while condition`.
+    /// As in `loop_`, the body can use `ctrl.break_(f)` and `ctrl.continue_(f)`;
+    /// `continue_` jumps to the condition.
+    pub fn do_while_<F: Fn(&mut Self, &LoopCtrl)>(&mut self, body: F, condition: ValueExpr) {
+        let head = self.declare_block();
+        let cond = self.declare_block();
+        let block_kind = self.cur_block().kind;
+        self.goto(head);
+        self.set_cur_block(head, block_kind);
+
+        let ctrl = LoopCtrl { head: cond, exit: Cell::new(None), scope_depth: self.scopes.len() };
+        body(self, &ctrl);
+        if self.cur_block.is_some() {
+            self.goto(cond);
+        }
+
+        // The condition sits alone in its block and jumps straight back to the body.
+        self.set_cur_block(cond, block_kind);
+        let exit = ctrl.exit.get().unwrap_or_else(|| self.declare_block());
+        self.finish_block(if_(condition, bbname_into_u32(head), bbname_into_u32(exit)));
+        self.set_cur_block(exit, block_kind);
+    }
+
+    /// Runs `body` for every integer from `start` (inclusive) to `end` (exclusive), like `for idx in start..end`.
+    /// Both bounds are evaluated once, before the loop; they must be integers of the same type.
+    /// `body` gets the current index as a value, the counter itself lives in a fresh local.
+    #[track_caller]
+    pub fn for_<F: Fn(&mut Self, ValueExpr)>(&mut self, start: ValueExpr, end: ValueExpr, body: F) {
+        let ty = self.int_value_ty(start);
+        let counter = self.declare_local_with_ty(ty);
+        let end_local = self.declare_local_with_ty(ty);
+        self.storage_live(counter);
+        self.storage_live(end_local);
+        self.assign(counter, start);
+        self.assign(end_local, end);
+
+        self.while_(lt(load(counter), load(end_local)), |f, _| {
+            body(f, load(counter));
+            if f.cur_block.is_some() {
+                // This cannot overflow: the counter is less than `end`.
+                let one = ValueExpr::Constant(Constant::Int(Int::ONE), ty);
+                f.assign(counter, add(load(counter), one));
+            }
+        });
+
+        self.storage_dead(counter);
+        self.storage_dead(end_local);
+    }
+
+    /// Indexes into `root` like safe Rust does: if `idx` (a `usize`) is out of bounds,
+    /// `out_of_bounds` is run instead, which has to diverge (e.g. by panicking or aborting).
+    /// `root` must be an array local, or a dereferenced array or slice pointer.
+    #[track_caller]
+    pub fn index_checked<F: Fn(&mut Self)>(
+        &mut self,
+        root: PlaceExpr,
+        idx: ValueExpr,
+        out_of_bounds: F,
+    ) -> PlaceExpr {
+        let len = self.len_of(root);
+        self.if_(
+            lt(idx, len),
+            |_| {},
+            |f| {
+                out_of_bounds(f);
+                if f.cur_block.is_some() {
+                    panic!("The out-of-bounds handler of `index_checked` must not fall through.")
+                }
+            },
+        );
+        index(root, idx)
+    }
+
+    /// Matches the array or slice `root` against a slice pattern like `[a, b, rest @ .., z]`,
+    /// given as its parts in order. If the length fits, `on_match` is run with the bound
+    /// elements and subslice; otherwise `on_mismatch` is run.
+    /// `root` must be an array local, or a dereferenced array or slice pointer.
+    #[track_caller]
+    pub fn match_slice<F, G>(
+        &mut self,
+        root: PlaceExpr,
+        pattern: &[SlicePat],
+        on_match: F,
+        on_mismatch: G,
+    ) where
+        F: Fn(&mut Self, &SliceBindings),
+        G: Fn(&mut Self),
+    {
+        let rest_idx = pattern.iter().position(|pat| *pat == SlicePat::Rest);
+        if pattern.iter().filter(|pat| **pat == SlicePat::Rest).count() > 1 {
+            panic!("a slice pattern can contain at most one `..`");
+        }
+        let (prefix, suffix) = match rest_idx {
+            Some(idx) => (idx, pattern.len() - idx - 1),
+            None => (pattern.len(), 0),
+        };
+        let (Type::Array { elem, .. } | Type::Slice { elem }) = self.place_ty(root) else {
+            panic!("cannot match a slice pattern against this place");
+        };
+        let elem_size = elem.extract().layout::<DefaultTarget>().expect_size("elements are sized");
+
+        let len = self.len_of(root);
+        let min_len = const_int(prefix + suffix);
+        let matches = if rest_idx.is_some() { ge(len, min_len) } else { eq(len, min_len) };
+
+        let mut elems: Vec<PlaceExpr> =
+            (0..prefix).map(|idx| index(root, const_int(idx))).collect();
+        // Elements after the `..` are counted from the end.
+        elems.extend((0..suffix).map(|idx| index(root, sub(len, const_int(suffix - idx)))));
+        let rest = rest_idx.map(|_| {
+            let start = match root {
+                PlaceExpr::Deref { operand, .. } => get_thin_pointer(operand.extract()),
+                _ => addr_of(root, raw_void_ptr_ty()),
+            };
+            let offset = const_int_typed::<usize>(elem_size.bytes() * Int::from(prefix));
+            let start = ptr_offset(start, offset, InBounds::Yes);
+            let rest_len = sub(len, const_int(prefix + suffix));
+            construct_wide_pointer(start, rest_len, raw_ptr_ty(PointerMetaKind::ElementCount))
+        });
+        let bindings = SliceBindings { elems, rest };
+
+        self.if_(matches, |f| on_match(f, &bindings), on_mismatch);
+    }
+
+    /// The number of elements in the array or slice `place`, as a `usize`.
+    #[track_caller]
+    fn len_of(&self, place: PlaceExpr) -> ValueExpr {
+        let ty = match place {
+            PlaceExpr::Local(name) => self.locals.get(name).unwrap(),
+            PlaceExpr::Deref { ty, .. } => ty,
+            _ => panic!("cannot determine the length of this place"),
+        };
+        match (ty, place) {
+            (Type::Array { count, .. }, _) => const_int_typed::<usize>(count),
+            (Type::Slice { .. }, PlaceExpr::Deref { operand, .. }) =>
+                get_metadata(operand.extract()),
+            _ => panic!("cannot index into a place of type {ty:?}"),
+        }
+    }
+
+    /// Destroys the `Box<dyn Trait>` value `boxed` like a MIR `Drop` does: first the drop glue of
+    /// the dynamic type is called through the vtable, then the box is deallocated with the
+    /// size and alignment recorded in the vtable.
+    /// The drop glue is the `drop_glue` method of the trait; it takes the data pointer as a `*mut T`.
+    pub fn drop_box_dyn(
+        &mut self,
+        boxed: ValueExpr,
+        trait_name: TraitName,
+        drop_glue: TraitMethodName,
+    ) {
+        let obj_ty = trait_object_ty(trait_name);
+        let data = get_thin_pointer(boxed);
+        let vtable = get_metadata(boxed);
+        self.call_ignoreret(vtable_method_lookup(vtable, drop_glue), &[by_value(data)]);
+        self.deallocate(data, compute_size(obj_ty, vtable), compute_align(obj_ty, vtable));
+    }
+
+    /// Assigns `l + r` to the integer place `dest`, aborting on overflow like debug builds do.
+    pub fn add_checked(&mut self, dest: PlaceExpr, l: ValueExpr, r: ValueExpr) {
+        self.assign_checked(dest, overflow_add(l, r), "attempt to add with overflow");
+    }
+
+    /// Assigns `l - r` to the integer place `dest`, aborting on overflow like debug builds do.
+    pub fn sub_checked(&mut self, dest: PlaceExpr, l: ValueExpr, r: ValueExpr) {
+        self.assign_checked(dest, overflow_sub(l, r), "attempt to subtract with overflow");
+    }
+
+    /// Assigns `l * r` to the integer place `dest`, aborting on overflow like debug builds do.
+    pub fn mul_checked(&mut self, dest: PlaceExpr, l: ValueExpr, r: ValueExpr) {
+        self.assign_checked(dest, overflow_mul(l, r), "attempt to multiply with overflow");
+    }
+
+    /// Assigns the integer part of `result`, an arithmetic operation with overflow, to `dest`.
+    /// If the operation overflowed, prints `msg` and aborts instead.
+    #[track_caller]
+    fn assign_checked(&mut self, dest: PlaceExpr, result: ValueExpr, msg: &str) {
+        let Type::Int(int_ty) = self.place_ty(dest) else {
+            panic!("checked arithmetic requires an integer destination");
+        };
+        self.scope(|f| {
+            let tmp = f.declare_temp_with_ty(int_ty.with_overflow::<DefaultTarget>());
+            f.assign(tmp, result);
+            f.if_(
+                load(field(tmp, 1)),
+                |f| {
+                    f.eprint(const_str(msg));
+                    f.abort();
+                },
+                |_| {},
+            );
+            f.assign(dest, load(field(tmp, 0)));
+        });
+    }
+
+    /// Panics with `payload` as the panic payload, of type `T`. See `panic_with_ty`.
+    pub fn panic<T: TypeConv + Freeze>(&mut self, payload: ValueExpr) {
+        self.panic_with_ty(T::get_type(), payload);
+    }
+
+    /// Panics with the string `msg` (a `[u8; N]`) as the panic payload. See `panic_with_ty`.
+    pub fn panic_str(&mut self, msg: &str) {
+        self.panic_with_ty(array_ty(<u8>::get_type(), msg.len()), const_str(msg));
+    }
+
+    /// Panics like `std::panic!` does: the payload is moved into a fresh heap allocation,
+    /// and unwinding starts with a pointer to it, via a cleanup block that resumes unwinding in the caller.
+    /// Whoever catches the panic gets the pointer from `get_unwind_payload` and owns the allocation.
+    /// Afterwards, there is no current block.
+    pub fn panic_with_ty(&mut self, ty: Type, payload: ValueExpr) {
+        let layout = ty.layout::<DefaultTarget>();
+        let size = layout.expect_size("panic payloads must be sized").bytes();
+        let align = layout.expect_align("panic payloads must be sized").bytes();
+        let payload_ptr = self.declare_local::<*mut u8>();
+        self.storage_live(payload_ptr);
+        self.allocate(
+            const_usize_on::<DefaultTarget>(size),
+            const_usize_on::<DefaultTarget>(align),
+            payload_ptr,
+        );
+        self.assign(deref(load(payload_ptr), ty), payload);
+        let cleanup = self.cleanup_block(|f| f.resume_unwind());
+        self.start_unwind(load(payload_ptr), cleanup);
+    }
+
+    /// Checks that `left` and `right` are equal. If they are not, prints both values to stderr
+    /// and aborts the program.
+    /// Prefer this over `assume(eq(left, right))` in tests: a failure is reported instead of being UB.
+    /// Both operands are evaluated exactly once, into locals as described for `if_value`,
+    /// so they need to be printable and the type of one of them needs to be known.
+    #[track_caller]
+    pub fn assert_eq(&mut self, left: ValueExpr, right: ValueExpr) {
+        self.assert_eq_with_header(left, right, "assertion `left == right` failed".to_string());
+    }
+
+    /// Like `assert_eq`, but also prints `context` if the values are not equal.
+    #[track_caller]
+    pub fn assert_eq_msg(&mut self, left: ValueExpr, right: ValueExpr, context: &str) {
+        self.assert_eq_with_header(
+            left,
+            right,
+            format!("assertion `left == right` failed: {context}"),
+        );
+    }
+
+    #[track_caller]
+    fn assert_eq_with_header(&mut self, left: ValueExpr, right: ValueExpr, header: String) {
+        let ty = value_type(left, self.locals)
+            .or_else(|| value_type(right, self.locals))
+            .expect("`assert_eq`: cannot determine the type of the operands");
+        let left_val = self.declare_result_local(ty);
+        self.assign(left_val, left);
+        let right_val = self.declare_result_local(ty);
+        self.assign(right_val, right);
+        self.if_(
+            eq(load(left_val), load(right_val)),
             |_| {},
+            |f| {
+                f.eprint(const_str(&header));
+                f.eprint(load(left_val));
+                f.eprint(load(right_val));
+                f.abort();
+            },
         );
     }
 }
 
+/// A part of a slice pattern, see `FunctionBuilder::match_slice`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlicePat {
+    /// A single element.
+    Elem,
+    /// The `..` matching any number of elements.
+    Rest,
+}
+
+/// What a slice pattern binds.
+pub struct SliceBindings {
+    /// The places of the matched elements, in the order of the `Elem`s in the pattern.
+    pub elems: Vec<PlaceExpr>,
+    /// A `*const [T]` to the elements matched by `..`, if the pattern has one.
+    pub rest: Option<ValueExpr>,
+}
+
+/// Where `break_` and `continue_` jump to, see `FunctionBuilder::loop_` and `FunctionBuilder::while_`.
+pub struct LoopCtrl {
+    head: BbName,
+    /// The block after the loop, declared by the first `break_`.
+    exit: Cell<Option<BbName>>,
+    /// The number of scopes that were open when the loop started.
+    /// Jumping to the head or exit leaves all scopes opened since.
+    scope_depth: usize,
+}
+
+impl LoopCtrl {
+    /// Leaves the loop: finishes the current block with a jump to the block after the loop.
+    pub fn break_(&self, f: &mut FunctionBuilder) {
+        let exit = self.exit.get().unwrap_or_else(|| f.declare_block());
+        self.exit.set(Some(exit));
+        f.end_scopes(self.scope_depth);
+        f.goto(exit);
+    }
+
+    /// Starts the next iteration: finishes the current block with a jump to the loop head.
+    pub fn continue_(&self, f: &mut FunctionBuilder) {
+        f.end_scopes(self.scope_depth);
+        f.goto(self.head);
+    }
+}
+
 pub fn goto(x: u32) -> Terminator {
     Terminator::Goto(BbName(Name::from_internal(x)))
 }
@@ -368,6 +1068,15 @@ pub fn assume(val: ValueExpr, next: u32) -> Terminator {
     }
 }
 
+pub fn assume_in_range(val: ValueExpr, start: ValueExpr, end: ValueExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::AssumeInRange,
+        arguments: list![val, start, end],
+        ret: unit_place(),
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
 pub fn print(arg: ValueExpr, next: u32) -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::PrintStdout,
@@ -395,6 +1104,20 @@ pub fn allocate(size: ValueExpr, align: ValueExpr, ret_place: PlaceExpr, next: u
     }
 }
 
+pub fn stack_allocate(
+    size: ValueExpr,
+    align: ValueExpr,
+    ret_place: PlaceExpr,
+    next: u32,
+) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::StackAllocate,
+        arguments: list![size, align],
+        ret: ret_place,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
 pub fn deallocate(ptr: ValueExpr, size: ValueExpr, align: ValueExpr, next: u32) -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::Deallocate,
@@ -413,6 +1136,15 @@ pub fn exit() -> Terminator {
     }
 }
 
+pub fn unreachable_unchecked() -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::UnreachableUnchecked,
+        arguments: list![],
+        ret: unit_place(),
+        next_block: None,
+    }
+}
+
 pub fn abort() -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::Abort,
@@ -438,10 +1170,14 @@ pub fn resume_unwind() -> Terminator {
     Terminator::ResumeUnwind
 }
 
-pub fn spawn(fn_ptr: ValueExpr, data_ptr: ValueExpr, ret: PlaceExpr, next: u32) -> Terminator {
+pub fn terminate() -> Terminator {
+    Terminator::Terminate
+}
+
+pub fn spawn(fn_ptr: ValueExpr, arg: ValueExpr, ret: PlaceExpr, next: u32) -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::Spawn,
-        arguments: list!(fn_ptr, data_ptr),
+        arguments: list!(fn_ptr, arg),
         ret,
         next_block: Some(BbName(Name::from_internal(next))),
     }
@@ -465,6 +1201,56 @@ pub fn raw_eq(ret: PlaceExpr, left_ptr: ValueExpr, right_ptr: ValueExpr, next: u
     }
 }
 
+pub fn ptr_guaranteed_cmp(
+    ret: PlaceExpr,
+    left: ValueExpr,
+    right: ValueExpr,
+    next: u32,
+) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::PtrGuaranteedCmp,
+        arguments: list!(left, right),
+        ret,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
+pub fn is_dereferenceable(ret: PlaceExpr, ptr: ValueExpr, len: ValueExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::IsDereferenceable,
+        arguments: list!(ptr, len),
+        ret,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
+pub fn allocation_size(ret: PlaceExpr, ptr: ValueExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::AllocationSize,
+        arguments: list!(ptr),
+        ret,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
+pub fn init_mask(ret: PlaceExpr, ptr: ValueExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::InitMask,
+        arguments: list!(ptr),
+        ret,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
+pub fn print_provenance_history(ptr: ValueExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::PrintProvenanceHistory,
+        arguments: list!(ptr),
+        ret: unit_place(),
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
 pub fn atomic_store(ptr: ValueExpr, src: ValueExpr, next: u32) -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::AtomicStore,
@@ -559,6 +1345,15 @@ pub fn lock_acquire(lock_id: ValueExpr, next: u32) -> Terminator {
     }
 }
 
+pub fn lock_acquire_poisoned(lock_id: ValueExpr, poisoned: PlaceExpr, next: u32) -> Terminator {
+    Terminator::Intrinsic {
+        intrinsic: IntrinsicOp::Lock(IntrinsicLockOp::Acquire),
+        arguments: list!(lock_id),
+        ret: poisoned,
+        next_block: Some(BbName(Name::from_internal(next))),
+    }
+}
+
 pub fn lock_release(lock_id: ValueExpr, next: u32) -> Terminator {
     Terminator::Intrinsic {
         intrinsic: IntrinsicOp::Lock(IntrinsicLockOp::Release),
@@ -52,6 +52,15 @@ pub fn box_ty(pointee: PointeeInfo) -> Type {
     Type::Ptr(PtrType::Box { pointee })
 }
 
+/// Create a minirust box type for a minirust type which implements default marker traits,
+/// i.e. the type is `Unpin`, `Freeze` and is inhabited.
+pub fn box_ty_default_markers_for(ty: Type) -> Type {
+    let layout = ty.layout::<DefaultTarget>();
+    let unsafe_cells = from_frozen_layout(layout);
+
+    box_ty(PointeeInfo { layout, inhabited: true, unsafe_cells, freeze: true, unpin: true })
+}
+
 pub fn raw_ptr_ty(meta_kind: PointerMetaKind) -> Type {
     Type::Ptr(PtrType::Raw { meta_kind })
 }
@@ -82,19 +91,146 @@ pub fn unsized_tuple_ty(
     }
 }
 
+/// A struct that wraps `field`, like a `repr(transparent)` struct: `field` comes first,
+/// followed by the 1-ZSTs in `zsts`. Function calls treat it like `field`.
+#[track_caller]
+pub fn transparent_ty(field: Type, zsts: &[Type]) -> Type {
+    let layout = field.layout::<DefaultTarget>();
+    let size = layout.expect_size("the wrapped field must be sized");
+    let align = layout.expect_align("the wrapped field must be sized");
+    let mut fields = vec![(offset(0), field)];
+    for zst in zsts {
+        let layout = zst.layout::<DefaultTarget>();
+        assert!(
+            layout == LayoutStrategy::Sized(Size::ZERO, Align::ONE),
+            "the other fields must be 1-ZSTs"
+        );
+        // Place the ZSTs at the end, so that they do not overlap with `field`.
+        fields.push((size, *zst));
+    }
+    tuple_ty(&fields, size, align)
+}
+
 pub fn union_ty(f: &[(Offset, Type)], size: Size, align: Align) -> Type {
     let chunks = list![(Size::ZERO, size)];
     Type::Union { fields: f.iter().copied().collect(), size, align, chunks }
 }
 
+/// Like `union_ty`, but the chunks are computed from the fields like rustc does for `repr(C)` unions:
+/// a byte is preserved if it is not padding in at least one field.
+pub fn union_ty_auto(f: &[(Offset, Type)], size: Size, align: Align) -> Type {
+    let len = size.bytes().try_to_usize().unwrap();
+    let mut data = vec![false; len];
+    for (offset, ty) in f {
+        mark_data_bytes(*ty, offset.bytes().try_to_usize().unwrap(), &mut data);
+    }
+
+    // Turn the runs of preserved bytes into chunks.
+    let mut chunks = List::new();
+    let mut start = None;
+    for (idx, is_data) in data.iter().copied().chain([false]).enumerate() {
+        match (start, is_data) {
+            (None, true) => start = Some(idx),
+            (Some(s), false) => {
+                chunks.push((offset(s), Size::from_bytes(idx - s).unwrap()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    Type::Union { fields: f.iter().copied().collect(), size, align, chunks }
+}
+
+/// Sets `data[offset + i]` for every byte `i` of `ty` that is not padding.
+fn mark_data_bytes(ty: Type, offset: usize, data: &mut [bool]) {
+    let to_usize = |i: Int| i.try_to_usize().unwrap();
+    match ty {
+        Type::Tuple { sized_fields, .. } =>
+            for (field_offset, field_ty) in sized_fields {
+                mark_data_bytes(field_ty, offset + to_usize(field_offset.bytes()), data);
+            },
+        Type::Array { elem, count } => {
+            let elem = elem.extract();
+            let elem_size = to_usize(
+                elem.layout::<DefaultTarget>().expect_size("union fields are sized").bytes(),
+            );
+            for i in 0..to_usize(count) {
+                mark_data_bytes(elem, offset + i * elem_size, data);
+            }
+        }
+        Type::Union { chunks, .. } =>
+            for (chunk_offset, chunk_size) in chunks {
+                let start = offset + to_usize(chunk_offset.bytes());
+                data[start..start + to_usize(chunk_size.bytes())].fill(true);
+            },
+        // Integers, Booleans, and pointers have no padding. For enums, the tag might be anywhere,
+        // so we conservatively preserve all of their bytes.
+        _ => {
+            let size = to_usize(
+                ty.layout::<DefaultTarget>().expect_size("union fields are sized").bytes(),
+            );
+            data[offset..offset + size].fill(true);
+        }
+    }
+}
+
+/// A single byte that preserves initialization and provenance, like `MaybeUninit<u8>`.
+pub fn maybe_uninit_byte_ty() -> Type {
+    union_ty(&[(size(0), <u8>::get_type())], size(1), align(1))
+}
+
 pub fn array_ty(elem: Type, count: impl Into<Int>) -> Type {
     Type::Array { elem: GcCow::new(elem), count: count.into() }
 }
 
+/// `[elem; count]` for a `count` that is only known when the test runs,
+/// so that a test can be generated for a whole range of lengths.
+/// Unlike `array_ty`, this makes sure the array is not too large for the default target.
+pub fn array_ty_dyn(elem: Type, count: usize) -> Type {
+    let elem_size = elem.layout::<DefaultTarget>().expect_size("array elements are sized");
+    let size = Size::from_bytes(elem_size.bytes() * Int::from(count));
+    if !size.is_some_and(DefaultTarget::valid_size) {
+        panic!("an array of {count} elements of size {} is too large", elem_size.bytes());
+    }
+    array_ty(elem, count)
+}
+
+/// The largest size a type or an allocation can have on target `T`: `isize::MAX` bytes.
+pub fn max_size<T: Target>() -> Size {
+    Size::from_bytes((Int::ONE << (T::PTR_SIZE.bits() - Int::ONE)) - Int::ONE).unwrap()
+}
+
+/// `[u8; n]` with `n` chosen such that the array is `max_size::<T>()` plus `excess` bytes large.
+/// With `excess == 0`, this is the largest type that is well-formed on `T`; with any other `excess`,
+/// it is too large for `T`.
+pub fn boundary_array_ty<T: Target>(excess: u32) -> Type {
+    array_ty(<u8>::get_type(), max_size::<T>().bytes() + Int::from(excess))
+}
+
+/// `usize` on target `T`, for programs that run on a target other than the default one.
+pub fn usize_ty_on<T: Target>() -> Type {
+    Type::Int(IntType::usize_ty::<T>())
+}
+
 pub fn slice_ty(elem: Type) -> Type {
     Type::Slice { elem: GcCow::new(elem) }
 }
 
+/// The type of the metadata of pointers to `pointee`:
+/// `usize` for slices, a vtable pointer for trait objects, and `()` for sized types.
+pub fn metadata_ty(pointee: Type) -> Type {
+    pointee.meta_kind().ty::<DefaultTarget>()
+}
+
+/// The `(thin pointer, metadata)` tuple with the same layout as the wide pointer type `ptr_ty`.
+#[track_caller]
+pub fn wide_pair_ty(ptr_ty: Type) -> Type {
+    let Type::Ptr(ptr_ty) = ptr_ty else {
+        panic!("wide_pair_ty requires Type::Ptr argument!");
+    };
+    ptr_ty.as_wide_pair::<DefaultTarget>().expect("wide_pair_ty requires a wide pointer type!")
+}
+
 pub fn trait_object_ty(trait_name: TraitName) -> Type {
     Type::TraitObject(trait_name)
 }
@@ -147,3 +283,128 @@ pub fn discriminator_branch<T: ToInt + TypeConv + Copy>(
             .collect(),
     }
 }
+
+/// The invalid values of an integer inside a type, which an enum can use to encode its other
+/// variants without a separate tag, like `Option<&T>` uses the null pointer.
+#[derive(Clone, Copy, Debug)]
+pub struct Niche {
+    pub offset: Offset,
+    pub int_ty: IntType,
+    /// The valid values of the integer, left-inclusive and right-exclusive.
+    /// Every other value of `int_ty` is free for the enum to use.
+    pub valid: (Int, Int),
+}
+
+impl Niche {
+    /// The null niche of a reference or box at `offset`.
+    pub fn null_ptr(offset: Offset) -> Niche {
+        let int_ty = IntType::usize_ty::<DefaultTarget>();
+        Niche { offset, int_ty, valid: (Int::ONE, Int::ONE << int_ty.size.bits()) }
+    }
+
+    /// The niche of a `bool` at `offset`.
+    pub fn bool(offset: Offset) -> Niche {
+        Niche { offset, int_ty: IntType::U8, valid: (Int::ZERO, Int::from(2)) }
+    }
+}
+
+/// An enum with the data variant `payload` (discriminant 0) and `n_empty` data-less variants
+/// (discriminants `1..=n_empty`) that are encoded in the niche of the payload.
+/// With `n_empty == 1`, this is how rustc lays out `Option<T>`.
+/// The niche values directly after the valid range are used if possible, otherwise those before it.
+#[track_caller]
+pub fn niche_enum_ty(payload: Type, niche: Niche, n_empty: u32) -> Type {
+    let (start, end) = niche.valid;
+    let after: Vec<Int> = (0..n_empty).map(|i| end + Int::from(i)).collect();
+    let before: Vec<Int> = (0..n_empty).map(|i| start - Int::from(n_empty - i)).collect();
+    let values = if after.iter().all(|v| niche.int_ty.can_represent(*v)) {
+        after
+    } else if before.iter().all(|v| niche.int_ty.can_represent(*v)) {
+        before
+    } else {
+        panic!("the niche does not have room for {n_empty} variants");
+    };
+
+    let layout = payload.layout::<DefaultTarget>();
+    let size = layout.expect_size("the payload of an enum must be sized");
+    let align = layout.expect_align("the payload of an enum must be sized");
+    let empty = tuple_ty(&[], size, align);
+
+    let mut variants = vec![(0, enum_variant(payload, &[]))];
+    let mut children = vec![((start, end), discriminator_known(0))];
+    for (i, value) in values.into_iter().enumerate() {
+        let discriminant = i as u32 + 1;
+        variants
+            .push((discriminant, enum_variant(empty, &[(niche.offset, (niche.int_ty, value))])));
+        children.push(((value, value + Int::ONE), discriminator_known(discriminant)));
+    }
+    let discriminator = Discriminator::Branch {
+        offset: niche.offset,
+        value_type: niche.int_ty,
+        fallback: GcCow::new(discriminator_invalid()),
+        children: children.into_iter().collect(),
+    };
+    enum_ty::<u32>(&variants, discriminator, size, align)
+}
+
+/// `Option<ptr_ty>` for a non-null thin pointer type, e.g. a reference, a box, or a function pointer.
+/// `None` is the null pointer, so function calls treat it like `ptr_ty`.
+#[track_caller]
+pub fn nullable_ptr_ty(ptr_ty: Type) -> Type {
+    let Type::Ptr(ptr) = ptr_ty else {
+        panic!("nullable_ptr_ty requires Type::Ptr argument!");
+    };
+    assert!(
+        ptr.meta_kind() == PointerMetaKind::None,
+        "nullable_ptr_ty requires a thin pointer type!"
+    );
+    niche_enum_ty(ptr_ty, Niche::null_ptr(offset(0)), 1)
+}
+
+/// An integer of type `int_ty` that is only valid within the given left-inclusive,
+/// right-exclusive ranges, like `NonZeroU32`.
+/// MiniRust has no such scalar type, so this is an enum with a single variant holding the integer,
+/// whose discriminator rejects all values outside the ranges. Loading an out-of-range value, or
+/// transmuting one to this type, is hence UB. Use `range_int_value` to access the integer.
+pub fn range_int_ty(int_ty: IntType, valid: &[(Int, Int)]) -> Type {
+    let int = Type::Int(int_ty);
+    let layout = int.layout::<DefaultTarget>();
+    let children: Map<(Int, Int), Discriminator> = valid
+        .iter()
+        .filter(|(start, end)| start < end)
+        .map(|range| (*range, discriminator_known(0)))
+        .collect();
+    let discriminator = Discriminator::Branch {
+        offset: offset(0),
+        value_type: int_ty,
+        fallback: GcCow::new(discriminator_invalid()),
+        children,
+    };
+    enum_ty::<u8>(
+        &[(0, enum_variant(int, &[]))],
+        discriminator,
+        layout.expect_size("integers are sized"),
+        layout.expect_align("integers are sized"),
+    )
+}
+
+/// An integer of type `int_ty` that must not be zero, like `NonZeroU32`.
+pub fn nonzero_ty(int_ty: IntType) -> Type {
+    let bits = int_ty.size.bits();
+    let (min, end) = match int_ty.signed {
+        Signed => (-(Int::ONE << (bits - Int::ONE)), Int::ONE << (bits - Int::ONE)),
+        Unsigned => (Int::ZERO, Int::ONE << bits),
+    };
+    range_int_ty(int_ty, &[(min, Int::ZERO), (Int::ONE, end)])
+}
+
+/// The integer inside a place of a `range_int_ty` or `nonzero_ty` type.
+pub fn range_int_value(place: PlaceExpr) -> PlaceExpr {
+    downcast(place, 0)
+}
+
+/// A value of a `range_int_ty` or `nonzero_ty` type. Like with `NonZero::new_unchecked`, `int` is
+/// not checked here; storing an out-of-range value makes later loads UB.
+pub fn range_int(int: ValueExpr, ty: Type) -> ValueExpr {
+    variant(0, int, ty)
+}
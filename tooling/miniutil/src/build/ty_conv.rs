@@ -7,6 +7,12 @@ use crate::build::*;
 pub trait TypeConv {
     fn get_type() -> Type;
 
+    /// The type on target `T`. `get_type` uses `DefaultTarget`, which only matters
+    /// for the pointer-sized integers `usize` and `isize`.
+    fn get_type_on<T: Target>() -> Type {
+        Self::get_type()
+    }
+
     // Convenience methods, these should not be overridden.
     fn get_layout() -> LayoutStrategy {
         Self::get_type().layout::<DefaultTarget>()
@@ -50,10 +56,48 @@ type_conv_int_impl!(i32, Signed, size(4));
 type_conv_int_impl!(i64, Signed, size(8));
 type_conv_int_impl!(i128, Signed, size(16));
 
+macro_rules! type_conv_ptr_sized_int_impl {
+    ($ty:ty, $signed:expr) => {
+        impl TypeConv for $ty {
+            fn get_type() -> Type {
+                Self::get_type_on::<DefaultTarget>()
+            }
+            fn get_type_on<T: Target>() -> Type {
+                Type::Int(IntType { signed: $signed, size: T::PTR_SIZE })
+            }
+        }
+    };
+}
+
 // We use `BasicMemory` to run a Program (see the `run` module),
-// hence we have to use its PTR_SIZE for `usize` and `isize`.
-type_conv_int_impl!(usize, Unsigned, DefaultTarget::PTR_SIZE);
-type_conv_int_impl!(isize, Signed, DefaultTarget::PTR_SIZE);
+// hence `get_type` uses its PTR_SIZE for `usize` and `isize`.
+type_conv_ptr_sized_int_impl!(usize, Unsigned);
+type_conv_ptr_sized_int_impl!(isize, Signed);
+
+macro_rules! type_conv_nonzero_impl {
+    ($ty:ty, $int:ty) => {
+        impl TypeConv for $ty {
+            fn get_type() -> Type {
+                let Type::Int(int_ty) = <$int>::get_type() else { unreachable!() };
+                nonzero_ty(int_ty)
+            }
+        }
+    };
+}
+
+type_conv_nonzero_impl!(std::num::NonZeroU8, u8);
+type_conv_nonzero_impl!(std::num::NonZeroU16, u16);
+type_conv_nonzero_impl!(std::num::NonZeroU32, u32);
+type_conv_nonzero_impl!(std::num::NonZeroU64, u64);
+type_conv_nonzero_impl!(std::num::NonZeroU128, u128);
+type_conv_nonzero_impl!(std::num::NonZeroUsize, usize);
+
+type_conv_nonzero_impl!(std::num::NonZeroI8, i8);
+type_conv_nonzero_impl!(std::num::NonZeroI16, i16);
+type_conv_nonzero_impl!(std::num::NonZeroI32, i32);
+type_conv_nonzero_impl!(std::num::NonZeroI64, i64);
+type_conv_nonzero_impl!(std::num::NonZeroI128, i128);
+type_conv_nonzero_impl!(std::num::NonZeroIsize, isize);
 
 impl<T: TypeConv + ?Sized> TypeConv for *const T {
     fn get_type() -> Type {
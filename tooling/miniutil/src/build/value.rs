@@ -0,0 +1,124 @@
+//! Converting Rust values to MiniRust values, and MiniRust values to and from bytes.
+//! This lets tests set up complex constants (arrays, structs, enums) in one go,
+//! e.g. as the initial value of a global, instead of building them statement by statement.
+
+use crate::build::*;
+
+/// Converts a Rust value to a MiniRust value of type `Self::get_type()`.
+/// Example usage: `let v: Value<BasicMem> = to_value([1u8, 2, 3]);`
+pub trait ToValue: TypeConv + Sized {
+    fn to_value<M: Memory>(self) -> Value<M>;
+}
+
+macro_rules! to_value_int_impl {
+    ($($ty:ty),*) => {
+        $(
+            impl ToValue for $ty {
+                fn to_value<M: Memory>(self) -> Value<M> {
+                    Value::Int(self.into())
+                }
+            }
+        )*
+    };
+}
+
+to_value_int_impl!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl ToValue for bool {
+    fn to_value<M: Memory>(self) -> Value<M> {
+        Value::Bool(self)
+    }
+}
+
+impl ToValue for () {
+    fn to_value<M: Memory>(self) -> Value<M> {
+        Value::Tuple(list![])
+    }
+}
+
+impl<T: ToValue, const N: usize> ToValue for [T; N] {
+    fn to_value<M: Memory>(self) -> Value<M> {
+        Value::Tuple(self.into_iter().map(|elem| elem.to_value()).collect())
+    }
+}
+
+pub fn to_value<M: Memory, T: ToValue>(val: T) -> Value<M> {
+    val.to_value()
+}
+
+/// The value of a struct or tuple with the given fields, in the order of the fields of its type.
+pub fn tuple_value<M: Memory>(fields: &[Value<M>]) -> Value<M> {
+    Value::Tuple(fields.iter().copied().collect())
+}
+
+/// The value of the enum variant with the given discriminant.
+pub fn variant_value<M: Memory>(discriminant: impl Into<Int>, data: Value<M>) -> Value<M> {
+    Value::Variant { discriminant: discriminant.into(), data: GcCow::new(data) }
+}
+
+/// The bytes that represent `val` at type `ty`, on the target of `M`.
+/// Uninitialized bytes, like padding, are `None`.
+/// Panics if `val` contains a pointer with provenance, as that cannot be represented by plain bytes.
+#[track_caller]
+pub fn encode_value<M: Memory>(ty: Type, val: Value<M>) -> List<Option<u8>> {
+    ty.encode::<M>(val)
+        .iter()
+        .map(|byte| {
+            match byte {
+                AbstractByte::Uninit => None,
+                AbstractByte::Init(data, None) => Some(data),
+                AbstractByte::Init(_, Some(_)) =>
+                    panic!("a pointer with provenance cannot be encoded"),
+            }
+        })
+        .collect()
+}
+
+/// The value of type `ty` represented by `bytes` on the target of `M`,
+/// or `None` if `bytes` are not a valid representation.
+#[track_caller]
+pub fn decode_value<M: Memory>(ty: Type, bytes: &[Option<u8>]) -> Option<Value<M>> {
+    let size = ty.layout::<M::T>().expect_size("only sized types have values");
+    assert_eq!(Int::from(bytes.len()), size.bytes(), "wrong number of bytes for this type");
+    let bytes = bytes
+        .iter()
+        .map(|byte| {
+            match byte {
+                Some(data) => AbstractByte::Init(*data, None),
+                None => AbstractByte::Uninit,
+            }
+        })
+        .collect();
+    ty.decode::<M>(bytes)
+}
+
+/// A constant expression for the value `val` of type `ty`.
+/// Panics for pointers and unions, as they have no constant expressions.
+#[track_caller]
+pub fn const_value<M: Memory>(ty: Type, val: Value<M>) -> ValueExpr {
+    match (ty, val) {
+        (Type::Int(_), Value::Int(int)) => ValueExpr::Constant(Constant::Int(int), ty),
+        (Type::Bool, Value::Bool(b)) => const_bool(b),
+        (Type::Tuple { sized_fields, .. }, Value::Tuple(vals)) => {
+            assert_eq!(sized_fields.len(), vals.len(), "wrong number of fields for this type");
+            let fields: Vec<ValueExpr> = sized_fields
+                .iter()
+                .zip(vals.iter())
+                .map(|((_, field_ty), val)| const_value(field_ty, val))
+                .collect();
+            tuple(&fields, ty)
+        }
+        (Type::Array { elem, .. }, Value::Tuple(vals)) => {
+            let elem = elem.extract();
+            let elems: Vec<ValueExpr> = vals.iter().map(|val| const_value(elem, val)).collect();
+            array(&elems, elem)
+        }
+        (Type::Enum { variants, .. }, Value::Variant { discriminant, data }) => {
+            let Some(variant_data) = variants.get(discriminant) else {
+                panic!("the enum has no variant with discriminant {discriminant}");
+            };
+            variant(discriminant, const_value(variant_data.ty, data.extract()), ty)
+        }
+        _ => panic!("cannot build a constant expression for this value and type"),
+    }
+}
@@ -1,5 +1,7 @@
 use super::*;
 
+use crate::analysis::place_type;
+
 // A formatted expression.
 // This type is used to insert the minimal required amount of parens to make expressions unambiguous, without implementing an operator priority.
 pub(super) enum FmtExpr {
@@ -33,28 +35,31 @@ impl FmtExpr {
     }
 }
 
-pub(super) fn fmt_place_expr(p: PlaceExpr, comptypes: &mut Vec<CompType>) -> FmtExpr {
+pub(super) fn fmt_place_expr(p: PlaceExpr, comptypes: &mut Vec<CompType>, names: Names) -> FmtExpr {
     match p {
-        PlaceExpr::Local(l) => FmtExpr::Atomic(fmt_local_name(l)),
+        PlaceExpr::Local(l) => FmtExpr::Atomic(fmt_local_name(l, names)),
         PlaceExpr::Deref { operand, ty } => {
             let ptype = fmt_type(ty, comptypes).to_string();
-            let expr = fmt_value_expr(operand.extract(), comptypes).to_string();
+            let expr = fmt_value_expr(operand.extract(), comptypes, names).to_string();
             FmtExpr::Atomic(format!("deref<{ptype}>({expr})"))
         }
         PlaceExpr::Field { root, field } => {
-            let root = fmt_place_expr(root.extract(), comptypes).to_atomic_string();
+            let name = place_type(root.extract(), names.locals)
+                .and_then(|ty| names.fields.get(ty, field))
+                .unwrap_or_else(|| field.to_string());
+            let root = fmt_place_expr(root.extract(), comptypes, names).to_atomic_string();
             // `&raw foo.bar` in Rust unambiguously means `&raw (foo.bar)`, and there is
             // no other context we have to worry about. Hence this can be atomic.
-            FmtExpr::Atomic(format!("{root}.{field}"))
+            FmtExpr::Atomic(format!("{root}.{name}"))
         }
         PlaceExpr::Index { root, index } => {
-            let root = fmt_place_expr(root.extract(), comptypes).to_atomic_string();
-            let index = fmt_value_expr(index.extract(), comptypes).to_string();
+            let root = fmt_place_expr(root.extract(), comptypes, names).to_atomic_string();
+            let index = fmt_value_expr(index.extract(), comptypes, names).to_string();
             // This can be considered atomic due to the same reasoning as for PlaceExpr::Field, see above.
             FmtExpr::Atomic(format!("{root}[{index}]"))
         }
         PlaceExpr::Downcast { root, discriminant } => {
-            let root = fmt_place_expr(root.extract(), comptypes).to_atomic_string();
+            let root = fmt_place_expr(root.extract(), comptypes, names).to_atomic_string();
             // This is not atomic as `local(1) as variant 3.0` illustrates. (Field 0 of downcast)
             // We can't do it nicely like in the Rust MIR ({root} as {variant name}) since we have no variant names.
             FmtExpr::NonAtomic(format!("{root} as variant {discriminant}"))
@@ -62,9 +67,22 @@ pub(super) fn fmt_place_expr(p: PlaceExpr, comptypes: &mut Vec<CompType>) -> Fmt
     }
 }
 
-pub(super) fn fmt_local_name(l: LocalName) -> String {
+// What the formatter needs to print names: the names themselves, the function the expression
+// belongs to, and the types of its locals.
+#[derive(Clone, Copy)]
+pub(super) struct Names<'a> {
+    pub(super) fields: &'a FieldNames,
+    pub(super) debug_info: &'a DebugInfo,
+    pub(super) fn_name: FnName,
+    pub(super) locals: Map<LocalName, Type>,
+}
+
+pub(super) fn fmt_local_name(l: LocalName, names: Names) -> String {
     let id = l.0.get_internal();
-    format!("_{id}")
+    match names.debug_info.local(names.fn_name, l) {
+        Some(name) => format!("_{id}_{name}"),
+        None => format!("_{id}"),
+    }
 }
 
 pub(super) fn fmt_global_name(g: GlobalName) -> String {
@@ -72,12 +90,12 @@ pub(super) fn fmt_global_name(g: GlobalName) -> String {
     format!("global({id})")
 }
 
-pub(super) fn fmt_constant(c: Constant) -> FmtExpr {
+pub(super) fn fmt_constant(c: Constant, names: Names) -> FmtExpr {
     match c {
         Constant::Int(int) => FmtExpr::Atomic(int.to_string()),
         Constant::Bool(b) => FmtExpr::Atomic(b.to_string()),
         Constant::GlobalPointer(relocation) => fmt_relocation(relocation),
-        Constant::FnPointer(fn_name) => FmtExpr::Atomic(fmt_fn_name(fn_name)),
+        Constant::FnPointer(fn_name) => FmtExpr::Atomic(fmt_fn_name(fn_name, names.debug_info)),
         Constant::VTablePointer(vt_name) => FmtExpr::Atomic(fmt_vtable_name(vt_name)),
         Constant::PointerWithoutProvenance(addr) =>
             if addr == 0 {
@@ -88,47 +106,48 @@ pub(super) fn fmt_constant(c: Constant) -> FmtExpr {
     }
 }
 
-pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> FmtExpr {
+pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>, names: Names) -> FmtExpr {
     match v {
-        ValueExpr::Constant(c, _ty) => fmt_constant(c),
+        ValueExpr::Constant(c, _ty) => fmt_constant(c, names),
         ValueExpr::Tuple(l, t) => {
             let (lparen, rparen) = match t {
                 Type::Array { .. } => ('[', ']'),
                 Type::Tuple { .. } => ('(', ')'),
                 _ => panic!(),
             };
-            let l: Vec<_> = l.iter().map(|x| fmt_value_expr(x, comptypes).to_string()).collect();
+            let l: Vec<_> =
+                l.iter().map(|x| fmt_value_expr(x, comptypes, names).to_string()).collect();
             let l = l.join(", ");
 
             FmtExpr::Atomic(format!("{lparen}{l}{rparen}"))
         }
         ValueExpr::Union { field, expr, union_ty } => {
             let union_ty = fmt_type(union_ty, comptypes).to_string();
-            let expr = fmt_value_expr(expr.extract(), comptypes).to_string();
+            let expr = fmt_value_expr(expr.extract(), comptypes, names).to_string();
             FmtExpr::NonAtomic(format!("{union_ty} {{ field{field}: {expr} }}"))
         }
         ValueExpr::Variant { discriminant, data, enum_ty } => {
             let enum_ty = fmt_type(enum_ty, comptypes).to_string();
-            let expr = fmt_value_expr(data.extract(), comptypes).to_string();
+            let expr = fmt_value_expr(data.extract(), comptypes, names).to_string();
             FmtExpr::NonAtomic(format!("{enum_ty}(variant {discriminant}): {expr}"))
         }
         ValueExpr::GetDiscriminant { place } => {
-            let place = fmt_place_expr(place.extract(), comptypes).to_string();
+            let place = fmt_place_expr(place.extract(), comptypes, names).to_string();
             FmtExpr::Atomic(format!("discriminant({place})"))
         }
         ValueExpr::Load { source } => {
             let source = source.extract();
-            let source = fmt_place_expr(source, comptypes).to_string();
+            let source = fmt_place_expr(source, comptypes, names).to_string();
             FmtExpr::Atomic(format!("load({source})"))
         }
         ValueExpr::AddrOf { target, ptr_ty: PtrType::Raw { .. } } => {
             let target = target.extract();
-            let target = fmt_place_expr(target, comptypes).to_atomic_string();
+            let target = fmt_place_expr(target, comptypes, names).to_atomic_string();
             FmtExpr::NonAtomic(format!("&raw {target}"))
         }
         ValueExpr::AddrOf { target, ptr_ty: PtrType::Ref { mutbl, .. } } => {
             let target = target.extract();
-            let target = fmt_place_expr(target, comptypes).to_atomic_string();
+            let target = fmt_place_expr(target, comptypes, names).to_atomic_string();
             let mutbl = match mutbl {
                 Mutability::Mutable => "mut ",
                 Mutability::Immutable => "",
@@ -139,7 +158,7 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
             panic!("unsupported ptr_ty for AddrOr!")
         }
         ValueExpr::UnOp { operator, operand } => {
-            let operand = fmt_value_expr(operand.extract(), comptypes).to_string();
+            let operand = fmt_value_expr(operand.extract(), comptypes, names).to_string();
             match operator {
                 UnOp::Int(IntUnOp::Neg) => FmtExpr::NonAtomic(format!("-({operand})")),
                 UnOp::Int(IntUnOp::BitNot) => FmtExpr::NonAtomic(format!("!({operand}")),
@@ -153,6 +172,14 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
                     let new_ty = fmt_type(new_ty, comptypes).to_string();
                     FmtExpr::Atomic(format!("transmute<{new_ty}>({operand})"))
                 }
+                UnOp::Cast(CastOp::PtrToAddr(int_ty)) => {
+                    let int_ty = fmt_int_type(int_ty);
+                    FmtExpr::Atomic(format!("ptr2addr<{int_ty}>({operand})"))
+                }
+                UnOp::Cast(CastOp::IntToPtr(ptr_ty)) => {
+                    let ptr_ty = fmt_type(Type::Ptr(ptr_ty), comptypes).to_string();
+                    FmtExpr::Atomic(format!("int2ptr<{ptr_ty}>({operand})"))
+                }
                 UnOp::GetThinPointer => FmtExpr::Atomic(format!("get_thin_ptr({operand})")),
                 UnOp::GetMetadata => FmtExpr::Atomic(format!("get_metadata({operand})")),
                 UnOp::ComputeSize(ty) => {
@@ -171,8 +198,8 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
             }
         }
         ValueExpr::BinOp { operator: BinOp::Int(int_op), left, right } => {
-            let l = fmt_value_expr(left.extract(), comptypes).to_atomic_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_atomic_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_atomic_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_atomic_string();
 
             use IntBinOp::*;
             let int_op = match int_op {
@@ -197,8 +224,8 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
             FmtExpr::NonAtomic(format!("{l} {int_op} {r}"))
         }
         ValueExpr::BinOp { operator: BinOp::IntWithOverflow(op), left, right } => {
-            let l = fmt_value_expr(left.extract(), comptypes).to_atomic_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_atomic_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_atomic_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_atomic_string();
 
             let name = match op {
                 IntBinOpWithOverflow::Add => "Add",
@@ -218,8 +245,8 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
                 RelOp::Cmp => "<=>",
             };
 
-            let l = fmt_value_expr(left.extract(), comptypes).to_atomic_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_atomic_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_atomic_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_atomic_string();
 
             FmtExpr::NonAtomic(format!("{l} {rel} {r}"))
         }
@@ -228,8 +255,8 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
                 true => "offset_inbounds",
                 false => "offset_wrapping",
             };
-            let l = fmt_value_expr(left.extract(), comptypes).to_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_string();
             FmtExpr::Atomic(format!("{offset_name}({l}, {r})"))
         }
         ValueExpr::BinOp { operator: BinOp::PtrOffsetFrom { inbounds, nonneg }, left, right } => {
@@ -238,13 +265,13 @@ pub(super) fn fmt_value_expr(v: ValueExpr, comptypes: &mut Vec<CompType>) -> Fmt
                 if inbounds { "inbounds" } else { "wrapping" },
                 if nonneg { "_nonneg" } else { "" },
             );
-            let l = fmt_value_expr(left.extract(), comptypes).to_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_string();
             FmtExpr::Atomic(format!("{offset_name}({l}, {r})"))
         }
         ValueExpr::BinOp { operator: BinOp::ConstructWidePointer(ptr_ty), left, right } => {
-            let l = fmt_value_expr(left.extract(), comptypes).to_string();
-            let r = fmt_value_expr(right.extract(), comptypes).to_string();
+            let l = fmt_value_expr(left.extract(), comptypes, names).to_string();
+            let r = fmt_value_expr(right.extract(), comptypes, names).to_string();
             let ptr_ty_str = fmt_ptr_type(ptr_ty).to_string();
             FmtExpr::Atomic(format!("construct_ptr<{ptr_ty_str}>({l}, {r})"))
         }
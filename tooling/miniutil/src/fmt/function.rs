@@ -2,7 +2,13 @@ use super::*;
 
 // Formats all functions found within the program.
 // All composite types that are used within `prog` will be added to `comptypes` exactly once.
-pub(super) fn fmt_functions(prog: Program, comptypes: &mut Vec<CompType>) -> String {
+pub(super) fn fmt_functions(
+    prog: Program,
+    options: FmtOptions,
+    field_names: &FieldNames,
+    debug_info: &DebugInfo,
+    comptypes: &mut Vec<CompType>,
+) -> String {
     let mut fns: Vec<(FnName, Function)> = prog.functions.iter().collect();
 
     // Functions are formatted in the order given by their name.
@@ -11,7 +17,8 @@ pub(super) fn fmt_functions(prog: Program, comptypes: &mut Vec<CompType>) -> Str
     let mut out = String::new();
     for (fn_name, f) in fns {
         let start = prog.start == fn_name;
-        out += &fmt_function(fn_name, f, start, comptypes);
+        let names = Names { fields: field_names, debug_info, fn_name, locals: f.locals };
+        out += &fmt_function(fn_name, f, start, options, names, comptypes);
     }
 
     out
@@ -21,23 +28,27 @@ fn fmt_function(
     fn_name: FnName,
     f: Function,
     start: bool,
+    options: FmtOptions,
+    names: Names,
     comptypes: &mut Vec<CompType>,
 ) -> String {
-    let fn_name = fmt_fn_name(fn_name).to_string();
+    let fn_name = fmt_fn_name(fn_name, names.debug_info);
 
     // Format function arguments
-    let args: Vec<String> = f.args.iter().map(|name| fmt_local_name(name).to_string()).collect();
+    let args: Vec<String> =
+        f.args.iter().map(|name| fmt_local_name(name, names).to_string()).collect();
     let args = args.join(", ");
 
     // Format return local
-    let ret_str = format!("-> {}", fmt_local_name(f.ret));
+    let ret_str = format!("-> {}", fmt_local_name(f.ret, names));
 
     // Format function signature
-    let mut out = if start {
-        format!("start fn {fn_name}({args}) {ret_str} {{\n")
+    let header = if start {
+        format!("start fn {fn_name}({args}) {ret_str}")
     } else {
-        format!("fn {fn_name}({args}) {ret_str} {{\n")
+        format!("fn {fn_name}({args}) {ret_str}")
     };
+    let mut out = format!("{} {{\n", paint(&header, Color::Bold, options));
 
     // Format locals
     let mut locals: Vec<(LocalName, Type)> = f.locals.iter().collect();
@@ -46,7 +57,7 @@ fn fmt_function(
     locals.sort_by_key(|(LocalName(name), _ty)| *name);
 
     for (l, ty) in locals {
-        let local = fmt_local_name(l).to_string();
+        let local = fmt_local_name(l, names).to_string();
         let ty = fmt_type(ty, comptypes).to_string();
         out += &format!("  let {local}: {ty};\n");
     }
@@ -59,63 +70,88 @@ fn fmt_function(
 
     for (bb_name, bb) in blocks {
         let start = f.start == bb_name;
-        out += &fmt_bb(bb_name, bb, start, comptypes);
+        out += &fmt_bb(bb_name, bb, start, options, comptypes, names);
     }
     out += "}\n\n";
 
     out
 }
 
-fn fmt_bb(bb_name: BbName, bb: BasicBlock, start: bool, comptypes: &mut Vec<CompType>) -> String {
-    let name = bb_name.0.get_internal();
-    let block_kind: String = fmt_bb_kind(bb);
+fn fmt_bb(
+    bb_name: BbName,
+    bb: BasicBlock,
+    start: bool,
+    options: FmtOptions,
+    comptypes: &mut Vec<CompType>,
+    names: Names,
+) -> String {
+    let name = fmt_bb_name(bb_name, names);
+    let block_kind: String = fmt_bb_kind(bb, options);
 
-    let mut out = if start {
-        format!("  start bb{name}{block_kind}:\n")
-    } else {
-        format!("  bb{name}{block_kind}:\n")
-    };
+    let header = if start { format!("start {name}") } else { name };
+    let mut out = format!("  {}{block_kind}:\n", paint(&header, Color::Bold, options));
 
     // Format statements
-    for st in bb.statements.iter() {
-        out += &fmt_statement(st, comptypes);
+    for (idx, st) in bb.statements.iter().enumerate() {
+        out += &fmt_comments(bb_name, idx, names);
+        out += &fmt_statement(st, comptypes, names);
         out.push('\n');
     }
-    // Format terminator
-    out += &fmt_terminator(bb.terminator, comptypes);
+    out += &fmt_comments(bb_name, bb.statements.iter().count(), names);
+    // Format terminator, keeping the indentation outside of the colored part.
+    let terminator = fmt_terminator(bb.terminator, comptypes, names);
+    let code = terminator.trim_start();
+    out += &terminator[..terminator.len() - code.len()];
+    out += &paint(code, Color::Magenta, options);
     out.push('\n');
     out
 }
 
-fn fmt_statement(st: Statement, comptypes: &mut Vec<CompType>) -> String {
+// The comments on the statement or terminator at index `idx` of `bb`, one line each.
+fn fmt_comments(bb: BbName, idx: usize, names: Names) -> String {
+    let mut out = String::new();
+    for comment in names.debug_info.comments(names.fn_name, bb, idx) {
+        for line in comment.lines() {
+            out += &format!("    // {line}\n");
+        }
+    }
+    out
+}
+
+pub(super) fn fmt_statement(st: Statement, comptypes: &mut Vec<CompType>, names: Names) -> String {
     match st {
         Statement::Assign { destination, source } => {
-            let left = fmt_place_expr(destination, comptypes).to_string();
-            let right = fmt_value_expr(source, comptypes).to_string();
+            let left = fmt_place_expr(destination, comptypes, names).to_string();
+            let right = fmt_value_expr(source, comptypes, names).to_string();
             format!("    {left} = {right};")
         }
+        Statement::CopyPlace { destination, source } => {
+            let left = fmt_place_expr(destination, comptypes, names).to_string();
+            let right = fmt_place_expr(source, comptypes, names).to_string();
+            format!("    copy_place({left}, {right});")
+        }
         Statement::PlaceMention(place) => {
-            let place = fmt_place_expr(place, comptypes).to_string();
+            let place = fmt_place_expr(place, comptypes, names).to_string();
             format!("    _ = {place};")
         }
         Statement::SetDiscriminant { destination, value } => {
-            let left = fmt_place_expr(destination, comptypes).to_string();
+            let left = fmt_place_expr(destination, comptypes, names).to_string();
             format!("    discriminant({left}) = {value};")
         }
         Statement::Validate { place, fn_entry } => {
-            let place = fmt_place_expr(place, comptypes).to_string();
+            let place = fmt_place_expr(place, comptypes, names).to_string();
             format!("    validate({place}, {fn_entry});")
         }
         Statement::Deinit { place } => {
-            let place = fmt_place_expr(place, comptypes).to_string();
+            let place = fmt_place_expr(place, comptypes, names).to_string();
             format!("    deinit({place});")
         }
         Statement::StorageLive(local) => {
-            let local = fmt_local_name(local).to_string();
+            let local = fmt_local_name(local, names).to_string();
             format!("    storage_live({local});")
         }
         Statement::StorageDead(local) => {
-            let local = fmt_local_name(local).to_string();
+            let local = fmt_local_name(local, names).to_string();
             format!("    storage_dead({local});")
         }
     }
@@ -126,25 +162,22 @@ fn fmt_call(
     callee: &str,
     conv: CallingConvention,
     args: String,
-    ret: PlaceExpr,
+    ret: String,
     next_block: Option<BbName>,
     unwind_block: Option<BbName>,
-    comptypes: &mut Vec<CompType>,
+    names: Names,
 ) -> String {
-    // Format return place
-    let r = fmt_place_expr(ret, comptypes).to_string();
-
     // Format next and unwind block
     let next_str = match next_block {
         Some(next_block) => {
-            let bb_name_str = fmt_bb_name(next_block);
+            let bb_name_str = fmt_bb_name(next_block, names);
             format!("return: {bb_name_str}")
         }
         None => String::new(),
     };
     let unwind_str = match unwind_block {
         Some(unwind_block) => {
-            let bb_name_str = fmt_bb_name(unwind_block);
+            let bb_name_str = fmt_bb_name(unwind_block, names);
             format!("unwind: {bb_name_str}")
         }
         None => String::new(),
@@ -163,25 +196,28 @@ fn fmt_call(
     // Format calling convention
     let conv = match conv {
         CallingConvention::Rust => format!(""),
+        CallingConvention::CUnwind => "extern \"C-unwind\"".to_string(),
         c => format!("extern \"{c:?}\""),
     };
 
-    format!("    {r} = {conv}{callee}({args}){next};")
+    format!("    {ret} = {conv}{callee}({args}){next};")
 }
 
-fn fmt_terminator(t: Terminator, comptypes: &mut Vec<CompType>) -> String {
+pub(super) fn fmt_terminator(t: Terminator, comptypes: &mut Vec<CompType>, names: Names) -> String {
     match t {
         Terminator::Goto(bb) => {
-            let bb = fmt_bb_name(bb);
+            let bb = fmt_bb_name(bb, names);
             format!("    goto -> {bb};")
         }
         Terminator::Switch { value, cases, fallback } => {
-            let branch_expr = fmt_value_expr(value, comptypes).to_string();
+            let branch_expr = fmt_value_expr(value, comptypes, names).to_string();
             let mut case_strs = cases
                 .iter()
-                .map(|(constant, successor)| format!("{}: {}", constant, fmt_bb_name(successor)))
+                .map(|(constant, successor)| {
+                    format!("{}: {}", constant, fmt_bb_name(successor, names))
+                })
                 .collect::<Vec<String>>();
-            case_strs.push(format!("otherwise: {}", fmt_bb_name(fallback)));
+            case_strs.push(format!("otherwise: {}", fmt_bb_name(fallback, names)));
             let cases_fmt = case_strs.join(",\n      ");
             format!(
                 "    switch({branch_expr}) -> [
@@ -200,46 +236,59 @@ fn fmt_terminator(t: Terminator, comptypes: &mut Vec<CompType>) -> String {
             next_block,
             unwind_block,
         } => {
-            let callee = fmt_value_expr(callee, comptypes).to_atomic_string();
+            let callee = fmt_value_expr(callee, comptypes, names).to_atomic_string();
             let args: Vec<_> = arguments
                 .iter()
                 .map(|arg| {
                     match arg {
                         ArgumentExpr::ByValue(value) => {
-                            format!("by-value({})", fmt_value_expr(value, comptypes).to_string())
+                            format!(
+                                "by-value({})",
+                                fmt_value_expr(value, comptypes, names).to_string()
+                            )
                         }
                         ArgumentExpr::InPlace(place) => {
-                            format!("in-place({})", fmt_place_expr(place, comptypes).to_string())
+                            format!(
+                                "in-place({})",
+                                fmt_place_expr(place, comptypes, names).to_string()
+                            )
                         }
                     }
                 })
                 .collect();
-            fmt_call(&callee, conv, args.join(", "), ret, next_block, unwind_block, comptypes)
+            let ret = fmt_place_expr(ret, comptypes, names).to_string();
+            fmt_call(&callee, conv, args.join(", "), ret, next_block, unwind_block, names)
         }
         Terminator::Return => {
             format!("    return;")
         }
         Terminator::StartUnwind { unwind_payload, unwind_block } => {
-            let bb_name = fmt_bb_name(unwind_block);
-            let unwind_payload = fmt_value_expr(unwind_payload, comptypes).to_string();
-            format!("    start unwind({unwind_payload}) -> unwind: {bb_name} ")
+            let bb_name = fmt_bb_name(unwind_block, names);
+            let unwind_payload = fmt_value_expr(unwind_payload, comptypes, names).to_string();
+            format!("    start unwind({unwind_payload}) -> unwind: {bb_name};")
         }
         Terminator::StopUnwind(block_name) => {
-            let bb_name = fmt_bb_name(block_name);
-            format!("    start unwind -> unwind: {bb_name} ")
+            let bb_name = fmt_bb_name(block_name, names);
+            format!("    stop unwind -> {bb_name};")
         }
         Terminator::ResumeUnwind => {
-            format!("    resume")
+            "    resume;".to_string()
+        }
+        Terminator::Terminate => {
+            "    terminate;".to_string()
         }
         Terminator::Intrinsic { intrinsic, arguments, ret, next_block } => {
             let callee = match intrinsic {
                 IntrinsicOp::Abort => "abort",
                 IntrinsicOp::Assume => "assume",
+                IntrinsicOp::AssumeInRange => "assume_in_range",
+                IntrinsicOp::UnreachableUnchecked => "unreachable_unchecked",
                 IntrinsicOp::Exit => "exit",
                 IntrinsicOp::PrintStdout => "print",
                 IntrinsicOp::PrintStderr => "eprint",
                 IntrinsicOp::Allocate => "allocate",
                 IntrinsicOp::Deallocate => "deallocate",
+                IntrinsicOp::StackAllocate => "stack_allocate",
                 IntrinsicOp::Spawn => "spawn",
                 IntrinsicOp::Join => "join",
                 IntrinsicOp::RawEq => "raw_eq",
@@ -253,18 +302,18 @@ fn fmt_terminator(t: Terminator, comptypes: &mut Vec<CompType>) -> String {
                 IntrinsicOp::PointerExposeProvenance => "pointer_expose_provenance",
                 IntrinsicOp::PointerWithExposedProvenance => "pointer_with_exposed_provenance",
                 IntrinsicOp::GetUnwindPayload => "get_unwind_payload",
+                IntrinsicOp::PtrGuaranteedCmp => "ptr_guaranteed_cmp",
+                IntrinsicOp::IsDereferenceable => "is_dereferenceable",
+                IntrinsicOp::AllocationSize => "allocation_size",
+                IntrinsicOp::InitMask => "init_mask",
+                IntrinsicOp::PrintProvenanceHistory => "print_provenance_history",
             };
-            let args: Vec<_> =
-                arguments.iter().map(|arg| fmt_value_expr(arg, comptypes).to_string()).collect();
-            fmt_call(
-                callee,
-                CallingConvention::Rust,
-                args.join(", "),
-                ret,
-                next_block,
-                None,
-                comptypes,
-            )
+            let args: Vec<_> = arguments
+                .iter()
+                .map(|arg| fmt_value_expr(arg, comptypes, names).to_string())
+                .collect();
+            let ret = fmt_place_expr(ret, comptypes, names).to_string();
+            fmt_call(callee, CallingConvention::Rust, args.join(", "), ret, next_block, None, names)
         }
     }
 }
@@ -278,21 +327,30 @@ fn fmt_fetch(binop: IntBinOp) -> &'static str {
     }
 }
 
-fn fmt_bb_name(bb: BbName) -> String {
+fn fmt_bb_name(bb: BbName, names: Names) -> String {
     let id = bb.0.get_internal();
-    format!("bb{id}")
+    match names.debug_info.block(names.fn_name, bb) {
+        Some(name) => format!("bb{id}_{name}"),
+        None => format!("bb{id}"),
+    }
 }
 
-fn fmt_bb_kind(bb: BasicBlock) -> String {
-    match bb.kind {
-        BbKind::Regular => "".to_string(),
-        BbKind::Cleanup => " (Cleanup)".to_string(),
-        BbKind::Catch => " (Catch)".to_string(),
-        BbKind::Terminate => " (Terminate)".to_string(),
-    }
+// The badge showing the kind of a block. Without colors, regular blocks have none.
+fn fmt_bb_kind(bb: BasicBlock, options: FmtOptions) -> String {
+    let (kind, color) = match bb.kind {
+        BbKind::Regular if !options.color => return String::new(),
+        BbKind::Regular => ("Regular", Color::Green),
+        BbKind::Cleanup => ("Cleanup", Color::Yellow),
+        BbKind::Catch => ("Catch", Color::Cyan),
+        BbKind::Terminate => ("Terminate", Color::Red),
+    };
+    format!(" {}", paint(&format!("({kind})"), color, options))
 }
 
-pub(super) fn fmt_fn_name(fn_name: FnName) -> String {
+pub(super) fn fmt_fn_name(fn_name: FnName, debug_info: &DebugInfo) -> String {
     let id = fn_name.0.get_internal();
-    format!("f{id}")
+    match debug_info.function(fn_name) {
+        Some(name) => format!("f{id}_{name}"),
+        None => format!("f{id}"),
+    }
 }
@@ -17,19 +17,219 @@ use vtables::*;
 
 // Print a program to stdout.
 pub fn dump_program(prog: Program) {
+    dump_program_to(prog, &mut std::io::stdout()).unwrap();
+}
+
+// Print a program to the given writer.
+pub fn dump_program_to(prog: Program, out: &mut impl std::io::Write) -> std::io::Result<()> {
     let s = fmt_program(prog);
-    println!("{s}");
+    writeln!(out, "{s}")
+}
+
+// Options for formatting a program.
+// The default produces plain text, which is what golden files should contain.
+#[derive(Clone, Copy, Default)]
+pub struct FmtOptions {
+    // Highlight function and block headers and terminators with ANSI colors,
+    // and give every block a badge showing its kind.
+    pub color: bool,
 }
 
 // Format a program into a string.
 pub fn fmt_program(prog: Program) -> String {
+    fmt_program_with(prog, FmtOptions::default())
+}
+
+// Format a program into a string using the given options.
+pub fn fmt_program_with(prog: Program, options: FmtOptions) -> String {
+    fmt_program_with_field_names(prog, options, &FieldNames::default())
+}
+
+// Format a program into a string, printing field projections with the given names.
+pub fn fmt_program_with_field_names(
+    prog: Program,
+    options: FmtOptions,
+    field_names: &FieldNames,
+) -> String {
+    fmt_program_with_debug_info(prog, options, field_names, &DebugInfo::default())
+}
+
+// Format a program into a string, printing field projections, functions, locals and blocks with the given names,
+// and the comments of `debug_info` above the statements and terminators they belong to.
+pub fn fmt_program_with_debug_info(
+    prog: Program,
+    options: FmtOptions,
+    field_names: &FieldNames,
+    debug_info: &DebugInfo,
+) -> String {
     let mut comptypes: Vec<CompType> = Vec::new();
 
-    let functions_string = fmt_functions(prog, &mut comptypes);
+    let functions_string = fmt_functions(prog, options, field_names, debug_info, &mut comptypes);
     let comptypes_string = fmt_comptypes(comptypes);
     let globals_string = fmt_globals(prog.globals);
     let traits_string = fmt_traits(prog.traits);
-    let vtables_string = fmt_vtables(prog.vtables);
+    let vtables_string = fmt_vtables(prog.vtables, debug_info);
 
     comptypes_string + &traits_string + &vtables_string + &functions_string + &globals_string
 }
+
+// Format every statement and terminator of the program on its own, together with its location:
+// the function, the block, and the index in the block (the terminator comes after the last statement).
+// Also returns the definitions of the composite types they refer to.
+pub fn fmt_instructions(prog: Program) -> (Vec<(FnName, BbName, usize, String)>, String) {
+    let mut comptypes: Vec<CompType> = Vec::new();
+    let mut fns: Vec<(FnName, Function)> = prog.functions.iter().collect();
+    fns.sort_by_key(|(FnName(name), _fn)| *name);
+
+    let mut out = Vec::new();
+    let field_names = FieldNames::default();
+    let debug_info = DebugInfo::default();
+    for (fn_name, f) in fns {
+        let names =
+            Names { fields: &field_names, debug_info: &debug_info, fn_name, locals: f.locals };
+        let mut blocks: Vec<(BbName, BasicBlock)> = f.blocks.iter().collect();
+        blocks.sort_by_key(|(BbName(name), _block)| *name);
+        for (bb_name, bb) in blocks {
+            let mut lines: Vec<String> =
+                bb.statements.iter().map(|st| fmt_statement(st, &mut comptypes, names)).collect();
+            lines.push(fmt_terminator(bb.terminator, &mut comptypes, names));
+            for (idx, s) in lines.into_iter().enumerate() {
+                out.push((fn_name, bb_name, idx, s.trim().to_string()));
+            }
+        }
+    }
+
+    (out, fmt_comptypes(comptypes))
+}
+
+// Names for the fields of struct types, so that field projections can be printed
+// as `_1.name` instead of `_1.0`. See `ProgramBuilder::declare_struct`.
+// There is one entry per declared struct. Types are structural, so different structs with the same
+// layout have the same type; their fields are only printed by name where all of them agree on the name.
+#[derive(Clone, Default)]
+pub struct FieldNames {
+    structs: Vec<(Type, Vec<String>)>,
+}
+
+impl FieldNames {
+    // Adds the names of the fields of a newly declared struct of type `ty`.
+    pub fn insert(&mut self, ty: Type, names: Vec<String>) {
+        self.structs.push((ty, names));
+    }
+
+    // Adds all the names from `other`.
+    pub fn extend(&mut self, other: &FieldNames) {
+        self.structs.extend(other.structs.iter().cloned());
+    }
+
+    // The name of field `field` of `ty`, if all structs declared with this type give it the same name.
+    pub fn get(&self, ty: Type, field: Int) -> Option<String> {
+        let field = field.try_to_usize()?;
+        let mut names =
+            self.structs.iter().filter(|(t, _names)| *t == ty).map(|(_ty, names)| names.get(field));
+        let name = names.next()??;
+        names.all(|other| other == Some(name)).then(|| name.clone())
+    }
+}
+
+// What the builder knows about a program beyond the program itself, for printing it:
+// - names for functions, see `ProgramBuilder::declare_function_named`, and for the locals and blocks
+//   of functions, see `FunctionBuilder::declare_local_named` and `FunctionBuilder::declare_block_named`.
+//   The names are printed after the number, like `f1_helper`, `_3_count` and `bb2_loop_head`,
+//   so that they stay unique.
+// - comments on statements and terminators, see `FunctionBuilder::comment`.
+//   They are located like in `fmt_instructions`: by function, block, and index in the block.
+#[derive(Clone, Default)]
+pub struct DebugInfo {
+    functions: Vec<(FnName, String)>,
+    locals: Vec<(FnName, LocalName, String)>,
+    blocks: Vec<(FnName, BbName, String)>,
+    comments: Vec<(FnName, BbName, usize, String)>,
+}
+
+impl DebugInfo {
+    pub fn insert_function(&mut self, f: FnName, name: &str) {
+        self.functions.push((f, name.to_string()));
+    }
+
+    pub fn insert_local(&mut self, f: FnName, local: LocalName, name: &str) {
+        self.locals.push((f, local, name.to_string()));
+    }
+
+    pub fn insert_block(&mut self, f: FnName, bb: BbName, name: &str) {
+        self.blocks.push((f, bb, name.to_string()));
+    }
+
+    pub fn insert_comment(&mut self, f: FnName, bb: BbName, idx: usize, comment: &str) {
+        self.comments.push((f, bb, idx, comment.to_string()));
+    }
+
+    // Adds everything from `other`, with its functions renamed by `rename`.
+    pub fn extend_renamed(&mut self, other: &DebugInfo, rename: impl Fn(FnName) -> FnName) {
+        for (f, name) in &other.functions {
+            self.functions.push((rename(*f), name.clone()));
+        }
+        for (f, local, name) in &other.locals {
+            self.locals.push((rename(*f), *local, name.clone()));
+        }
+        for (f, bb, name) in &other.blocks {
+            self.blocks.push((rename(*f), *bb, name.clone()));
+        }
+        for (f, bb, idx, comment) in &other.comments {
+            self.comments.push((rename(*f), *bb, *idx, comment.clone()));
+        }
+    }
+
+    // The name of function `f`, if there is one.
+    pub fn function(&self, f: FnName) -> Option<&str> {
+        let (_f, name) = self.functions.iter().find(|(g, _name)| *g == f)?;
+        Some(name)
+    }
+
+    // The name of local `local` of function `f`, if there is one.
+    pub fn local(&self, f: FnName, local: LocalName) -> Option<&str> {
+        let (_f, _local, name) = self.locals.iter().find(|(g, l, _name)| *g == f && *l == local)?;
+        Some(name)
+    }
+
+    // The name of block `bb` of function `f`, if there is one.
+    pub fn block(&self, f: FnName, bb: BbName) -> Option<&str> {
+        let (_f, _bb, name) = self.blocks.iter().find(|(g, b, _name)| *g == f && *b == bb)?;
+        Some(name)
+    }
+
+    // The comments on the statement or terminator at index `idx` of block `bb` of function `f`,
+    // in the order they were made.
+    pub fn comments(&self, f: FnName, bb: BbName, idx: usize) -> impl Iterator<Item = &str> + '_ {
+        self.comments
+            .iter()
+            .filter(move |(g, b, i, _comment)| *g == f && *b == bb && *i == idx)
+            .map(|(_f, _bb, _idx, comment)| comment.as_str())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Color {
+    Bold,
+    Red,
+    Green,
+    Yellow,
+    Magenta,
+    Cyan,
+}
+
+// Wrap `s` in the ANSI escape codes for `color`, if colors are enabled.
+pub(super) fn paint(s: &str, color: Color, options: FmtOptions) -> String {
+    if !options.color {
+        return s.to_string();
+    }
+    let code = match color {
+        Color::Bold => "1",
+        Color::Red => "31",
+        Color::Green => "32",
+        Color::Yellow => "33",
+        Color::Magenta => "35",
+        Color::Cyan => "36",
+    };
+    format!("\x1b[{code}m{s}\x1b[0m")
+}
@@ -1,6 +1,6 @@
 use super::*;
 
-pub(super) fn fmt_vtables(vtables: Map<VTableName, VTable>) -> String {
+pub(super) fn fmt_vtables(vtables: Map<VTableName, VTable>, debug_info: &DebugInfo) -> String {
     let mut out = String::new();
 
     let mut vtables: Vec<(VTableName, VTable)> = vtables.iter().collect();
@@ -9,7 +9,7 @@ pub(super) fn fmt_vtables(vtables: Map<VTableName, VTable>) -> String {
     vtables.sort_by_key(|(VTableName(name), _)| *name);
 
     for (vname, vtable) in vtables {
-        out += &fmt_vtable(vname, vtable);
+        out += &fmt_vtable(vname, vtable, debug_info);
     }
     out += "\n";
     out
@@ -19,7 +19,7 @@ pub fn fmt_vtable_name(vname: VTableName) -> String {
     format!("vtable{id}", id = vname.0.get_internal())
 }
 
-fn fmt_vtable(vname: VTableName, vtable: VTable) -> String {
+fn fmt_vtable(vname: VTableName, vtable: VTable, debug_info: &DebugInfo) -> String {
     let mut out = fmt_vtable_name(vname);
     out += " {\n";
 
@@ -31,7 +31,7 @@ fn fmt_vtable(vname: VTableName, vtable: VTable) -> String {
         out += &format!(
             "  {meth}() = {f},\n",
             meth = fmt_trait_method_name(meth),
-            f = fmt_fn_name(impel)
+            f = fmt_fn_name(impel, debug_info)
         );
     }
 
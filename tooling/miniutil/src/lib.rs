@@ -19,11 +19,31 @@ pub use std::marker::Freeze;
 pub use std::result::Result;
 pub use std::string::String;
 
+pub mod analysis;
 pub mod build;
 pub mod fmt;
 pub mod mock_write;
+pub mod promela;
+pub mod report;
 pub mod run;
+pub mod stdlib;
+pub mod symbolic;
+pub mod transform;
 
 pub type DefaultTarget = x86_64;
 pub type BasicMem = BasicMemory<DefaultTarget>;
 pub type TreeBorrowMem = TreeBorrowsMemory<DefaultTarget>;
+
+/// A memory model that can be instantiated for any target.
+/// This lets the same program be run against every target, using e.g. `<BasicMem as Retarget>::On<arm>`.
+pub trait Retarget: Memory {
+    type On<U: Target>: Memory<T = U>;
+}
+
+impl<T: Target> Retarget for BasicMemory<T> {
+    type On<U: Target> = BasicMemory<U>;
+}
+
+impl<T: Target> Retarget for TreeBorrowsMemory<T> {
+    type On<U: Target> = TreeBorrowsMemory<U>;
+}
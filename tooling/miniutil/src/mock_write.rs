@@ -41,6 +41,25 @@ impl GcCompat for MockWrite {
     fn points_to(&self, _buffer: &mut std::collections::HashSet<usize>) {}
 }
 
+/// A GcWrite object that discards everything written to it.
+#[derive(Default, Clone, Copy)]
+pub struct NullWrite;
+
+impl Write for NullWrite {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Nothing within has anything to do with specr-lang. This points to nothing.
+impl GcCompat for NullWrite {
+    fn points_to(&self, _buffer: &mut std::collections::HashSet<usize>) {}
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,376 @@
+//! Export of concurrent programs to Promela, so that they can be model checked with SPIN.
+//! This lets us cross-validate what MiniRust says about small concurrency tests
+//! (deadlocks, lock misuse, failing assertions) against an independent tool.
+//!
+//! Only a small subset of MiniRust is supported:
+//! - locals and globals of integer and Boolean type, with the usual arithmetic and comparison operators,
+//! - atomic loads, stores, compare-exchange and fetch-and-op on globals,
+//! - locks, `spawn` and `join`,
+//! - calls of functions by name, with by-value arguments and no return value,
+//! - `print`, `assume` (also on ranges), `abort`, `exit` and `unreachable`.
+//!
+//! Every function becomes a `proctype`; calls run the callee as a process and wait for it to finish.
+//! The translation is not exact in a few ways:
+//! - Promela integers are 32 bit wide and arithmetic does not wrap at the MiniRust type's size.
+//! - Data races are not reported; non-atomic accesses to globals just become plain accesses.
+//! - `exit` only stops the current process. It sets the global `exited`, which properties can check.
+//! - Thread ids are Promela process ids, which are also used up by function calls.
+//!
+//! UB that the model can detect (failing `assume`, `unreachable`, releasing an unlocked lock) and `abort`
+//! are translated to failing assertions.
+
+use crate::build::{TypeConv, unit_place};
+use crate::*;
+
+/// The number of entries of the arrays used to track finished processes and locks.
+const MAX_ENTRIES: u32 = 255;
+
+/// Translate `prog` to Promela. Fails if the program uses anything outside the supported subset.
+pub fn to_promela(prog: Program) -> Result<String, String> {
+    let mut out = String::new();
+    out += "/* Generated from a MiniRust program. */\n";
+    out += &format!("bool done[{MAX_ENTRIES}];\n");
+    out += "bool exited;\n";
+    out += &format!("bool locked[{MAX_ENTRIES}];\n");
+    out += "byte nlocks;\n";
+
+    let mut globals: Vec<_> = prog.globals.iter().collect();
+    globals.sort_by_key(|(GlobalName(name), _global)| name.get_internal());
+    for (name, global) in globals {
+        let value = global_value(global)
+            .ok_or_else(|| format!("{} is not an integer global", fmt_global(name)))?;
+        out += &format!("int {} = {value};\n", fmt_global(name));
+    }
+
+    let mut functions: Vec<_> = prog.functions.iter().collect();
+    functions.sort_by_key(|(FnName(name), _f)| name.get_internal());
+    for (name, f) in functions {
+        out += "\n";
+        out += &Translator { prog, name, f }.proctype()?;
+    }
+    Ok(out)
+}
+
+/// The initial value of an integer global, read as an unsigned little-endian integer.
+fn global_value(global: Global) -> Option<Int> {
+    if !global.relocations.is_empty() {
+        return None;
+    }
+    let mut value = Int::from(0);
+    for byte in global.bytes.iter().collect::<Vec<_>>().into_iter().rev() {
+        value = value * Int::from(256) + Int::from(byte?);
+    }
+    fits_int(value).then_some(value)
+}
+
+fn fits_int(value: Int) -> bool {
+    Int::from(i32::MIN) <= value && value <= Int::from(i32::MAX)
+}
+
+fn fmt_global(name: GlobalName) -> String {
+    format!("g{}", name.0.get_internal())
+}
+
+fn fmt_fn(name: FnName) -> String {
+    format!("f{}", name.0.get_internal())
+}
+
+fn fmt_local(name: LocalName) -> String {
+    format!("_{}", name.0.get_internal())
+}
+
+fn fmt_bb(name: BbName) -> String {
+    format!("bb{}", name.0.get_internal())
+}
+
+struct Translator {
+    prog: Program,
+    name: FnName,
+    f: Function,
+}
+
+impl Translator {
+    fn error(&self, what: &str) -> String {
+        format!("{what} in {} is not supported", fmt_fn(self.name))
+    }
+
+    fn proctype(&self) -> Result<String, String> {
+        let mut locals: Vec<_> = self.f.locals.iter().collect();
+        locals.sort_by_key(|(LocalName(name), _ty)| name.get_internal());
+        let decl = |(local, ty): (LocalName, Type)| {
+            promela_type(ty).map(|ty| format!("{ty} {}", fmt_local(local)))
+        };
+
+        // Arguments of types the model does not represent, like the data pointers that
+        // spawned functions often take, are dropped; the others are passed as parameters.
+        let params: Vec<String> = self
+            .f
+            .args
+            .iter()
+            .filter_map(|arg| decl((arg, self.f.locals.get(arg).unwrap())))
+            .collect();
+        let start = if self.prog.start == self.name { "active " } else { "" };
+        let mut out = format!("{start}proctype {}({}) {{\n", fmt_fn(self.name), params.join("; "));
+        out += "  pid child;\n";
+        for (local, ty) in locals {
+            if self.f.args.iter().any(|arg| arg == local) {
+                continue;
+            }
+            if let Some(decl) = decl((local, ty)) {
+                out += &format!("  {decl};\n");
+            }
+        }
+        out += &format!("  goto {};\n", fmt_bb(self.f.start));
+
+        let mut blocks: Vec<_> = self.f.blocks.iter().collect();
+        blocks.sort_by_key(|(BbName(name), _bb)| name.get_internal());
+        for (name, bb) in blocks {
+            out += &format!("{}: skip;\n", fmt_bb(name));
+            for st in bb.statements.iter() {
+                if let Some(st) = self.statement(st)? {
+                    out += &format!("  {st};\n");
+                }
+            }
+            out += &format!("  {};\n", self.terminator(bb.terminator)?);
+        }
+        out += "finish: done[_pid] = true\n}\n";
+        Ok(out)
+    }
+
+    fn statement(&self, st: Statement) -> Result<Option<String>, String> {
+        Ok(match st {
+            Statement::Assign { destination, source } =>
+                Some(format!("{} = {}", self.place(destination)?, self.value(source)?)),
+            // Locals are just variables in the model.
+            Statement::StorageLive(_) | Statement::StorageDead(_) => None,
+            _ => return Err(self.error("this statement")),
+        })
+    }
+
+    fn terminator(&self, term: Terminator) -> Result<String, String> {
+        let goto = |next: Option<BbName>| {
+            match next {
+                Some(next) => format!("goto {}", fmt_bb(next)),
+                // Returning from the intrinsic is UB.
+                None => "assert(false)".to_string(),
+            }
+        };
+        Ok(match term {
+            Terminator::Goto(bb) => format!("goto {}", fmt_bb(bb)),
+            Terminator::Switch { value, cases, fallback } => {
+                let value = self.value(value)?;
+                let mut cases: Vec<_> = cases.iter().collect();
+                cases.sort_by_key(|(case, _bb)| *case);
+                let mut out = String::from("if\n");
+                for (case, bb) in cases {
+                    out += &format!("  :: ({value} == {case}) -> goto {}\n", fmt_bb(bb));
+                }
+                out += &format!("  :: else -> goto {}\n  fi", fmt_bb(fallback));
+                out
+            }
+            Terminator::Unreachable => "assert(false)".to_string(),
+            Terminator::Return => "goto finish".to_string(),
+            Terminator::Call { callee, arguments, next_block, .. } => {
+                let ValueExpr::Constant(Constant::FnPointer(callee), _) = callee else {
+                    return Err(self.error("calling a function pointer"));
+                };
+                let callee_fn = self.prog.functions.get(callee).unwrap();
+                if callee_fn.locals.get(callee_fn.ret).unwrap() != <()>::get_type() {
+                    return Err(self.error("calling a function with a return value"));
+                }
+                let mut args = Vec::new();
+                for arg in arguments.iter() {
+                    let ArgumentExpr::ByValue(arg) = arg else {
+                        return Err(self.error("passing an argument in-place"));
+                    };
+                    args.push(self.value(arg)?);
+                }
+                format!(
+                    "atomic {{ child = run {}({}); done[child] = false }};\n  (done[child]);\n  {}",
+                    fmt_fn(callee),
+                    args.join(", "),
+                    goto(next_block)
+                )
+            }
+            Terminator::Intrinsic { intrinsic, arguments, ret, next_block } => {
+                let args: Vec<ValueExpr> = arguments.iter().collect();
+                let op = self.intrinsic(intrinsic, &args, ret)?;
+                format!("{op};\n  {}", goto(next_block))
+            }
+            Terminator::StartUnwind { .. }
+            | Terminator::StopUnwind(_)
+            | Terminator::ResumeUnwind
+            | Terminator::Terminate => return Err(self.error("unwinding")),
+        })
+    }
+
+    fn intrinsic(
+        &self,
+        intrinsic: IntrinsicOp,
+        args: &[ValueExpr],
+        ret: PlaceExpr,
+    ) -> Result<String, String> {
+        let arg = |idx: usize| {
+            let Some(arg) = args.get(idx) else {
+                return Err(self.error("an intrinsic with missing arguments"));
+            };
+            self.value(*arg)
+        };
+        let global_arg = |idx: usize| {
+            let Some(arg) = args.get(idx) else {
+                return Err(self.error("an intrinsic with missing arguments"));
+            };
+            self.global(*arg)
+        };
+        Ok(match intrinsic {
+            IntrinsicOp::Abort | IntrinsicOp::UnreachableUnchecked => "assert(false)".to_string(),
+            IntrinsicOp::Assume => format!("assert({})", arg(0)?),
+            IntrinsicOp::AssumeInRange => {
+                let val = arg(0)?;
+                format!("assert({} <= {val} && {val} <= {})", arg(1)?, arg(2)?)
+            }
+            IntrinsicOp::Exit => "exited = true;\n  goto finish".to_string(),
+            IntrinsicOp::PrintStdout | IntrinsicOp::PrintStderr =>
+                format!("printf(\"%d\\n\", {})", arg(0)?),
+            IntrinsicOp::Spawn => {
+                let Some(ValueExpr::Constant(Constant::FnPointer(callee), _)) = args.first() else {
+                    return Err(self.error("spawning a function pointer"));
+                };
+                // Pass the argument if the spawned function has a parameter for it.
+                let param_ty = self
+                    .prog
+                    .functions
+                    .get(*callee)
+                    .and_then(|f| f.args.iter().next().and_then(|param| f.locals.get(param)));
+                let run_arg =
+                    if param_ty.and_then(promela_type).is_some() { arg(1)? } else { String::new() };
+                format!(
+                    "atomic {{ child = run {}({run_arg}); done[child] = false }};\n  {} = child",
+                    fmt_fn(*callee),
+                    self.place(ret)?
+                )
+            }
+            IntrinsicOp::Join => format!("(done[{}])", arg(0)?),
+            IntrinsicOp::AtomicStore => format!("{} = {}", global_arg(0)?, arg(1)?),
+            IntrinsicOp::AtomicLoad => format!("{} = {}", self.place(ret)?, global_arg(0)?),
+            IntrinsicOp::AtomicCompareExchange => {
+                let (ret, global) = (self.place(ret)?, global_arg(0)?);
+                format!(
+                    "atomic {{\n    {ret} = {global};\n    if\n    :: ({ret} == {}) -> {global} = {}\n    :: else -> skip\n    fi\n  }}",
+                    arg(1)?,
+                    arg(2)?
+                )
+            }
+            IntrinsicOp::AtomicFetchAndOp(op) => {
+                let (ret, global) = (self.place(ret)?, global_arg(0)?);
+                format!(
+                    "atomic {{ {ret} = {global}; {global} = {global} {} {} }}",
+                    self.int_bin_op(op)?,
+                    arg(1)?
+                )
+            }
+            IntrinsicOp::Lock(IntrinsicLockOp::Create) =>
+                format!("atomic {{ {} = nlocks; nlocks++ }}", self.place(ret)?),
+            IntrinsicOp::Lock(IntrinsicLockOp::Acquire) => {
+                let lock = arg(0)?;
+                // Locks never get poisoned since unwinding is not supported.
+                let poisoned = if ret == unit_place() {
+                    String::new()
+                } else {
+                    format!("; {} = false", self.place(ret)?)
+                };
+                format!("atomic {{ !locked[{lock}] -> locked[{lock}] = true{poisoned} }}")
+            }
+            IntrinsicOp::Lock(IntrinsicLockOp::Release) => {
+                let lock = arg(0)?;
+                format!("atomic {{ assert(locked[{lock}]); locked[{lock}] = false }}")
+            }
+            _ => return Err(self.error("this intrinsic")),
+        })
+    }
+
+    /// The variable for a pointer to a global, which must point to its beginning.
+    fn global(&self, ptr: ValueExpr) -> Result<String, String> {
+        match ptr {
+            ValueExpr::Constant(Constant::GlobalPointer(relocation), _)
+                if relocation.offset == Size::ZERO =>
+                Ok(fmt_global(relocation.name)),
+            // `&*ptr` for a pointer to a global.
+            ValueExpr::AddrOf { target, .. } =>
+                match target.extract() {
+                    PlaceExpr::Deref { operand, .. } => self.global(operand.extract()),
+                    _ => Err(self.error("a pointer that does not point to a global")),
+                },
+            _ => Err(self.error("a pointer that does not point to a global")),
+        }
+    }
+
+    fn place(&self, place: PlaceExpr) -> Result<String, String> {
+        match place {
+            PlaceExpr::Local(local)
+                if promela_type(self.f.locals.get(local).unwrap()).is_some() =>
+                Ok(fmt_local(local)),
+            PlaceExpr::Deref { operand, ty } if promela_type(ty).is_some() =>
+                self.global(operand.extract()),
+            _ => Err(self.error("a place that is not an integer or Boolean variable")),
+        }
+    }
+
+    fn value(&self, value: ValueExpr) -> Result<String, String> {
+        Ok(match value {
+            ValueExpr::Constant(Constant::Int(int), _) if fits_int(int) => int.to_string(),
+            ValueExpr::Constant(Constant::Bool(b), _) => b.to_string(),
+            ValueExpr::Load { source } => self.place(source.extract())?,
+            ValueExpr::UnOp { operator, operand } => {
+                let operand = self.value(operand.extract())?;
+                match operator {
+                    UnOp::Int(IntUnOp::Neg) => format!("(-{operand})"),
+                    UnOp::Int(IntUnOp::BitNot) => format!("(~{operand})"),
+                    // Integers are not truncated in the model.
+                    UnOp::Cast(CastOp::IntToInt(_)) => operand,
+                    _ => return Err(self.error("this unary operator")),
+                }
+            }
+            ValueExpr::BinOp { operator, left, right } => {
+                let (left, right) = (self.value(left.extract())?, self.value(right.extract())?);
+                let op = match operator {
+                    BinOp::Int(op) => self.int_bin_op(op)?,
+                    BinOp::Rel(RelOp::Lt) => "<",
+                    BinOp::Rel(RelOp::Gt) => ">",
+                    BinOp::Rel(RelOp::Le) => "<=",
+                    BinOp::Rel(RelOp::Ge) => ">=",
+                    BinOp::Rel(RelOp::Eq) => "==",
+                    BinOp::Rel(RelOp::Ne) => "!=",
+                    _ => return Err(self.error("this binary operator")),
+                };
+                format!("({left} {op} {right})")
+            }
+            _ => return Err(self.error("this expression")),
+        })
+    }
+
+    fn int_bin_op(&self, op: IntBinOp) -> Result<&'static str, String> {
+        Ok(match op {
+            IntBinOp::Add | IntBinOp::AddUnchecked => "+",
+            IntBinOp::Sub | IntBinOp::SubUnchecked => "-",
+            IntBinOp::Mul | IntBinOp::MulUnchecked => "*",
+            IntBinOp::Div | IntBinOp::DivExact => "/",
+            IntBinOp::Rem => "%",
+            IntBinOp::Shl | IntBinOp::ShlUnchecked => "<<",
+            IntBinOp::Shr | IntBinOp::ShrUnchecked => ">>",
+            IntBinOp::BitAnd => "&",
+            IntBinOp::BitOr => "|",
+            IntBinOp::BitXor => "^",
+        })
+    }
+}
+
+/// The Promela type used for values of type `ty`, if it is supported.
+/// Values of other types, like the unit return value and pointers, are not represented at all.
+fn promela_type(ty: Type) -> Option<&'static str> {
+    match ty {
+        Type::Int(_) => Some("int"),
+        Type::Bool => Some("bool"),
+        _ => None,
+    }
+}
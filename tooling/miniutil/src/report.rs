@@ -0,0 +1,221 @@
+//! Execution reports: a program run rendered as an HTML page, showing which thread executed what
+//! in each step, the steps that touched memory, and the program listing with the executed code highlighted.
+//!
+//! ```rust,ignore
+//! let trace = trace_execution::<BasicMem>(prog);
+//! std::fs::write("report.html", html_report(prog, &trace)).unwrap();
+//! ```
+
+use crate::fmt::fmt_instructions;
+use crate::mock_write::MockWrite;
+use crate::run::default_config;
+use crate::*;
+
+/// One step of an execution: the statement or terminator that a thread executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutedStep {
+    pub thread: usize,
+    /// The function the executed code belongs to.
+    pub function: FnName,
+    pub block: BbName,
+    /// The index of the statement in the block; the terminator comes after the last statement.
+    pub index: usize,
+    /// Whether the step accesses memory in a way that is interesting to look at:
+    /// (de)allocation, storage markers, atomics and locks, and writes through pointers.
+    pub memory_event: bool,
+}
+
+/// Everything that happened in one execution of a program.
+#[derive(Clone, Debug)]
+pub struct ExecutionTrace {
+    pub termination: TerminationInfo,
+    pub steps: Vec<ExecutedStep>,
+    pub stdout: Vec<String>,
+}
+
+/// Run the program and record which code each step executed.
+/// Stderr is just forwarded to the host.
+pub fn trace_execution<M: Memory>(prog: Program) -> ExecutionTrace {
+    let out = MockWrite::new();
+    let err = DynWrite::new(std::io::stderr());
+
+    let mut machine =
+        match Machine::<M>::new(prog, default_config(), DynWrite::new(out.clone()), err)
+            .get_internal()
+        {
+            Ok(machine) => machine,
+            Err(termination) =>
+                return ExecutionTrace {
+                    termination,
+                    steps: Vec::new(),
+                    stdout: out.into_strings(),
+                },
+        };
+
+    let mut steps = Vec::new();
+    loop {
+        // We only learn which thread runs after the step, so remember where each thread is.
+        let thread_count = machine.thread_count().try_to_usize().unwrap();
+        let locations: Vec<_> =
+            (0..thread_count).map(|id| machine.thread_location(Int::from(id))).collect();
+        let steps_before = machine.schedule().len();
+
+        let res = machine.step().get_internal();
+
+        let schedule = machine.schedule();
+        if schedule.len() > steps_before {
+            let thread = schedule.last().unwrap().try_to_usize().unwrap();
+            let (function, block, index) = locations[thread].unwrap();
+            let index = index.try_to_usize().unwrap();
+            let bb = prog.functions.get(function).unwrap().blocks.get(block).unwrap();
+            let memory_event = is_memory_event(bb, index);
+            steps.push(ExecutedStep { thread, function, block, index, memory_event });
+        }
+
+        if let Err(termination) = res {
+            return ExecutionTrace { termination, steps, stdout: out.into_strings() };
+        }
+
+        // Drops everything not reachable from `machine`.
+        mark_and_sweep(&machine);
+    }
+}
+
+fn is_memory_event(bb: BasicBlock, index: usize) -> bool {
+    fn through_ptr(place: PlaceExpr) -> bool {
+        match place {
+            PlaceExpr::Local(_) => false,
+            PlaceExpr::Deref { .. } => true,
+            PlaceExpr::Field { root, .. }
+            | PlaceExpr::Index { root, .. }
+            | PlaceExpr::Downcast { root, .. } => through_ptr(root.extract()),
+        }
+    }
+
+    if index < bb.statements.iter().count() {
+        return match bb.statements.iter().nth(index).unwrap() {
+            Statement::StorageLive(_) | Statement::StorageDead(_) => true,
+            Statement::Assign { destination, .. }
+            | Statement::CopyPlace { destination, .. }
+            | Statement::SetDiscriminant { destination, .. }
+            | Statement::Deinit { place: destination } => through_ptr(destination),
+            Statement::PlaceMention(_) | Statement::Validate { .. } => false,
+        };
+    }
+    match bb.terminator {
+        Terminator::Intrinsic { intrinsic, .. } =>
+            matches!(
+                intrinsic,
+                IntrinsicOp::Allocate
+                    | IntrinsicOp::Deallocate
+                    | IntrinsicOp::StackAllocate
+                    | IntrinsicOp::AtomicStore
+                    | IntrinsicOp::AtomicLoad
+                    | IntrinsicOp::AtomicCompareExchange
+                    | IntrinsicOp::AtomicFetchAndOp(_)
+                    | IntrinsicOp::Lock(_)
+            ),
+        _ => false,
+    }
+}
+
+/// Render the trace of an execution of `prog` as a self-contained HTML page.
+pub fn html_report(prog: Program, trace: &ExecutionTrace) -> String {
+    let (instructions, comptypes) = fmt_instructions(prog);
+    let text_of = |step: &ExecutedStep| {
+        instructions
+            .iter()
+            .find(|(f, bb, idx, _text)| {
+                step.function == *f && *bb == step.block && *idx == step.index
+            })
+            .map(|(_f, _bb, _idx, text)| text.as_str())
+            .unwrap_or("")
+    };
+
+    let mut out = String::new();
+    out += "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n";
+    out += "<title>MiniRust execution report</title>\n";
+    out += "<style>\n";
+    out += "body { font-family: sans-serif; }\n";
+    out += "pre, td { font-family: monospace; }\n";
+    out += "table { border-collapse: collapse; }\n";
+    out += "td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: left; }\n";
+    out += ".executed { background: #cfc; }\n";
+    out += ".memory { background: #fec; }\n";
+    out += "</style>\n</head>\n<body>\n";
+
+    out += "<h1>Execution report</h1>\n";
+    out += &format!("<p>Outcome: {}</p>\n", escape(&format!("{:?}", trace.termination)));
+    out += &format!("<h2>Output</h2>\n<pre>{}</pre>\n", escape(&trace.stdout.join("\n")));
+
+    // One column per thread, one row per step.
+    let threads = trace.steps.iter().map(|step| step.thread + 1).max().unwrap_or(0);
+    out += "<h2>Timeline</h2>\n<table>\n<tr><th>step</th>";
+    for thread in 0..threads {
+        out += &format!("<th>thread {thread}</th>");
+    }
+    out += "</tr>\n";
+    for (idx, step) in trace.steps.iter().enumerate() {
+        out += &format!("<tr><td>{idx}</td>");
+        for thread in 0..threads {
+            if thread == step.thread {
+                let class = if step.memory_event { " class=\"memory\"" } else { "" };
+                out += &format!("<td{class}>{}</td>", escape(text_of(step)));
+            } else {
+                out += "<td></td>";
+            }
+        }
+        out += "</tr>\n";
+    }
+    out += "</table>\n";
+
+    out += "<h2>Memory events</h2>\n<table>\n<tr><th>step</th><th>thread</th><th>code</th></tr>\n";
+    for (idx, step) in trace.steps.iter().enumerate().filter(|(_idx, step)| step.memory_event) {
+        out += &format!(
+            "<tr><td>{idx}</td><td>{}</td><td>{}</td></tr>\n",
+            step.thread,
+            escape(text_of(step))
+        );
+    }
+    out += "</table>\n";
+
+    // The listing, with each executed line annotated with how often it ran.
+    out += "<h2>Program</h2>\n<pre>\n";
+    out += &escape(&comptypes);
+    let mut cur = None;
+    for (f, bb, idx, text) in instructions.iter() {
+        if cur != Some(*f) {
+            if cur.is_some() {
+                out += "}\n\n";
+            }
+            cur = Some(*f);
+            let start = if prog.start == *f { "start " } else { "" };
+            out += &format!("{start}fn f{} {{\n", f.0.get_internal());
+        }
+        if *idx == 0 {
+            out += &format!("  bb{}:\n", bb.0.get_internal());
+        }
+        let count = trace
+            .steps
+            .iter()
+            .filter(|step| step.function == *f && step.block == *bb && step.index == *idx)
+            .count();
+        if count > 0 {
+            out += &format!(
+                "    <span class=\"executed\" title=\"executed {count} times\">{}</span>\n",
+                escape(text)
+            );
+        } else {
+            out += &format!("    {}\n", escape(text));
+        }
+    }
+    if cur.is_some() {
+        out += "}\n";
+    }
+    out += "</pre>\n</body>\n</html>\n";
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -1,25 +1,137 @@
-use crate::{mock_write::MockWrite, *};
+use crate::{
+    mock_write::{MockWrite, NullWrite},
+    *,
+};
+
+/// The machine configuration used when none is given explicitly.
+/// Setting the `MINIRUST_FULL_VALIDATION` environment variable turns on `full_validation`,
+/// so that an entire test suite run also checks every assignment.
+pub fn default_config() -> Config {
+    let mut config = Config::default();
+    config.full_validation = std::env::var_os("MINIRUST_FULL_VALIDATION").is_some();
+    config
+}
+
+/// `default_config` without the well-formedness check, for negative tests of the checks
+/// the machine performs at runtime. Running an ill-formed program may make the machine panic.
+pub fn unchecked_config() -> Config {
+    let mut config = default_config();
+    config.check_wf = false;
+    config
+}
 
 /// Run the program and return its TerminationInfo.
 /// Stdout/stderr are just forwarded to the host.
 pub fn run_program<M: Memory>(prog: Program) -> TerminationInfo {
+    run_program_with_config::<M>(prog, default_config())
+}
+
+/// Run the program with the given machine configuration and return its TerminationInfo.
+/// Stdout/stderr are just forwarded to the host.
+pub fn run_program_with_config<M: Memory>(prog: Program, config: Config) -> TerminationInfo {
     let out = std::io::stdout();
     let err = std::io::stderr();
 
-    let res: Result<!, TerminationInfo> = run::<M>(prog, out, err);
+    let res: Result<!, TerminationInfo> = run::<M>(prog, config, out, err);
     match res {
         Ok(never) => never,
         Err(t) => t,
     }
 }
 
+/// A thread that was passed over for more steps than a fairness bound allows, see `check_fairness`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Starvation {
+    pub thread: usize,
+    /// The number of consecutive steps the thread had been enabled without being picked.
+    pub waiting: usize,
+    /// The number of steps taken so far.
+    pub step: usize,
+}
+
+/// Run the program like `run_program_with_config`, and check that the schedule would also be possible
+/// with `Config::fairness_bound` set to `bound`: no thread may stay enabled for more than `bound`
+/// consecutive steps without being picked. The run stops at the first thread that does, which is returned.
+/// A test whose outcome changes with such a schedule relies on the scheduler being unfair.
+pub fn check_fairness<M: Memory>(
+    prog: Program,
+    config: Config,
+    bound: usize,
+) -> Result<TerminationInfo, Starvation> {
+    let out = std::io::stdout();
+    let err = std::io::stderr();
+
+    let res: NdResult<!> = try {
+        let mut machine = Machine::<M>::new(prog, config, DynWrite::new(out), DynWrite::new(err))?;
+
+        loop {
+            machine.step()?;
+
+            for (thread, waiting) in machine.waiting_steps().iter().enumerate() {
+                if waiting > Int::from(bound) {
+                    let waiting = waiting.try_to_usize().unwrap();
+                    let step = machine.schedule().len().try_to_usize().unwrap();
+                    return Err(Starvation { thread, waiting, step });
+                }
+            }
+
+            // Drops everything not reachable from `machine`.
+            mark_and_sweep(&machine);
+        }
+    };
+
+    match res.get_internal() {
+        Ok(never) => never,
+        Err(t) => Ok(t),
+    }
+}
+
+/// How many steps `run_program_fast` takes between two garbage collections.
+pub const FAST_GC_INTERVAL: usize = 1024;
+
+/// Run the program with the given machine configuration like `run_program_with_config`,
+/// but without the overhead that only matters for tests: all output is discarded,
+/// and garbage is only collected every `FAST_GC_INTERVAL` steps instead of after every step.
+/// This is meant for benchmarks and other long runs; pass `Config::default()` to also skip
+/// the options that `default_config` reads from the environment.
+pub fn run_program_fast<M: Memory>(prog: Program, config: Config) -> TerminationInfo {
+    let res: NdResult<!> = try {
+        let mut machine =
+            Machine::<M>::new(prog, config, DynWrite::new(NullWrite), DynWrite::new(NullWrite))?;
+
+        let mut steps = 0;
+        loop {
+            machine.step()?;
+
+            steps += 1;
+            if steps % FAST_GC_INTERVAL == 0 {
+                // Drops everything not reachable from `machine`.
+                mark_and_sweep(&machine);
+            }
+        }
+    };
+
+    match res.get_internal() {
+        Ok(never) => never,
+        Err(t) => t,
+    }
+}
+
+/// Run the program once for every target and return the TerminationInfo of each run,
+/// labeled with the name of the target.
+pub fn run_program_on_all_targets<M: Retarget>(
+    prog: Program,
+) -> Vec<(&'static str, TerminationInfo)> {
+    vec![("x86_64", run_program::<M::On<x86_64>>(prog)), ("arm", run_program::<M::On<arm>>(prog))]
+}
+
 /// Run the program and return stdout as a `Vec<String>`  or a termination info
 /// if it did not terminate correctly. Stderr is just forwarded to the host.
 pub fn get_stdout<M: Memory>(prog: Program) -> Result<Vec<String>, TerminationInfo> {
     let out = MockWrite::new();
     let err = std::io::stderr();
 
-    let res = run::<M>(prog, out.clone(), err);
+    let res = run::<M>(prog, default_config(), out.clone(), err);
     match res {
         Ok(never) => never,
         Err(TerminationInfo::MachineStop) => Ok(out.into_strings()),
@@ -27,16 +139,405 @@ pub fn get_stdout<M: Memory>(prog: Program) -> Result<Vec<String>, TerminationIn
     }
 }
 
+/// Run the program and return its TerminationInfo together with everything it wrote to stderr.
+/// Stdout is just forwarded to the host.
+pub fn get_stderr<M: Memory>(prog: Program) -> (TerminationInfo, Vec<String>) {
+    let out = std::io::stdout();
+    let err = MockWrite::new();
+
+    let res = run::<M>(prog, default_config(), out, err.clone());
+    match res {
+        Ok(never) => never,
+        Err(info) => (info, err.into_strings()),
+    }
+}
+
+/// Everything that can be observed about one execution of a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Behavior {
+    pub termination: TerminationInfo,
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+/// Run the program and return its observable behavior.
+pub fn observe<M: Memory>(prog: Program) -> Behavior {
+    observe_with_config::<M>(prog, default_config())
+}
+
+/// Run the program with the given machine configuration and return its observable behavior.
+pub fn observe_with_config<M: Memory>(prog: Program, config: Config) -> Behavior {
+    let out = MockWrite::new();
+    let err = MockWrite::new();
+
+    let res = run::<M>(prog, config, out.clone(), err.clone());
+    match res {
+        Ok(never) => never,
+        Err(termination) =>
+            Behavior { termination, stdout: out.into_strings(), stderr: err.into_strings() },
+    }
+}
+
+/// The scheduling decisions of one execution, see `record` and `replay`.
+/// A trace only covers the scheduling: the other non-deterministic choices, like the addresses of allocations,
+/// are made by the `pick` of libspecr, which cannot be observed from outside, so replaying makes them afresh.
+/// The `Display` form is a comma-separated list of thread IDs that `FromStr` parses back,
+/// so that a trace can be attached to a bug report.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    /// The thread that ran in each step.
+    pub schedule: Vec<usize>,
+}
+
+impl std::fmt::Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ids: Vec<String> = self.schedule.iter().map(|id| id.to_string()).collect();
+        write!(f, "{}", ids.join(","))
+    }
+}
+
+impl std::str::FromStr for Trace {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Trace, Self::Err> {
+        if s.is_empty() {
+            return Ok(Trace::default());
+        }
+        let schedule = s.split(',').map(|id| id.trim().parse()).collect::<Result<_, _>>()?;
+        Ok(Trace { schedule })
+    }
+}
+
+/// Run the program and return its TerminationInfo together with the trace of its scheduling decisions.
+/// Stdout/stderr are just forwarded to the host.
+pub fn record<M: Memory>(prog: Program) -> (TerminationInfo, Trace) {
+    let out = DynWrite::new(std::io::stdout());
+    let err = DynWrite::new(std::io::stderr());
+
+    let mut machine = match Machine::<M>::new(prog, default_config(), out, err).get_internal() {
+        Ok(machine) => machine,
+        Err(info) => return (info, Trace::default()),
+    };
+    loop {
+        if let Err(info) = machine.step().get_internal() {
+            let schedule = machine.schedule().iter().map(|id| id.try_to_usize().unwrap()).collect();
+            return (info, Trace { schedule });
+        }
+
+        // Drops everything not reachable from `machine`.
+        mark_and_sweep(&machine);
+    }
+}
+
+/// Run the program taking the scheduling decisions recorded in `trace`, and return its TerminationInfo.
+/// Other non-deterministic choices (like the addresses of allocations) are not part of the trace,
+/// so programs that branch on those may not be replayed faithfully.
+/// Stdout/stderr are just forwarded to the host.
+pub fn replay<M: Memory>(prog: Program, trace: &Trace) -> TerminationInfo {
+    let mut config = default_config();
+    config.replay_schedule = Some(trace.schedule.iter().map(|id| Int::from(*id)).collect());
+    run_program_with_config::<M>(prog, config)
+}
+
+/// Run the program with threads scheduled by a pseudo-random generator seeded with `seed`
+/// (see `Config::schedule_seed`), and return its TerminationInfo.
+/// Stdout/stderr are just forwarded to the host.
+pub fn run_program_with_seed<M: Memory>(prog: Program, seed: usize) -> TerminationInfo {
+    let mut config = default_config();
+    config.schedule_seed = Some(Int::from(seed));
+    run_program_with_config::<M>(prog, config)
+}
+
+/// How one run of `chaos` was set up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChaosRun {
+    pub target: &'static str,
+    /// The `Config::schedule_seed`, or `None` if threads were scheduled non-deterministically.
+    pub schedule_seed: Option<usize>,
+}
+
+/// The outcome of `chaos`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChaosReport {
+    /// The number of runs that fit into the time budget.
+    pub runs: usize,
+    /// Every distinct behavior, in the order they were first observed,
+    /// with the first run that showed it.
+    pub behaviors: Vec<(Behavior, ChaosRun)>,
+}
+
+impl ChaosReport {
+    /// Whether all runs behaved the same.
+    pub fn is_deterministic(&self) -> bool {
+        self.behaviors.len() <= 1
+    }
+}
+
+/// Runs the program over and over until `duration` has passed (but at least once per target),
+/// alternating between the targets and between non-deterministic and seeded scheduling,
+/// and collects the distinct behaviors.
+/// The other non-deterministic choices, like the addresses of allocations, are made afresh in every run.
+/// This is a cheap check for programs whose behavior should not depend on any of that.
+pub fn chaos<M: Retarget>(prog: Program, duration: std::time::Duration) -> ChaosReport {
+    let start = std::time::Instant::now();
+    let mut report = ChaosReport { runs: 0, behaviors: Vec::new() };
+    while report.runs < 2 || start.elapsed() < duration {
+        let run = ChaosRun {
+            target: if report.runs % 2 == 0 { "x86_64" } else { "arm" },
+            // Every other pair of runs uses a new seed.
+            schedule_seed: if report.runs % 4 < 2 { None } else { Some(report.runs / 4) },
+        };
+        let mut config = default_config();
+        config.schedule_seed = run.schedule_seed.map(Int::from);
+        let behavior = match run.target {
+            "x86_64" => observe_with_config::<M::On<x86_64>>(prog, config),
+            _ => observe_with_config::<M::On<arm>>(prog, config),
+        };
+        if !report.behaviors.iter().any(|(seen, _)| *seen == behavior) {
+            report.behaviors.push((behavior, run));
+        }
+        report.runs += 1;
+    }
+    report
+}
+
+/// A machine running a program one step at a time, for tools that want to control
+/// the execution themselves (debuggers, visualizers, fuzzing drivers, ...).
+/// Stdout and stderr are captured and available in the `state`.
+///
+/// ```rust,ignore
+/// let mut interp = Interpreter::<BasicMem>::new(prog);
+/// while interp.step().is_ok() {
+///     println!("{:?}", interp.state().threads);
+/// }
+/// ```
+pub struct Interpreter<M: Memory> {
+    prog: Program,
+    /// `None` if creating the machine already failed.
+    machine: Option<Machine<M>>,
+    termination: Option<TerminationInfo>,
+    steps: usize,
+    stdout: MockWrite,
+    stderr: MockWrite,
+    /// For each thread, its stack depth and location after the last step it took.
+    locations: Vec<Option<(usize, FnName, BbName, Int)>>,
+    cleanups: Vec<CleanupEvent>,
+}
+
+/// Where a thread continues executing, see `Machine::thread_location`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadLocation {
+    /// The function of the topmost stack frame.
+    pub function: FnName,
+    pub block: BbName,
+    /// The index of the next statement; the terminator comes after the last statement.
+    pub index: usize,
+}
+
+/// A thread entering a cleanup block, see `Interpreter::cleanups`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CleanupEvent {
+    pub thread: usize,
+    /// The number of stack frames of the thread, which tells apart the frames of nested calls.
+    pub depth: usize,
+    /// The function of the frame running the cleanup.
+    pub function: FnName,
+    pub block: BbName,
+}
+
+/// A snapshot of what an `Interpreter` has done so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachineState {
+    /// The number of steps that were taken successfully.
+    pub steps: usize,
+    /// How the program terminated, if it did.
+    pub termination: Option<TerminationInfo>,
+    /// For each thread created so far, where it continues; `None` once it has no stack frames left.
+    pub threads: Vec<Option<ThreadLocation>>,
+    /// For each thread created so far, the number of consecutive steps it has been enabled
+    /// without being picked, see `Config::fairness_bound`.
+    pub waiting: Vec<usize>,
+    /// For each thread created so far, the number of bytes used by the live locals and stack allocations
+    /// of each of its stack frames, starting at the bottom of the stack. See `Config::max_stack_size`.
+    pub frame_sizes: Vec<Vec<usize>>,
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+impl<M: Memory> Interpreter<M> {
+    pub fn new(prog: Program) -> Interpreter<M> {
+        Interpreter::with_config(prog, default_config())
+    }
+
+    pub fn with_config(prog: Program, config: Config) -> Interpreter<M> {
+        let stdout = MockWrite::new();
+        let stderr = MockWrite::new();
+        let machine = Machine::<M>::new(
+            prog,
+            config,
+            DynWrite::new(stdout.clone()),
+            DynWrite::new(stderr.clone()),
+        )
+        .get_internal();
+        let (machine, termination) = match machine {
+            Ok(machine) => (Some(machine), None),
+            Err(info) => (None, Some(info)),
+        };
+        Interpreter {
+            prog,
+            machine,
+            termination,
+            steps: 0,
+            stdout,
+            stderr,
+            locations: Vec::new(),
+            cleanups: Vec::new(),
+        }
+    }
+
+    /// Take one step. Once the program has terminated, this keeps returning how it terminated.
+    pub fn step(&mut self) -> Result<(), TerminationInfo> {
+        if let Some(info) = self.termination {
+            return Err(info);
+        }
+        let machine = self.machine.as_mut().unwrap();
+        if let Err(info) = machine.step().get_internal() {
+            self.termination = Some(info);
+            return Err(info);
+        }
+        self.steps += 1;
+        let thread = machine.schedule().last().unwrap();
+        self.track_cleanups(thread);
+
+        // Drops everything not reachable from `machine`.
+        mark_and_sweep(self.machine.as_ref().unwrap());
+        Ok(())
+    }
+
+    /// The cleanup blocks entered so far, in the order in which they were entered.
+    pub fn cleanups(&self) -> &[CleanupEvent] {
+        &self.cleanups
+    }
+
+    /// Records the cleanup block `thread` entered in the step it just took, if any,
+    /// and checks that it only left a frame by unwinding after running cleanup code.
+    fn track_cleanups(&mut self, thread: ThreadId) {
+        let machine = self.machine.as_ref().unwrap();
+        let id = thread.try_to_usize().unwrap();
+        let depth = machine.stack_depth(thread).try_to_usize().unwrap();
+        let location = machine.thread_location(thread);
+        if self.locations.len() <= id {
+            self.locations.resize(id + 1, None);
+        }
+        let prev = std::mem::replace(
+            &mut self.locations[id],
+            location.map(|(func, block, index)| (depth, func, block, index)),
+        );
+        let Some((func, block, index)) = location else { return };
+
+        // A thread enters a block when it starts a new frame or executes a terminator.
+        let entered = match prev {
+            None => index == Int::ZERO,
+            Some((prev_depth, prev_func, prev_block, prev_index)) => {
+                let prev_bb =
+                    self.prog.functions.get(prev_func).unwrap().blocks.get(prev_block).unwrap();
+                if depth < prev_depth && !matches!(prev_bb.terminator, Terminator::Return) {
+                    assert!(
+                        prev_bb.kind == BbKind::Cleanup
+                            && matches!(prev_bb.terminator, Terminator::ResumeUnwind),
+                        "thread {id} unwound out of a frame without finishing its cleanup"
+                    );
+                }
+                depth != prev_depth || prev_index == prev_bb.statements.len()
+            }
+        };
+        let bb = self.prog.functions.get(func).unwrap().blocks.get(block).unwrap();
+        if entered && bb.kind == BbKind::Cleanup {
+            self.cleanups.push(CleanupEvent { thread: id, depth, function: func, block });
+        }
+    }
+
+    /// Take steps until the program terminates or `max_steps` steps have been taken.
+    /// Returns how the program terminated, if it did.
+    pub fn run_steps(&mut self, max_steps: usize) -> Option<TerminationInfo> {
+        for _ in 0..max_steps {
+            if let Err(info) = self.step() {
+                return Some(info);
+            }
+        }
+        None
+    }
+
+    /// Take steps until the program terminates.
+    pub fn run(&mut self) -> TerminationInfo {
+        loop {
+            if let Err(info) = self.step() {
+                return info;
+            }
+        }
+    }
+
+    pub fn state(&self) -> MachineState {
+        let waiting = match &self.machine {
+            None => Vec::new(),
+            Some(machine) =>
+                machine.waiting_steps().iter().map(|n| n.try_to_usize().unwrap()).collect(),
+        };
+        let threads = match &self.machine {
+            None => Vec::new(),
+            Some(machine) => {
+                let thread_count = machine.thread_count().try_to_usize().unwrap();
+                (0..thread_count)
+                    .map(|id| {
+                        let (func, block, index) = machine.thread_location(Int::from(id))?;
+                        Some(ThreadLocation {
+                            function: func,
+                            block,
+                            index: index.try_to_usize().unwrap(),
+                        })
+                    })
+                    .collect()
+            }
+        };
+        let frame_sizes = match &self.machine {
+            None => Vec::new(),
+            Some(machine) => {
+                let thread_count = machine.thread_count().try_to_usize().unwrap();
+                (0..thread_count)
+                    .map(|id| {
+                        machine
+                            .frame_sizes(Int::from(id))
+                            .iter()
+                            .map(|size| size.bytes().try_to_usize().unwrap())
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+        MachineState {
+            steps: self.steps,
+            termination: self.termination,
+            threads,
+            waiting,
+            frame_sizes,
+            stdout: self.stdout.clone().into_strings(),
+            stderr: self.stderr.clone().into_strings(),
+        }
+    }
+}
+
 /// Run the program to completion using the given writers for stdout/stderr.
 ///
 /// We fix `BasicMemory` as a memory for now.
 fn run<M: Memory>(
     prog: Program,
+    config: Config,
     stdout: impl GcWrite,
     stderr: impl GcWrite,
 ) -> Result<!, TerminationInfo> {
     let res: NdResult<!> = try {
-        let mut machine = Machine::<M>::new(prog, DynWrite::new(stdout), DynWrite::new(stderr))?;
+        let mut machine =
+            Machine::<M>::new(prog, config, DynWrite::new(stdout), DynWrite::new(stderr))?;
 
         loop {
             machine.step()?;
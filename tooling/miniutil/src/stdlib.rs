@@ -0,0 +1,298 @@
+//! Ready-made MiniRust functions that tests can add to their program.
+//!
+//! Example:
+//!
+//! ```rust
+//! use miniutil::build::*;
+//! use miniutil::stdlib;
+//!
+//! let mut p = ProgramBuilder::new();
+//! let min = stdlib::declare_min::<u32>(&mut p);
+//!
+//! let mut f = p.declare_function();
+//! let x = f.declare_local::<u32>();
+//! f.storage_live(x);
+//! f.call_nounwind(x, fn_ptr(min), &[by_value(const_int(4u32)), by_value(const_int(2u32))]);
+//! f.print(load(x));
+//! f.exit();
+//! let f = p.finish_function(f);
+//!
+//! let program = p.finish_program(f);
+//! ```
+
+use crate::build::*;
+use crate::*;
+
+/// Declares `fn(T, T) -> T` returning the smaller argument.
+pub fn declare_min<T: TypeConv + Freeze>(p: &mut ProgramBuilder) -> FnName {
+    declare_select::<T>(p, lt)
+}
+
+/// Declares `fn(T, T) -> T` returning the larger argument.
+pub fn declare_max<T: TypeConv + Freeze>(p: &mut ProgramBuilder) -> FnName {
+    declare_select::<T>(p, gt)
+}
+
+/// Declares `fn(a: T, b: T) -> T` returning `a` if `cmp(a, b)` holds, and `b` otherwise.
+fn declare_select<T: TypeConv + Freeze>(
+    p: &mut ProgramBuilder,
+    cmp: fn(ValueExpr, ValueExpr) -> ValueExpr,
+) -> FnName {
+    let mut f = p.declare_function();
+    let a = f.declare_arg::<T>();
+    let b = f.declare_arg::<T>();
+    let ret = f.declare_ret::<T>();
+    f.if_(cmp(load(a), load(b)), |f| f.assign(ret, load(a)), |f| f.assign(ret, load(b)));
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Declares `fn(*mut T, *mut T)` swapping the values behind the two pointers, like `ptr::swap`.
+pub fn declare_memswap<T: TypeConv + Freeze>(p: &mut ProgramBuilder) -> FnName {
+    let mut f = p.declare_function();
+    let a = f.declare_arg::<*mut T>();
+    let b = f.declare_arg::<*mut T>();
+    let tmp = f.declare_local::<T>();
+    let a = deref(load(a), T::get_type());
+    let b = deref(load(b), T::get_type());
+    f.storage_live(tmp);
+    f.assign(tmp, load(a));
+    f.assign(a, load(b));
+    f.assign(b, load(tmp));
+    f.storage_dead(tmp);
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Declares `fn()` that prints `msg` to stderr and aborts the program.
+pub fn declare_panic_handler(p: &mut ProgramBuilder, msg: &str) -> FnName {
+    let mut f = p.declare_function();
+    f.eprint(const_str(msg));
+    f.abort();
+    p.finish_function(f)
+}
+
+/// The type of a growable buffer of `T`s, like `Vec<T>`.
+/// It consists of a pointer to the heap allocation, the capacity, and the length.
+pub fn vec_ty<T: TypeConv>() -> Type {
+    let ptr = <*mut T>::get_type();
+    let usize_ = <usize>::get_type();
+    let usize_size = <usize>::get_size();
+    tuple_ty(
+        &[(size(0), ptr), (usize_size, usize_), (usize_size * Int::from(2), usize_)],
+        usize_size * Int::from(3),
+        <usize>::get_align(),
+    )
+}
+
+/// An empty `vec_ty::<T>()`, which does not own a heap allocation yet.
+pub fn vec_new<T: TypeConv>() -> ValueExpr {
+    tuple(&[null(), const_int(0_usize), const_int(0_usize)], vec_ty::<T>())
+}
+
+/// The pointer to the heap allocation of the vector `v`.
+pub fn vec_buf(v: PlaceExpr) -> PlaceExpr {
+    field(v, 0)
+}
+
+/// The capacity of the vector `v`.
+pub fn vec_cap(v: PlaceExpr) -> PlaceExpr {
+    field(v, 1)
+}
+
+/// The length of the vector `v`.
+pub fn vec_len(v: PlaceExpr) -> PlaceExpr {
+    field(v, 2)
+}
+
+/// The element at `idx` (a `usize`) of the vector `v`. This does not check bounds.
+pub fn vec_elem<T: TypeConv>(v: PlaceExpr, idx: ValueExpr) -> PlaceExpr {
+    buf_elem::<T>(load(vec_buf(v)), idx)
+}
+
+/// The element at `idx` of the buffer of `T`s starting at `buf`.
+fn buf_elem<T: TypeConv>(buf: ValueExpr, idx: ValueExpr) -> PlaceExpr {
+    let offset = mul(idx, const_int_typed::<usize>(T::get_size().bytes()));
+    deref(ptr_offset(buf, offset, InBounds::Yes), T::get_type())
+}
+
+/// Declares `fn(*mut Vec<T>, T)` appending an element to a `vec_ty::<T>()`,
+/// growing the heap allocation if needed.
+pub fn declare_vec_push<T: TypeConv + Freeze>(p: &mut ProgramBuilder) -> FnName {
+    let elem_size = const_int_typed::<usize>(T::get_size().bytes());
+    let elem_align = const_int_typed::<usize>(T::get_align().bytes());
+
+    let mut f = p.declare_function();
+    let vec = f.declare_arg::<*mut ()>();
+    let val = f.declare_arg::<T>();
+    let v = deref(load(vec), vec_ty::<T>());
+    let (buf, cap, len) = (vec_buf(v), vec_cap(v), vec_len(v));
+
+    f.if_(
+        eq(load(len), load(cap)),
+        |f| {
+            // The buffer is full: move the elements to an allocation twice as large.
+            f.scope(|f| {
+                let new_cap = f.declare_temp::<usize>();
+                let new_buf = f.declare_temp::<*mut T>();
+                let i = f.declare_temp::<usize>();
+                f.if_(
+                    eq(load(cap), const_int(0_usize)),
+                    |f| f.assign(new_cap, const_int(4_usize)),
+                    |f| f.assign(new_cap, mul(load(cap), const_int(2_usize))),
+                );
+                f.allocate(mul(load(new_cap), elem_size), elem_align, new_buf);
+                f.assign(i, const_int(0_usize));
+                f.while_(lt(load(i), load(len)), |f, _| {
+                    let elem = load(buf_elem::<T>(load(buf), load(i)));
+                    f.assign(buf_elem::<T>(load(new_buf), load(i)), elem);
+                    f.assign(i, add(load(i), const_int(1_usize)));
+                });
+                f.if_(
+                    eq(load(cap), const_int(0_usize)),
+                    |_| {},
+                    |f| f.deallocate(load(buf), mul(load(cap), elem_size), elem_align),
+                );
+                f.assign(buf, load(new_buf));
+                f.assign(cap, load(new_cap));
+            });
+        },
+        |_| {},
+    );
+    f.assign(buf_elem::<T>(load(buf), load(len)), load(val));
+    f.assign(len, add(load(len), const_int(1_usize)));
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Declares `fn(*mut Vec<T>)` freeing the heap allocation of a `vec_ty::<T>()`.
+pub fn declare_vec_drop<T: TypeConv>(p: &mut ProgramBuilder) -> FnName {
+    let elem_size = const_int_typed::<usize>(T::get_size().bytes());
+    let elem_align = const_int_typed::<usize>(T::get_align().bytes());
+
+    let mut f = p.declare_function();
+    let vec = f.declare_arg::<*mut ()>();
+    let v = deref(load(vec), vec_ty::<T>());
+    f.if_(
+        eq(load(vec_cap(v)), const_int(0_usize)),
+        |_| {},
+        |f| f.deallocate(load(vec_buf(v)), mul(load(vec_cap(v)), elem_size), elem_align),
+    );
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Whether the reference count of an `Rc`-like pointer is updated atomically, like `Arc`,
+/// or non-atomically, like `Rc`.
+#[derive(Clone, Copy)]
+pub enum RcKind {
+    Rc,
+    Arc,
+}
+
+/// The type of the heap allocation behind an `Rc<T>` or `Arc<T>`:
+/// the strong count (a `usize`) followed by the payload.
+pub fn rc_box_ty<T: TypeConv>() -> Type {
+    let usize_size = <usize>::get_size();
+    let payload_offset = usize_size.align_to(T::get_align());
+    let align = T::get_align().max(<usize>::get_align());
+    tuple_ty(
+        &[(size(0), <usize>::get_type()), (payload_offset, T::get_type())],
+        (payload_offset + T::get_size()).align_to(align),
+        align,
+    )
+}
+
+/// The strong count behind the `Rc`-like pointer `rc`.
+pub fn rc_count<T: TypeConv>(rc: ValueExpr) -> PlaceExpr {
+    field(deref(rc, rc_box_ty::<T>()), 0)
+}
+
+/// The payload behind the `Rc`-like pointer `rc`.
+pub fn rc_payload<T: TypeConv>(rc: ValueExpr) -> PlaceExpr {
+    field(deref(rc, rc_box_ty::<T>()), 1)
+}
+
+/// Declares `fn(T) -> *mut RcBox<T>` moving the argument to a new heap allocation
+/// with a strong count of 1. The result can be used both as an `Rc` and as an `Arc`.
+pub fn declare_rc_new<T: TypeConv + Freeze>(p: &mut ProgramBuilder) -> FnName {
+    let rc_box = rc_box_ty::<T>();
+    let box_size = rc_box.layout::<DefaultTarget>().expect_size("RcBox is sized");
+    let box_align = rc_box.layout::<DefaultTarget>().expect_align("RcBox is sized");
+
+    let mut f = p.declare_function();
+    let val = f.declare_arg::<T>();
+    let ret = f.declare_ret::<*mut ()>();
+    f.allocate(
+        const_int_typed::<usize>(box_size.bytes()),
+        const_int_typed::<usize>(box_align.bytes()),
+        ret,
+    );
+    f.assign(rc_count::<T>(load(ret)), const_int(1_usize));
+    f.assign(rc_payload::<T>(load(ret)), load(val));
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Declares `fn(*mut RcBox<T>)` incrementing the strong count.
+/// Like the standard library, this aborts if the count would get too large:
+/// `Rc` aborts on overflow, `Arc` as soon as the old count exceeds `isize::MAX`.
+pub fn declare_rc_clone<T: TypeConv>(p: &mut ProgramBuilder, kind: RcKind) -> FnName {
+    let mut f = p.declare_function();
+    let rc = f.declare_arg::<*mut ()>();
+    let count = rc_count::<T>(load(rc));
+    match kind {
+        RcKind::Rc => {
+            f.if_(eq(load(count), const_int(usize::MAX)), |f| f.abort(), |_| {});
+            f.assign(count, add(load(count), const_int(1_usize)));
+        }
+        RcKind::Arc => {
+            let old = f.declare_local::<usize>();
+            f.storage_live(old);
+            let count_ptr = addr_of(count, <*mut usize>::get_type());
+            f.atomic_fetch(FetchBinOp::Add, old, count_ptr, const_int(1_usize));
+            f.if_(gt(load(old), const_int(isize::MAX as usize)), |f| f.abort(), |_| {});
+            f.storage_dead(old);
+        }
+    }
+    f.return_();
+    p.finish_function(f)
+}
+
+/// Declares `fn(*mut RcBox<T>)` decrementing the strong count,
+/// and freeing the heap allocation once the count reaches 0.
+pub fn declare_rc_drop<T: TypeConv>(p: &mut ProgramBuilder, kind: RcKind) -> FnName {
+    let rc_box = rc_box_ty::<T>();
+    let box_size = rc_box.layout::<DefaultTarget>().expect_size("RcBox is sized");
+    let box_align = rc_box.layout::<DefaultTarget>().expect_align("RcBox is sized");
+
+    let mut f = p.declare_function();
+    let rc = f.declare_arg::<*mut ()>();
+    let old = f.declare_local::<usize>();
+    let count = rc_count::<T>(load(rc));
+    f.storage_live(old);
+    match kind {
+        RcKind::Rc => {
+            f.assign(old, load(count));
+            f.assign(count, sub(load(old), const_int(1_usize)));
+        }
+        RcKind::Arc => {
+            let count_ptr = addr_of(count, <*mut usize>::get_type());
+            f.atomic_fetch(FetchBinOp::Sub, old, count_ptr, const_int(1_usize));
+        }
+    }
+    f.if_(
+        eq(load(old), const_int(1_usize)),
+        |f| {
+            f.deallocate(
+                load(rc),
+                const_int_typed::<usize>(box_size.bytes()),
+                const_int_typed::<usize>(box_align.bytes()),
+            )
+        },
+        |_| {},
+    );
+    f.storage_dead(old);
+    f.return_();
+    p.finish_function(f)
+}
@@ -0,0 +1,516 @@
+//! Symbolic execution of small functions, as an alternative to running them concretely.
+//!
+//! The arguments of the function become SMT variables, integers are bitvectors of their type's size,
+//! and every possible UB (overflow in unchecked arithmetic, division by zero, violated `assume`,
+//! reads of uninitialized locals, ...) becomes a query to an SMT solver.
+//! This either proves that no input causes UB, or finds a concrete input that does.
+//! Loops are unrolled up to a bound; executions that run longer are not checked.
+//!
+//! Only functions working on integer and Boolean locals are supported: there is no memory,
+//! and calls and unwinding are not supported either.
+//!
+//! ```rust,ignore
+//! match check_function(prog, f, 10, &mut ExternalSolver::z3()) {
+//!     Verdict::Ub { message, inputs } => run_program::<BasicMem>(counterexample_program(prog, f, &inputs)),
+//!     ...
+//! }
+//! ```
+
+use crate::build::{TypeConv, exit, fn_ptr};
+use crate::*;
+
+use std::collections::HashMap;
+
+mod solver;
+mod term;
+
+pub use solver::*;
+pub use term::*;
+
+/// The outcome of symbolically executing a function.
+#[derive(Clone, Debug)]
+pub enum Verdict {
+    /// No input makes the function have UB. If `complete` is false, some executions ran into the loop bound
+    /// and were not followed further, so this only holds for executions that stay within the bound.
+    NoUb { complete: bool },
+    /// Calling the function with `inputs` causes UB.
+    Ub { message: String, inputs: Vec<ValueExpr> },
+    /// The function uses something that is not supported, or the solver gave up.
+    Unknown(String),
+}
+
+/// Symbolically executes `f` for all possible arguments, entering every block at most `loop_bound` times.
+pub fn check_function(
+    prog: Program,
+    f: FnName,
+    loop_bound: usize,
+    solver: &mut impl Solver,
+) -> Verdict {
+    let func = prog.functions.get(f).unwrap();
+    let mut args = Vec::new();
+    let mut locals: HashMap<LocalName, LocalState> =
+        func.locals.iter().map(|(local, _ty)| (local, LocalState::Dead)).collect();
+    for (idx, local) in func.args.iter().enumerate() {
+        let ty = func.locals.get(local).unwrap();
+        let Some(sort) = sort_of(ty) else {
+            return Verdict::Unknown(format!("unsupported type of argument {idx}"));
+        };
+        let var = Term::Var(format!("arg{idx}"), sort);
+        locals.insert(local, LocalState::Init(var));
+        args.push(ty);
+    }
+
+    let mut executor = Executor { func, args, loop_bound, solver, complete: true };
+    let start = Path { block: func.start, locals, conditions: Vec::new(), visits: HashMap::new() };
+    let mut todo = Vec::new();
+    executor.enter(start, func.start, &mut todo);
+    while let Some(path) = todo.pop() {
+        if let Err(verdict) = executor.step(path, &mut todo) {
+            return verdict;
+        }
+    }
+    Verdict::NoUb { complete: executor.complete }
+}
+
+/// A program that calls `f` with `inputs` and then exits, to reproduce UB found by `check_function`.
+pub fn counterexample_program(mut prog: Program, f: FnName, inputs: &[ValueExpr]) -> Program {
+    let func = prog.functions.get(f).unwrap();
+    let result = LocalName(Name::from_internal(0));
+    let unit = LocalName(Name::from_internal(1));
+    let call = BasicBlock {
+        statements: list![Statement::StorageLive(result)],
+        terminator: Terminator::Call {
+            callee: fn_ptr(f),
+            calling_convention: func.calling_convention,
+            arguments: inputs.iter().map(|input| ArgumentExpr::ByValue(*input)).collect(),
+            ret: PlaceExpr::Local(result),
+            next_block: Some(BbName(Name::from_internal(1))),
+            unwind_block: None,
+        },
+        kind: BbKind::Regular,
+    };
+    let done = BasicBlock { statements: list![], terminator: exit(), kind: BbKind::Regular };
+    let start = Function {
+        locals: [(result, func.locals.get(func.ret).unwrap()), (unit, <()>::get_type())]
+            .into_iter()
+            .collect(),
+        args: list![],
+        ret: unit,
+        calling_convention: CallingConvention::C,
+        blocks: [(BbName(Name::from_internal(0)), call), (BbName(Name::from_internal(1)), done)]
+            .into_iter()
+            .collect(),
+        start: BbName(Name::from_internal(0)),
+    };
+
+    let next = prog.functions.iter().map(|(FnName(name), _f)| name.get_internal() + 1).max();
+    let name = FnName(Name::from_internal(next.unwrap_or(0)));
+    prog.functions.insert(name, start);
+    prog.start = name;
+    prog
+}
+
+fn sort_of(ty: Type) -> Option<Sort> {
+    match ty {
+        Type::Int(int_ty) => Some(Sort::BitVec(width(int_ty))),
+        Type::Bool => Some(Sort::Bool),
+        _ => None,
+    }
+}
+
+fn width(int_ty: IntType) -> u32 {
+    int_ty.size.bits().try_to_usize().unwrap() as u32
+}
+
+#[derive(Clone)]
+enum LocalState {
+    Dead,
+    Uninit,
+    Init(Term),
+}
+
+/// One execution path through the function.
+#[derive(Clone)]
+struct Path {
+    block: BbName,
+    locals: HashMap<LocalName, LocalState>,
+    /// What has to hold for the inputs to take this path.
+    conditions: Vec<Term>,
+    /// How often each block was entered on this path.
+    visits: HashMap<BbName, usize>,
+}
+
+struct Executor<'a, S: Solver> {
+    func: Function,
+    /// The types of the arguments.
+    args: Vec<Type>,
+    loop_bound: usize,
+    solver: &'a mut S,
+    /// Whether no path was cut off at the loop bound.
+    complete: bool,
+}
+
+impl<S: Solver> Executor<'_, S> {
+    fn unsupported(what: &str) -> Verdict {
+        Verdict::Unknown(format!("{what} is not supported"))
+    }
+
+    fn enter(&mut self, mut path: Path, block: BbName, todo: &mut Vec<Path>) {
+        let visits = path.visits.entry(block).or_insert(0);
+        *visits += 1;
+        if *visits > self.loop_bound {
+            self.complete = false;
+            return;
+        }
+        path.block = block;
+        todo.push(path);
+    }
+
+    fn feasible(&mut self, conditions: &[Term]) -> Result<bool, Verdict> {
+        match self.solver.check(conditions) {
+            SolverResult::Sat(_) => Ok(true),
+            SolverResult::Unsat => Ok(false),
+            SolverResult::Unknown(reason) => Err(Verdict::Unknown(reason)),
+        }
+    }
+
+    /// Fails with a counterexample if `cond` can be true on `path`;
+    /// otherwise the path continues under the assumption that it is false.
+    fn check_ub(&mut self, path: &mut Path, cond: Term, message: &str) -> Result<(), Verdict> {
+        let mut conditions = path.conditions.clone();
+        conditions.push(cond.clone());
+        match self.solver.check(&conditions) {
+            SolverResult::Sat(model) =>
+                Err(Verdict::Ub { message: message.to_string(), inputs: self.inputs(&model) }),
+            SolverResult::Unsat => {
+                path.conditions.push(cond.negated());
+                Ok(())
+            }
+            SolverResult::Unknown(reason) => Err(Verdict::Unknown(reason)),
+        }
+    }
+
+    /// Fails with a counterexample for UB that happens whenever `path` gets here.
+    fn ub(&mut self, path: &mut Path, message: &str) -> Verdict {
+        match self.check_ub(path, Term::Bool(true), message) {
+            Err(verdict) => verdict,
+            Ok(()) => panic!("only feasible paths are followed"),
+        }
+    }
+
+    fn inputs(&self, model: &HashMap<String, Int>) -> Vec<ValueExpr> {
+        self.args
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| {
+                let value = model.get(&format!("arg{idx}")).copied().unwrap_or(Int::ZERO);
+                let constant = match ty {
+                    Type::Int(int_ty) if int_ty.signed == Signedness::Signed =>
+                        Constant::Int(to_signed(value, width(*int_ty))),
+                    Type::Int(_) => Constant::Int(value),
+                    _ => Constant::Bool(value != Int::ZERO),
+                };
+                ValueExpr::Constant(constant, *ty)
+            })
+            .collect()
+    }
+
+    /// Executes the block `path` is at, and adds the paths it can continue on to `todo`.
+    fn step(&mut self, mut path: Path, todo: &mut Vec<Path>) -> Result<(), Verdict> {
+        let bb = self.func.blocks.get(path.block).unwrap();
+        for st in bb.statements.iter() {
+            self.statement(&mut path, st)?;
+        }
+        let next = |next_block: Option<BbName>| {
+            next_block.ok_or_else(|| Self::unsupported("an intrinsic without next block"))
+        };
+        match bb.terminator {
+            Terminator::Goto(block) => self.enter(path, block, todo),
+            Terminator::Switch { value, cases, fallback } => {
+                let (value, _ty) = self.value(&mut path, value)?;
+                let case_term = |case: Int| {
+                    match value.sort() {
+                        Sort::Bool => Term::Bool(case != Int::ZERO),
+                        Sort::BitVec(width) => Term::bv(case, width),
+                    }
+                };
+                let mut fallback_path = path.clone();
+                for (case, block) in cases.iter() {
+                    let hit = value.clone().equals(case_term(case));
+                    let mut case_path = path.clone();
+                    case_path.conditions.push(hit.clone());
+                    if self.feasible(&case_path.conditions)? {
+                        self.enter(case_path, block, todo);
+                    }
+                    fallback_path.conditions.push(hit.negated());
+                }
+                if self.feasible(&fallback_path.conditions)? {
+                    self.enter(fallback_path, fallback, todo);
+                }
+            }
+            Terminator::Unreachable => return Err(self.ub(&mut path, "reached unreachable code")),
+            Terminator::Return => {}
+            Terminator::Intrinsic { intrinsic, arguments, ret: _, next_block } => {
+                let mut args = Vec::new();
+                let mut arg_tys = Vec::new();
+                for arg in arguments.iter() {
+                    let (term, ty) = self.value(&mut path, arg)?;
+                    args.push(term);
+                    arg_tys.push(ty);
+                }
+                match intrinsic {
+                    IntrinsicOp::Assume => {
+                        let [cond] = args.as_slice() else {
+                            return Err(Self::unsupported("`Assume` without exactly one argument"));
+                        };
+                        self.check_ub(
+                            &mut path,
+                            cond.clone().negated(),
+                            "`Assume` intrinsic called on condition that is violated",
+                        )?;
+                        self.enter(path, next(next_block)?, todo);
+                    }
+                    IntrinsicOp::AssumeInRange => {
+                        let [val, start, end] = args.as_slice() else {
+                            return Err(Self::unsupported(
+                                "`AssumeInRange` without exactly three arguments",
+                            ));
+                        };
+                        // The bounds are compared as bitvectors, so they need the type of the value.
+                        let Type::Int(int_ty) = arg_tys[0] else {
+                            return Err(Self::unsupported("`AssumeInRange` on a non-integer"));
+                        };
+                        if arg_tys.iter().any(|ty| *ty != arg_tys[0]) {
+                            return Err(Self::unsupported(
+                                "`AssumeInRange` with bounds of another type",
+                            ));
+                        }
+                        let le =
+                            if int_ty.signed == Signedness::Signed { Op::BvSle } else { Op::BvUle };
+                        let in_range = Term::app(le, &[start.clone(), val.clone()])
+                            .and(Term::app(le, &[val.clone(), end.clone()]));
+                        self.check_ub(
+                            &mut path,
+                            in_range.negated(),
+                            "`AssumeInRange` intrinsic called on value outside of the range",
+                        )?;
+                        self.enter(path, next(next_block)?, todo);
+                    }
+                    IntrinsicOp::PrintStdout | IntrinsicOp::PrintStderr =>
+                        self.enter(path, next(next_block)?, todo),
+                    IntrinsicOp::Exit | IntrinsicOp::Abort => {}
+                    IntrinsicOp::UnreachableUnchecked =>
+                        return Err(self.ub(&mut path, "`UnreachableUnchecked` intrinsic called")),
+                    _ => return Err(Self::unsupported("this intrinsic")),
+                }
+            }
+            Terminator::Call { .. } => return Err(Self::unsupported("calling a function")),
+            Terminator::StartUnwind { .. }
+            | Terminator::StopUnwind(_)
+            | Terminator::ResumeUnwind
+            | Terminator::Terminate => return Err(Self::unsupported("unwinding")),
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, path: &mut Path, st: Statement) -> Result<(), Verdict> {
+        match st {
+            Statement::StorageLive(local) => {
+                path.locals.insert(local, LocalState::Uninit);
+            }
+            Statement::StorageDead(local) => {
+                path.locals.insert(local, LocalState::Dead);
+            }
+            Statement::Assign { destination: PlaceExpr::Local(local), source } => {
+                if let LocalState::Dead = path.locals[&local] {
+                    return Err(self.ub(path, "access to a dead local"));
+                }
+                let (value, _ty) = self.value(path, source)?;
+                path.locals.insert(local, LocalState::Init(value));
+            }
+            _ => return Err(Self::unsupported("this statement")),
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, path: &mut Path, value: ValueExpr) -> Result<(Term, Type), Verdict> {
+        match value {
+            ValueExpr::Constant(Constant::Int(int), Type::Int(int_ty)) =>
+                Ok((Term::bv(int, width(int_ty)), Type::Int(int_ty))),
+            ValueExpr::Constant(Constant::Bool(b), ty) => Ok((Term::Bool(b), ty)),
+            ValueExpr::Load { source } => {
+                let PlaceExpr::Local(local) = source.extract() else {
+                    return Err(Self::unsupported("loading from memory"));
+                };
+                let ty = self.func.locals.get(local).unwrap();
+                if sort_of(ty).is_none() {
+                    return Err(Self::unsupported("a local that is not an integer or Boolean"));
+                }
+                match path.locals[&local].clone() {
+                    LocalState::Init(term) => Ok((term, ty)),
+                    LocalState::Dead => Err(self.ub(path, "access to a dead local")),
+                    LocalState::Uninit => {
+                        let message = format!(
+                            "load at type {ty:?} but the data in memory violates the language invariant"
+                        );
+                        Err(self.ub(path, &message))
+                    }
+                }
+            }
+            ValueExpr::UnOp { operator, operand } => {
+                let (operand, ty) = self.value(path, operand.extract())?;
+                // `bool_to_int`, which `if_` and `while_` switch on:
+                // Booleans are represented as a single byte that is 0 or 1.
+                if let (UnOp::Cast(CastOp::Transmute(Type::Int(int_ty))), Type::Bool) =
+                    (operator, ty)
+                {
+                    if width(int_ty) == 8 {
+                        let (one, zero) = (Term::bv(Int::ONE, 8), Term::bv(Int::ZERO, 8));
+                        return Ok((Term::app(Op::Ite, &[operand, one, zero]), Type::Int(int_ty)));
+                    }
+                }
+                let Type::Int(int_ty) = ty else {
+                    return Err(Self::unsupported("a unary operator on a Boolean"));
+                };
+                match operator {
+                    UnOp::Int(IntUnOp::Neg) => Ok((Term::app(Op::BvNeg, &[operand]), ty)),
+                    UnOp::Int(IntUnOp::BitNot) => Ok((Term::app(Op::BvNot, &[operand]), ty)),
+                    UnOp::Cast(CastOp::IntToInt(new_ty)) => {
+                        let (old, new) = (width(int_ty), width(new_ty));
+                        let term = if new > old && int_ty.signed == Signedness::Signed {
+                            Term::app(Op::SignExtend(new - old), &[operand])
+                        } else if new > old {
+                            Term::app(Op::ZeroExtend(new - old), &[operand])
+                        } else if new < old {
+                            Term::app(Op::Extract(new - 1, 0), &[operand])
+                        } else {
+                            operand
+                        };
+                        Ok((term, Type::Int(new_ty)))
+                    }
+                    _ => Err(Self::unsupported("this unary operator")),
+                }
+            }
+            ValueExpr::BinOp { operator, left, right } => {
+                let (left, ty) = self.value(path, left.extract())?;
+                let (right, _ty) = self.value(path, right.extract())?;
+                match (operator, ty) {
+                    (BinOp::Int(op), Type::Int(int_ty)) =>
+                        Ok((self.int_bin_op(path, op, left, right, int_ty)?, ty)),
+                    (BinOp::Rel(op), Type::Int(int_ty)) => {
+                        let signed = int_ty.signed == Signedness::Signed;
+                        let (lt, le) =
+                            if signed { (Op::BvSlt, Op::BvSle) } else { (Op::BvUlt, Op::BvUle) };
+                        let term = match op {
+                            RelOp::Lt => Term::app(lt, &[left, right]),
+                            RelOp::Le => Term::app(le, &[left, right]),
+                            RelOp::Gt => Term::app(lt, &[right, left]),
+                            RelOp::Ge => Term::app(le, &[right, left]),
+                            RelOp::Eq => left.equals(right),
+                            RelOp::Ne => left.equals(right).negated(),
+                            RelOp::Cmp => return Err(Self::unsupported("three-way comparison")),
+                        };
+                        Ok((term, Type::Bool))
+                    }
+                    (BinOp::Rel(RelOp::Eq), Type::Bool) => Ok((left.equals(right), Type::Bool)),
+                    (BinOp::Rel(RelOp::Ne), Type::Bool) =>
+                        Ok((left.equals(right).negated(), Type::Bool)),
+                    _ => Err(Self::unsupported("this binary operator")),
+                }
+            }
+            _ => Err(Self::unsupported("this expression")),
+        }
+    }
+
+    fn int_bin_op(
+        &mut self,
+        path: &mut Path,
+        op: IntBinOp,
+        left: Term,
+        right: Term,
+        int_ty: IntType,
+    ) -> Result<Term, Verdict> {
+        let w = width(int_ty);
+        let signed = int_ty.signed == Signedness::Signed;
+        let bin = |op: Op| Term::app(op, &[left.clone(), right.clone()]);
+        // Whether the result of `op` differs from the infinite-precision result,
+        // computed by doing the operation on `extra` more bits.
+        let overflows = |op: Op, extra: u32| {
+            let extend = if signed { Op::SignExtend(extra) } else { Op::ZeroExtend(extra) };
+            let ext = |t: &Term| Term::app(extend, &[t.clone()]);
+            let wide = Term::app(op, &[ext(&left), ext(&right)]);
+            wide.equals(ext(&bin(op))).negated()
+        };
+        let zero = Term::bv(Int::ZERO, w);
+        let divides_by_zero = right.clone().equals(zero.clone());
+        // `int::MIN / -1` does not fit in the type.
+        let min = Term::bv(Int::ONE << Int::from(w - 1), w);
+        let div_overflows = if signed {
+            left.clone().equals(min).and(right.clone().equals(Term::bv(Int::from(-1), w)))
+        } else {
+            Term::Bool(false)
+        };
+        let (div, rem) = if signed { (Op::BvSDiv, Op::BvSRem) } else { (Op::BvUDiv, Op::BvURem) };
+        // The shift amount can have another type than the value being shifted, so it is brought to the
+        // same width. Only its lowest bits matter, so zero extension works for signed amounts as well.
+        let r = right.width();
+        let amount = if r < w {
+            Term::app(Op::ZeroExtend(w - r), &[right.clone()])
+        } else if r > w {
+            Term::app(Op::Extract(w - 1, 0), &[right.clone()])
+        } else {
+            right.clone()
+        };
+        // Shift amounts are taken modulo the number of bits, which is a power of two.
+        let masked_shift = |op: Op| {
+            let masked = Term::app(Op::BvAnd, &[amount.clone(), Term::bv(Int::from(w - 1), w)]);
+            Term::app(op, &[left.clone(), masked])
+        };
+        let shr = if signed { Op::BvAShr } else { Op::BvLShr };
+
+        Ok(match op {
+            IntBinOp::Add => bin(Op::BvAdd),
+            IntBinOp::Sub => bin(Op::BvSub),
+            IntBinOp::Mul => bin(Op::BvMul),
+            IntBinOp::AddUnchecked => {
+                self.check_ub(path, overflows(Op::BvAdd, 1), "overflow in unchecked add")?;
+                bin(Op::BvAdd)
+            }
+            IntBinOp::SubUnchecked => {
+                self.check_ub(path, overflows(Op::BvSub, 1), "overflow in unchecked sub")?;
+                bin(Op::BvSub)
+            }
+            IntBinOp::MulUnchecked => {
+                self.check_ub(path, overflows(Op::BvMul, w), "overflow in unchecked mul")?;
+                bin(Op::BvMul)
+            }
+            IntBinOp::Div | IntBinOp::DivExact => {
+                self.check_ub(path, divides_by_zero, "division by zero")?;
+                self.check_ub(path, div_overflows, "overflow in division")?;
+                if matches!(op, IntBinOp::DivExact) {
+                    let inexact = bin(rem).equals(zero).negated();
+                    self.check_ub(path, inexact, "non-zero remainder in exact division")?;
+                }
+                bin(div)
+            }
+            IntBinOp::Rem => {
+                self.check_ub(path, divides_by_zero, "modulus of remainder is zero")?;
+                self.check_ub(path, div_overflows, "overflow in remainder")?;
+                bin(rem)
+            }
+            IntBinOp::Shl => masked_shift(Op::BvShl),
+            IntBinOp::Shr => masked_shift(shr),
+            IntBinOp::ShlUnchecked | IntBinOp::ShrUnchecked => {
+                // A negative amount is at least `w` when read as unsigned, since the amount
+                // has at least 8 bits and `w` is at most 128.
+                let too_far = Term::app(Op::BvUlt, &[right.clone(), Term::bv(Int::from(w), r)]);
+                self.check_ub(path, too_far.negated(), "overflow in unchecked shift")?;
+                let op = if matches!(op, IntBinOp::ShlUnchecked) { Op::BvShl } else { shr };
+                Term::app(op, &[left.clone(), amount.clone()])
+            }
+            IntBinOp::BitAnd => bin(Op::BvAnd),
+            IntBinOp::BitOr => bin(Op::BvOr),
+            IntBinOp::BitXor => bin(Op::BvXor),
+        })
+    }
+}
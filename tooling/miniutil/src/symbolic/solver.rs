@@ -0,0 +1,178 @@
+use super::*;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The answer of a solver to whether a set of assertions can be satisfied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolverResult {
+    /// The assertions hold for these values of the variables.
+    /// Bitvectors are in `0..2^width`; Booleans are 0 or 1.
+    Sat(HashMap<String, Int>),
+    Unsat,
+    Unknown(String),
+}
+
+pub trait Solver {
+    /// Determine whether all `assertions` can be true at the same time.
+    fn check(&mut self, assertions: &[Term]) -> SolverResult;
+}
+
+/// The SMT-LIB script asking whether `assertions` are satisfiable, and for a model if they are.
+pub fn smtlib_script(assertions: &[Term]) -> String {
+    let mut vars = Vec::new();
+    for assertion in assertions {
+        assertion.vars(&mut vars);
+    }
+
+    let mut out = String::from("(set-logic QF_BV)\n");
+    for (name, sort) in vars {
+        out += &format!("(declare-const {name} {})\n", sort.to_smtlib());
+    }
+    for assertion in assertions {
+        out += &format!("(assert {})\n", assertion.to_smtlib());
+    }
+    out += "(check-sat)\n(get-model)\n";
+    out
+}
+
+/// Runs an external SMT solver that reads SMT-LIB from its stdin, like `z3 -in`.
+pub struct ExternalSolver {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl ExternalSolver {
+    pub fn z3() -> Self {
+        ExternalSolver { command: "z3".to_string(), args: vec!["-in".to_string()] }
+    }
+}
+
+impl Solver for ExternalSolver {
+    fn check(&mut self, assertions: &[Term]) -> SolverResult {
+        let child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) =>
+                return SolverResult::Unknown(format!("cannot run {}: {err}", self.command)),
+        };
+        let script = smtlib_script(assertions);
+        if let Err(err) = child.stdin.take().unwrap().write_all(script.as_bytes()) {
+            return SolverResult::Unknown(format!("cannot talk to {}: {err}", self.command));
+        }
+        let output = match child.wait_with_output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+            Err(err) => return SolverResult::Unknown(format!("{} failed: {err}", self.command)),
+        };
+        match output.lines().next().map(str::trim) {
+            Some("sat") =>
+                match parse_model(&output) {
+                    Ok(model) => SolverResult::Sat(model),
+                    Err(err) => SolverResult::Unknown(format!("cannot parse the model: {err}")),
+                },
+            Some("unsat") => SolverResult::Unsat,
+            _ => SolverResult::Unknown(output),
+        }
+    }
+}
+
+/// Extracts the variable values from the `(define-fun name () sort value)` entries of a model.
+fn parse_model(output: &str) -> Result<HashMap<String, Int>, String> {
+    let spaced = output.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let token = |idx: usize| tokens.get(idx).copied().ok_or("the model ends in a `define-fun`");
+    let mut model = HashMap::new();
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if tokens[idx] != "define-fun" {
+            idx += 1;
+            continue;
+        }
+        let name = token(idx + 1)?;
+        // Skip the empty parameter list and the sort, which is either `Bool` or `(_ BitVec n)`.
+        idx += 4;
+        if token(idx)? == "(" {
+            while token(idx)? != ")" {
+                idx += 1;
+            }
+        }
+        idx += 1;
+        if let Some(value) = parse_value(token(idx)?) {
+            model.insert(name.to_string(), value);
+        }
+    }
+    Ok(model)
+}
+
+fn parse_value(token: &str) -> Option<Int> {
+    let (digits, radix) = if let Some(hex) = token.strip_prefix("#x") {
+        (hex, 16)
+    } else if let Some(bin) = token.strip_prefix("#b") {
+        (bin, 2)
+    } else {
+        return match token {
+            "true" => Some(Int::ONE),
+            "false" => Some(Int::ZERO),
+            _ => None,
+        };
+    };
+    let mut value = Int::ZERO;
+    for digit in digits.chars() {
+        value = value * Int::from(radix) + Int::from(digit.to_digit(radix)?);
+    }
+    Some(value)
+}
+
+/// Tries all values of the variables. This is only feasible for very few bits in total,
+/// but does not need an external solver.
+pub struct BruteForceSolver {
+    /// The maximal number of variable bits that will be enumerated.
+    pub max_bits: u32,
+}
+
+impl Default for BruteForceSolver {
+    fn default() -> Self {
+        BruteForceSolver { max_bits: 20 }
+    }
+}
+
+impl Solver for BruteForceSolver {
+    fn check(&mut self, assertions: &[Term]) -> SolverResult {
+        let mut vars = Vec::new();
+        for assertion in assertions {
+            assertion.vars(&mut vars);
+        }
+        let widths: Vec<u32> = vars
+            .iter()
+            .map(|(_name, sort)| {
+                match sort {
+                    Sort::Bool => 1,
+                    Sort::BitVec(width) => *width,
+                }
+            })
+            .collect();
+        let total: u32 = widths.iter().sum();
+        if total > self.max_bits {
+            return SolverResult::Unknown(format!("too many bits to enumerate: {total}"));
+        }
+
+        for assignment in 0..(1u64 << total) {
+            let mut env = HashMap::new();
+            let mut offset = 0;
+            for ((name, _sort), width) in vars.iter().zip(&widths) {
+                let value = (assignment >> offset) & ((1u64 << width) - 1);
+                env.insert(name.clone(), Int::from(value));
+                offset += width;
+            }
+            if assertions.iter().all(|assertion| assertion.eval(&env) == Val::Bool(true)) {
+                return SolverResult::Sat(env);
+            }
+        }
+        SolverResult::Unsat
+    }
+}
@@ -0,0 +1,287 @@
+use super::*;
+
+/// The sort of an SMT term.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sort {
+    Bool,
+    BitVec(u32),
+}
+
+/// The SMT-LIB functions used to express MiniRust operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    BvAdd,
+    BvSub,
+    BvMul,
+    BvUDiv,
+    BvSDiv,
+    BvURem,
+    BvSRem,
+    BvShl,
+    BvLShr,
+    BvAShr,
+    BvAnd,
+    BvOr,
+    BvXor,
+    BvNeg,
+    BvNot,
+    BvUlt,
+    BvUle,
+    BvSlt,
+    BvSle,
+    Eq,
+    Not,
+    And,
+    Or,
+    Ite,
+    ZeroExtend(u32),
+    SignExtend(u32),
+    /// Bits `hi` down to `lo`, both inclusive.
+    Extract(u32, u32),
+}
+
+/// A term in the SMT-LIB theory of bitvectors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Var(String, Sort),
+    /// A bitvector constant of the given width. The value is in `0..2^width`.
+    BitVec(Int, u32),
+    Bool(bool),
+    App(Op, Vec<Term>),
+}
+
+/// The value of a term.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Val {
+    Bool(bool),
+    /// A bitvector of the given width. The value is in `0..2^width`.
+    BitVec(Int, u32),
+}
+
+fn pow2(width: u32) -> Int {
+    Int::ONE << Int::from(width)
+}
+
+/// Interprets the bitvector `value` of the given width in two's complement.
+pub fn to_signed(value: Int, width: u32) -> Int {
+    if value >= pow2(width - 1) { value - pow2(width) } else { value }
+}
+
+/// Wraps `value` into `0..2^width`.
+pub fn to_bits(value: Int, width: u32) -> Int {
+    value.rem_euclid(pow2(width))
+}
+
+impl Term {
+    pub fn bv(value: Int, width: u32) -> Term {
+        Term::BitVec(to_bits(value, width), width)
+    }
+
+    pub fn app(op: Op, args: &[Term]) -> Term {
+        Term::App(op, args.to_vec())
+    }
+
+    pub fn negated(self) -> Term {
+        Term::App(Op::Not, vec![self])
+    }
+
+    pub fn and(self, other: Term) -> Term {
+        Term::App(Op::And, vec![self, other])
+    }
+
+    pub fn or(self, other: Term) -> Term {
+        Term::App(Op::Or, vec![self, other])
+    }
+
+    pub fn equals(self, other: Term) -> Term {
+        Term::App(Op::Eq, vec![self, other])
+    }
+
+    pub fn sort(&self) -> Sort {
+        match self {
+            Term::Var(_, sort) => *sort,
+            Term::BitVec(_, width) => Sort::BitVec(*width),
+            Term::Bool(_) => Sort::Bool,
+            Term::App(op, args) =>
+                match op {
+                    Op::BvUlt | Op::BvUle | Op::BvSlt | Op::BvSle => Sort::Bool,
+                    Op::Eq | Op::Not | Op::And | Op::Or => Sort::Bool,
+                    Op::Ite => args[1].sort(),
+                    Op::ZeroExtend(n) | Op::SignExtend(n) => Sort::BitVec(args[0].width() + n),
+                    Op::Extract(hi, lo) => Sort::BitVec(hi - lo + 1),
+                    _ => args[0].sort(),
+                },
+        }
+    }
+
+    /// The width of a bitvector term.
+    pub fn width(&self) -> u32 {
+        match self.sort() {
+            Sort::BitVec(width) => width,
+            Sort::Bool => panic!("`width` called on a Boolean term"),
+        }
+    }
+
+    /// All variables that occur in the term.
+    pub fn vars(&self, vars: &mut Vec<(String, Sort)>) {
+        match self {
+            Term::Var(name, sort) =>
+                if !vars.iter().any(|(other, _)| other == name) {
+                    vars.push((name.clone(), *sort));
+                },
+            Term::BitVec(..) | Term::Bool(_) => {}
+            Term::App(_, args) =>
+                for arg in args {
+                    arg.vars(vars);
+                },
+        }
+    }
+
+    /// Evaluate the term. Bitvector variables are looked up in `env`, Boolean ones are 0 or 1.
+    pub fn eval(&self, env: &HashMap<String, Int>) -> Val {
+        let bv = |t: &Term| {
+            match t.eval(env) {
+                Val::BitVec(value, _) => value,
+                Val::Bool(_) => panic!("Boolean term used as bitvector"),
+            }
+        };
+        let boolean = |t: &Term| {
+            match t.eval(env) {
+                Val::Bool(b) => b,
+                Val::BitVec(..) => panic!("bitvector term used as Boolean"),
+            }
+        };
+        match self {
+            Term::Var(name, Sort::Bool) => Val::Bool(env[name] != Int::ZERO),
+            Term::Var(name, Sort::BitVec(width)) => Val::BitVec(env[name], *width),
+            Term::BitVec(value, width) => Val::BitVec(*value, *width),
+            Term::Bool(b) => Val::Bool(*b),
+            Term::App(op, args) => {
+                let width = match self.sort() {
+                    Sort::BitVec(width) => width,
+                    // For comparisons, the width of the operands.
+                    Sort::Bool =>
+                        match args[0].sort() {
+                            Sort::BitVec(width) => width,
+                            Sort::Bool => 1,
+                        },
+                };
+                let signed = |t: &Term| to_signed(bv(t), width);
+                let res = |value: Int| Val::BitVec(to_bits(value, width), width);
+                match op {
+                    Op::BvAdd => res(bv(&args[0]) + bv(&args[1])),
+                    Op::BvSub => res(bv(&args[0]) - bv(&args[1])),
+                    Op::BvMul => res(bv(&args[0]) * bv(&args[1])),
+                    // Division by zero is defined in SMT-LIB, even though it is UB in MiniRust.
+                    Op::BvUDiv =>
+                        if bv(&args[1]) == Int::ZERO {
+                            res(Int::from(-1))
+                        } else {
+                            res(bv(&args[0]) / bv(&args[1]))
+                        },
+                    Op::BvURem =>
+                        if bv(&args[1]) == Int::ZERO {
+                            res(bv(&args[0]))
+                        } else {
+                            res(bv(&args[0]) % bv(&args[1]))
+                        },
+                    Op::BvSDiv =>
+                        if bv(&args[1]) == Int::ZERO {
+                            res(if signed(&args[0]) < Int::ZERO { Int::ONE } else { Int::from(-1) })
+                        } else {
+                            res(signed(&args[0]) / signed(&args[1]))
+                        },
+                    Op::BvSRem =>
+                        if bv(&args[1]) == Int::ZERO {
+                            res(bv(&args[0]))
+                        } else {
+                            res(signed(&args[0]) % signed(&args[1]))
+                        },
+                    Op::BvShl | Op::BvLShr | Op::BvAShr => {
+                        let amount = bv(&args[1]);
+                        let amount =
+                            if amount >= Int::from(width) { Int::from(width) } else { amount };
+                        match op {
+                            Op::BvShl => res(bv(&args[0]) << amount),
+                            Op::BvLShr => res(bv(&args[0]) >> amount),
+                            _ => res(signed(&args[0]) >> amount),
+                        }
+                    }
+                    Op::BvAnd => res(bv(&args[0]) & bv(&args[1])),
+                    Op::BvOr => res(bv(&args[0]) | bv(&args[1])),
+                    Op::BvXor => res(bv(&args[0]) ^ bv(&args[1])),
+                    Op::BvNeg => res(-bv(&args[0])),
+                    Op::BvNot => res(pow2(width) - Int::ONE - bv(&args[0])),
+                    Op::BvUlt => Val::Bool(bv(&args[0]) < bv(&args[1])),
+                    Op::BvUle => Val::Bool(bv(&args[0]) <= bv(&args[1])),
+                    Op::BvSlt => Val::Bool(signed(&args[0]) < signed(&args[1])),
+                    Op::BvSle => Val::Bool(signed(&args[0]) <= signed(&args[1])),
+                    Op::Eq => Val::Bool(args[0].eval(env) == args[1].eval(env)),
+                    Op::Not => Val::Bool(!boolean(&args[0])),
+                    Op::And => Val::Bool(args.iter().all(&boolean)),
+                    Op::Or => Val::Bool(args.iter().any(&boolean)),
+                    Op::Ite =>
+                        if boolean(&args[0]) {
+                            args[1].eval(env)
+                        } else {
+                            args[2].eval(env)
+                        },
+                    Op::ZeroExtend(_) => res(bv(&args[0])),
+                    Op::SignExtend(_) => res(to_signed(bv(&args[0]), args[0].width())),
+                    Op::Extract(_, lo) => res(bv(&args[0]) >> Int::from(*lo)),
+                }
+            }
+        }
+    }
+
+    /// The term in SMT-LIB syntax.
+    pub fn to_smtlib(&self) -> String {
+        match self {
+            Term::Var(name, _) => name.clone(),
+            Term::BitVec(value, width) => format!("(_ bv{value} {width})"),
+            Term::Bool(b) => b.to_string(),
+            Term::App(op, args) => {
+                let op = match op {
+                    Op::BvAdd => "bvadd".to_string(),
+                    Op::BvSub => "bvsub".to_string(),
+                    Op::BvMul => "bvmul".to_string(),
+                    Op::BvUDiv => "bvudiv".to_string(),
+                    Op::BvSDiv => "bvsdiv".to_string(),
+                    Op::BvURem => "bvurem".to_string(),
+                    Op::BvSRem => "bvsrem".to_string(),
+                    Op::BvShl => "bvshl".to_string(),
+                    Op::BvLShr => "bvlshr".to_string(),
+                    Op::BvAShr => "bvashr".to_string(),
+                    Op::BvAnd => "bvand".to_string(),
+                    Op::BvOr => "bvor".to_string(),
+                    Op::BvXor => "bvxor".to_string(),
+                    Op::BvNeg => "bvneg".to_string(),
+                    Op::BvNot => "bvnot".to_string(),
+                    Op::BvUlt => "bvult".to_string(),
+                    Op::BvUle => "bvule".to_string(),
+                    Op::BvSlt => "bvslt".to_string(),
+                    Op::BvSle => "bvsle".to_string(),
+                    Op::Eq => "=".to_string(),
+                    Op::Not => "not".to_string(),
+                    Op::And => "and".to_string(),
+                    Op::Or => "or".to_string(),
+                    Op::Ite => "ite".to_string(),
+                    Op::ZeroExtend(n) => format!("(_ zero_extend {n})"),
+                    Op::SignExtend(n) => format!("(_ sign_extend {n})"),
+                    Op::Extract(hi, lo) => format!("(_ extract {hi} {lo})"),
+                };
+                let args: Vec<String> = args.iter().map(Term::to_smtlib).collect();
+                format!("({op} {})", args.join(" "))
+            }
+        }
+    }
+}
+
+impl Sort {
+    pub fn to_smtlib(self) -> String {
+        match self {
+            Sort::Bool => "Bool".to_string(),
+            Sort::BitVec(width) => format!("(_ BitVec {width})"),
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use super::*;
+
+/// Folds operations on integer and boolean constants and turns `Switch`es on constants into `Goto`s,
+/// in every function of `prog`.
+///
+/// Within a block, loads from locals that were just assigned a constant are replaced by that constant.
+/// Locals whose address is taken anywhere in the function are left alone, since they might
+/// be written through a pointer. Operations that can have UB (like division or unchecked arithmetic)
+/// are only folded if their constant operands show that there is no UB.
+pub fn const_prop(mut prog: Program) -> Program {
+    for (name, mut f) in prog.functions.iter() {
+        let address_taken = address_taken_locals(f);
+        let mut blocks = Map::new();
+        for (bb_name, bb) in f.blocks.iter() {
+            let mut prop = ConstProp { known: Map::new(), address_taken };
+            blocks.insert(bb_name, prop.block(bb));
+        }
+        f.blocks = blocks;
+        prog.functions.insert(name, f);
+    }
+    prog
+}
+
+struct ConstProp {
+    /// The constant value each local currently holds, as far as we know.
+    known: Map<LocalName, ValueExpr>,
+    address_taken: Set<LocalName>,
+}
+
+impl ConstProp {
+    fn block(&mut self, mut bb: BasicBlock) -> BasicBlock {
+        bb.statements = bb.statements.iter().map(|st| self.statement(st)).collect();
+        bb.terminator = self.terminator(bb.terminator);
+        bb
+    }
+
+    fn statement(&mut self, st: Statement) -> Statement {
+        match st {
+            Statement::Assign { destination, source } => {
+                let destination = self.place(destination);
+                let source = self.value(source);
+                self.forget(destination);
+                if let (
+                    PlaceExpr::Local(local),
+                    ValueExpr::Constant(Constant::Int(_) | Constant::Bool(_), _),
+                ) = (destination, source)
+                {
+                    if !self.address_taken.contains(local) {
+                        self.known.insert(local, source);
+                    }
+                }
+                Statement::Assign { destination, source }
+            }
+            Statement::CopyPlace { destination, source } => {
+                let destination = self.place(destination);
+                let source = self.place(source);
+                self.forget(destination);
+                Statement::CopyPlace { destination, source }
+            }
+            Statement::PlaceMention(place) => Statement::PlaceMention(self.place(place)),
+            Statement::SetDiscriminant { destination, value } => {
+                let destination = self.place(destination);
+                self.forget(destination);
+                Statement::SetDiscriminant { destination, value }
+            }
+            Statement::Validate { place, fn_entry } =>
+                Statement::Validate { place: self.place(place), fn_entry },
+            Statement::Deinit { place } => {
+                let place = self.place(place);
+                self.forget(place);
+                Statement::Deinit { place }
+            }
+            Statement::StorageLive(local) | Statement::StorageDead(local) => {
+                self.known.remove(local);
+                st
+            }
+        }
+    }
+
+    /// Forget what we know about the local that `place` is part of, since it gets overwritten.
+    fn forget(&mut self, place: PlaceExpr) {
+        if let Some(local) = root_local(place) {
+            self.known.remove(local);
+        }
+    }
+
+    fn terminator(&mut self, term: Terminator) -> Terminator {
+        match term {
+            Terminator::Switch { value, cases, fallback } => {
+                let value = self.value(value);
+                if let ValueExpr::Constant(Constant::Int(int), _) = value {
+                    return Terminator::Goto(cases.get(int).unwrap_or(fallback));
+                }
+                Terminator::Switch { value, cases, fallback }
+            }
+            Terminator::Intrinsic { intrinsic, arguments, ret, next_block } => {
+                let arguments = arguments.iter().map(|arg| self.value(arg)).collect();
+                Terminator::Intrinsic { intrinsic, arguments, ret: self.place(ret), next_block }
+            }
+            Terminator::Call {
+                callee,
+                calling_convention,
+                arguments,
+                ret,
+                next_block,
+                unwind_block,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|arg| {
+                        match arg {
+                            ArgumentExpr::ByValue(value) =>
+                                ArgumentExpr::ByValue(self.value(value)),
+                            ArgumentExpr::InPlace(place) =>
+                                ArgumentExpr::InPlace(self.place(place)),
+                        }
+                    })
+                    .collect();
+                Terminator::Call {
+                    callee: self.value(callee),
+                    calling_convention,
+                    arguments,
+                    ret: self.place(ret),
+                    next_block,
+                    unwind_block,
+                }
+            }
+            Terminator::StartUnwind { unwind_payload, unwind_block } =>
+                Terminator::StartUnwind { unwind_payload: self.value(unwind_payload), unwind_block },
+            Terminator::Goto(_)
+            | Terminator::Unreachable
+            | Terminator::Return
+            | Terminator::StopUnwind(_)
+            | Terminator::ResumeUnwind
+            | Terminator::Terminate => term,
+        }
+    }
+
+    fn place(&self, place: PlaceExpr) -> PlaceExpr {
+        match place {
+            PlaceExpr::Local(_) => place,
+            PlaceExpr::Deref { operand, ty } =>
+                PlaceExpr::Deref { operand: GcCow::new(self.value(operand.extract())), ty },
+            PlaceExpr::Field { root, field } =>
+                PlaceExpr::Field { root: GcCow::new(self.place(root.extract())), field },
+            PlaceExpr::Index { root, index } =>
+                PlaceExpr::Index {
+                    root: GcCow::new(self.place(root.extract())),
+                    index: GcCow::new(self.value(index.extract())),
+                },
+            PlaceExpr::Downcast { root, discriminant } =>
+                PlaceExpr::Downcast { root: GcCow::new(self.place(root.extract())), discriminant },
+        }
+    }
+
+    fn value(&self, value: ValueExpr) -> ValueExpr {
+        match value {
+            ValueExpr::Constant(..) => value,
+            ValueExpr::Tuple(exprs, ty) =>
+                ValueExpr::Tuple(exprs.iter().map(|expr| self.value(expr)).collect(), ty),
+            ValueExpr::Union { field, expr, union_ty } =>
+                ValueExpr::Union { field, expr: GcCow::new(self.value(expr.extract())), union_ty },
+            ValueExpr::Variant { discriminant, data, enum_ty } =>
+                ValueExpr::Variant {
+                    discriminant,
+                    data: GcCow::new(self.value(data.extract())),
+                    enum_ty,
+                },
+            ValueExpr::GetDiscriminant { place } =>
+                ValueExpr::GetDiscriminant { place: GcCow::new(self.place(place.extract())) },
+            ValueExpr::Load { source } => {
+                if let PlaceExpr::Local(local) = source.extract() {
+                    if let Some(constant) = self.known.get(local) {
+                        return constant;
+                    }
+                }
+                ValueExpr::Load { source: GcCow::new(self.place(source.extract())) }
+            }
+            ValueExpr::AddrOf { target, ptr_ty } =>
+                ValueExpr::AddrOf { target: GcCow::new(self.place(target.extract())), ptr_ty },
+            ValueExpr::UnOp { operator, operand } => {
+                let operand = self.value(operand.extract());
+                fold_un_op(operator, operand)
+                    .unwrap_or(ValueExpr::UnOp { operator, operand: GcCow::new(operand) })
+            }
+            ValueExpr::BinOp { operator, left, right } => {
+                let left = self.value(left.extract());
+                let right = self.value(right.extract());
+                fold_bin_op(operator, left, right).unwrap_or(ValueExpr::BinOp {
+                    operator,
+                    left: GcCow::new(left),
+                    right: GcCow::new(right),
+                })
+            }
+        }
+    }
+}
+
+/// The local that `place` is part of, unless it goes through a pointer.
+fn root_local(place: PlaceExpr) -> Option<LocalName> {
+    match place {
+        PlaceExpr::Local(local) => Some(local),
+        PlaceExpr::Field { root, .. }
+        | PlaceExpr::Index { root, .. }
+        | PlaceExpr::Downcast { root, .. } => root_local(root.extract()),
+        PlaceExpr::Deref { .. } => None,
+    }
+}
+
+/// The locals of `f` that some `AddrOf` expression takes the address of.
+pub(super) fn address_taken_locals(f: Function) -> Set<LocalName> {
+    fn value(v: ValueExpr, out: &mut Set<LocalName>) {
+        match v {
+            ValueExpr::Constant(..) => {}
+            ValueExpr::Tuple(exprs, _) =>
+                for expr in exprs.iter() {
+                    value(expr, out);
+                },
+            ValueExpr::Union { expr, .. } => value(expr.extract(), out),
+            ValueExpr::Variant { data, .. } => value(data.extract(), out),
+            ValueExpr::GetDiscriminant { place: p } | ValueExpr::Load { source: p } =>
+                place(p.extract(), out),
+            ValueExpr::AddrOf { target, .. } => {
+                if let Some(local) = root_local(target.extract()) {
+                    out.insert(local);
+                }
+                place(target.extract(), out);
+            }
+            ValueExpr::UnOp { operand, .. } => value(operand.extract(), out),
+            ValueExpr::BinOp { left, right, .. } => {
+                value(left.extract(), out);
+                value(right.extract(), out);
+            }
+        }
+    }
+    fn place(p: PlaceExpr, out: &mut Set<LocalName>) {
+        match p {
+            PlaceExpr::Local(_) => {}
+            PlaceExpr::Deref { operand, .. } => value(operand.extract(), out),
+            PlaceExpr::Field { root, .. } | PlaceExpr::Downcast { root, .. } =>
+                place(root.extract(), out),
+            PlaceExpr::Index { root, index } => {
+                place(root.extract(), out);
+                value(index.extract(), out);
+            }
+        }
+    }
+
+    let mut out = Set::new();
+    for (_bb_name, bb) in f.blocks.iter() {
+        for st in bb.statements.iter() {
+            match st {
+                Statement::Assign { destination, source } => {
+                    place(destination, &mut out);
+                    value(source, &mut out);
+                }
+                Statement::CopyPlace { destination, source } => {
+                    place(destination, &mut out);
+                    place(source, &mut out);
+                }
+                Statement::PlaceMention(p)
+                | Statement::SetDiscriminant { destination: p, .. }
+                | Statement::Validate { place: p, .. }
+                | Statement::Deinit { place: p } => place(p, &mut out),
+                Statement::StorageLive(_) | Statement::StorageDead(_) => {}
+            }
+        }
+        match bb.terminator {
+            Terminator::Switch { value: v, .. }
+            | Terminator::StartUnwind { unwind_payload: v, .. } => value(v, &mut out),
+            Terminator::Intrinsic { arguments, ret, .. } => {
+                for arg in arguments.iter() {
+                    value(arg, &mut out);
+                }
+                place(ret, &mut out);
+            }
+            Terminator::Call { callee, arguments, ret, .. } => {
+                value(callee, &mut out);
+                for arg in arguments.iter() {
+                    match arg {
+                        ArgumentExpr::ByValue(v) => value(v, &mut out),
+                        ArgumentExpr::InPlace(p) => place(p, &mut out),
+                    }
+                }
+                place(ret, &mut out);
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn fold_un_op(operator: UnOp, operand: ValueExpr) -> Option<ValueExpr> {
+    let ValueExpr::Constant(operand, op_ty) = operand else { return None };
+    match (operator, operand, op_ty) {
+        (UnOp::Int(IntUnOp::Neg), Constant::Int(i), Type::Int(int_ty)) =>
+            Some(int_constant(int_ty.bring_in_bounds(-i), int_ty)),
+        (UnOp::Int(IntUnOp::BitNot), Constant::Int(i), Type::Int(int_ty)) =>
+            Some(int_constant(int_ty.bring_in_bounds(!i), int_ty)),
+        (UnOp::Cast(CastOp::IntToInt(int_ty)), Constant::Int(i), Type::Int(_)) =>
+            Some(int_constant(int_ty.bring_in_bounds(i), int_ty)),
+        // `bool_to_int` and its inverse: booleans are represented as a single byte that is 0 or 1.
+        (UnOp::Cast(CastOp::Transmute(Type::Int(int_ty))), Constant::Bool(b), Type::Bool)
+            if int_ty.size.bytes() == 1 =>
+            Some(int_constant(Int::from(b as u8), int_ty)),
+        (UnOp::Cast(CastOp::Transmute(Type::Bool)), Constant::Int(i), Type::Int(int_ty))
+            if int_ty.size.bytes() == 1 && (i == Int::ZERO || i == Int::ONE) =>
+            Some(ValueExpr::Constant(Constant::Bool(i == Int::ONE), Type::Bool)),
+        _ => None,
+    }
+}
+
+fn fold_bin_op(operator: BinOp, left: ValueExpr, right: ValueExpr) -> Option<ValueExpr> {
+    let ValueExpr::Constant(left, l_ty) = left else { return None };
+    let ValueExpr::Constant(right, _r_ty) = right else { return None };
+    match (operator, left, right, l_ty) {
+        (BinOp::Int(op), Constant::Int(l), Constant::Int(r), Type::Int(int_ty)) => {
+            let result = fold_int_bin_op(op, l, r, int_ty)?;
+            Some(int_constant(int_ty.bring_in_bounds(result), int_ty))
+        }
+        (BinOp::Rel(rel), Constant::Int(l), Constant::Int(r), Type::Int(_)) =>
+            Some(fold_rel_op(rel, l.cmp(&r))),
+        (BinOp::Rel(rel), Constant::Bool(l), Constant::Bool(r), Type::Bool) =>
+            Some(fold_rel_op(rel, l.cmp(&r))),
+        _ => None,
+    }
+}
+
+/// Compute the result of an integer operation, or `None` if it is UB.
+fn fold_int_bin_op(op: IntBinOp, l: Int, r: Int, int_ty: IntType) -> Option<Int> {
+    use IntBinOp::*;
+    let checked = |result: Int| int_ty.can_represent(result).then_some(result);
+    match op {
+        Add => Some(l + r),
+        Sub => Some(l - r),
+        Mul => Some(l * r),
+        AddUnchecked => checked(l + r),
+        SubUnchecked => checked(l - r),
+        MulUnchecked => checked(l * r),
+        Div | DivExact | Rem if r == Int::ZERO => None,
+        Div => checked(l / r),
+        DivExact if l % r != Int::ZERO => None,
+        DivExact => checked(l / r),
+        Rem => checked(l / r).map(|_| l % r),
+        Shl => Some(l << r.rem_euclid(int_ty.size.bits())),
+        Shr => Some(l >> r.rem_euclid(int_ty.size.bits())),
+        ShlUnchecked | ShrUnchecked if r < Int::ZERO || r >= int_ty.size.bits() => None,
+        ShlUnchecked => Some(l << r),
+        ShrUnchecked => Some(l >> r),
+        BitAnd => Some(l & r),
+        BitOr => Some(l | r),
+        BitXor => Some(l ^ r),
+    }
+}
+
+fn fold_rel_op(rel: RelOp, ord: std::cmp::Ordering) -> ValueExpr {
+    use RelOp::*;
+    let b = match rel {
+        Lt => ord.is_lt(),
+        Gt => ord.is_gt(),
+        Le => ord.is_le(),
+        Ge => ord.is_ge(),
+        Eq => ord.is_eq(),
+        Ne => ord.is_ne(),
+        Cmp => return int_constant(Int::from(ord as i8), IntType::I8),
+    };
+    ValueExpr::Constant(Constant::Bool(b), Type::Bool)
+}
+
+fn int_constant(i: Int, int_ty: IntType) -> ValueExpr {
+    ValueExpr::Constant(Constant::Int(i), Type::Int(int_ty))
+}
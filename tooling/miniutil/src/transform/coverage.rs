@@ -0,0 +1,27 @@
+use super::*;
+
+use crate::build::{const_str, eprint};
+
+/// Makes block `bb` of function `func` print `marker` to stderr every time it is entered,
+/// so that running the program shows whether (and how often) the block is reached.
+///
+/// The contents of `bb` move to a new block of the same kind, and `bb` itself prints
+/// the marker and jumps there. All edges to `bb` stay intact.
+#[track_caller]
+pub fn mark_block(mut prog: Program, func: FnName, bb: BbName, marker: &str) -> Program {
+    let mut f = prog.functions.get(func).expect("`mark_block`: function does not exist");
+    let old = f.blocks.get(bb).expect("`mark_block`: block does not exist");
+
+    let next_name = f.blocks.iter().map(|(BbName(name), _bb)| name.get_internal()).max().unwrap();
+    let moved = next_name + 1;
+    f.blocks.insert(BbName(Name::from_internal(moved)), old);
+    let marker_bb = BasicBlock {
+        statements: list![],
+        terminator: eprint(const_str(marker), moved),
+        kind: old.kind,
+    };
+    f.blocks.insert(bb, marker_bb);
+
+    prog.functions.insert(func, f);
+    prog
+}
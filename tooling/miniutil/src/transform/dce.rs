@@ -0,0 +1,295 @@
+use super::*;
+
+use crate::analysis::CallGraph;
+
+/// Removes code from `prog` that cannot affect its behavior:
+/// - functions that can be reached neither from the start function (see `CallGraph`) nor from a vtable,
+/// - blocks that are not reachable from the start block of their function,
+/// - assignments to locals whose value is not live, i.e. that are overwritten, marked as dead,
+///   or left behind on every path before they could be read, and
+/// - storage markers of locals that are not used otherwise.
+///
+/// Liveness is computed by a backward data-flow analysis over the control-flow graph of each function.
+/// Locals whose address is taken are always live, since they might be read through a pointer.
+///
+/// Removing an operation that has UB would be a correct refinement, but it would hide the UB
+/// from anyone running the optimized program, so this pass does not do that. An assignment is
+/// only removed if it certainly has no UB: the value is computed from constants by operations that cannot fail,
+/// and the local is known to be live because it was made live earlier in the same block.
+pub fn dead_code_elimination(mut prog: Program) -> Program {
+    // `prog.vtables` refers to the methods of every vtable, whether or not the vtable is used,
+    // so they must stay around like the start function.
+    let call_graph = CallGraph::new(prog);
+    let mut roots = vec![prog.start];
+    for (_name, vtable) in prog.vtables.iter() {
+        roots.extend(vtable.methods.iter().map(|(_method, f)| f));
+    }
+    let mut reachable = Set::new();
+    for root in roots {
+        for f in call_graph.reachable_from(root) {
+            reachable.insert(f);
+        }
+    }
+    let unreachable: Vec<FnName> =
+        prog.functions.iter().map(|(name, _f)| name).filter(|f| !reachable.contains(*f)).collect();
+    for name in unreachable {
+        prog.functions.remove(name);
+    }
+
+    for (name, mut f) in prog.functions.iter() {
+        remove_unreachable_blocks(&mut f);
+        remove_dead_stores(&mut f);
+        prog.functions.insert(name, f);
+    }
+    prog
+}
+
+fn remove_dead_stores(f: &mut Function) {
+    let address_taken = address_taken_locals(*f);
+    let live_in = live_at_start(*f);
+
+    // Remove the assignments.
+    let mut blocks = Map::new();
+    for (bb_name, mut bb) in f.blocks.iter() {
+        let statements: Vec<Statement> = bb.statements.iter().collect();
+
+        // Going backwards, find the assignments whose value is not live.
+        let mut live = live_at_end(*f, bb, &live_in);
+        let mut unread = Vec::new();
+        for (idx, st) in statements.iter().enumerate().rev() {
+            if let Statement::Assign { destination: PlaceExpr::Local(local), .. } = st {
+                if !live.contains(*local) && !address_taken.contains(*local) {
+                    unread.push(idx);
+                }
+            }
+            statement_liveness(*st, &mut live);
+        }
+
+        // Going forwards, remove those that certainly have no UB.
+        let mut storage_live = Set::new();
+        let mut kept = list![];
+        for (idx, st) in statements.into_iter().enumerate() {
+            match st {
+                Statement::StorageLive(local) => {
+                    storage_live.insert(local);
+                }
+                Statement::StorageDead(local) => {
+                    storage_live.remove(local);
+                }
+                Statement::Assign { destination: PlaceExpr::Local(local), source }
+                    if unread.contains(&idx)
+                        && storage_live.contains(local)
+                        && cannot_fail(source) =>
+                    continue,
+                _ => {}
+            }
+            kept.push(st);
+        }
+        bb.statements = kept;
+        blocks.insert(bb_name, bb);
+    }
+    f.blocks = blocks;
+
+    // Remove the storage markers of locals that are now unused.
+    let used = used_locals(*f);
+    let is_unused = |local: LocalName| {
+        !used.contains(local) && local != f.ret && !f.args.iter().any(|arg| arg == local)
+    };
+    for (bb_name, mut bb) in f.blocks.iter() {
+        bb.statements = bb
+            .statements
+            .iter()
+            .filter(|st| {
+                match st {
+                    Statement::StorageLive(local) | Statement::StorageDead(local) =>
+                        !is_unused(*local),
+                    _ => true,
+                }
+            })
+            .collect();
+        f.blocks.insert(bb_name, bb);
+    }
+}
+
+/// The locals whose current value might still be read, at the start of each block of `f`.
+fn live_at_start(f: Function) -> Map<BbName, Set<LocalName>> {
+    let names = block_names(f);
+    let mut live_in: Map<BbName, Set<LocalName>> =
+        names.iter().map(|bb| (*bb, Set::new())).collect();
+    // Liveness only grows, so this terminates.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &bb_name in names.iter().rev() {
+            let bb = f.blocks.get(bb_name).unwrap();
+            let mut live = live_at_end(f, bb, &live_in);
+            for st in bb.statements.iter().rev() {
+                statement_liveness(st, &mut live);
+            }
+            if live != live_in.get(bb_name).unwrap() {
+                live_in.insert(bb_name, live);
+                changed = true;
+            }
+        }
+    }
+    live_in
+}
+
+/// The locals whose current value might still be read after the statements of `bb`:
+/// those read by its terminator and those live at the start of its successors.
+fn live_at_end(
+    f: Function,
+    bb: BasicBlock,
+    live_in: &Map<BbName, Set<LocalName>>,
+) -> Set<LocalName> {
+    let mut live = Set::new();
+    for succ in successors(bb.terminator) {
+        for local in live_in.get(succ).unwrap().iter() {
+            live.insert(local);
+        }
+    }
+    terminator_locals(bb.terminator, &mut live);
+    if matches!(bb.terminator, Terminator::Return) {
+        live.insert(f.ret);
+    }
+    live
+}
+
+/// Updates `live` from the locals live after `st` to those live before it.
+fn statement_liveness(st: Statement, live: &mut Set<LocalName>) {
+    match st {
+        Statement::Assign { destination: PlaceExpr::Local(local), source } => {
+            live.remove(local);
+            value_locals(source, live);
+        }
+        Statement::CopyPlace { destination: PlaceExpr::Local(local), source } => {
+            live.remove(local);
+            place_locals(source, live);
+        }
+        Statement::StorageLive(local) | Statement::StorageDead(local) => {
+            live.remove(local);
+        }
+        // Writing to a part of a local keeps the rest, so these count as reads.
+        _ => statement_locals(st, live),
+    }
+}
+
+/// Whether evaluating `value` certainly does not cause UB.
+fn cannot_fail(value: ValueExpr) -> bool {
+    match value {
+        ValueExpr::Constant(Constant::Int(_) | Constant::Bool(_), _) => true,
+        ValueExpr::Tuple(exprs, _) => exprs.iter().all(cannot_fail),
+        ValueExpr::UnOp {
+            operator: UnOp::Int(IntUnOp::Neg | IntUnOp::BitNot) | UnOp::Cast(CastOp::IntToInt(_)),
+            operand,
+        } => cannot_fail(operand.extract()),
+        ValueExpr::BinOp {
+            operator:
+                BinOp::Int(
+                    IntBinOp::Add
+                    | IntBinOp::Sub
+                    | IntBinOp::Mul
+                    | IntBinOp::Shl
+                    | IntBinOp::Shr
+                    | IntBinOp::BitAnd
+                    | IntBinOp::BitOr
+                    | IntBinOp::BitXor,
+                )
+                | BinOp::Rel(_),
+            left,
+            right,
+        } => cannot_fail(left.extract()) && cannot_fail(right.extract()),
+        _ => false,
+    }
+}
+
+/// The locals of `f` that are mentioned anywhere other than in storage markers.
+fn used_locals(f: Function) -> Set<LocalName> {
+    let mut out = Set::new();
+    for (_bb_name, bb) in f.blocks.iter() {
+        for st in bb.statements.iter() {
+            statement_locals(st, &mut out);
+        }
+        terminator_locals(bb.terminator, &mut out);
+    }
+    out
+}
+
+/// Adds the locals that `st` mentions, other than in storage markers, to `out`.
+fn statement_locals(st: Statement, out: &mut Set<LocalName>) {
+    match st {
+        Statement::Assign { destination, source } => {
+            place_locals(destination, out);
+            value_locals(source, out);
+        }
+        Statement::CopyPlace { destination, source } => {
+            place_locals(destination, out);
+            place_locals(source, out);
+        }
+        Statement::PlaceMention(p)
+        | Statement::SetDiscriminant { destination: p, .. }
+        | Statement::Validate { place: p, .. }
+        | Statement::Deinit { place: p } => place_locals(p, out),
+        Statement::StorageLive(_) | Statement::StorageDead(_) => {}
+    }
+}
+
+/// Adds the locals that `term` mentions to `out`.
+fn terminator_locals(term: Terminator, out: &mut Set<LocalName>) {
+    match term {
+        Terminator::Switch { value: v, .. } | Terminator::StartUnwind { unwind_payload: v, .. } =>
+            value_locals(v, out),
+        Terminator::Intrinsic { arguments, ret, .. } => {
+            for arg in arguments.iter() {
+                value_locals(arg, out);
+            }
+            place_locals(ret, out);
+        }
+        Terminator::Call { callee, arguments, ret, .. } => {
+            value_locals(callee, out);
+            for arg in arguments.iter() {
+                match arg {
+                    ArgumentExpr::ByValue(v) => value_locals(v, out),
+                    ArgumentExpr::InPlace(p) => place_locals(p, out),
+                }
+            }
+            place_locals(ret, out);
+        }
+        _ => {}
+    }
+}
+
+fn value_locals(v: ValueExpr, out: &mut Set<LocalName>) {
+    match v {
+        ValueExpr::Constant(..) => {}
+        ValueExpr::Tuple(exprs, _) =>
+            for expr in exprs.iter() {
+                value_locals(expr, out);
+            },
+        ValueExpr::Union { expr, .. } => value_locals(expr.extract(), out),
+        ValueExpr::Variant { data, .. } => value_locals(data.extract(), out),
+        ValueExpr::GetDiscriminant { place: p }
+        | ValueExpr::Load { source: p }
+        | ValueExpr::AddrOf { target: p, .. } => place_locals(p.extract(), out),
+        ValueExpr::UnOp { operand, .. } => value_locals(operand.extract(), out),
+        ValueExpr::BinOp { left, right, .. } => {
+            value_locals(left.extract(), out);
+            value_locals(right.extract(), out);
+        }
+    }
+}
+
+fn place_locals(p: PlaceExpr, out: &mut Set<LocalName>) {
+    match p {
+        PlaceExpr::Local(local) => {
+            out.insert(local);
+        }
+        PlaceExpr::Deref { operand, .. } => value_locals(operand.extract(), out),
+        PlaceExpr::Field { root, .. } | PlaceExpr::Downcast { root, .. } =>
+            place_locals(root.extract(), out),
+        PlaceExpr::Index { root, index } => {
+            place_locals(root.extract(), out);
+            value_locals(index.extract(), out);
+        }
+    }
+}
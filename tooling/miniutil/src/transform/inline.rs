@@ -0,0 +1,211 @@
+use super::*;
+
+use crate::analysis::{place_type, value_type};
+
+/// How eager `inline_functions` is to inline calls to a function,
+/// like the `#[inline]` attributes in Rust.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InlineHint {
+    /// Inline calls to this function if it is small, see `SMALL_FUNCTION_BLOCKS`.
+    Hint,
+    /// Inline all calls to this function where that is possible.
+    Always,
+    /// Never inline calls to this function.
+    Never,
+}
+
+/// Functions with at most this many blocks get inlined if they have `InlineHint::Hint`.
+pub const SMALL_FUNCTION_BLOCKS: usize = 4;
+
+/// The inline hints of the functions of a program.
+/// Functions without a hint are not inlined.
+#[derive(Clone, Default)]
+pub struct InlineHints {
+    hints: Vec<(FnName, InlineHint)>,
+}
+
+impl InlineHints {
+    pub fn insert(&mut self, f: FnName, hint: InlineHint) {
+        self.hints.retain(|(other, _hint)| *other != f);
+        self.hints.push((f, hint));
+    }
+
+    pub fn get(&self, f: FnName) -> Option<InlineHint> {
+        self.hints.iter().find(|(other, _hint)| *other == f).map(|(_f, hint)| *hint)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FnName, InlineHint)> + '_ {
+        self.hints.iter().copied()
+    }
+
+    fn should_inline(&self, f: FnName, body: Function) -> bool {
+        match self.get(f) {
+            Some(InlineHint::Always) => true,
+            Some(InlineHint::Hint) => body.blocks.iter().count() <= SMALL_FUNCTION_BLOCKS,
+            Some(InlineHint::Never) | None => false,
+        }
+    }
+}
+
+/// Inlines direct calls to the functions that `hints` asks for.
+///
+/// The blocks of the callee get spliced into the caller, with fresh names for all its blocks and locals:
+/// - the call block makes the argument and return locals live and initializes the arguments,
+/// - `Return` becomes a copy of the return value into the caller's return place, followed by
+///   `StorageDead` for all callee locals and a jump to the call's `next_block`,
+/// - `ResumeUnwind` becomes `StorageDead` for all callee locals followed by a jump to the call's `unwind_block`.
+///
+/// A missing `next_block` or `unwind_block` turns into `Unreachable`, which is UB just like the original.
+/// Block kinds are kept, so the callee's cleanup blocks must be able to jump to the caller's `unwind_block`:
+/// calls that unwind to a catch block, and calls in non-regular blocks, are not inlined.
+/// Neither are calls that do not obviously satisfy the ABI checks of `Call`, calls whose return place
+/// might denote different memory by the time the callee returns, and calls to functions using
+/// `StackAllocate`, whose allocations live until the callee returns.
+///
+/// Only calls in the original program are considered, so calls that are inlined themselves
+/// stay calls and recursive functions get inlined at most once per call site.
+pub fn inline_functions(mut prog: Program, hints: &InlineHints) -> Program {
+    let orig = prog;
+    for (name, mut f) in orig.functions.iter() {
+        for bb_name in block_names(f) {
+            let Terminator::Call {
+                callee: ValueExpr::Constant(Constant::FnPointer(callee), _),
+                ..
+            } = f.blocks.get(bb_name).unwrap().terminator
+            else {
+                continue;
+            };
+            let Some(body) = orig.functions.get(callee) else { continue };
+            if hints.should_inline(callee, body) {
+                inline_call(&mut f, bb_name, body);
+            }
+        }
+        prog.functions.insert(name, f);
+    }
+    prog
+}
+
+/// Replaces the call that `call_bb` ends in by the body of `callee`, if that is possible.
+fn inline_call(f: &mut Function, call_bb: BbName, callee: Function) {
+    let mut bb = f.blocks.get(call_bb).unwrap();
+    let Terminator::Call { calling_convention, arguments, ret, next_block, unwind_block, .. } =
+        bb.terminator
+    else {
+        return;
+    };
+    if bb.kind != BbKind::Regular
+        || calling_convention != callee.calling_convention
+        || !is_fixed_place(ret)
+        || place_type(ret, f.locals) != Some(callee.locals.get(callee.ret).unwrap())
+        || arguments.len() != callee.args.len()
+    {
+        return;
+    }
+    if let Some(unwind_block) = unwind_block {
+        if f.blocks.get(unwind_block).unwrap().kind != BbKind::Cleanup {
+            return;
+        }
+    }
+    let arg_ty = |arg: ArgumentExpr| {
+        match arg {
+            ArgumentExpr::ByValue(value) => value_type(value, f.locals),
+            ArgumentExpr::InPlace(place) => place_type(place, f.locals),
+        }
+    };
+    if !arguments
+        .zip(callee.args)
+        .all(|(arg, local)| arg_ty(arg) == Some(callee.locals.get(local).unwrap()))
+    {
+        return;
+    }
+    if callee.blocks.iter().any(|(_name, bb)| {
+        matches!(bb.terminator, Terminator::Intrinsic { intrinsic: IntrinsicOp::StackAllocate, .. })
+    }) {
+        return;
+    }
+
+    // Pick fresh names for everything in the callee.
+    let mut rename = Rename::default();
+    let mut next_local =
+        f.locals.iter().map(|(LocalName(name), _ty)| name.get_internal()).max().unwrap() + 1;
+    let mut callee_locals: Vec<LocalName> =
+        callee.locals.iter().map(|(local, _ty)| local).collect();
+    callee_locals.sort_by_key(|LocalName(name)| name.get_internal());
+    for local in callee_locals.iter().copied() {
+        let new = LocalName(Name::from_internal(next_local));
+        next_local += 1;
+        rename.locals.insert(local, new);
+        f.locals.insert(new, callee.locals.get(local).unwrap());
+    }
+    let mut next_bb =
+        f.blocks.iter().map(|(BbName(name), _bb)| name.get_internal()).max().unwrap() + 1;
+    for name in block_names(callee) {
+        rename.blocks.insert(name, BbName(Name::from_internal(next_bb)));
+        next_bb += 1;
+    }
+    let storage_dead: Vec<Statement> =
+        callee_locals.iter().map(|local| Statement::StorageDead(rename.local(*local))).collect();
+
+    // The call block sets up the locals that a new stack frame starts with.
+    bb.statements.push(Statement::StorageLive(rename.local(callee.ret)));
+    for local in callee.args.iter() {
+        bb.statements.push(Statement::StorageLive(rename.local(local)));
+    }
+    for (arg, local) in arguments.zip(callee.args) {
+        let source = match arg {
+            ArgumentExpr::ByValue(value) => value,
+            ArgumentExpr::InPlace(place) => ValueExpr::Load { source: GcCow::new(place) },
+        };
+        bb.statements
+            .push(Statement::Assign { destination: PlaceExpr::Local(rename.local(local)), source });
+    }
+    bb.terminator = Terminator::Goto(rename.block(callee.start));
+    f.blocks.insert(call_bb, bb);
+
+    // Copy the callee blocks, turning the ways out of the callee into jumps back to the caller.
+    let leave =
+        |target: Option<BbName>| target.map(Terminator::Goto).unwrap_or(Terminator::Unreachable);
+    for (name, callee_bb) in callee.blocks.iter() {
+        let mut statements: List<Statement> =
+            callee_bb.statements.iter().map(|st| rename.statement(st)).collect();
+        let terminator = match callee_bb.terminator {
+            Terminator::Return => {
+                statements.push(Statement::Assign {
+                    destination: ret,
+                    source: ValueExpr::Load {
+                        source: GcCow::new(PlaceExpr::Local(rename.local(callee.ret))),
+                    },
+                });
+                for st in storage_dead.iter() {
+                    statements.push(*st);
+                }
+                leave(next_block)
+            }
+            Terminator::ResumeUnwind => {
+                for st in storage_dead.iter() {
+                    statements.push(*st);
+                }
+                leave(unwind_block)
+            }
+            term => rename.terminator(term),
+        };
+        f.blocks.insert(
+            rename.block(name),
+            BasicBlock { statements, terminator, kind: callee_bb.kind },
+        );
+    }
+}
+
+/// Whether `place` denotes the same memory no matter when it is evaluated in the caller.
+/// The return place of a call is evaluated before the call, but the inlined code
+/// only stores to it when the callee returns.
+fn is_fixed_place(place: PlaceExpr) -> bool {
+    match place {
+        PlaceExpr::Local(_) => true,
+        PlaceExpr::Deref { operand, .. } => matches!(operand.extract(), ValueExpr::Constant(..)),
+        PlaceExpr::Field { root, .. } | PlaceExpr::Downcast { root, .. } =>
+            is_fixed_place(root.extract()),
+        PlaceExpr::Index { root, index } =>
+            is_fixed_place(root.extract()) && matches!(index.extract(), ValueExpr::Constant(..)),
+    }
+}
@@ -0,0 +1,28 @@
+//! Transformations of MiniRust programs.
+//!
+//! The builder generates rather verbose code, and many tests do not want to care about
+//! details like storage markers. The passes in this module take care of such details.
+//! Others simplify or optimize programs without changing their behavior.
+
+use crate::*;
+
+mod storage;
+pub use storage::*;
+
+mod coverage;
+pub use coverage::*;
+
+mod const_prop;
+pub use const_prop::*;
+
+mod simplify_cfg;
+pub use simplify_cfg::*;
+
+mod dce;
+pub use dce::*;
+
+mod rename;
+pub(crate) use rename::*;
+
+mod inline;
+pub use inline::*;
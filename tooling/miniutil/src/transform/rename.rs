@@ -0,0 +1,164 @@
+use super::*;
+
+/// Renames the names used by code, e.g. to move it into another function or program.
+/// Names that have no entry in the respective map are kept.
+#[derive(Default)]
+pub(crate) struct Rename {
+    pub(crate) locals: Map<LocalName, LocalName>,
+    pub(crate) blocks: Map<BbName, BbName>,
+    pub(crate) functions: Map<FnName, FnName>,
+    pub(crate) globals: Map<GlobalName, GlobalName>,
+}
+
+impl Rename {
+    pub(crate) fn local(&self, local: LocalName) -> LocalName {
+        self.locals.get(local).unwrap_or(local)
+    }
+
+    pub(crate) fn block(&self, block: BbName) -> BbName {
+        self.blocks.get(block).unwrap_or(block)
+    }
+
+    pub(crate) fn function(&self, f: FnName) -> FnName {
+        self.functions.get(f).unwrap_or(f)
+    }
+
+    pub(crate) fn relocation(&self, relocation: Relocation) -> Relocation {
+        Relocation {
+            name: self.globals.get(relocation.name).unwrap_or(relocation.name),
+            ..relocation
+        }
+    }
+
+    pub(crate) fn statement(&self, st: Statement) -> Statement {
+        match st {
+            Statement::Assign { destination, source } =>
+                Statement::Assign {
+                    destination: self.place(destination),
+                    source: self.value(source),
+                },
+            Statement::CopyPlace { destination, source } =>
+                Statement::CopyPlace {
+                    destination: self.place(destination),
+                    source: self.place(source),
+                },
+            Statement::PlaceMention(place) => Statement::PlaceMention(self.place(place)),
+            Statement::SetDiscriminant { destination, value } =>
+                Statement::SetDiscriminant { destination: self.place(destination), value },
+            Statement::Validate { place, fn_entry } =>
+                Statement::Validate { place: self.place(place), fn_entry },
+            Statement::Deinit { place } => Statement::Deinit { place: self.place(place) },
+            Statement::StorageLive(local) => Statement::StorageLive(self.local(local)),
+            Statement::StorageDead(local) => Statement::StorageDead(self.local(local)),
+        }
+    }
+
+    pub(crate) fn terminator(&self, term: Terminator) -> Terminator {
+        let bb = |name: BbName| self.block(name);
+        match term {
+            Terminator::Goto(name) => Terminator::Goto(bb(name)),
+            Terminator::Switch { value, cases, fallback } =>
+                Terminator::Switch {
+                    value: self.value(value),
+                    cases: cases.iter().map(|(case, name)| (case, bb(name))).collect(),
+                    fallback: bb(fallback),
+                },
+            Terminator::Intrinsic { intrinsic, arguments, ret, next_block } =>
+                Terminator::Intrinsic {
+                    intrinsic,
+                    arguments: arguments.iter().map(|arg| self.value(arg)).collect(),
+                    ret: self.place(ret),
+                    next_block: next_block.map(bb),
+                },
+            Terminator::Call {
+                callee,
+                calling_convention,
+                arguments,
+                ret,
+                next_block,
+                unwind_block,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|arg| {
+                        match arg {
+                            ArgumentExpr::ByValue(value) =>
+                                ArgumentExpr::ByValue(self.value(value)),
+                            ArgumentExpr::InPlace(place) =>
+                                ArgumentExpr::InPlace(self.place(place)),
+                        }
+                    })
+                    .collect();
+                Terminator::Call {
+                    callee: self.value(callee),
+                    calling_convention,
+                    arguments,
+                    ret: self.place(ret),
+                    next_block: next_block.map(bb),
+                    unwind_block: unwind_block.map(bb),
+                }
+            }
+            Terminator::StartUnwind { unwind_payload, unwind_block } =>
+                Terminator::StartUnwind {
+                    unwind_payload: self.value(unwind_payload),
+                    unwind_block: bb(unwind_block),
+                },
+            Terminator::StopUnwind(name) => Terminator::StopUnwind(bb(name)),
+            Terminator::Unreachable
+            | Terminator::Return
+            | Terminator::ResumeUnwind
+            | Terminator::Terminate => term,
+        }
+    }
+
+    pub(crate) fn place(&self, place: PlaceExpr) -> PlaceExpr {
+        match place {
+            PlaceExpr::Local(local) => PlaceExpr::Local(self.local(local)),
+            PlaceExpr::Deref { operand, ty } =>
+                PlaceExpr::Deref { operand: GcCow::new(self.value(operand.extract())), ty },
+            PlaceExpr::Field { root, field } =>
+                PlaceExpr::Field { root: GcCow::new(self.place(root.extract())), field },
+            PlaceExpr::Index { root, index } =>
+                PlaceExpr::Index {
+                    root: GcCow::new(self.place(root.extract())),
+                    index: GcCow::new(self.value(index.extract())),
+                },
+            PlaceExpr::Downcast { root, discriminant } =>
+                PlaceExpr::Downcast { root: GcCow::new(self.place(root.extract())), discriminant },
+        }
+    }
+
+    pub(crate) fn value(&self, value: ValueExpr) -> ValueExpr {
+        match value {
+            ValueExpr::Constant(Constant::FnPointer(f), ty) =>
+                ValueExpr::Constant(Constant::FnPointer(self.function(f)), ty),
+            ValueExpr::Constant(Constant::GlobalPointer(relocation), ty) =>
+                ValueExpr::Constant(Constant::GlobalPointer(self.relocation(relocation)), ty),
+            ValueExpr::Constant(..) => value,
+            ValueExpr::Tuple(exprs, ty) =>
+                ValueExpr::Tuple(exprs.iter().map(|expr| self.value(expr)).collect(), ty),
+            ValueExpr::Union { field, expr, union_ty } =>
+                ValueExpr::Union { field, expr: GcCow::new(self.value(expr.extract())), union_ty },
+            ValueExpr::Variant { discriminant, data, enum_ty } =>
+                ValueExpr::Variant {
+                    discriminant,
+                    data: GcCow::new(self.value(data.extract())),
+                    enum_ty,
+                },
+            ValueExpr::GetDiscriminant { place } =>
+                ValueExpr::GetDiscriminant { place: GcCow::new(self.place(place.extract())) },
+            ValueExpr::Load { source } =>
+                ValueExpr::Load { source: GcCow::new(self.place(source.extract())) },
+            ValueExpr::AddrOf { target, ptr_ty } =>
+                ValueExpr::AddrOf { target: GcCow::new(self.place(target.extract())), ptr_ty },
+            ValueExpr::UnOp { operator, operand } =>
+                ValueExpr::UnOp { operator, operand: GcCow::new(self.value(operand.extract())) },
+            ValueExpr::BinOp { operator, left, right } =>
+                ValueExpr::BinOp {
+                    operator,
+                    left: GcCow::new(self.value(left.extract())),
+                    right: GcCow::new(self.value(right.extract())),
+                },
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::*;
+
+/// Simplifies the control-flow graph of every function in `prog`:
+/// - blocks that are not reachable from the start block are removed,
+/// - jumps to empty blocks that just `Goto` somewhere else go there directly,
+/// - a block ending in `Goto` is merged with its target if it is the only way to get there.
+///
+/// Blocks are only ever combined with blocks of the same kind, so that all edges stay well-formed.
+/// The structured builders (`if_`, `while_`, ...) leave behind many empty blocks which this cleans up.
+pub fn simplify_cfg(mut prog: Program) -> Program {
+    for (name, f) in prog.functions.iter() {
+        prog.functions.insert(name, simplify_function_cfg(f));
+    }
+    prog
+}
+
+fn simplify_function_cfg(mut f: Function) -> Function {
+    loop {
+        remove_unreachable_blocks(&mut f);
+        if !(skip_empty_block(&mut f) || merge_block(&mut f)) {
+            return f;
+        }
+    }
+}
+
+/// The names of the blocks of `f`, sorted so the result does not depend on the map iteration order.
+pub(super) fn block_names(f: Function) -> Vec<BbName> {
+    let mut names: Vec<BbName> = f.blocks.iter().map(|(name, _bb)| name).collect();
+    names.sort_by_key(|BbName(name)| name.get_internal());
+    names
+}
+
+/// All blocks `term` can jump to, with one entry per edge.
+pub(super) fn successors(term: Terminator) -> Vec<BbName> {
+    match term {
+        Terminator::Goto(bb) | Terminator::StopUnwind(bb) => vec![bb],
+        Terminator::Switch { cases, fallback, .. } =>
+            cases.iter().map(|(_case, bb)| bb).chain([fallback]).collect(),
+        Terminator::Intrinsic { next_block, .. } => next_block.into_iter().collect(),
+        Terminator::Call { next_block, unwind_block, .. } =>
+            next_block.into_iter().chain(unwind_block).collect(),
+        Terminator::StartUnwind { unwind_block, .. } => vec![unwind_block],
+        Terminator::Unreachable
+        | Terminator::Return
+        | Terminator::ResumeUnwind
+        | Terminator::Terminate => vec![],
+    }
+}
+
+/// Make every edge of `term` that goes to `from` go to `to` instead.
+fn redirect(term: Terminator, from: BbName, to: BbName) -> Terminator {
+    let r = |bb: BbName| if bb == from { to } else { bb };
+    match term {
+        Terminator::Goto(bb) => Terminator::Goto(r(bb)),
+        Terminator::StopUnwind(bb) => Terminator::StopUnwind(r(bb)),
+        Terminator::Switch { value, cases, fallback } =>
+            Terminator::Switch {
+                value,
+                cases: cases.iter().map(|(case, bb)| (case, r(bb))).collect(),
+                fallback: r(fallback),
+            },
+        Terminator::Intrinsic { intrinsic, arguments, ret, next_block } =>
+            Terminator::Intrinsic { intrinsic, arguments, ret, next_block: next_block.map(r) },
+        Terminator::Call {
+            callee,
+            calling_convention,
+            arguments,
+            ret,
+            next_block,
+            unwind_block,
+        } =>
+            Terminator::Call {
+                callee,
+                calling_convention,
+                arguments,
+                ret,
+                next_block: next_block.map(r),
+                unwind_block: unwind_block.map(r),
+            },
+        Terminator::StartUnwind { unwind_payload, unwind_block } =>
+            Terminator::StartUnwind { unwind_payload, unwind_block: r(unwind_block) },
+        Terminator::Unreachable
+        | Terminator::Return
+        | Terminator::ResumeUnwind
+        | Terminator::Terminate => term,
+    }
+}
+
+/// Removes the blocks of `f` that cannot be reached from its start block.
+pub(super) fn remove_unreachable_blocks(f: &mut Function) {
+    let mut reached = Set::new();
+    let mut todo = vec![f.start];
+    while let Some(bb) = todo.pop() {
+        if reached.contains(bb) {
+            continue;
+        }
+        reached.insert(bb);
+        todo.extend(successors(f.blocks.get(bb).unwrap().terminator));
+    }
+    f.blocks = f.blocks.iter().filter(|(name, _bb)| reached.contains(*name)).collect();
+}
+
+/// Find a block without statements that just jumps to another block of the same kind,
+/// and make everything jump there directly. Returns whether something changed.
+fn skip_empty_block(f: &mut Function) -> bool {
+    for name in block_names(*f) {
+        let bb = f.blocks.get(name).unwrap();
+        let Terminator::Goto(target) = bb.terminator else { continue };
+        if !bb.statements.is_empty()
+            || target == name
+            || f.blocks.get(target).unwrap().kind != bb.kind
+        {
+            continue;
+        }
+        f.blocks = f
+            .blocks
+            .iter()
+            .map(|(other, mut other_bb)| {
+                other_bb.terminator = redirect(other_bb.terminator, name, target);
+                (other, other_bb)
+            })
+            .collect();
+        if f.start == name {
+            f.start = target;
+        }
+        // The block is now unreachable and will get removed.
+        return true;
+    }
+    false
+}
+
+/// Find a block that ends in a `Goto` to a block of the same kind that has no other incoming edges,
+/// and merge the two. Returns whether something changed.
+fn merge_block(f: &mut Function) -> bool {
+    for name in block_names(*f) {
+        let mut bb = f.blocks.get(name).unwrap();
+        let Terminator::Goto(target) = bb.terminator else { continue };
+        let target_bb = f.blocks.get(target).unwrap();
+        if target == name || target == f.start || target_bb.kind != bb.kind {
+            continue;
+        }
+        let incoming: usize = f
+            .blocks
+            .iter()
+            .map(|(_other, other_bb)| {
+                successors(other_bb.terminator).into_iter().filter(|bb| *bb == target).count()
+            })
+            .sum();
+        if incoming != 1 {
+            continue;
+        }
+        for st in target_bb.statements.iter() {
+            bb.statements.push(st);
+        }
+        bb.terminator = target_bb.terminator;
+        f.blocks.insert(name, bb);
+        f.blocks.remove(target);
+        return true;
+    }
+    false
+}
@@ -0,0 +1,267 @@
+use super::*;
+
+/// Inserts storage markers for all locals of `f` that are never explicitly marked as live
+/// (the arguments and the return local are live anyway).
+///
+/// Each of these locals is made live at the start of the latest block that comes before all its uses
+/// and runs at most once: a block that dominates all blocks mentioning the local and is not part of a loop.
+/// If there is no such block, the local is made live in a new start block, i.e. on entry of the function.
+///
+/// The local is made dead at the start of every block where no further use can follow,
+/// and before every `Return` that comes after a use. Locals whose address is taken are only made
+/// dead before `Return`, since their uses through pointers cannot be tracked.
+/// Locals that are never mentioned get no markers at all.
+pub fn insert_storage_markers(mut f: Function) -> Function {
+    // Find the locals that the function manages itself.
+    let mut explicit: Set<LocalName> = Set::new();
+    for (_bb_name, bb) in f.blocks.iter() {
+        for st in bb.statements.iter() {
+            if let Statement::StorageLive(local) = st {
+                explicit.insert(local);
+            }
+        }
+    }
+
+    let mut auto_locals: Vec<LocalName> = f
+        .locals
+        .iter()
+        .map(|(local, _ty)| local)
+        .filter(|local| {
+            *local != f.ret && !f.args.iter().any(|arg| arg == *local) && !explicit.contains(*local)
+        })
+        .collect();
+    // Keep the order of the inserted statements independent of the map iteration order.
+    auto_locals.sort_by_key(|LocalName(name)| name.get_internal());
+
+    let cfg = Cfg::new(f);
+    let address_taken = address_taken_locals(f);
+    // The statements to insert at the start and at the end of each block.
+    let mut at_start: Map<BbName, List<Statement>> = Map::new();
+    let mut at_end: Map<BbName, List<Statement>> = Map::new();
+    let push = |map: &mut Map<BbName, List<Statement>>, bb: BbName, st: Statement| {
+        let mut statements = map.get(bb).unwrap_or_default();
+        statements.push(st);
+        map.insert(bb, statements);
+    };
+    let mut new_start: List<Statement> = list![];
+
+    for local in auto_locals {
+        let uses: Vec<BbName> = cfg
+            .blocks
+            .iter()
+            .copied()
+            .filter(|bb| mentions(f.blocks.get(*bb).unwrap(), local))
+            .collect();
+        if uses.is_empty() {
+            continue;
+        }
+
+        match cfg.live_block(&uses) {
+            Some(bb) => push(&mut at_start, bb, Statement::StorageLive(local)),
+            None => new_start.push(Statement::StorageLive(local)),
+        }
+
+        let before_uses = cfg.reaching(&uses);
+        let mut dead_at: Vec<BbName> = Vec::new();
+        if !address_taken.contains(local) {
+            for &bb in cfg.blocks.iter() {
+                let after_uses = cfg.predecessors(bb).any(|pred| before_uses.contains(pred));
+                if !before_uses.contains(bb)
+                    && after_uses
+                    && f.blocks.get(bb).unwrap().kind == BbKind::Regular
+                {
+                    dead_at.push(bb);
+                }
+            }
+        }
+        for &bb in cfg.blocks.iter() {
+            let returns = matches!(f.blocks.get(bb).unwrap().terminator, Terminator::Return);
+            if dead_at.contains(&bb) {
+                push(&mut at_start, bb, Statement::StorageDead(local));
+            } else if returns && (before_uses.contains(bb) || address_taken.contains(local)) {
+                push(&mut at_end, bb, Statement::StorageDead(local));
+            }
+        }
+    }
+
+    let mut blocks: Map<BbName, BasicBlock> = Map::new();
+    for (bb_name, mut bb) in f.blocks.iter() {
+        let mut statements = at_start.get(bb_name).unwrap_or_default();
+        statements.append(bb.statements);
+        // Make the locals dead in the reverse order of the declaration.
+        statements.append(at_end.get(bb_name).unwrap_or_default().iter().rev().collect());
+        bb.statements = statements;
+        blocks.insert(bb_name, bb);
+    }
+    f.blocks = blocks;
+
+    if !new_start.is_empty() {
+        // We do not use the old start block since it might be the target of a back edge.
+        let next_name =
+            f.blocks.iter().map(|(BbName(name), _bb)| name.get_internal()).max().unwrap();
+        let start = BbName(Name::from_internal(next_name + 1));
+        let start_bb = BasicBlock {
+            statements: new_start,
+            terminator: Terminator::Goto(f.start),
+            kind: BbKind::Regular,
+        };
+        f.blocks.insert(start, start_bb);
+        f.start = start;
+    }
+    f
+}
+
+/// The blocks of a function that are reachable from its start block, and the edges between them.
+struct Cfg {
+    /// The reachable blocks, sorted by name.
+    blocks: Vec<BbName>,
+    /// The edges, as pairs of a block and one of its successors.
+    edges: Vec<(BbName, BbName)>,
+    /// The dominators of each block, i.e. the blocks that every path from the start block to it goes through.
+    dominators: Map<BbName, Set<BbName>>,
+}
+
+impl Cfg {
+    fn new(f: Function) -> Cfg {
+        let mut reached = Set::new();
+        let mut todo = vec![f.start];
+        let mut edges = Vec::new();
+        while let Some(bb) = todo.pop() {
+            if reached.contains(bb) {
+                continue;
+            }
+            reached.insert(bb);
+            for succ in successors(f.blocks.get(bb).unwrap().terminator) {
+                edges.push((bb, succ));
+                todo.push(succ);
+            }
+        }
+        let blocks: Vec<BbName> =
+            block_names(f).into_iter().filter(|bb| reached.contains(*bb)).collect();
+
+        // Iterate to a fixpoint, starting with all blocks dominating everything except the start block.
+        let mut dominators = Map::new();
+        for &bb in blocks.iter() {
+            let initial = if bb == f.start { [bb].into_iter().collect() } else { reached };
+            dominators.insert(bb, initial);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &bb in blocks.iter().filter(|bb| **bb != f.start) {
+                let mut doms = reached;
+                for (pred, _succ) in edges.iter().filter(|(_pred, succ)| *succ == bb) {
+                    let pred_doms: Set<BbName> = dominators.get(*pred).unwrap();
+                    doms = doms.iter().filter(|d| pred_doms.contains(*d)).collect();
+                }
+                doms.insert(bb);
+                if doms != dominators.get(bb).unwrap() {
+                    dominators.insert(bb, doms);
+                    changed = true;
+                }
+            }
+        }
+
+        Cfg { blocks, edges, dominators }
+    }
+
+    fn predecessors(&self, bb: BbName) -> impl Iterator<Item = BbName> + '_ {
+        self.edges.iter().filter(move |(_pred, succ)| *succ == bb).map(|(pred, _succ)| *pred)
+    }
+
+    /// The blocks from which one of `targets` can be reached, including `targets` themselves.
+    fn reaching(&self, targets: &[BbName]) -> Set<BbName> {
+        let mut reached = Set::new();
+        let mut todo = targets.to_vec();
+        while let Some(bb) = todo.pop() {
+            if reached.contains(bb) {
+                continue;
+            }
+            reached.insert(bb);
+            todo.extend(self.predecessors(bb));
+        }
+        reached
+    }
+
+    /// Whether `bb` is part of a loop, i.e. it might run more than once.
+    fn in_loop(&self, bb: BbName) -> bool {
+        let succs: Vec<BbName> = self
+            .edges
+            .iter()
+            .filter(|(pred, _succ)| *pred == bb)
+            .map(|(_pred, succ)| *succ)
+            .collect();
+        self.reaching(&[bb]).iter().any(|pred| succs.contains(&pred))
+    }
+
+    /// The latest block that dominates all of `uses` and runs at most once, if any.
+    fn live_block(&self, uses: &[BbName]) -> Option<BbName> {
+        // The dominators of a block form a chain, so the common dominators that are dominated
+        // by most other blocks come latest.
+        self.blocks
+            .iter()
+            .copied()
+            .filter(|bb| uses.iter().all(|u| self.dominators.get(*u).unwrap().contains(*bb)))
+            .filter(|bb| !self.in_loop(*bb))
+            .max_by_key(|bb| self.dominators.get(*bb).unwrap().iter().count())
+    }
+}
+
+/// Whether `bb` mentions `local` anywhere.
+fn mentions(bb: BasicBlock, local: LocalName) -> bool {
+    fn value(v: ValueExpr, local: LocalName) -> bool {
+        match v {
+            ValueExpr::Constant(..) => false,
+            ValueExpr::Tuple(exprs, _) => exprs.iter().any(|expr| value(expr, local)),
+            ValueExpr::Union { expr, .. } => value(expr.extract(), local),
+            ValueExpr::Variant { data, .. } => value(data.extract(), local),
+            ValueExpr::GetDiscriminant { place: p }
+            | ValueExpr::Load { source: p }
+            | ValueExpr::AddrOf { target: p, .. } => place(p.extract(), local),
+            ValueExpr::UnOp { operand, .. } => value(operand.extract(), local),
+            ValueExpr::BinOp { left, right, .. } =>
+                value(left.extract(), local) || value(right.extract(), local),
+        }
+    }
+    fn place(p: PlaceExpr, local: LocalName) -> bool {
+        match p {
+            PlaceExpr::Local(l) => l == local,
+            PlaceExpr::Deref { operand, .. } => value(operand.extract(), local),
+            PlaceExpr::Field { root, .. } | PlaceExpr::Downcast { root, .. } =>
+                place(root.extract(), local),
+            PlaceExpr::Index { root, index } =>
+                place(root.extract(), local) || value(index.extract(), local),
+        }
+    }
+
+    let in_statement = |st: Statement| {
+        match st {
+            Statement::Assign { destination, source } =>
+                place(destination, local) || value(source, local),
+            Statement::CopyPlace { destination, source } =>
+                place(destination, local) || place(source, local),
+            Statement::PlaceMention(p)
+            | Statement::SetDiscriminant { destination: p, .. }
+            | Statement::Validate { place: p, .. }
+            | Statement::Deinit { place: p } => place(p, local),
+            Statement::StorageLive(l) | Statement::StorageDead(l) => l == local,
+        }
+    };
+    let in_terminator = match bb.terminator {
+        Terminator::Switch { value: v, .. } | Terminator::StartUnwind { unwind_payload: v, .. } =>
+            value(v, local),
+        Terminator::Intrinsic { arguments, ret, .. } =>
+            arguments.iter().any(|arg| value(arg, local)) || place(ret, local),
+        Terminator::Call { callee, arguments, ret, .. } =>
+            value(callee, local)
+                || arguments.iter().any(|arg| {
+                    match arg {
+                        ArgumentExpr::ByValue(v) => value(v, local),
+                        ArgumentExpr::InPlace(p) => place(p, local),
+                    }
+                })
+                || place(ret, local),
+        _ => false,
+    };
+    bb.statements.iter().any(in_statement) || in_terminator
+}